@@ -0,0 +1,9 @@
+//! The constraint system front-end: the traits and types needed to write
+//! and inspect circuits, without pulling in a proving backend or a
+//! pairing-friendly curve.
+
+pub use better_bellman::{
+    capabilities, embed, Capabilities, Circuit, CircuitStats, Coefficient, ConstraintSystem,
+    Constant, Index, LinearCombination, Namespace, Ports, PublicInputLayout, RecordedCircuit, Replay, Result,
+    SparseLinearCombination, Standalone, SubCircuit, SynthesisError, WireMap, Witness,
+};
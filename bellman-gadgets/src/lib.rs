@@ -0,0 +1,12 @@
+//! Gadgets for common primitives, written against the constraint system
+//! front-end in `bellman-core`. Only the verifying key gadget depends on
+//! a proving backend, and it is gated behind the `groth16` feature.
+
+pub use better_bellman::gadgets::{
+    blake2b, blake2s, boolean, bytes, chacha20, comparison, gadget, int64, keccak, lookup, merkle,
+    mimc, multieq, multipack, mux, num, poseidon, range, rescue, sha256, sha512, sponge, test,
+    uint32, uint64, uint8,
+};
+
+#[cfg(feature = "groth16")]
+pub use better_bellman::gadgets::verifying_key;
@@ -0,0 +1,11 @@
+//! The Groth16 proving backend: parameter generation, proving and
+//! verification for circuits written against `bellman-core`.
+
+pub use better_bellman::groth16::*;
+
+/// The thread pool controls used by the prover.
+pub use better_bellman::{set_default_parallelism, Worker};
+
+/// The features and backends compiled into this build, so that verifier-only
+/// deployments can check what they shipped at startup.
+pub use better_bellman::capabilities::{capabilities, Backend, Capabilities, Curve, Feature, ProofSystem};
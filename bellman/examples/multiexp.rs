@@ -0,0 +1,64 @@
+//! Times dense multi-exponentiations in G1 of BLS12-381 on the default
+//! worker pool. Only the public `multiexp` API is used, so the same file can
+//! be run against an older revision to compare the two:
+//!
+//!     cargo run --release --example multiexp -- 10 12 14 16
+//!
+//! Each argument is the base-two logarithm of a size to time, and defaults
+//! to 10, 12, 14 and 16. The best and median of several runs are reported.
+
+use better_bellman::{multiexp, FullDensity};
+use ff::{Field, PrimeField, ScalarEngine};
+use futures::Future;
+use group::CurveProjective;
+use pairing::{bls12_381::Bls12, Engine};
+use rand::SeedableRng;
+
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn main() {
+    let sizes: Vec<u32> = env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("sizes are given as base-two logarithms"))
+        .collect();
+    let sizes: Vec<u32> = if sizes.is_empty() {
+        vec![10, 12, 14, 16]
+    } else {
+        sizes
+    };
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+
+    for log_n in sizes {
+        let n: usize = 1 << log_n;
+
+        // Successive multiples of a random point are much cheaper to
+        // generate than independent random points.
+        let step = <Bls12 as Engine>::G1::random(rng);
+        let mut acc = <Bls12 as Engine>::G1::random(rng);
+        let mut bases = Vec::with_capacity(n);
+        for _ in 0..n {
+            bases.push(acc.into_affine());
+            acc.add_assign(&step);
+        }
+        let bases = Arc::new(bases);
+        let exponents = Arc::new((0..n)
+            .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+            .collect::<Vec<_>>());
+
+        let runs: usize = if log_n >= 16 { 3 } else { 7 };
+        let mut elapsed: Vec<Duration> = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let start: Instant = Instant::now();
+            multiexp((bases.clone(), 0), FullDensity, exponents.clone()).wait().unwrap();
+            elapsed.push(start.elapsed());
+        }
+        elapsed.sort();
+
+        println!("2^{}: best {:?}, median {:?}", log_n, elapsed[0], elapsed[runs / 2]);
+    }
+}
@@ -0,0 +1,48 @@
+use ff::{Field, ScalarEngine};
+
+use crate::error::{Result, SynthesisError};
+
+/// Replaces every element of `values` with its inverse, using Montgomery's
+/// trick so that only one field inversion is performed for the whole slice.
+///
+/// If any element is zero, `DivisionByZero` is returned and `values` is
+/// left untouched.
+pub fn batch_inverse<E>(values: &mut [E::Fr]) -> Result<()>
+where
+    E: ScalarEngine
+{
+    if !E::Fr::batch_invert(values) {
+        return Err(SynthesisError::DivisionByZero)
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_batch_inverse() {
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand::SeedableRng;
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+
+    for len in 0..10 {
+        let values: Vec<Fr> = (0..len).map(|_| Fr::random(rng)).collect();
+        let mut inverses: Vec<Fr> = values.clone();
+        batch_inverse::<Bls12>(&mut inverses).unwrap();
+
+        for (value, inverse) in values.iter().zip(inverses.iter()) {
+            assert_eq!(*inverse, value.inverse().unwrap());
+        }
+    }
+
+    let mut values: Vec<Fr> = vec![Fr::random(rng), Fr::zero(), Fr::random(rng)];
+    let original: Vec<Fr> = values.clone();
+    match batch_inverse::<Bls12>(&mut values) {
+        Err(SynthesisError::DivisionByZero) => {},
+        _ => panic!("expected a division by zero")
+    }
+    assert_eq!(values, original);
+}
@@ -0,0 +1,262 @@
+//! Microbenchmarks of the multi-exponentiation and FFT primitives, for
+//! tuning the window size and thread count on the machine that will prove.
+//!
+//! Every measurement runs on a dedicated pool of the requested size and
+//! reports the best and mean of its runs. Inputs are random, so only the
+//! timings are meaningful.
+
+use ff::{Field, PrimeField, ScalarEngine};
+use group::{CurveAffine, CurveProjective};
+use rand_core::RngCore;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::domain::{fft_algorithm, multiexp_slice, multiexp_with_window, planned_window, run_fft};
+use crate::domain::{Domain, FftAlgorithm, FullDensity, Scalar};
+use crate::error::Result;
+use crate::multicore::{Worker, MULTI_THREAD};
+
+/// The primitive a `Timing` measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Multiexp,
+    Fft
+}
+
+/// The time taken by a primitive over `size` elements on `threads` threads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub primitive: Primitive,
+    pub size: usize,
+    pub threads: usize,
+    /// The window size of a multi-exponentiation, or `None` for an FFT.
+    pub window: Option<u32>,
+    /// The algorithm of an FFT, or `None` for a multi-exponentiation or an
+    /// FFT left to `Domain::fft`.
+    pub algorithm: Option<FftAlgorithm>,
+    /// Whether the window or algorithm is the one `multiexp` or `Domain::fft`
+    /// would have planned.
+    pub planned: bool,
+    pub best: Duration,
+    pub mean: Duration
+}
+
+/// Generates `size` random bases. The bases are successive multiples of a
+/// random point, which is much cheaper than sampling each one.
+pub fn random_bases<G, R>(rng: &mut R, size: usize) -> Vec<G>
+where
+    G: CurveAffine,
+    R: RngCore
+{
+    let step: G::Projective = G::Projective::random(rng);
+    let mut acc: G::Projective = G::Projective::random(rng);
+    let mut bases: Vec<G::Projective> = Vec::with_capacity(size);
+    for _ in 0..size {
+        bases.push(acc);
+        acc.add_assign(&step);
+    }
+
+    G::Projective::batch_normalization(&mut bases);
+    bases.iter().map(|base| base.into_affine()).collect()
+}
+
+/// Generates `size` random scalars in the representation taken by
+/// multi-exponentiation.
+pub fn random_scalars<E, R>(rng: &mut R, size: usize) -> Vec<<E::Fr as PrimeField>::Repr>
+where
+    E: ScalarEngine,
+    R: RngCore
+{
+    (0..size).map(|_| E::Fr::random(rng).into_repr()).collect()
+}
+
+/// Times a dense multi-exponentiation over `size` random bases for every
+/// pair of thread count and window size, taking `runs` runs of each. With
+/// no `windows`, the planned window is timed for each thread count.
+pub fn time_multiexp<G, R>(rng: &mut R, size: usize, windows: &[u32], threads: &[usize], runs: usize) -> Result<Vec<Timing>>
+where
+    G: CurveAffine,
+    R: RngCore
+{
+    let bases: Arc<Vec<G>> = Arc::new(random_bases::<G,_>(rng, size));
+    let scalars: Vec<_> = random_scalars::<G::Engine,_>(rng, size);
+
+    let mut timings: Vec<Timing> = Vec::new();
+    for &num_threads in threads {
+        let worker: Worker = Worker::with_threads(num_threads);
+        let planned: u32 = worker.install(|| planned_window::<G>(size));
+
+        if windows.is_empty() {
+            let (best, mean): _ = time_runs(&worker, runs, || {
+                multiexp_slice((bases.clone(), 0), &FullDensity, &scalars).map(|_| ())
+            })?;
+            timings.push(Timing {
+                primitive: Primitive::Multiexp,
+                size,
+                threads: num_threads,
+                window: Some(planned),
+                algorithm: None,
+                planned: true,
+                best,
+                mean
+            });
+        }
+
+        for &window in windows {
+            let (best, mean): _ = time_runs(&worker, runs, || {
+                multiexp_with_window((bases.clone(), 0), &FullDensity, &scalars, window).map(|_| ())
+            })?;
+            timings.push(Timing {
+                primitive: Primitive::Multiexp,
+                size,
+                threads: num_threads,
+                window: Some(window),
+                algorithm: None,
+                planned: window == planned,
+                best,
+                mean
+            });
+        }
+    }
+    Ok(timings)
+}
+
+/// Times an FFT over `size` random coefficients for every pair of thread
+/// count and algorithm, taking `runs` runs of each. With no `algorithms`,
+/// the coefficients are rounded up to the size of their evaluation domain
+/// and transformed as `Domain::fft` would. Otherwise they are rounded up to
+/// a power of two and transformed with each algorithm in turn.
+pub fn time_fft<E, R>(rng: &mut R, size: usize, algorithms: &[FftAlgorithm], threads: &[usize], runs: usize) -> Result<Vec<Timing>>
+where
+    E: ScalarEngine,
+    R: RngCore
+{
+    let coeffs: Vec<Scalar<E>> = (0..size).map(|_| Scalar(E::Fr::random(rng))).collect();
+    let domain_size: usize = Domain::<E, Scalar<E>>::new(coeffs.clone())?.as_ref().len();
+
+    let log_n: u32 = size.next_power_of_two().trailing_zeros();
+    let mut padded: Vec<Scalar<E>> = coeffs.clone();
+    padded.resize(1 << log_n, Scalar(E::Fr::zero()));
+    let mut omega: E::Fr = E::Fr::root_of_unity();
+    for _ in log_n..E::Fr::S {
+        omega.square();
+    }
+
+    let mut timings: Vec<Timing> = Vec::new();
+    for &num_threads in threads {
+        let worker: Worker = Worker::with_threads(num_threads);
+
+        if algorithms.is_empty() {
+            // Building the domain copies the coefficients, so it is kept out
+            // of the timed part of each run.
+            let mut elapsed: Vec<Duration> = Vec::with_capacity(runs);
+            for _ in 0..runs {
+                let mut domain: Domain<E, Scalar<E>> = Domain::new(coeffs.clone())?;
+                elapsed.push(worker.install(|| {
+                    let start: Instant = Instant::now();
+                    domain.fft();
+                    start.elapsed()
+                }));
+            }
+
+            let (best, mean): _ = summarise(&elapsed);
+            timings.push(Timing {
+                primitive: Primitive::Fft,
+                size: domain_size,
+                threads: num_threads,
+                window: None,
+                algorithm: None,
+                planned: true,
+                best,
+                mean
+            });
+        }
+
+        let planned: FftAlgorithm = worker.install(|| fft_algorithm(log_n, MULTI_THREAD.log_num_cpus()));
+        for &algorithm in algorithms {
+            let mut elapsed: Vec<Duration> = Vec::with_capacity(runs);
+            for _ in 0..runs {
+                let mut coeffs: Vec<Scalar<E>> = padded.clone();
+                elapsed.push(worker.install(|| {
+                    let start: Instant = Instant::now();
+                    run_fft(&mut coeffs, &omega, log_n, algorithm);
+                    start.elapsed()
+                }));
+            }
+
+            let (best, mean): _ = summarise(&elapsed);
+            timings.push(Timing {
+                primitive: Primitive::Fft,
+                size: 1 << log_n,
+                threads: num_threads,
+                window: None,
+                algorithm: Some(algorithm),
+                planned: algorithm == planned,
+                best,
+                mean
+            });
+        }
+    }
+    Ok(timings)
+}
+
+fn time_runs<F>(worker: &Worker, runs: usize, f: F) -> Result<(Duration, Duration)>
+where
+    F: Fn() -> Result<()> + Send + Sync
+{
+    let mut elapsed: Vec<Duration> = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        elapsed.push(worker.install(|| {
+            let start: Instant = Instant::now();
+            f().map(|_| start.elapsed())
+        })?);
+    }
+    Ok(summarise(&elapsed))
+}
+
+/// The best and mean of some runs, both zero if there were none.
+fn summarise(elapsed: &[Duration]) -> (Duration, Duration) {
+    let best: Duration = elapsed.iter().min().copied().unwrap_or_default();
+    let total: Duration = elapsed.iter().sum();
+    let mean: Duration = if elapsed.is_empty() {
+        Duration::default()
+    } else {
+        total / elapsed.len() as u32
+    };
+    (best, mean)
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_bench_timings() {
+    use pairing::{bls12_381::Bls12, Engine};
+
+    let rng = &mut rand::thread_rng();
+
+    let timings = time_multiexp::<<Bls12 as Engine>::G1Affine, _>(rng, 64, &[2, 3], &[1, 2], 2).unwrap();
+    assert_eq!(timings.len(), 4);
+    assert!(timings.iter().all(|t| t.primitive == Primitive::Multiexp && t.size == 64 && t.best <= t.mean));
+    assert_eq!(
+        timings.iter().map(|t| (t.threads, t.window)).collect::<Vec<_>>(),
+        vec![(1, Some(2)), (1, Some(3)), (2, Some(2)), (2, Some(3))]
+    );
+
+    let planned = time_multiexp::<<Bls12 as Engine>::G1Affine, _>(rng, 64, &[], &[1], 1).unwrap();
+    assert_eq!(planned.len(), 1);
+    assert!(planned[0].planned);
+
+    let timings = time_fft::<Bls12, _>(rng, 100, &[], &[1, 2], 2).unwrap();
+    assert_eq!(timings.len(), 2);
+    assert!(timings.iter().all(|t| t.primitive == Primitive::Fft && t.size == 128 && t.window.is_none()));
+    assert!(timings.iter().all(|t| t.algorithm.is_none() && t.planned));
+
+    let algorithms = [FftAlgorithm::Serial, FftAlgorithm::Parallel, FftAlgorithm::InPlace];
+    let timings = time_fft::<Bls12, _>(rng, 100, &algorithms, &[1], 1).unwrap();
+    assert_eq!(timings.len(), 3);
+    assert!(timings.iter().all(|t| t.size == 128));
+    assert_eq!(
+        timings.iter().filter(|t| t.planned).map(|t| t.algorithm).collect::<Vec<_>>(),
+        vec![Some(FftAlgorithm::Serial)]
+    );
+}
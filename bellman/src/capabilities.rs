@@ -0,0 +1,111 @@
+use crate::multicore::MULTI_THREAD;
+
+/// A strategy for scheduling the prover's parallel work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Work runs on the calling thread.
+    SingleThread,
+    /// Work is spread over a CPU pool sized to the machine.
+    Multicore
+}
+
+/// A pairing-friendly curve this build can prove over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Bls12_381
+}
+
+/// A proof system this build can generate parameters and proofs for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofSystem {
+    Groth16
+}
+
+/// An optional cargo feature which changes how this build proves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// Scalar multiplications split by the GLV endomorphism.
+    Glv,
+    /// The `bench` module of microbenchmarks.
+    Bench
+}
+
+/// The features compiled into this build, so that deployments can check at
+/// startup that the binary they shipped has the support they expect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+    pub backend: Backend,
+    /// Base-2 logarithm of the number of threads used by the backend.
+    pub log_num_cpus: u32,
+    pub curves: Vec<Curve>,
+    pub proof_systems: Vec<ProofSystem>,
+    pub features: Vec<Feature>,
+    /// Whether batches of field multiplications, as in the FFTs, run on
+    /// the vectorised kernel, which needs an x86_64 CPU with AVX2.
+    pub vectorised_field: bool
+}
+
+impl Capabilities {
+    pub fn supports_curve(&self, curve: Curve) -> bool {
+        self.curves.contains(&curve)
+    }
+
+    pub fn supports_proof_system(&self, proof_system: ProofSystem) -> bool {
+        self.proof_systems.contains(&proof_system)
+    }
+
+    pub fn has_feature(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// Reports the capabilities of this build.
+pub fn capabilities() -> Capabilities {
+    let backend: Backend = if cfg!(all(feature = "multicore", not(target_arch = "wasm32"))) {
+        Backend::Multicore
+    } else {
+        Backend::SingleThread
+    };
+
+    let mut curves: Vec<Curve> = Vec::new();
+    if cfg!(feature = "pairing") {
+        curves.push(Curve::Bls12_381);
+    }
+
+    let mut proof_systems: Vec<ProofSystem> = Vec::new();
+    if cfg!(feature = "groth16") {
+        proof_systems.push(ProofSystem::Groth16);
+    }
+
+    let mut features: Vec<Feature> = Vec::new();
+    if cfg!(feature = "glv") {
+        features.push(Feature::Glv);
+    }
+    if cfg!(all(feature = "bench", not(target_arch = "wasm32"))) {
+        features.push(Feature::Bench);
+    }
+
+    Capabilities {
+        backend,
+        log_num_cpus: MULTI_THREAD.log_num_cpus(),
+        curves,
+        proof_systems,
+        features,
+        vectorised_field: ff::batch::is_vectorised()
+    }
+}
+
+#[test]
+fn test_capabilities() {
+    let caps = capabilities();
+
+    assert_eq!(caps.supports_curve(Curve::Bls12_381), cfg!(feature = "pairing"));
+    assert_eq!(caps.supports_proof_system(ProofSystem::Groth16), cfg!(feature = "groth16"));
+    assert_eq!(caps.has_feature(Feature::Glv), cfg!(feature = "glv"));
+    if caps.backend == Backend::SingleThread {
+        assert_eq!(caps.log_num_cpus, 0);
+    }
+    if !cfg!(target_arch = "x86_64") {
+        assert!(!caps.vectorised_field);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{Namespace, domain, error};
-use domain::{Coefficient, LinearCombination, Index};
+use domain::{Coefficient, Constant, LinearCombination, Index};
 use error::Result;
 
 use ff::ScalarEngine;
@@ -19,6 +19,15 @@ where
         Coefficient::new_unchecked(Index::Input(0))
     }
 
+    /// Returns `value` as a constant term for use in linear combinations.
+    /// Constants are multiples of the "one" input, so they allocate no
+    /// variable however many are requested, and equal values give equal
+    /// constants. The assembly and prover fold every constant term of a
+    /// linear combination into a single term.
+    fn alloc_constant(&mut self, value: E::Fr) -> Constant<E> {
+        Constant::new(value, Self::one())
+    }
+
     /// Allocate a private variable in the constraint system. The provided function is used to
     /// determine the assignment of the variable. The given `annotation` function is invoked
     /// in testing contexts in order to derive a unique name for this variable in the current
@@ -37,6 +46,20 @@ where
         A: FnOnce() -> AR,
         AR: Into<String>;
 
+    /// Allocate a public variable which is identified by `name`, qualified
+    /// by the current namespace path, in the `PublicInputLayout` recorded by
+    /// the assembly, so that verifiers can build the input vector by name
+    /// rather than by allocation order. Allocating the same name twice in
+    /// one namespace fails with `DuplicateInput`.
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        N: Into<String>,
+    {
+        let name: String = name.into();
+        self.alloc_input(|| name, f)
+    }
+
     /// Enforce that `A` * `B` = `C`. The `annotation` function is invoked in testing contexts
     /// in order to derive a unique name for the constraint in the current namespace.
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
@@ -47,6 +70,38 @@ where
         LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
         LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>;
 
+    /// Enforce that `A` = 0, using the constraint `A` * 1 = 0. Implementations
+    /// may override this to lay the constraint out more densely.
+    fn enforce_zero<A, AR, LA>(&mut self, annotation: A, a: LA)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.enforce(annotation, a, |lc| lc + Self::one(), |lc| lc)
+    }
+
+    /// Enforce that `A` = `B`, using the constraint `A` * 1 = `B`.
+    fn enforce_equal<A, AR, LA, LB>(&mut self, annotation: A, a: LA, b: LB)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.enforce(annotation, a, |lc| lc + Self::one(), b)
+    }
+
+    /// Enforce that `var` is either 0 or 1, using the constraint
+    /// (1 - `var`) * `var` = 0.
+    fn enforce_boolean<A, AR>(&mut self, annotation: A, var: Coefficient)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.enforce(annotation, |lc| lc + Self::one() - var, |lc| lc + var, |lc| lc)
+    }
+
     /// Create a new (sub)namespace and enter into it. Not intended
     /// for downstream use; use `namespace` instead.
     fn push_namespace<NR, N>(&mut self, name_fn: N)
@@ -102,6 +157,14 @@ impl<'cs, E: ScalarEngine, CS: ConstraintSystem<E>> ConstraintSystem<E> for &'cs
         (**self).alloc_input(annotation, f)
     }
 
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        N: Into<String>,
+    {
+        (**self).alloc_input_named(name, f)
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -113,6 +176,33 @@ impl<'cs, E: ScalarEngine, CS: ConstraintSystem<E>> ConstraintSystem<E> for &'cs
         (**self).enforce(annotation, a, b, c)
     }
 
+    fn enforce_zero<A, AR, LA>(&mut self, annotation: A, a: LA)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        (**self).enforce_zero(annotation, a)
+    }
+
+    fn enforce_equal<A, AR, LA, LB>(&mut self, annotation: A, a: LA, b: LB)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        (**self).enforce_equal(annotation, a, b)
+    }
+
+    fn enforce_boolean<A, AR>(&mut self, annotation: A, var: Coefficient)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        (**self).enforce_boolean(annotation, var)
+    }
+
     fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
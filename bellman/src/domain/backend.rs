@@ -0,0 +1,82 @@
+use ff::ScalarEngine;
+use lazy_static::lazy_static;
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::multicore::MULTI_THREAD;
+use super::{fft, Group};
+
+lazy_static!{
+    static ref DEFAULT_FFT_BACKENDS: RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>> = RwLock::new(HashMap::new());
+}
+
+/// Where the transforms of a `Domain` over elements `T` run, so that a
+/// GPU implementation can take the place of the CPU one. A backend is
+/// given a slice and a primitive root of unity of its length, and must
+/// leave the slice evaluated at the powers of that root in order.
+pub trait FftBackend<E: ScalarEngine, T>: Send + Sync {
+    /// Transforms the 2^log_n elements of `a` over `omega`.
+    fn radix_2_fft(&self, a: &mut [T], omega: &E::Fr, log_n: u32);
+
+    /// Transforms the 3 * 2^log_n elements of `a` over `omega`. By default
+    /// the radix-3 stage runs on the CPU and each third is transformed by
+    /// `radix_2_fft`.
+    fn radix_3_fft(&self, a: &mut [T], omega: &E::Fr, log_n: u32)
+    where
+        for <'a> T: Group<'a,E>
+    {
+        fft::radix_3_fft_with(a, omega, log_n, |part, omega_cubed| self.radix_2_fft(part, omega_cubed, log_n));
+    }
+}
+
+/// The FFTs of the worker pool, with the algorithm `fft_algorithm` picks
+/// for the size of the domain and the number of threads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuFftBackend;
+
+impl<E,T> FftBackend<E,T> for CpuFftBackend
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    fn radix_2_fft(&self, a: &mut [T], omega: &E::Fr, log_n: u32) {
+        fft::run_optimal_fft(a, omega, log_n);
+    }
+
+    fn radix_3_fft(&self, a: &mut [T], omega: &E::Fr, log_n: u32) {
+        let algorithm: fft::FftAlgorithm = fft::fft_algorithm(log_n, MULTI_THREAD.log_num_cpus());
+        fft::radix_3_fft(a, omega, log_n, algorithm);
+    }
+}
+
+/// Sets the backend that runs the transforms of every `Domain` over `E`
+/// and `T` that was not given one with `Domain::with_fft_backend`, the
+/// prover's included. With `None`, such domains go back to the FFTs they
+/// choose for themselves on the CPU.
+pub fn set_default_fft_backend<E,T>(backend: Option<Arc<dyn FftBackend<E,T>>>)
+where
+    E: ScalarEngine,
+    T: 'static
+{
+    let mut backends: _ = DEFAULT_FFT_BACKENDS.write().expect("default fft backend lock was poisoned");
+    let key: TypeId = TypeId::of::<(E, T)>();
+    match backend {
+        Some(backend) => backends.insert(key, Box::new(backend)),
+        None => backends.remove(&key)
+    };
+}
+
+/// The backend set by `set_default_fft_backend` for `E` and `T`, if any.
+pub fn default_fft_backend<E,T>() -> Option<Arc<dyn FftBackend<E,T>>>
+where
+    E: ScalarEngine,
+    T: 'static
+{
+    DEFAULT_FFT_BACKENDS.read()
+        .expect("default fft backend lock was poisoned")
+        .get(&TypeId::of::<(E, T)>())
+        .and_then(|backend| backend.downcast_ref::<Arc<dyn FftBackend<E,T>>>())
+        .cloned()
+}
@@ -0,0 +1,187 @@
+use ff::{Field, ScalarEngine};
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::multicore::MULTI_THREAD;
+use super::{fft, Encodable, Group};
+
+/// An FFT over 2^log_n elements that are kept in `input`, a file or any
+/// other seekable store of their encodings, rather than in memory. No more
+/// than `memory` elements are held at once, which must be at least
+/// 2^((log_n + 1) / 2), the side of the matrix the elements are read as.
+///
+/// The four-step FFT is run in two passes over the store: the first
+/// transforms and twiddles the columns of the matrix in blocks, writing
+/// them back to `input`, and the second transforms its rows in blocks and
+/// writes them transposed to `output`, which then holds the evaluations.
+/// `input` is left holding intermediate values.
+pub fn external_fft<E,T,S>(input: &mut S, output: &mut S, omega: &E::Fr, log_n: u32, memory: usize) -> io::Result<()>
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E> + Encodable,
+    S: Read + Write + Seek
+{
+    // Element j1 + n1 * j2 is at row j2 and column j1 of the matrix.
+    let log_n1: u32 = log_n / 2;
+    let log_n2: u32 = log_n - log_n1;
+    let (n1, n2): (usize, usize) = (1 << log_n1, 1 << log_n2);
+    if memory < n2 {
+        let msg: String = format!("an external fft over 2^{} elements needs memory for {} of them", log_n, n2);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    }
+    let size: usize = encoded_size::<E,T>()?;
+
+    // Transform a block of columns j1 over omega^n1 at a time, twiddling
+    // element k2 of each by omega^(j1 * k2).
+    let width: usize = (memory / n2).min(n1);
+    let omega_n1: E::Fr = omega.pow(&[n1 as u64]);
+    let mut columns: Vec<T> = Vec::with_capacity(width * n2);
+    for first in (0..n1).step_by(width) {
+        let width: usize = width.min(n1 - first);
+
+        // Gathered column-major, so each column is contiguous.
+        columns.clear();
+        columns.resize(width * n2, T::zero());
+        for row in 0..n2 {
+            let elements: Vec<T> = read_elements::<E,T,S>(input, row * n1 + first, width, size)?;
+            for (col, element) in elements.into_iter().enumerate() {
+                columns[col * n2 + row] = element;
+            }
+        }
+
+        MULTI_THREAD.scope(width, |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
+            for (idx, columns) in columns.chunks_mut(chunk_size * n2).enumerate() {
+                scope.spawn(move || {
+                    let mut step: E::Fr = omega.pow(&[(first + idx * chunk_size) as u64]);
+                    for column in columns.chunks_mut(n2) {
+                        fft::serial_fft(column, &omega_n1, log_n2);
+
+                        let mut w: E::Fr = E::Fr::one();
+                        for element in column.iter_mut() {
+                            *element *= &w;
+                            w.mul_assign(&step);
+                        }
+                        step.mul_assign(omega);
+                    }
+                });
+            }
+        });
+
+        for row in 0..n2 {
+            let elements: Vec<T> = (0..width).map(|col| columns[col * n2 + row]).collect();
+            write_elements::<E,T,S>(input, row * n1 + first, &elements, size)?;
+        }
+    }
+
+    // Transform a block of rows k2 over omega^n2 at a time. Output
+    // k2 + n2 * k1 is then at row k2 and column k1, so each column of the
+    // block is written out as one run.
+    let height: usize = (memory / n1).min(n2);
+    let omega_n2: E::Fr = omega.pow(&[n2 as u64]);
+    for first in (0..n2).step_by(height) {
+        let height: usize = height.min(n2 - first);
+        let mut rows: Vec<T> = read_elements::<E,T,S>(input, first * n1, height * n1, size)?;
+
+        MULTI_THREAD.scope(height, |scope, chunk_size| {
+            for rows in rows.chunks_mut(chunk_size.max(1) * n1) {
+                scope.spawn(move || {
+                    for row in rows.chunks_mut(n1) {
+                        fft::serial_fft(row, &omega_n2, log_n1);
+                    }
+                });
+            }
+        });
+
+        for col in 0..n1 {
+            let elements: Vec<T> = (0..height).map(|row| rows[row * n1 + col]).collect();
+            write_elements::<E,T,S>(output, col * n2 + first, &elements, size)?;
+        }
+    }
+
+    output.flush()
+}
+
+/// Writes `elements` to `store` from element `start` on, each in the
+/// fixed-size encoding `external_fft` reads.
+pub fn write_elements<E,T,S>(store: &mut S, start: usize, elements: &[T], size: usize) -> io::Result<()>
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E> + Encodable,
+    S: Write + Seek
+{
+    let mut buf: Vec<u8> = Vec::with_capacity(elements.len() * size);
+    for element in elements.iter() {
+        element.write(&mut buf)?;
+    }
+
+    store.seek(SeekFrom::Start((start * size) as u64))?;
+    store.write_all(&buf)
+}
+
+/// Reads `len` elements from `store`, from element `start` on.
+pub fn read_elements<E,T,S>(store: &mut S, start: usize, len: usize, size: usize) -> io::Result<Vec<T>>
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E> + Encodable,
+    S: Read + Seek
+{
+    let mut buf: Vec<u8> = vec![0; len * size];
+    store.seek(SeekFrom::Start((start * size) as u64))?;
+    store.read_exact(&mut buf)?;
+
+    buf.chunks(size)
+        .map(|encoded| T::read(encoded, false))
+        .collect()
+}
+
+/// The number of bytes each element takes in a store.
+pub fn encoded_size<E,T>() -> io::Result<usize>
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E> + Encodable
+{
+    let mut encoded: Vec<u8> = Vec::new();
+    T::zero().write(&mut encoded)?;
+    Ok(encoded.len())
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_external_fft() {
+    use ff::PrimeField;
+    use pairing::bls12_381::{Bls12, Fr};
+    use std::io::Cursor;
+    use super::Scalar;
+
+    let rng = &mut rand::thread_rng();
+
+    for log_n in 0..10 {
+        let n: usize = 1 << log_n;
+        let mut omega = Fr::root_of_unity();
+        for _ in log_n..Fr::S {
+            omega.square();
+        }
+
+        let values: Vec<Scalar<Bls12>> = (0..n).map(|_| Scalar(Fr::random(rng))).collect();
+        let mut expected = values.clone();
+        fft::serial_fft(&mut expected, &omega, log_n);
+
+        let side: usize = 1 << (log_n - log_n / 2);
+        for &memory in [side, 3 * side, n].iter() {
+            let size = encoded_size::<Bls12, Scalar<Bls12>>().unwrap();
+            let mut input = Cursor::new(vec![]);
+            let mut output = Cursor::new(vec![]);
+            write_elements::<Bls12, _, _>(&mut input, 0, &values, size).unwrap();
+
+            external_fft::<Bls12, Scalar<Bls12>, _>(&mut input, &mut output, &omega, log_n, memory).unwrap();
+            let actual = read_elements::<Bls12, Scalar<Bls12>, _>(&mut output, 0, n, size).unwrap();
+            assert!(actual == expected);
+        }
+    }
+
+    let mut input = Cursor::new(vec![0; 64 * 32]);
+    let mut output = Cursor::new(vec![]);
+    let result = external_fft::<Bls12, Scalar<Bls12>, _>(&mut input, &mut output, &Fr::one(), 6, 7);
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
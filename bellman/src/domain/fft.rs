@@ -4,17 +4,53 @@ use domain::Group;
 
 use ff::{Field, ScalarEngine};
 
+use std::cmp;
+
+/// The algorithms `run_optimal_fft` chooses between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FftAlgorithm {
+    /// Radix-2 on the calling thread.
+    Serial,
+    /// One radix-2 sub-FFT per thread, combined by a shuffle.
+    Parallel,
+    /// Radix-2 stages run in place, each spread across the pool, so no
+    /// more memory than a table of twiddles is needed.
+    InPlace
+}
+
+/// The algorithm `run_optimal_fft` uses for a domain of 2^log_n elements
+/// on 2^log_cpus threads. A single thread always runs the serial FFT, as
+/// the others only add work that pays for itself when it is shared.
+pub fn fft_algorithm(log_n: u32, log_cpus: u32) -> FftAlgorithm {
+    if log_cpus == 0 || log_n <= log_cpus {
+        FftAlgorithm::Serial
+    } else {
+        FftAlgorithm::Parallel
+    }
+}
+
 pub fn run_optimal_fft<E,T>(a: &mut [T], omega: &E::Fr, log_n: u32) 
 where
     E: ScalarEngine,
     for <'a> T: Group<'a,E> 
 {
     let log_cpus = MULTI_THREAD.log_num_cpus();
+    run_fft(a, omega, log_n, fft_algorithm(log_n, log_cpus));
+}
 
-    if log_n <= log_cpus {
-        serial_fft(a, omega, log_n);
-    } else {
-        parallel_fft(a, omega, log_n, log_cpus);
+/// Runs an FFT over 2^log_n elements with the given algorithm.
+pub fn run_fft<E,T>(a: &mut [T], omega: &E::Fr, log_n: u32, algorithm: FftAlgorithm) 
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E> 
+{
+    match algorithm {
+        FftAlgorithm::Serial => serial_fft(a, omega, log_n),
+        FftAlgorithm::Parallel => {
+            let log_cpus: u32 = cmp::min(MULTI_THREAD.log_num_cpus(), log_n);
+            parallel_fft(a, omega, log_n, log_cpus)
+        },
+        FftAlgorithm::InPlace => in_place_fft(a, omega, log_n)
     }
 }
 
@@ -26,38 +62,131 @@ where
     let len: u32 = series.len() as u32;
     assert_eq!(len, 1 << log_n);
 
-    for k in 0..len {
-        let rk: u32 = bitreverse(k, log_n);
-        if k < rk {
-            series.swap(rk as usize, k as usize);
-        }
-    }
+    bitreverse_permutation(series, log_n);
 
     let mut m: u32 = 1;
+    let mut twiddles: Vec<E::Fr> = Vec::with_capacity((len / 2) as usize);
     for _ in 0..log_n {
         let w_m = omega.pow(&[(len / (2 * m)) as u64]);
 
-        let mut k = 0;
-        while k < len {
-            let mut w: _ = E::Fr::one();
-            for j in 0..m {
-                let mut t: T = series[(k + j + m) as usize];
-                t *= &w;
+        // The twiddle factors are shared by every block of the stage.
+        twiddles.clear();
+        let mut w: _ = E::Fr::one();
+        for _ in 0..m {
+            twiddles.push(w);
+            w.mul_assign(&w_m);
+        }
 
-                let mut tmp: T = series[(k + j) as usize];
-                tmp -= &t;
+        for block in series.chunks_mut(2 * m as usize) {
+            let (lo, hi): (&mut [T], &mut [T]) = block.split_at_mut(m as usize);
+            butterflies::<E,T>(lo, hi, &twiddles, 1);
+        }
+        m *= 2;
+    }
+}
 
-                series[(k + j + m) as usize] = tmp;
-                series[(k + j) as usize] += &t;
+/// A radix-2 FFT like `serial_fft`, but with the butterflies of each stage
+/// spread across the pool. Late stages have fewer blocks than threads, so
+/// their blocks are split between threads too.
+pub fn in_place_fft<E,T>(a: &mut [T], omega: &E::Fr, log_n: u32)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    // Every stage reads its twiddles from the powers the last one needs.
+    let mut powers: Vec<E::Fr> = vec![E::Fr::zero(); a.len() / 2];
+    fill_powers(&mut powers, *omega);
+    in_place_fft_with_powers(a, &powers, 1, log_n);
+}
 
-                w.mul_assign(&w_m);
+/// `in_place_fft` with the powers of omega read from a table, where
+/// `powers[stride * k]` is omega^k for every k below 2^(log_n - 1).
+pub fn in_place_fft_with_powers<E,T>(a: &mut [T], powers: &[E::Fr], stride: usize, log_n: u32)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    let n: usize = a.len();
+    assert_eq!(n, 1 << log_n);
+
+    bitreverse_permutation(a, log_n);
+
+    let mut m: usize = 1;
+    for _ in 0..log_n {
+        // Twiddle k of the stage is omega^(k * n / 2m).
+        let step: usize = stride * n / (2 * m);
+        MULTI_THREAD.scope(n / 2, |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
+            if chunk_size < m {
+                for block in a.chunks_mut(2 * m) {
+                    let (lo, hi): (&mut [T], &mut [T]) = block.split_at_mut(m);
+                    let pieces: _ = lo.chunks_mut(chunk_size)
+                        .zip(hi.chunks_mut(chunk_size))
+                        .enumerate();
+                    for (idx, (lo, hi)) in pieces {
+                        let twiddles: &[E::Fr] = &powers[idx * chunk_size * step..];
+                        scope.spawn(move || butterflies::<E,T>(lo, hi, twiddles, step));
+                    }
+                }
+            } else {
+                let blocks_per_task: usize = chunk_size / m;
+                for blocks in a.chunks_mut(2 * m * blocks_per_task) {
+                    scope.spawn(move || {
+                        for block in blocks.chunks_mut(2 * m) {
+                            let (lo, hi): (&mut [T], &mut [T]) = block.split_at_mut(m);
+                            butterflies::<E,T>(lo, hi, powers, step);
+                        }
+                    });
+                }
             }
-            k += 2 * m;
-        }
+        });
         m *= 2;
     }
 }
 
+/// Sets `powers[i]` to base^i, a chunk of powers per thread.
+pub(crate) fn fill_powers<F: Field>(powers: &mut [F], base: F) {
+    MULTI_THREAD.scope(powers.len(), |scope, chunk_size| {
+        let chunk_size: usize = chunk_size.max(1);
+        for (idx, powers) in powers.chunks_mut(chunk_size).enumerate() {
+            scope.spawn(move || {
+                let mut power: F = base.pow(&[(idx * chunk_size) as u64]);
+                for p in powers.iter_mut() {
+                    *p = power;
+                    power.mul_assign(&base);
+                }
+            });
+        }
+    });
+}
+
+/// Twiddles each element of `hi` by every `step`-th of `twiddles` and runs
+/// a radix-2 butterfly on it and the element of `lo` it pairs with.
+fn butterflies<E,T>(lo: &mut [T], hi: &mut [T], twiddles: &[E::Fr], step: usize)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    T::batch_mul_assign(hi, twiddles.iter().step_by(step));
+
+    for (u, t) in lo.iter_mut().zip(hi.iter_mut()) {
+        let mut tmp: T = *u;
+        tmp -= &*t;
+
+        *u += &*t;
+        *t = tmp;
+    }
+}
+
+fn bitreverse_permutation<T>(a: &mut [T], log_n: u32) {
+    for k in 0..a.len() as u32 {
+        let rk: u32 = bitreverse(k, log_n);
+        if k < rk {
+            a.swap(rk as usize, k as usize);
+        }
+    }
+}
+
 pub fn parallel_fft<E,T>(a: &mut [T], omega: &E::Fr, log_n: u32, log_cpus: u32) 
 where
     E: ScalarEngine,
@@ -104,6 +233,166 @@ where
     });
 }
 
+/// An FFT over `3 * 2^log_n` elements, where `omega` is a primitive root of
+/// unity of that order. A radix-3 butterfly over the three thirds of the
+/// elements leaves each third to be transformed by a radix-2 FFT with
+/// `algorithm`, after which output 3k + s is at position s * 2^log_n + k
+/// and is moved into place. Only the radix-2 FFTs may need extra memory.
+pub fn radix_3_fft<E,T>(a: &mut [T], omega: &E::Fr, log_n: u32, algorithm: FftAlgorithm)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    radix_3_fft_with(a, omega, log_n, |part, omega_cubed| run_fft(part, omega_cubed, log_n, algorithm));
+}
+
+/// `radix_3_fft` with each third transformed by `radix_2_fft`, which is
+/// given the third and omega^3, a primitive root of unity of its order.
+pub fn radix_3_fft_with<E,T,F>(a: &mut [T], omega: &E::Fr, log_n: u32, mut radix_2_fft: F)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>,
+    F: FnMut(&mut [T], &E::Fr)
+{
+    let third: usize = 1 << log_n;
+    assert_eq!(a.len(), 3 * third);
+
+    radix_3_butterflies(a, omega, None);
+
+    let omega_cubed: E::Fr = omega.pow(&[3]);
+    for part in a.chunks_mut(third) {
+        radix_2_fft(part, &omega_cubed);
+    }
+
+    unshuffle_thirds(a, third);
+}
+
+/// `radix_3_fft` with the powers of omega read from a table, where
+/// `powers[k]` is omega^k for every k below 2^(log_n + 1). The thirds are
+/// transformed by `in_place_fft_with_powers` over the same table.
+pub fn radix_3_fft_with_powers<E,T>(a: &mut [T], powers: &[E::Fr], log_n: u32)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    let third: usize = 1 << log_n;
+    assert_eq!(a.len(), 3 * third);
+    assert!(powers.len() >= 2 * third);
+
+    radix_3_butterflies(a, &powers[1], Some(powers));
+
+    for part in a.chunks_mut(third) {
+        in_place_fft_with_powers(part, powers, 3, log_n);
+    }
+
+    unshuffle_thirds(a, third);
+}
+
+/// Replaces each triple u_t = a[j + third * t] with its radix-3 butterfly,
+/// y_s = omega^(js) * (u_0 + c^s u_1 + c^(2s) u_2) for the cube root of
+/// unity c = omega^third, taking the powers of omega from `powers` if given.
+fn radix_3_butterflies<E,T>(a: &mut [T], omega: &E::Fr, powers: Option<&[E::Fr]>)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    let third: usize = a.len() / 3;
+
+    // c^2 = -1 - c lets every butterfly get by with a single
+    // multiplication by c.
+    let cube_root: E::Fr = match powers {
+        Some(powers) => powers[third],
+        None => omega.pow(&[third as u64])
+    };
+    let cube_root: &E::Fr = &cube_root;
+    let (x0, rest): (&mut [T], &mut [T]) = a.split_at_mut(third);
+    let (x1, x2): (&mut [T], &mut [T]) = rest.split_at_mut(third);
+
+    MULTI_THREAD.scope(third, |scope, chunk_size| {
+        let chunk_size: usize = chunk_size.max(1);
+        let chunks: _ = x0.chunks_mut(chunk_size)
+            .zip(x1.chunks_mut(chunk_size))
+            .zip(x2.chunks_mut(chunk_size))
+            .enumerate();
+        for (idx, ((x0, x1), x2)) in chunks {
+            scope.spawn(move || {
+                let start: usize = idx * chunk_size;
+                let triples: _ = x0.iter_mut().zip(x1.iter_mut()).zip(x2.iter_mut());
+
+                match powers {
+                    Some(powers) => {
+                        for (j, ((x0, x1), x2)) in (start..).zip(triples) {
+                            radix_3_butterfly(x0, x1, x2, cube_root, &powers[j], &powers[2 * j]);
+                        }
+                    },
+                    None => {
+                        let omega_sq: E::Fr = omega.pow(&[2]);
+                        let mut w1: E::Fr = omega.pow(&[start as u64]);
+                        let mut w2: E::Fr = omega_sq.pow(&[start as u64]);
+                        for ((x0, x1), x2) in triples {
+                            radix_3_butterfly(x0, x1, x2, cube_root, &w1, &w2);
+                            w1.mul_assign(omega);
+                            w2.mul_assign(&omega_sq);
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[inline]
+fn radix_3_butterfly<E,T>(x0: &mut T, x1: &mut T, x2: &mut T, cube_root: &E::Fr, w1: &E::Fr, w2: &E::Fr)
+where
+    E: ScalarEngine,
+    for <'a> T: Group<'a,E>
+{
+    let (u0, u1, u2): (T, T, T) = (*x0, *x1, *x2);
+
+    let mut d: T = u1;
+    d -= &u2;
+    d *= cube_root;
+
+    *x0 += &u1;
+    *x0 += &u2;
+
+    *x1 = u0;
+    *x1 += &d;
+    *x1 -= &u2;
+    *x1 *= w1;
+
+    *x2 = u0;
+    *x2 -= &d;
+    *x2 -= &u1;
+    *x2 *= w2;
+}
+
+/// Moves the element at s * third + k to 3k + s, following the cycles of
+/// the permutation so that only a bit per element is needed to track them.
+fn unshuffle_thirds<T: Copy>(a: &mut [T], third: usize) {
+    let source = |q: usize| (q % 3) * third + q / 3;
+
+    let mut visited: Vec<u64> = vec![0; (a.len() + 63) / 64];
+    for start in 0..a.len() {
+        if visited[start / 64] & (1 << (start % 64)) != 0 {
+            continue;
+        }
+
+        let first: T = a[start];
+        let mut q: usize = start;
+        loop {
+            visited[q / 64] |= 1 << (q % 64);
+            let src: usize = source(q);
+            if src == start {
+                a[q] = first;
+                break;
+            }
+            a[q] = a[src];
+            q = src;
+        }
+    }
+}
+
 fn bitreverse(mut n: u32, l: u32) -> u32 {
     let mut r = 0;
     for _ in 0..l {
@@ -158,3 +447,11 @@ mod test {
         test_comp::<Bls12, _>(rng);
     }
 }
+
+#[test]
+fn test_fft_algorithm() {
+    assert_eq!(fft_algorithm(10, 0), FftAlgorithm::Serial);
+    assert_eq!(fft_algorithm(2, 2), FftAlgorithm::Serial);
+    assert_eq!(fft_algorithm(10, 2), FftAlgorithm::Parallel);
+    assert_eq!(fft_algorithm(24, 3), FftAlgorithm::Parallel);
+}
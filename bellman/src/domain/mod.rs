@@ -1,17 +1,27 @@
 pub mod primitives;
+pub mod backend;
+pub mod external;
 pub mod fft;
 pub mod multiexp;
+pub mod precomputation;
 
 pub use primitives::*;
+pub use backend::*;
+pub use external::*;
 pub use fft::*;
 pub use multiexp::*;
+pub use precomputation::*;
 
-use ff::{Field, PrimeField, ScalarEngine};
+use ff::{Field, PrimeField, ScalarEngine, SqrtField};
 
-use crate::{error, multi_thread};
-use error::{SynthesisError, Result};
+use crate::{arith, error, multi_thread};
+use crate::multicore::MULTI_THREAD;
+use error::{DomainTooLarge, SynthesisError, Result};
 
-use std::ops;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use std::{io, iter, ops};
+use std::sync::Arc;
 
 /// A `Domain` abstraction for
 /// performing various kinds of polynomial arithmetic on top of
@@ -24,12 +34,21 @@ use std::ops;
 /// roots to be the powers of a 2^n root of unity in the field.
 /// This allows us to perform polynomial operations in O(n)
 /// by performing an O(n log n) FFT over such a domain.
+///
+/// When the field also has a cube root of unity, domains of
+/// size 3 * 2^n are admissible too, and the smaller of the two
+/// candidate sizes is used. This shrinks the domain by a quarter
+/// when the constraint count is just past a power of two.
 pub struct Domain<E,G> 
 where
     E: ScalarEngine
 {
     coeffs: Vec<G>,
     exp: u32,
+    radix_3: bool,
+    in_place: bool,
+    twiddles: Option<Arc<precomputation::Twiddles<E>>>,
+    backend: Option<Arc<dyn FftBackend<E,G>>>,
     omega: E::Fr,
     omegainv: E::Fr,
     geninv: E::Fr,
@@ -41,21 +60,63 @@ where
     E: ScalarEngine,
     for <'a> G: Group<'a,E>
 {
-    pub fn new(mut coeffs: Vec<G>) -> Result<Self> {
-        let (m,exp): (usize,u32) = Self::size_of(&coeffs)?;
-        let omega: E::Fr = Self::square_primitive_root_of_unity_to_degree(exp);
+    pub fn new(coeffs: Vec<G>) -> Result<Self> {
+        let (m,exp,radix_3): (usize,u32,bool) = Self::size_of(&coeffs)?;
+        Self::with_shape(coeffs, m, exp, radix_3)
+    }
+
+    /// Like `new`, but pads the coefficients to `size` instead when that is
+    /// an admissible domain size no smaller than the number of coefficients.
+    /// This lets a prover match the domain its parameters were generated
+    /// over, even if that is not the smallest one.
+    pub fn with_preferred_size(coeffs: Vec<G>, size: usize) -> Result<Self> {
+        let radix_3: bool = size % 3 == 0;
+        let twos: usize = if radix_3 { size / 3 } else { size };
+        let exp: u32 = twos.trailing_zeros();
+
+        if Self::is_admissible_size(size) && size >= coeffs.len() {
+            Self::with_shape(coeffs, size, exp, radix_3)
+        } else {
+            Self::new(coeffs)
+        }
+    }
 
-        let omegainv: _ = omega.inverse()?;
-        let geninv: _ = E::Fr::multiplicative_generator().inverse()?;
+    /// Whether `size` is a power of two, or three times one when the field
+    /// has a cube root of unity, within the two-adicity of the field.
+    pub fn is_admissible_size(size: usize) -> bool {
+        let radix_3: bool = size % 3 == 0;
+        let twos: usize = if radix_3 { size / 3 } else { size };
+
+        twos.is_power_of_two()
+            && twos.trailing_zeros() < E::Fr::S
+            && (!radix_3 || Self::primitive_cube_root_of_unity().is_some())
+    }
+
+    fn with_shape(mut coeffs: Vec<G>, m: usize, exp: u32, radix_3: bool) -> Result<Self> {
+        let mut omega: E::Fr = Self::square_primitive_root_of_unity_to_degree(exp);
+        if radix_3 {
+            omega.mul_assign(&Self::primitive_cube_root_of_unity()?);
+        }
 
         let casted_m: _ = format!("{}",m);
-        let minv: _ = E::Fr::from_str(&casted_m)?.inverse()?;
+        let mut inverses: [E::Fr; 3] = [
+            omega,
+            E::Fr::multiplicative_generator(),
+            E::Fr::from_str(&casted_m)?
+        ];
+        arith::batch_inverse::<E>(&mut inverses)?;
+        let [omegainv, geninv, minv]: [E::Fr; 3] = inverses;
 
         coeffs.resize(m, G::zero());
+        coeffs.shrink_to_fit();
 
         let domain: _ = Domain {
             coeffs,
             exp,
+            radix_3,
+            in_place: false,
+            twiddles: None,
+            backend: None,
             omega,
             omegainv,
             geninv,
@@ -64,6 +125,38 @@ where
         Ok(domain)
     }
 
+    /// Runs every transform of the domain with the in-place FFT, which
+    /// needs no buffer the size of the domain. It is slower than the FFT
+    /// `run_optimal_fft` picks on many threads, so it suits provers short of
+    /// memory rather than of time.
+    pub fn with_in_place_fft(self) -> Self {
+        Domain {
+            in_place: true,
+            ..self
+        }
+    }
+
+    /// Runs every transform of the domain in place over the powers of its
+    /// roots of unity held by `precomputation`, which computes them the
+    /// first time a domain of this size asks.
+    pub fn with_precomputation(self, precomputation: &DomainPrecomputation<E>) -> Self {
+        let twiddles: Arc<precomputation::Twiddles<E>> = precomputation.twiddles(&self);
+        Domain {
+            twiddles: Some(twiddles),
+            ..self
+        }
+    }
+
+    /// Runs every transform of the domain on `backend`, in place of the
+    /// default backend and of the FFTs chosen with `with_in_place_fft` or
+    /// `with_precomputation`.
+    pub fn with_fft_backend(self, backend: Arc<dyn FftBackend<E,G>>) -> Self {
+        Domain {
+            backend: Some(backend),
+            ..self
+        }
+    }
+
     // Compute omega, the 2^exp primitive root of unity
     fn square_primitive_root_of_unity_to_degree(degree: u32) -> E::Fr {
         let mut omega: _ = E::Fr::root_of_unity();
@@ -73,19 +166,62 @@ where
         omega
     }
 
-    fn size_of(coeffs: &Vec<G>) -> Result<(usize,u32)> {
+    // A primitive cube root of unity is a root of x^2 + x + 1, that is
+    // (-1 + sqrt(-3)) / 2, which exists exactly when 3 divides r - 1.
+    fn primitive_cube_root_of_unity() -> Option<E::Fr> {
+        let mut minus_three: E::Fr = E::Fr::from_str("3")?;
+        minus_three.negate();
+
+        let mut root: E::Fr = minus_three.sqrt()?;
+        root.sub_assign(&E::Fr::one());
+        root.mul_assign(&E::Fr::from_str("2")?.inverse()?);
+        Some(root)
+    }
+
+    // The smallest admissible size of at least coeffs.len(), as the size,
+    // its power of two and whether it carries a factor of three.
+    fn size_of(coeffs: &Vec<G>) -> Result<(usize,u32,bool)> {
+        let upper_bound: u32 = E::Fr::S;
+        let has_cube_root: bool = Self::primitive_cube_root_of_unity().is_some();
+
         let mut m: usize = 1;
         let mut exp: u32 = 0;
         while m < coeffs.len() {
             m *= 2;
             exp += 1;
+        }
 
-            let upper_bound: _ = E::Fr::S;
-            if exp >= upper_bound {
-                return Err(SynthesisError::PolynomialDegreeTooLarge);
-            }
+        // 3 * 2^(exp - 2) lies between 2^(exp - 1) and 2^exp, so it is the
+        // only smaller candidate with a factor of three.
+        if exp >= 2 && exp - 2 < upper_bound && 3 << (exp - 2) >= coeffs.len() && has_cube_root {
+            return Ok((3 << (exp - 2), exp - 2, true));
+        }
+        if exp < upper_bound {
+            return Ok((m,exp,false));
+        }
+
+        // Past the two-adicity of the field, three times the largest power
+        // of two is the last size left to try.
+        if exp == upper_bound && has_cube_root {
+            return Ok((3 << (exp - 1), exp - 1, true));
+        }
+        let size: DomainTooLarge = DomainTooLarge {
+            required: coeffs.len(),
+            available: Self::max_size()
+        };
+        Err(SynthesisError::DomainTooLarge(size))
+    }
+
+    /// The size of the largest domain over the scalar field, which is
+    /// 2^(S - 1) for a field of two-adicity S, or three times that if the
+    /// field has a cube root of unity too.
+    pub fn max_size() -> usize {
+        let largest: usize = 1 << (E::Fr::S - 1);
+        if Self::primitive_cube_root_of_unity().is_some() {
+            3 * largest
+        } else {
+            largest
         }
-        Ok((m,exp))
     }
 
     pub fn as_mut(&mut self) -> &mut [G] {
@@ -100,21 +236,56 @@ where
         &self.coeffs
     }
 
+    /// Evaluates the polynomial whose coefficients the domain holds at
+    /// every point of the domain, in place. Point i is omega^i.
     pub fn fft(&mut self) {
-        fft::run_optimal_fft(&mut self.coeffs, &self.omega, self.exp);
+        self.transform(false);
     }
 
+    /// Interpolates the evaluations the domain holds at its points back
+    /// into the coefficients of a polynomial, in place. The inverse of `fft`.
     pub fn ifft(&mut self) {
-        fft::run_optimal_fft(&mut self.coeffs, &self.omegainv, self.exp);
-        let coeff_len: usize = self.coeffs.len();
+        self.transform(true);
         let mul_inv: E::Fr = self.minv;
-        multi_thread!(coeff_len, iter(self.coeffs) => {
-            for v in iter => {
-                *v *= &mul_inv;
+        batch_mul_assign_by::<E,G>(&mut self.coeffs, mul_inv);
+    }
+
+    fn transform(&mut self, inverse: bool) {
+        let omega: &E::Fr = if inverse { &self.omegainv } else { &self.omega };
+        if let Some(backend) = self.backend.clone().or_else(default_fft_backend::<E,G>) {
+            if self.radix_3 {
+                backend.radix_3_fft(&mut self.coeffs, omega, self.exp);
+            } else {
+                backend.radix_2_fft(&mut self.coeffs, omega, self.exp);
             }
-        });
+            return
+        }
+
+        if let Some(twiddles) = self.twiddles.as_ref() {
+            let powers: &[E::Fr] = if inverse { &twiddles.omegainv } else { &twiddles.omega };
+            if self.radix_3 {
+                fft::radix_3_fft_with_powers(&mut self.coeffs, powers, self.exp);
+            } else {
+                fft::in_place_fft_with_powers(&mut self.coeffs, powers, 1, self.exp);
+            }
+            return
+        }
+
+        let algorithm: FftAlgorithm = if self.in_place {
+            FftAlgorithm::InPlace
+        } else {
+            fft::fft_algorithm(self.exp, MULTI_THREAD.log_num_cpus())
+        };
+
+        if self.radix_3 {
+            fft::radix_3_fft(&mut self.coeffs, omega, self.exp, algorithm);
+        } else {
+            fft::run_fft(&mut self.coeffs, omega, self.exp, algorithm);
+        }
     }
 
+    /// Multiplies coefficient i by g^i, which turns the polynomial p(x)
+    /// into p(gx).
     pub fn distribute_powers(&mut self, g: E::Fr) {
         multi_thread!(self.coeffs.len(), enumerate(self.coeffs) => {
             for (i, v) in coeffs => {
@@ -123,11 +294,17 @@ where
         });
     }
 
+    /// Evaluates the polynomial at every point of the coset g * omega^i of
+    /// the domain, in place, where g is the multiplicative generator of the
+    /// field. The target polynomial Z vanishes on the domain but not on the
+    /// coset, which is what makes division by it possible there.
     pub fn coset_fft(&mut self) {
         self.distribute_powers(E::Fr::multiplicative_generator());
         self.fft();
     }
 
+    /// Interpolates evaluations over the coset of `coset_fft` back into
+    /// coefficients, in place. The inverse of `coset_fft`.
     pub fn icoset_fft(&mut self) {
         let geninv = self.geninv;
 
@@ -135,6 +312,8 @@ where
         self.distribute_powers(geninv);
     }
 
+    /// Replaces `tau` with Z(tau) = tau^m - 1, the target polynomial of a
+    /// domain of m points evaluated at `tau`.
     pub fn raise_tau_to_size(&self, tau: &mut E::Fr) {
         let size: u64 = self.coeffs.len() as u64;
         let mut tmp: E::Fr = tau.pow(&[size]);
@@ -142,21 +321,80 @@ where
         *tau = tmp;
     }
 
-    pub fn divide_over_coset(&mut self) -> Result<()> {
-        let mut tau: _ = E::Fr::multiplicative_generator();
-        self.raise_tau_to_size(&mut tau);
-        let tau_inv: E::Fr = tau.inverse()?;
-        
-        multi_thread!(self.coeffs.len(), iter(self.coeffs) => {
-            for val in coeffs => {
-                *val *= &tau_inv
-            }
-        });
+    /// Divides evaluations over the coset of `coset_fft` by the target
+    /// polynomial Z, in place. Z(g * omega^i) = g^m - 1 is the same at every
+    /// point of the coset, so this is a single multiplication by its inverse.
+    /// A zero Z is reported as `DivisionByZero`.
+    pub fn divide_by_z_on_coset(&mut self) -> Result<()> {
+        let mut tau: [E::Fr; 1] = [E::Fr::multiplicative_generator()];
+        self.raise_tau_to_size(&mut tau[0]);
+        arith::batch_inverse::<E>(&mut tau)?;
+        batch_mul_assign_by::<E,G>(&mut self.coeffs, tau[0]);
 
         Ok(())
     }
 }
 
+impl<E,G> Domain<E,G> 
+where
+    E: ScalarEngine,
+    for <'a> G: Group<'a,E> + Encodable
+{
+    /// Writes the elements of the domain, whether they are coefficients or
+    /// evaluations, so that they can be cached between prover runs.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u32::<BigEndian>(self.coeffs.len() as u32)?;
+        for coeff in self.coeffs.iter() {
+            coeff.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a domain written by `write`. If `checked`, elements which are
+    /// curve points are validated to be on the curve and in the prime order
+    /// subgroup. Everything else about the domain is recomputed from its size,
+    /// which must be admissible.
+    pub fn read<R: io::Read>(mut reader: R, checked: bool) -> io::Result<Self> {
+        let len: usize = reader.read_u32::<BigEndian>()? as usize;
+        if !Self::is_admissible_size(len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an admissible domain size"
+            ))
+        }
+
+        // Even an admissible length is untrusted, so the domain grows as
+        // elements are read rather than being allocated up front.
+        let mut coeffs: Vec<G> = vec![];
+        for _ in 0..len {
+            coeffs.push(G::read(&mut reader, checked)?);
+        }
+
+        let domain: Self = Self::with_preferred_size(coeffs, len)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if domain.coeffs.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an admissible domain size"
+            ))
+        }
+        Ok(domain)
+    }
+}
+
+/// Multiplies every coefficient by `by`, in batches spread across the pool.
+fn batch_mul_assign_by<E,G>(coeffs: &mut [G], by: E::Fr)
+where
+    E: ScalarEngine,
+    for <'a> G: Group<'a,E>
+{
+    MULTI_THREAD.scope(coeffs.len(), |scope, chunk_size| {
+        for chunk in coeffs.chunks_mut(chunk_size.max(1)) {
+            scope.spawn(move || G::batch_mul_assign(chunk, iter::repeat(&by)));
+        }
+    });
+}
+
 impl<'a,E,G> ops::SubAssign<&'a Self> for Domain<E,G> 
 where
     E: ScalarEngine,
@@ -181,9 +419,10 @@ where
     fn mul_assign(&mut self, rhs: &'a Domain<E,Scalar<E>>) {
         assert_eq!(self.coeffs.len(), rhs.coeffs.len());
 
-        multi_thread!(self.coeffs.len(), iter(self.coeffs, rhs.coeffs) => {
-            for (l,r) in lhs_coeffs, rhs_coeffs => {
-                *l *= &r.0
+        MULTI_THREAD.scope(self.coeffs.len(), |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
+            for (lhs, rhs) in self.coeffs.chunks_mut(chunk_size).zip(rhs.coeffs.chunks(chunk_size)) {
+                scope.spawn(move || G::batch_mul_assign(lhs, rhs.iter().map(|r| &r.0)));
             }
         });
     }       
@@ -249,6 +488,319 @@ fn polynomial_arith() {
     test_mul::<Bls12, _>(rng);
 }
 
+// Test that a domain of size 3 * 2^n is used whenever it is the smaller
+// one, and that its FFT agrees with naive evaluation at the powers of omega.
+#[cfg(feature = "pairing")]
+#[test]
+fn radix_3_domain() {
+    use pairing::bls12_381::Bls12;
+    use rand_core::RngCore;
+
+    fn test_radix_3<E: ScalarEngine, R: RngCore>(rng: &mut R) {
+        let sizes = [(1, 1), (2, 2), (3, 3), (4, 4), (5, 6), (7, 8), (9, 12), (13, 16), (17, 24), (25, 32)];
+        for &(len, expected) in sizes.iter() {
+            let domain = Domain::<E, _>::new(vec![Scalar::<E>(E::Fr::zero()); len]).unwrap();
+            assert_eq!(domain.as_ref().len(), expected);
+        }
+
+        // A preferred size is used only if it is admissible and large enough.
+        for &(size, expected) in [(8, 8), (12, 12), (7, 6), (4, 6), (0, 6)].iter() {
+            let domain = Domain::<E, _>::with_preferred_size(vec![Scalar::<E>(E::Fr::zero()); 5], size).unwrap();
+            assert_eq!(domain.as_ref().len(), expected);
+        }
+
+        for &len in [3, 6, 12, 24, 48, 96].iter() {
+            let v: Vec<_> = (0..len)
+                .map(|_| Scalar::<E>(E::Fr::random(rng)))
+                .collect();
+            let mut domain = Domain::new(v.clone()).unwrap();
+            assert!(domain.radix_3);
+            assert_eq!(domain.omega.pow(&[len as u64]), E::Fr::one());
+            assert_ne!(domain.omega.pow(&[len as u64 / 2]), E::Fr::one());
+            assert_ne!(domain.omega.pow(&[len as u64 / 3]), E::Fr::one());
+
+            domain.fft();
+            for (i, eval) in domain.as_coeffs().iter().enumerate() {
+                let point = domain.omega.pow(&[i as u64]);
+                let mut naive = E::Fr::zero();
+                for coeff in v.iter().rev() {
+                    naive.mul_assign(&point);
+                    naive.add_assign(&coeff.0);
+                }
+                assert_eq!(eval.0, naive);
+            }
+
+            domain.ifft();
+            assert!(v == domain.as_coeffs());
+        }
+    }
+
+    let rng = &mut rand::thread_rng();
+
+    test_radix_3::<Bls12, _>(rng);
+}
+
+// Test that dividing A * B - C by Z on the coset, where A * B = C at every
+// point of the domain, gives the quotient h with A * B - C = h * Z.
+#[cfg(feature = "pairing")]
+#[test]
+fn coset_division() {
+    use pairing::bls12_381::Bls12;
+    use rand_core::RngCore;
+
+    fn eval<E: ScalarEngine>(coeffs: &[Scalar<E>], point: &E::Fr) -> E::Fr {
+        let mut acc = E::Fr::zero();
+        for coeff in coeffs.iter().rev() {
+            acc.mul_assign(point);
+            acc.add_assign(&coeff.0);
+        }
+        acc
+    }
+
+    fn test_division<E: ScalarEngine, R: RngCore>(rng: &mut R) {
+        for &len in [4, 6, 8, 12, 16].iter() {
+            let a: Vec<_> = (0..len).map(|_| Scalar::<E>(E::Fr::random(rng))).collect();
+            let b: Vec<_> = (0..len).map(|_| Scalar::<E>(E::Fr::random(rng))).collect();
+            let c: Vec<_> = a.iter().zip(b.iter()).map(|(a, b)| {
+                let mut c = a.0;
+                c.mul_assign(&b.0);
+                Scalar::<E>(c)
+            }).collect();
+
+            let mut a = Domain::new(a).unwrap();
+            let mut b = Domain::new(b).unwrap();
+            let mut c = Domain::new(c).unwrap();
+            a.ifft();
+            b.ifft();
+            c.ifft();
+
+            let point = E::Fr::random(rng);
+            let mut expected = eval::<E>(a.as_coeffs(), &point);
+            expected.mul_assign(&eval::<E>(b.as_coeffs(), &point));
+            expected.sub_assign(&eval::<E>(c.as_coeffs(), &point));
+
+            a.coset_fft();
+            b.coset_fft();
+            c.coset_fft();
+            a *= &b;
+            a -= &c;
+            a.divide_by_z_on_coset().unwrap();
+            a.icoset_fft();
+
+            // The quotient has degree at most len - 2.
+            assert!(a.as_coeffs()[len - 1].0.is_zero());
+
+            let mut z = point;
+            a.raise_tau_to_size(&mut z);
+            let mut actual = eval::<E>(a.as_coeffs(), &point);
+            actual.mul_assign(&z);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    let rng = &mut rand::thread_rng();
+
+    test_division::<Bls12, _>(rng);
+}
+
+// Test that the in-place FFT agrees with the serial one, and that domains
+// of both shapes transform alike with and without it.
+#[cfg(feature = "pairing")]
+#[test]
+fn in_place_fft_consistency() {
+    use pairing::bls12_381::Bls12;
+    use rand_core::RngCore;
+
+    fn test_consistency<E: ScalarEngine, R: RngCore>(rng: &mut R) {
+        for log_d in 0..12 {
+            let d = 1 << log_d;
+
+            let v1 = (0..d)
+                .map(|_| Scalar::<E>(E::Fr::random(rng)))
+                .collect::<Vec<_>>();
+            let mut v1 = Domain::new(v1).unwrap();
+            let mut v2 = Domain::new(v1.coeffs.clone()).unwrap();
+
+            fft::in_place_fft(&mut v1.coeffs, &v1.omega, log_d);
+            fft::serial_fft(&mut v2.coeffs, &v2.omega, log_d);
+
+            assert!(v1.coeffs == v2.coeffs);
+        }
+
+        for &len in [3, 6, 8, 12, 96, 1000].iter() {
+            let v = (0..len)
+                .map(|_| Scalar::<E>(E::Fr::random(rng)))
+                .collect::<Vec<_>>();
+            let mut v1 = Domain::new(v.clone()).unwrap().with_in_place_fft();
+            let mut v2 = Domain::new(v).unwrap();
+
+            v1.coset_fft();
+            v2.coset_fft();
+            assert!(v1.coeffs == v2.coeffs);
+
+            v1.icoset_fft();
+            v2.icoset_fft();
+            assert!(v1.coeffs == v2.coeffs);
+        }
+    }
+
+    let rng = &mut rand::thread_rng();
+
+    test_consistency::<Bls12, _>(rng);
+}
+
+// Test that transforms over precomputed tables agree with the others, and
+// that the tables are computed once per domain size.
+#[cfg(feature = "pairing")]
+#[test]
+fn precomputed_fft_consistency() {
+    use pairing::bls12_381::Bls12;
+    use rand_core::RngCore;
+
+    fn test_consistency<E: ScalarEngine, R: RngCore>(rng: &mut R) {
+        let precomputation = DomainPrecomputation::<E>::new();
+        for &len in [1, 2, 3, 6, 8, 12, 96, 1000, 1000].iter() {
+            let v = (0..len)
+                .map(|_| Scalar::<E>(E::Fr::random(rng)))
+                .collect::<Vec<_>>();
+            let mut v1 = Domain::new(v.clone()).unwrap().with_precomputation(&precomputation);
+            let mut v2 = Domain::new(v).unwrap();
+
+            v1.coset_fft();
+            v2.coset_fft();
+            assert!(v1.coeffs == v2.coeffs);
+
+            v1.icoset_fft();
+            v2.icoset_fft();
+            assert!(v1.coeffs == v2.coeffs);
+        }
+        assert_eq!(precomputation.len(), 8);
+    }
+
+    let rng = &mut rand::thread_rng();
+
+    test_consistency::<Bls12, _>(rng);
+}
+
+// Test that domains run their transforms on the backend they are given,
+// or failing that on the default backend for their field and elements.
+#[cfg(feature = "pairing")]
+#[test]
+fn fft_backend() {
+    use pairing::bls12_381::{Bls12, Fr};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recording(Mutex<Vec<usize>>);
+
+    impl FftBackend<Bls12, Scalar<Bls12>> for Recording {
+        fn radix_2_fft(&self, a: &mut [Scalar<Bls12>], omega: &Fr, log_n: u32) {
+            self.0.lock().unwrap().push(a.len());
+            FftBackend::<Bls12, _>::radix_2_fft(&CpuFftBackend, a, omega, log_n);
+        }
+    }
+
+    let rng = &mut rand::thread_rng();
+    for &(len, sub_len, calls) in [(8, 8, 2), (12, 4, 6)].iter() {
+        let v: Vec<_> = (0..len).map(|_| Scalar::<Bls12>(Fr::random(rng))).collect();
+        let backend = Arc::new(Recording::default());
+        let mut v1 = Domain::new(v.clone()).unwrap().with_fft_backend(backend.clone());
+        let mut v2 = Domain::new(v).unwrap();
+
+        v1.coset_fft();
+        v2.coset_fft();
+        assert!(v1.coeffs == v2.coeffs);
+
+        v1.icoset_fft();
+        v2.icoset_fft();
+        assert!(v1.coeffs == v2.coeffs);
+
+        // A 3 * 2^k domain has each of its thirds transformed by the backend.
+        assert_eq!(*backend.0.lock().unwrap(), vec![sub_len; calls]);
+    }
+
+    // No other test transforms domains of this size, so the calls recorded
+    // for it are all from this domain.
+    let backend = Arc::new(Recording::default());
+    set_default_fft_backend::<Bls12, Scalar<Bls12>>(Some(backend.clone()));
+    let mut domain = Domain::new(vec![Scalar::<Bls12>(Fr::one()); 1536]).unwrap();
+    domain.ifft();
+    set_default_fft_backend::<Bls12, Scalar<Bls12>>(None);
+
+    assert_eq!(backend.0.lock().unwrap().iter().filter(|&&len| len == 512).count(), 3);
+    assert!(domain.as_coeffs()[0].0 == Fr::one());
+    assert!(domain.as_coeffs()[1..].iter().all(|c| c.0.is_zero()));
+    assert!(default_fft_backend::<Bls12, Scalar<Bls12>>().is_none());
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn domain_serialization() {
+    use pairing::{bls12_381::Bls12, Engine};
+    use group::CurveProjective;
+    use rand_core::RngCore;
+
+    fn test_roundtrip<E: Engine, R: RngCore>(rng: &mut R) {
+        for &len in [1, 5, 8, 12].iter() {
+            let v: Vec<_> = (0..len)
+                .map(|_| Scalar::<E>(E::Fr::random(rng)))
+                .collect();
+            let mut domain = Domain::new(v).unwrap();
+            domain.fft();
+
+            let mut encoded = vec![];
+            domain.write(&mut encoded).unwrap();
+            let mut decoded = Domain::<E, Scalar<E>>::read(&encoded[..], true).unwrap();
+            assert!(decoded.as_coeffs() == domain.as_coeffs());
+
+            // The decoded domain transforms like the original.
+            domain.ifft();
+            decoded.ifft();
+            assert!(decoded.as_coeffs() == domain.as_coeffs());
+
+            // Truncated input is an error.
+            assert!(Domain::<E, Scalar<E>>::read(&encoded[..encoded.len() - 1], true).is_err());
+        }
+
+        let mut points: Vec<_> = (0..6).map(|_| Point(E::G1::random(rng))).collect();
+        points[2] = Point(E::G1::zero());
+        let domain = Domain::<E, _>::new(points).unwrap();
+
+        let mut encoded = vec![];
+        domain.write(&mut encoded).unwrap();
+        for &checked in [true, false].iter() {
+            let decoded = Domain::<E, Point<E::G1>>::read(&encoded[..], checked).unwrap();
+            assert!(decoded.as_coeffs() == domain.as_coeffs());
+        }
+
+        // Five elements would be padded to a domain of six.
+        let mut encoded = vec![];
+        Domain::<E, _>::new(vec![Scalar::<E>(E::Fr::one()); 8])
+            .unwrap()
+            .write(&mut encoded)
+            .unwrap();
+        encoded[3] = 5;
+        encoded.truncate(4 + 5 * 32);
+        assert!(Domain::<E, Scalar<E>>::read(&encoded[..], true).is_err());
+
+        // Inadmissible sizes are rejected before anything is read, and
+        // admissible ones must be followed by their elements.
+        assert!(!Domain::<E, Scalar<E>>::is_admissible_size(5));
+        assert!(Domain::<E, Scalar<E>>::is_admissible_size(12));
+        assert!(Domain::<E, Scalar<E>>::read(&[0xff, 0xff, 0xff, 0xff][..], true).is_err());
+        assert!(Domain::<E, Scalar<E>>::read(&[0x80, 0, 0, 0][..], true).is_err());
+
+        // Non-canonical scalars are rejected.
+        let mut encoded = vec![0, 0, 0, 1];
+        encoded.extend_from_slice(&[0xff; 32]);
+        assert!(Domain::<E, Scalar<E>>::read(&encoded[..], true).is_err());
+    }
+
+    let rng = &mut rand::thread_rng();
+
+    test_roundtrip::<Bls12, _>(rng);
+}
+
 #[cfg(feature = "pairing")]
 #[test]
 fn parallel_fft_consistency() {
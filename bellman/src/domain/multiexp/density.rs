@@ -1,8 +1,14 @@
 use bit_vec::{self, BitVec};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{self, Read, Write};
 use std::iter;
 
 pub trait QueryDensity: IntoIterator<Item = bool> {
     fn get_query_size(self) -> Option<usize>;
+
+    /// The number of elements which are present in the query, if known
+    /// ahead of time. This is used to plan the multi-exponentiation.
+    fn get_total_density(self) -> Option<usize>;
 }
 
 #[derive(Clone)]
@@ -18,6 +24,10 @@ impl<'a> QueryDensity for &'a FullDensity {
     fn get_query_size(self) -> Option<usize> {
         None
     }
+
+    fn get_total_density(self) -> Option<usize> {
+        None
+    }
 }
 
 impl<'a> IntoIterator for &'a FullDensity {
@@ -29,15 +39,20 @@ impl<'a> IntoIterator for &'a FullDensity {
     }
 }
 
+/// Records which elements of a query are present, as a bit per element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct DensityTracker {
     bv: BitVec,
-    total_density: usize,
 }
 
 impl<'a> QueryDensity for &'a DensityTracker {
     fn get_query_size(self) -> Option<usize> {
         Some(self.bv.len())
     }
+
+    fn get_total_density(self) -> Option<usize> {
+        Some(DensityTracker::get_total_density(self))
+    }
 }
 
 impl<'a> IntoIterator for &'a DensityTracker {
@@ -53,7 +68,6 @@ impl DensityTracker {
     pub fn new() -> DensityTracker {
         DensityTracker {
             bv: BitVec::new(),
-            total_density: 0,
         }
     }
 
@@ -62,13 +76,129 @@ impl DensityTracker {
     }
 
     pub fn inc(&mut self, idx: usize) {
-        if !self.bv.get(idx).unwrap() {
-            self.bv.set(idx, true);
-            self.total_density += 1;
-        }
+        self.bv.set(idx, true);
     }
 
+    /// The number of elements present, counted a block at a time.
     pub fn get_total_density(&self) -> usize {
-        self.total_density
+        self.bv.blocks()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    /// The elements present in both trackers. The thread will panic if the
+    /// trackers are not the same size.
+    pub fn and(&self, other: &DensityTracker) -> DensityTracker {
+        assert_eq!(self.bv.len(), other.bv.len(), "expected density trackers of the same size");
+        let mut bv: BitVec = self.bv.clone();
+        bv.intersect(&other.bv);
+        DensityTracker { bv }
     }
-}
\ No newline at end of file
+
+    /// The elements present in either tracker. The thread will panic if the
+    /// trackers are not the same size.
+    pub fn or(&self, other: &DensityTracker) -> DensityTracker {
+        assert_eq!(self.bv.len(), other.bv.len(), "expected density trackers of the same size");
+        let mut bv: BitVec = self.bv.clone();
+        bv.union(&other.bv);
+        DensityTracker { bv }
+    }
+
+    /// The indices of the elements present, in increasing order.
+    pub fn iter_set(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bv.iter()
+            .enumerate()
+            .filter(|(_, present)| *present)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Writes the number of elements followed by the bits, most significant
+    /// bit of each byte first.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u64::<BigEndian>(self.bv.len() as u64)?;
+        writer.write_all(&self.bv.to_bytes())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let len = reader.read_u64::<BigEndian>()?;
+        let num_bytes = len.checked_add(7).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "density length overflows")
+        })? / 8;
+
+        // The length is untrusted, so only the bytes actually present are
+        // read rather than allocating for all of them up front.
+        let mut bytes = vec![];
+        reader.by_ref().take(num_bytes).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "density bits are truncated",
+            ));
+        }
+        let len = len as usize;
+
+        let mut bv: BitVec = BitVec::from_bytes(&bytes);
+        if bv.iter().skip(len).any(|present| present) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "density padding bits are set",
+            ));
+        }
+        bv.truncate(len);
+
+        Ok(DensityTracker { bv })
+    }
+}
+
+#[test]
+fn test_density_tracker() {
+    let mut a = DensityTracker::new();
+    let mut b = DensityTracker::new();
+    for i in 0..100 {
+        a.add_element();
+        b.add_element();
+        if i % 2 == 0 {
+            a.inc(i);
+        }
+        if i % 3 == 0 {
+            b.inc(i);
+            b.inc(i);
+        }
+    }
+    assert_eq!(a.get_total_density(), 50);
+    assert_eq!(b.get_total_density(), 34);
+
+    let both = a.and(&b);
+    assert_eq!(both.iter_set().collect::<Vec<_>>(), (0..100).step_by(6).collect::<Vec<_>>());
+    assert_eq!(both.get_total_density(), 17);
+
+    let either = a.or(&b);
+    assert!(either.iter_set().all(|i| i % 2 == 0 || i % 3 == 0));
+    assert_eq!(either.get_total_density(), 50 + 34 - 17);
+    assert_eq!((&either).into_iter().filter(|present| *present).count(), 67);
+
+    // Round trips through serialization, including a partial final byte.
+    let mut bytes = vec![];
+    either.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 8 + 13);
+    let read = DensityTracker::read(&bytes[..]).unwrap();
+    assert_eq!(read, either);
+    assert_eq!(read.get_total_density(), 67);
+
+    // Bits past the end of the query are rejected, as is a short read.
+    *bytes.last_mut().unwrap() |= 1;
+    assert!(DensityTracker::read(&bytes[..]).is_err());
+    assert!(DensityTracker::read(&bytes[..10]).is_err());
+
+    // Lengths which overflow, or claim far more bits than follow, are errors.
+    let huge = [0xff; 8];
+    assert_eq!(DensityTracker::read(&huge[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    let mut huge = vec![0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    huge.extend_from_slice(&[0; 16]);
+    assert_eq!(DensityTracker::read(&huge[..]).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+    let empty = DensityTracker::new();
+    let mut bytes = vec![];
+    empty.write(&mut bytes).unwrap();
+    assert_eq!(DensityTracker::read(&bytes[..]).unwrap(), empty);
+}
@@ -0,0 +1,82 @@
+use ff::PrimeField;
+use group::{CurveProjective, Wnaf};
+
+use crate::multicore::MULTI_THREAD;
+
+type Scalar<G> = <<G as CurveProjective>::Scalar as PrimeField>::Repr;
+
+/// Fixed-base exponentiation with a wNAF window table. The table for the
+/// base is built once, sized for the number of scalars it is expected to
+/// be used with, and then shared by every multiplication, which makes this
+/// the cheaper choice when many scalars multiply the same base.
+///
+/// With the `glv` feature, scalars are split with the curve endomorphism,
+/// so the base must be in the prime order subgroup.
+pub struct FixedBaseMultiexp<G: CurveProjective> {
+    wnaf: Wnaf<usize, Vec<G>, Vec<i64>>
+}
+
+impl<G> FixedBaseMultiexp<G>
+where
+    G: CurveProjective
+{
+    /// Builds the window table for `base`, planned for `num_scalars`
+    /// multiplications.
+    pub fn new(base: G, num_scalars: usize) -> Self {
+        FixedBaseMultiexp {
+            wnaf: Wnaf::new().into_base(base, num_scalars)
+        }
+    }
+
+    /// Multiplies the base by `scalar`.
+    pub fn mul(&self, scalar: Scalar<G>) -> G {
+        self.wnaf.shared().scalar(scalar)
+    }
+
+    /// Multiplies the base by every scalar, spreading the work across the
+    /// worker pool. The results are in the same order as the scalars.
+    pub fn mul_all(&self, scalars: &[Scalar<G>]) -> Vec<G> {
+        let mut results: Vec<G> = vec![G::zero(); scalars.len()];
+
+        MULTI_THREAD.scope(scalars.len(), |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
+            for (scalars, results) in scalars.chunks(chunk_size).zip(results.chunks_mut(chunk_size)) {
+                let mut wnaf: _ = self.wnaf.shared();
+                scope.spawn(move || {
+                    for (scalar, result) in scalars.iter().zip(results.iter_mut()) {
+                        *result = wnaf.scalar(*scalar);
+                    }
+                });
+            }
+        });
+
+        results
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_fixed_base_multiexp() {
+    use ff::{Field, ScalarEngine};
+    use pairing::{bls12_381::Bls12, Engine};
+
+    let rng = &mut rand::thread_rng();
+
+    let base = <Bls12 as Engine>::G1::random(rng);
+    let scalars: Vec<_> = (0..100)
+        .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+        .collect();
+
+    let fixed = FixedBaseMultiexp::new(base, scalars.len());
+    let results = fixed.mul_all(&scalars);
+    assert_eq!(results.len(), scalars.len());
+
+    for (scalar, result) in scalars.iter().zip(results.iter()) {
+        let mut expected = base;
+        expected.mul_assign(*scalar);
+        assert_eq!(*result, expected);
+        assert_eq!(fixed.mul(*scalar), expected);
+    }
+
+    assert!(fixed.mul_all(&[]).is_empty());
+}
@@ -3,8 +3,10 @@ use futures::Future;
 use group::{CurveAffine, CurveProjective};
 use std::sync::Arc;
 
-use crate::error::SynthesisError;
-use super::{SourceBuilder, QueryDensity, Exponents, RegionCounter, SourceIter};
+use crate::error::{Result, SynthesisError};
+use super::{SourceBuilder, QueryDensity, Exponent, Exponents, RegionCounter, SourceIter};
+#[cfg(feature = "glv")]
+use super::SplitExponents;
 
 pub fn multiexp_inner<Q,D,G,S>(bases: S, density_map: D, exponents: Arc<Exponents<G>>, mut rc: RegionCounter) -> Box<dyn Future<Item=G::Projective, Error=SynthesisError>>
 where
@@ -19,21 +21,7 @@ where
         let bases: _ = bases.clone();
 
         MULTI_THREAD.compute(move || {
-            let mut bases: SourceIter<_> = bases.new();
-            bases.configure(rc);
-
-            let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << rc.get_cpu()) - 1];
-            let density_iter: _ = density_map.as_ref();
-            let mut forward_total: G::Projective = exponents.iter()
-                .zip(density_iter)
-                .fold(Ok(G::Projective::zero()), |accumulator, (exp, density)| {
-                    if density {
-                        accumulator.and_then(|acc| bases.try_sort(acc, &mut buckets, exp))
-                    } else { accumulator }
-                })?;
-
-            add_assign_by_parts::<G>(&mut forward_total, buckets);
-            Ok(forward_total)
+            bucket_region::<Q,G,S>(&bases, density_map.as_ref(), &exponents, rc)
         })
     };
     
@@ -53,6 +41,122 @@ where
     } else { Box::new(this_region) }
 }
 
+/// As `multiexp_inner`, over exponents split with the curve endomorphism
+/// into halves of at most `num_bits` bits.
+#[cfg(feature = "glv")]
+pub fn multiexp_split<Q,D,G,S>(bases: S, density_map: D, exponents: Arc<SplitExponents<G>>, num_bits: u32, mut rc: RegionCounter) -> Box<dyn Future<Item=G::Projective, Error=SynthesisError>>
+where
+    for<'a> &'a Q: QueryDensity,
+    D: Send + Sync + 'static + Clone + AsRef<Q>,
+    G: CurveAffine,
+    S: SourceBuilder<G> + Send,
+{
+    let this_region: _ = {
+        let exponents = exponents.clone();
+        let density_map = density_map.clone();
+        let bases: _ = bases.clone();
+
+        MULTI_THREAD.compute(move || {
+            bucket_region_split::<Q,G,S>(&bases, density_map.as_ref(), &exponents, rc)
+        })
+    };
+
+    rc.next_region();
+    if rc.still_more_bits(num_bits) {
+        Box::new(
+            this_region.join(
+                multiexp_split(bases, density_map, exponents, num_bits, rc)
+            ).map(move |(this, mut higher)| {
+                for _ in 0..rc.get_cpu() {
+                    higher.double();
+                }
+                higher.add_assign(&this);
+                higher
+            }),
+        )
+    } else { Box::new(this_region) }
+}
+
+/// Performs multi-exponentiation by bucketing every window below `num_bits`
+/// with `bucket` on its own scoped task, blocking until they complete. The
+/// windows may borrow from the caller, unlike those of `multiexp_inner`.
+pub fn multiexp_scoped<G,F>(mut rc: RegionCounter, num_bits: u32, bucket: F) -> Result<G::Projective>
+where
+    G: CurveAffine,
+    F: Fn(RegionCounter) -> Result<G::Projective> + Sync,
+{
+    let mut regions: Vec<RegionCounter> = Vec::new();
+    while rc.still_more_bits(num_bits) {
+        regions.push(rc);
+        rc.next_region();
+    }
+
+    let mut totals: Vec<Result<G::Projective>> = regions.iter()
+        .map(|_| Ok(G::Projective::zero()))
+        .collect();
+    MULTI_THREAD.scope(regions.len(), |scope, _| {
+        let bucket: &F = &bucket;
+        for (rc, total) in regions.iter().zip(totals.iter_mut()) {
+            scope.spawn(move || *total = bucket(*rc));
+        }
+    });
+
+    let mut acc: G::Projective = G::Projective::zero();
+    for (rc, total) in regions.iter().zip(totals).rev() {
+        for _ in 0..rc.get_cpu() {
+            acc.double();
+        }
+        acc.add_assign(&total?);
+    }
+    Ok(acc)
+}
+
+/// Buckets the bases of the dense exponents by their digit in the window of
+/// `rc` and sums the buckets.
+pub fn bucket_region<Q,G,S>(bases: &S, density_map: &Q, exponents: &[Exponent<G>], rc: RegionCounter) -> Result<G::Projective>
+where
+    for<'a> &'a Q: QueryDensity,
+    G: CurveAffine,
+    S: SourceBuilder<G>,
+{
+    let mut bases: SourceIter<_> = bases.new();
+    bases.configure(rc);
+
+    let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << rc.get_cpu()) - 1];
+    for (exp, density) in exponents.iter().zip(density_map) {
+        if density {
+            bases.try_into_bucket(&mut buckets, exp)?;
+        }
+    }
+
+    let mut forward_total: G::Projective = G::Projective::zero();
+    add_assign_by_parts::<G>(&mut forward_total, buckets);
+    Ok(forward_total)
+}
+
+/// As `bucket_region`, over exponents split with the curve endomorphism.
+#[cfg(feature = "glv")]
+pub fn bucket_region_split<Q,G,S>(bases: &S, density_map: &Q, exponents: &SplitExponents<G>, rc: RegionCounter) -> Result<G::Projective>
+where
+    for<'a> &'a Q: QueryDensity,
+    G: CurveAffine,
+    S: SourceBuilder<G>,
+{
+    let mut bases: SourceIter<_> = bases.new();
+    bases.configure(rc);
+
+    let mut buckets = vec![<G as CurveAffine>::Projective::zero(); (1 << rc.get_cpu()) - 1];
+    for ((k1, k2), density) in exponents.iter().zip(density_map) {
+        if density {
+            bases.try_into_buckets_split(&mut buckets, k1, k2)?;
+        }
+    }
+
+    let mut forward_total: G::Projective = G::Projective::zero();
+    add_assign_by_parts::<G>(&mut forward_total, buckets);
+    Ok(forward_total)
+}
+
 // Summation by parts
 // e.g. 3a + 2b + 1c = a +
 //                    (a) + b +
@@ -3,21 +3,34 @@ use ff::{PrimeField, ScalarEngine};
 use std::sync::Arc;
 use futures::Future;
 
-use crate::error::SynthesisError;
+use crate::error::{SynthesisError, LengthMismatch, Operand};
 
 mod density;
+mod fixed;
 mod inner;
+mod precomputed;
 mod region;
 mod source;
 
 pub use density::*;
+pub use fixed::*;
+pub use precomputed::*;
 use source::SourceIter;
 use region::RegionCounter;
 
-type Exponents<G> = Vec<<<<G as CurveAffine>::Engine as ScalarEngine>::Fr as PrimeField>::Repr>;
+type Exponent<G> = <<<G as CurveAffine>::Engine as ScalarEngine>::Fr as PrimeField>::Repr;
+type Exponents<G> = Vec<Exponent<G>>;
+#[cfg(feature = "glv")]
+type SplitExponents<G> = Vec<(<<G as CurveAffine>::Scalar as PrimeField>::Repr, <<G as CurveAffine>::Scalar as PrimeField>::Repr)>;
 
-/// Perform multi-exponentiation. The thread will panic if the
-/// query size is the not the same as the number of exponents.
+/// Perform multi-exponentiation. A `LengthMismatch` is returned if the
+/// query size is not the same as the number of exponents, or if there are
+/// fewer bases than exponents present in the query.
+///
+/// With the `glv` feature, exponents are split with the curve endomorphism,
+/// which assumes every base is in the prime order subgroup as `CurveAffine`
+/// promises. Checking each base here would cost more than the split saves,
+/// so `Parameters::read` checks them once as they are loaded instead.
 pub fn multiexp<Q,D,G,S>(bases: S, density_map: D, exponents: Arc<Exponents<G>>) -> Box<dyn Future<Item=G::Projective, Error=SynthesisError>>
 where
     for<'a> &'a Q: QueryDensity,
@@ -25,9 +38,154 @@ where
     G: CurveAffine,
     S: SourceBuilder<G>,
 {
-    let region: _ = RegionCounter::try_new::<G,Q>(&exponents, density_map.as_ref())
-        .expect("could not build region for multi-exponentiation");     
-    inner::multiexp_inner(bases, density_map, exponents, region)
+    if let Err(e) = try_check_bases::<Q,G,S>(&bases, density_map.as_ref(), &exponents) {
+        return Box::new(futures::future::err(e))
+    }
+
+    #[cfg(feature = "glv")]
+    {
+        if let Some((split, num_bits)) = split_exponents::<G>(&exponents) {
+            return match RegionCounter::try_new_split::<G,Q>(&exponents, density_map.as_ref(), num_bits) {
+                Ok(region) => inner::multiexp_split(bases, density_map, Arc::new(split), num_bits, region),
+                Err(e) => Box::new(futures::future::err(e))
+            }
+        }
+    }
+
+    match RegionCounter::try_new::<G,Q>(&exponents, density_map.as_ref()) {
+        Ok(region) => inner::multiexp_inner(bases, density_map, exponents, region),
+        Err(e) => Box::new(futures::future::err(e))
+    }
+}
+
+/// Performs multi-exponentiation over borrowed exponents and density,
+/// blocking until every window has been bucketed on the worker pool. This
+/// avoids moving the exponents into shared storage when the caller already
+/// holds them. Length mismatches are reported, and exponents split, as
+/// with `multiexp`.
+pub fn multiexp_slice<Q,G,S>(bases: S, density_map: &Q, exponents: &[Exponent<G>]) -> Result<G::Projective, SynthesisError>
+where
+    for<'a> &'a Q: QueryDensity,
+    Q: Sync,
+    G: CurveAffine,
+    S: SourceBuilder<G>,
+{
+    try_check_bases::<Q,G,S>(&bases, density_map, exponents)?;
+
+    #[cfg(feature = "glv")]
+    {
+        if let Some((split, num_bits)) = split_exponents::<G>(exponents) {
+            let region: _ = RegionCounter::try_new_split::<G,Q>(exponents, density_map, num_bits)?;
+            return inner::multiexp_scoped::<G,_>(region, num_bits, |rc| {
+                inner::bucket_region_split::<Q,G,S>(&bases, density_map, &split, rc)
+            })
+        }
+    }
+
+    let region: _ = RegionCounter::try_new::<G,Q>(exponents, density_map)?;
+    let num_bits: u32 = <G::Engine as ScalarEngine>::Fr::NUM_BITS;
+    inner::multiexp_scoped::<G,_>(region, num_bits, |rc| {
+        inner::bucket_region::<Q,G,S>(&bases, density_map, exponents, rc)
+    })
+}
+
+/// Performs multi-exponentiation with windows of `window` bits rather than
+/// the planned size, and without splitting the exponents, so that a window
+/// found to suit a machine better can be used. Panics if the window is not
+/// between 1 and 20 bits.
+pub fn multiexp_with_window<Q,G,S>(bases: S, density_map: &Q, exponents: &[Exponent<G>], window: u32) -> Result<G::Projective, SynthesisError>
+where
+    for<'a> &'a Q: QueryDensity,
+    Q: Sync,
+    G: CurveAffine,
+    S: SourceBuilder<G>,
+{
+    try_check_bases::<Q,G,S>(&bases, density_map, exponents)?;
+
+    let region: _ = RegionCounter::with_window(window);
+    let num_bits: u32 = <G::Engine as ScalarEngine>::Fr::NUM_BITS;
+    inner::multiexp_scoped::<G,_>(region, num_bits, |rc| {
+        inner::bucket_region::<Q,G,S>(&bases, density_map, exponents, rc)
+    })
+}
+
+/// The window size, in bits, that `multiexp` plans for `dense` exponents
+/// on the worker pool of the calling thread.
+pub fn planned_window<G>(dense: usize) -> u32
+where
+    G: CurveAffine
+{
+    let num_bits: u32 = <G::Engine as ScalarEngine>::Fr::NUM_BITS;
+    RegionCounter::window_size(dense, num_bits, planned_cpus())
+}
+
+/// The number of threads a multi-exponentiation is planned for. In
+/// deterministic mode the plan must not depend on the machine, so a single
+/// thread is assumed.
+fn planned_cpus() -> usize {
+    use crate::multicore::MULTI_THREAD;
+
+    if MULTI_THREAD.is_deterministic() {
+        1
+    } else {
+        1 << MULTI_THREAD.log_num_cpus()
+    }
+}
+
+/// Checks that `bases` has a base for every exponent present in
+/// `density_map`, and that the query covers exactly the exponents.
+fn try_check_bases<Q,G,S>(bases: &S, density_map: &Q, exponents: &[Exponent<G>]) -> Result<(), SynthesisError>
+where
+    for<'a> &'a Q: QueryDensity,
+    G: CurveAffine,
+    S: SourceBuilder<G>,
+{
+    let dense: usize = RegionCounter::try_dense::<G,Q>(exponents, density_map)?;
+    let available: usize = bases.new().remaining();
+    if available < dense {
+        return Err(SynthesisError::LengthMismatch(LengthMismatch {
+            query: None,
+            operand: Operand::Bases,
+            expected: dense,
+            actual: available
+        }))
+    }
+    Ok(())
+}
+
+/// Splits every exponent with the curve endomorphism, if the curve has one,
+/// returning the halves and the width of the widest half.
+#[cfg(feature = "glv")]
+fn split_exponents<G>(exponents: &[Exponent<G>]) -> Option<(SplitExponents<G>, u32)>
+where
+    G: CurveAffine
+{
+    use ff::PrimeFieldRepr;
+    use crate::multicore::MULTI_THREAD;
+
+    G::decompose_scalar(&Default::default())?;
+
+    let mut split: SplitExponents<G> = vec![Default::default(); exponents.len()];
+    MULTI_THREAD.scope(exponents.len(), |scope, chunk_size| {
+        let chunk_size: usize = chunk_size.max(1);
+        for (exponents, split) in exponents.chunks(chunk_size).zip(split.chunks_mut(chunk_size)) {
+            scope.spawn(move || {
+                for (exp, halves) in exponents.iter().zip(split.iter_mut()) {
+                    *halves = G::decompose_scalar(exp)
+                        .expect("the curve decomposes every scalar");
+                }
+            });
+        }
+    });
+
+    // Windows above the widest half only hold zero digits, so they are not
+    // bucketed at all.
+    let num_bits: u32 = split.iter()
+        .map(|(k1, k2)| k1.num_bits().max(k2.num_bits()))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    Some((split, num_bits))
 }
 
 /// An object that builds a source of bases.
@@ -88,3 +246,241 @@ fn test_with_bls12() {
 
     assert_eq!(naive, fast);
 }
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_sparse_density_with_bls12() {
+    use ff::{Field, PrimeField};
+    use group::CurveProjective;
+    use pairing::{bls12_381::Bls12, Engine};
+    use rand;
+
+    const SAMPLES: usize = 1 << 10;
+
+    let rng = &mut rand::thread_rng();
+    let v = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+            .collect::<Vec<_>>(),
+    );
+
+    // Only every eighth element is present in the query, so the source
+    // holds just those bases.
+    let mut density = DensityTracker::new();
+    let mut naive = <Bls12 as Engine>::G1::zero();
+    let mut g = vec![];
+    for (i, exp) in v.iter().enumerate() {
+        density.add_element();
+        if i % 8 == 0 {
+            density.inc(i);
+
+            let base = <Bls12 as Engine>::G1::random(rng).into_affine();
+            naive.add_assign(&base.mul(*exp));
+            g.push(base);
+        }
+    }
+
+    assert_eq!(density.get_total_density(), SAMPLES / 8);
+    let _lock = crate::multicore::lock_default_parallelism();
+    let rc = RegionCounter::try_new::<<Bls12 as Engine>::G1Affine, _>(&v, &density).unwrap();
+    let num_bits = <Bls12 as ScalarEngine>::Fr::NUM_BITS;
+    let cpus = 1 << crate::multicore::MULTI_THREAD.log_num_cpus();
+    assert_eq!(rc.get_cpu(), RegionCounter::window_size(SAMPLES / 8, num_bits, cpus));
+
+    let fast = multiexp((Arc::new(g), 0), Arc::new(density), v).wait().unwrap();
+
+    assert_eq!(naive, fast);
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_multiexp_slice_with_bls12() {
+    use ff::Field;
+    use group::CurveProjective;
+    use pairing::{bls12_381::Bls12, Engine};
+    use rand::SeedableRng;
+
+    const SAMPLES: usize = 1 << 10;
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+    let v = (0..SAMPLES)
+        .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+        .collect::<Vec<_>>();
+    let g = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as Engine>::G1::random(rng).into_affine())
+            .collect::<Vec<_>>(),
+    );
+
+    // Borrowed exponents agree with the shared ones.
+    let expected = multiexp((g.clone(), 0), FullDensity, Arc::new(v.clone())).wait().unwrap();
+    assert_eq!(multiexp_slice((g.clone(), 0), &FullDensity, &v).unwrap(), expected);
+
+    let mut density = DensityTracker::new();
+    for i in 0..SAMPLES {
+        density.add_element();
+        if i % 5 == 0 {
+            density.inc(i);
+        }
+    }
+    let expected = multiexp((g.clone(), 3), Arc::new(density.clone()), Arc::new(v.clone())).wait().unwrap();
+    assert_eq!(multiexp_slice((g.clone(), 3), &density, &v).unwrap(), expected);
+
+    // Any window agrees with the planned one.
+    for window in [1, 7, 16].iter() {
+        assert_eq!(multiexp_with_window((g.clone(), 3), &density, &v, *window).unwrap(), expected);
+    }
+
+    // Running out of bases is an error rather than a panic, and reports how
+    // many bases were needed.
+    match multiexp_slice((g.clone(), SAMPLES - 1), &FullDensity, &v) {
+        Err(SynthesisError::LengthMismatch(mismatch)) => {
+            assert_eq!(mismatch, LengthMismatch { query: None, operand: Operand::Bases, expected: SAMPLES, actual: 1 });
+        },
+        _ => panic!("expected a length mismatch")
+    }
+
+    // As does a density map over a different number of exponents.
+    match multiexp_slice((g, 0), &density, &v[1..]) {
+        Err(e @ SynthesisError::LengthMismatch(_)) => {
+            assert_eq!(
+                e.in_query(crate::error::Query::B1).to_string(),
+                format!("multi-exponentiation length mismatch in the b1 query: expected {} exponents but found {}", SAMPLES, SAMPLES - 1)
+            );
+        },
+        _ => panic!("expected a length mismatch")
+    }
+    assert_eq!(
+        multiexp_slice((Arc::new(Vec::<<Bls12 as Engine>::G1Affine>::new()), 0), &FullDensity, &v[..0]).unwrap(),
+        <Bls12 as Engine>::G1::zero()
+    );
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_deterministic_reduction() {
+    use ff::Field;
+    use group::CurveProjective;
+    use pairing::{bls12_381::Bls12, Engine};
+    use crate::multicore::Worker;
+
+    const SAMPLES: usize = 2500;
+
+    let rng = &mut rand::thread_rng();
+    let g = Arc::new((0..SAMPLES)
+        .map(|_| <Bls12 as Engine>::G1::random(rng).into_affine())
+        .collect::<Vec<_>>());
+    let v = Arc::new((0..SAMPLES)
+        .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+        .collect::<Vec<_>>());
+    let tables = PrecomputedBases::with_window(&g, 8);
+
+    // The projective coordinates, which unlike equality of points depend on
+    // the order of additions.
+    let coordinates = |threads: usize| {
+        Worker::with_threads(threads).with_deterministic(true).install(|| {
+            let slice = multiexp_slice((g.clone(), 0), &FullDensity, &v).unwrap();
+            let precomputed = multiexp_precomputed(&tables, 0, FullDensity, v.clone()).wait().unwrap();
+            format!("{:?} {:?}", slice, precomputed)
+        })
+    };
+
+    assert_eq!(coordinates(1), coordinates(4));
+}
+
+#[test]
+fn test_window_size() {
+    // Larger multi-exponentiations amortise more buckets per window.
+    assert_eq!(RegionCounter::window_size(1 << 7, 255, 1), 5);
+    assert_eq!(RegionCounter::window_size(1 << 10, 255, 1), 7);
+    assert_eq!(RegionCounter::window_size(1 << 16, 255, 1), 12);
+
+    // With more threads than windows, narrower windows keep every thread busy.
+    assert!(RegionCounter::window_size(1 << 16, 255, 16) < RegionCounter::window_size(1 << 16, 255, 1));
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_window_digits() {
+    use ff::{Field, PrimeField, PrimeFieldRepr};
+    use pairing::bls12_381::{Fr, G1Affine};
+    use rand::SeedableRng;
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+
+    // Windows which straddle limb boundaries agree with shifting the exponent.
+    for _ in 0..100 {
+        let exp = Fr::random(rng).into_repr();
+        for &window in &[1, 5, 7, 12, 20] {
+            let mut rc = RegionCounter::with_window(window);
+            while rc.still_more_regions::<G1Affine>() {
+                let mut shifted = exp;
+                shifted.shr(rc.get_count());
+                let expected = shifted.as_ref()[0] % (1 << window);
+                assert_eq!(rc.digit::<G1Affine>(&exp) as u64, expected);
+                rc.next_region();
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "pairing", feature = "glv"))]
+#[test]
+fn test_split_exponents_with_bls12() {
+    use ff::Field;
+    use group::CurveProjective;
+    use pairing::{bls12_381::Bls12, Engine};
+    use rand::SeedableRng;
+
+    const SAMPLES: usize = 1 << 10;
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+    let mut v = (0..SAMPLES)
+        .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+        .collect::<Vec<_>>();
+    // Zero exponents and the largest exponent decompose without overflow.
+    v[1] = <Bls12 as ScalarEngine>::Fr::zero().into_repr();
+    let mut max = <Bls12 as ScalarEngine>::Fr::zero();
+    max.sub_assign(&<Bls12 as ScalarEngine>::Fr::one());
+    v[2] = max.into_repr();
+    let v = Arc::new(v);
+
+    let g1 = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as Engine>::G1::random(rng).into_affine())
+            .collect::<Vec<_>>(),
+    );
+    let (_, num_bits) = split_exponents::<<Bls12 as Engine>::G1Affine>(&v).unwrap();
+    assert!(num_bits <= 128);
+
+    // The split path agrees with bucketing the full exponents.
+    let mut density = DensityTracker::new();
+    let mut sparse = vec![];
+    for (i, base) in g1.iter().enumerate() {
+        density.add_element();
+        if i % 3 != 1 {
+            density.inc(i);
+            sparse.push(*base);
+        }
+    }
+    let density = Arc::new(density);
+    let sparse = (Arc::new(sparse), 0);
+    let rc = RegionCounter::try_new::<<Bls12 as Engine>::G1Affine, _>(&v, density.as_ref()).unwrap();
+    let expected = inner::multiexp_inner(sparse.clone(), density.clone(), v.clone(), rc).wait().unwrap();
+    assert_eq!(multiexp(sparse, density, v.clone()).wait().unwrap(), expected);
+
+    // G2 has no endomorphism, so its exponents are not split.
+    assert!(split_exponents::<<Bls12 as Engine>::G2Affine>(&v).is_none());
+    let g2 = Arc::new(vec![<Bls12 as Engine>::G2::random(rng).into_affine(); SAMPLES]);
+    let mut expected = <Bls12 as Engine>::G2::zero();
+    for exp in v.iter() {
+        expected.add_assign(&g2[0].mul(*exp));
+    }
+    assert_eq!(multiexp((g2, 0), FullDensity, v).wait().unwrap(), expected);
+}
@@ -0,0 +1,266 @@
+use ff::{PrimeField, ScalarEngine};
+use futures::Future;
+use group::{CurveAffine, CurveProjective};
+use std::sync::Arc;
+
+use crate::error::{SynthesisError, LengthMismatch, Operand};
+use crate::multicore::MULTI_THREAD;
+use super::{planned_cpus, Exponents, QueryDensity, RegionCounter};
+
+/// Windowed tables for a fixed set of bases. For each base `B` the table
+/// holds `2^(window * j) * B` for every window `j` of a scalar, so a
+/// multi-exponentiation over these bases fills a single set of buckets
+/// instead of one per window. This trades `windows` times the memory of the
+/// bases for less work on every proof that reuses them.
+pub struct PrecomputedBases<G: CurveAffine> {
+    window: u32,
+    windows: usize,
+    tables: Arc<Vec<G>>,
+}
+
+impl<G> PrecomputedBases<G>
+where
+    G: CurveAffine
+{
+    /// Builds tables for `bases` with a window planned from their number and
+    /// the worker pool.
+    pub fn new(bases: &[G]) -> Self {
+        let window: u32 = Self::window_size(bases.len(), Self::num_bits(), planned_cpus());
+        Self::with_window(bases, window)
+    }
+
+    /// Builds tables for `bases` with windows of `window` bits.
+    pub fn with_window(bases: &[G], window: u32) -> Self {
+        // Panics on an unsupported window, as the digits are read with it.
+        RegionCounter::with_window(window);
+
+        let windows: usize = ((Self::num_bits() + window - 1) / window) as usize;
+        let mut tables: Vec<G::Projective> = vec![G::Projective::zero(); bases.len() * windows];
+
+        MULTI_THREAD.scope(bases.len(), |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
+            for (bases, tables) in bases.chunks(chunk_size).zip(tables.chunks_mut(chunk_size * windows)) {
+                scope.spawn(move || {
+                    for (base, table) in bases.iter().zip(tables.chunks_mut(windows)) {
+                        let mut shifted: G::Projective = base.into_projective();
+                        for entry in table.iter_mut() {
+                            *entry = shifted;
+                            for _ in 0..window {
+                                shifted.double();
+                            }
+                        }
+                    }
+                    G::Projective::batch_normalization(tables);
+                });
+            }
+        });
+
+        PrecomputedBases {
+            window,
+            windows,
+            tables: Arc::new(tables.iter().map(|p| p.into_affine()).collect()),
+        }
+    }
+
+    /// The window size, in bits, used for precomputed tables over `dense`
+    /// exponents. Every window shares one set of buckets per thread, so
+    /// the cost is one addition per exponent per window, spread across the
+    /// threads, and two per bucket to sum them.
+    pub fn window_size(dense: usize, num_bits: u32, cpus: usize) -> u32 {
+        let cost = |window: u32| -> u64 {
+            let windows: u64 = u64::from((num_bits + window - 1) / window);
+            let per_thread: u64 = (dense as u64 + cpus as u64 - 1) / cpus as u64;
+            per_thread * windows + (2 << window)
+        };
+
+        (1..=16)
+            .min_by_key(|window| cost(*window))
+            .expect("the planner considers at least one window")
+    }
+
+    /// The number of bases the tables were built for.
+    pub fn len(&self) -> usize {
+        self.tables.len() / self.windows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window
+    }
+
+    fn num_bits() -> u32 {
+        <G::Engine as ScalarEngine>::Fr::NUM_BITS
+    }
+}
+
+impl<G: CurveAffine> Clone for PrecomputedBases<G> {
+    fn clone(&self) -> Self {
+        PrecomputedBases {
+            window: self.window,
+            windows: self.windows,
+            tables: self.tables.clone(),
+        }
+    }
+}
+
+/// Performs multi-exponentiation over precomputed bases, starting from the
+/// base at `skip`. As with `multiexp`, only the exponents present in the
+/// density map consume a base, and length mismatches are reported as with
+/// `multiexp`. In deterministic mode the result is only reproducible across
+/// machines if the tables were built with the same window, so they should be
+/// built with `with_window` or inside the deterministic worker.
+pub fn multiexp_precomputed<Q,D,G>(bases: &PrecomputedBases<G>, skip: usize, density_map: D, exponents: Arc<Exponents<G>>) -> Box<dyn Future<Item=G::Projective, Error=SynthesisError>>
+where
+    for<'a> &'a Q: QueryDensity,
+    D: Send + Sync + 'static + Clone + AsRef<Q>,
+    G: CurveAffine,
+{
+    if let Some(query_size) = density_map.as_ref().get_query_size() {
+        if query_size != exponents.len() {
+            return Box::new(futures::future::err(SynthesisError::LengthMismatch(LengthMismatch {
+                query: None,
+                operand: Operand::Exponents,
+                expected: query_size,
+                actual: exponents.len()
+            })))
+        }
+    }
+
+    // Pair every dense exponent with the base it consumes.
+    let mut dense: Vec<(usize, usize)> = Vec::new();
+    for (position, present) in (0..exponents.len()).zip(density_map.as_ref()) {
+        if present {
+            dense.push((skip + dense.len(), position));
+        }
+    }
+    if dense.last().map_or(false, |(base, _)| *base >= bases.len()) {
+        return Box::new(futures::future::err(SynthesisError::LengthMismatch(LengthMismatch {
+            query: None,
+            operand: Operand::Bases,
+            expected: dense.len(),
+            actual: bases.len().saturating_sub(skip)
+        })))
+    }
+
+    // In deterministic mode the parts are cut to a fixed size rather than
+    // one per thread, so that they are summed the same way on any machine.
+    let dense: Arc<Vec<(usize, usize)>> = Arc::new(dense);
+    let chunk_size: usize = if MULTI_THREAD.is_deterministic() {
+        DETERMINISTIC_CHUNK
+    } else {
+        let cpus: usize = 1 << MULTI_THREAD.log_num_cpus();
+        ((dense.len() + cpus - 1) / cpus).max(1)
+    };
+
+    let mut parts: Vec<Box<dyn Future<Item=G::Projective, Error=SynthesisError>>> = Vec::new();
+    for start in (0..dense.len()).step_by(chunk_size) {
+        let bases: PrecomputedBases<G> = bases.clone();
+        let dense = dense.clone();
+        let exponents = exponents.clone();
+
+        parts.push(Box::new(MULTI_THREAD.compute(move || {
+            let end: usize = (start + chunk_size).min(dense.len());
+            let mut buckets = vec![G::Projective::zero(); (1 << bases.window) - 1];
+
+            for (base, position) in dense[start..end].iter() {
+                let exp = &exponents[*position];
+                let table: &[G] = &bases.tables[base * bases.windows..(base + 1) * bases.windows];
+
+                let mut rc: RegionCounter = RegionCounter::with_window(bases.window);
+                for entry in table.iter() {
+                    let digit: usize = rc.digit::<G>(exp);
+                    if digit != 0 {
+                        buckets[digit - 1].add_assign_mixed(entry);
+                    }
+                    rc.next_region();
+                }
+            }
+
+            // Summation by parts, as in the windowed multiexp.
+            let mut acc: G::Projective = G::Projective::zero();
+            let mut sigma: G::Projective = G::Projective::zero();
+            for bucket in buckets.iter().rev() {
+                sigma.add_assign(bucket);
+                acc.add_assign(&sigma);
+            }
+            Ok(acc)
+        })));
+    }
+
+    Box::new(futures::future::join_all(parts).map(tree_sum))
+}
+
+/// The number of dense exponents in each part of a deterministic
+/// `multiexp_precomputed`.
+const DETERMINISTIC_CHUNK: usize = 1 << 10;
+
+/// Sums `parts` pairwise, then the pairs pairwise, and so on, so that the
+/// order of additions depends only on the number of parts.
+fn tree_sum<G: CurveProjective>(mut parts: Vec<G>) -> G {
+    while parts.len() > 1 {
+        parts = parts.chunks(2)
+            .map(|pair| {
+                let mut sum: G = pair[0];
+                if let Some(other) = pair.get(1) {
+                    sum.add_assign(other);
+                }
+                sum
+            })
+            .collect();
+    }
+    parts.pop().unwrap_or_else(G::zero)
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_precomputed_bases() {
+    use ff::Field;
+    use pairing::{bls12_381::Bls12, Engine};
+    use rand::SeedableRng;
+    use super::{multiexp, DensityTracker, FullDensity};
+
+    const SAMPLES: usize = 1 << 9;
+
+    let rng = &mut rand_xorshift::XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc, 0xe5,
+    ]);
+    let v = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as ScalarEngine>::Fr::random(rng).into_repr())
+            .collect::<Vec<_>>(),
+    );
+    let g = Arc::new(
+        (0..SAMPLES)
+            .map(|_| <Bls12 as Engine>::G1::random(rng).into_affine())
+            .collect::<Vec<_>>(),
+    );
+
+    let expected = multiexp((g.clone(), 0), FullDensity, v.clone()).wait().unwrap();
+    for &window in &[1, 7, 16] {
+        let tables = PrecomputedBases::with_window(&g, window);
+        assert_eq!(tables.len(), SAMPLES);
+        let fast = multiexp_precomputed(&tables, 0, FullDensity, v.clone()).wait().unwrap();
+        assert_eq!(fast, expected);
+    }
+
+    // Sparse queries consume bases from `skip` onwards.
+    let tables = PrecomputedBases::new(&g);
+    let mut density = DensityTracker::new();
+    for i in 0..SAMPLES / 2 {
+        density.add_element();
+        if i % 3 == 0 {
+            density.inc(i);
+        }
+    }
+    let exps = Arc::new(v[..SAMPLES / 2].to_vec());
+    let density = Arc::new(density);
+    let expected = multiexp((g.clone(), 7), density.clone(), exps.clone()).wait().unwrap();
+    let fast = multiexp_precomputed(&tables, 7, density, exps).wait().unwrap();
+    assert_eq!(fast, expected);
+
+    // Running out of bases is an error rather than a panic.
+    assert!(multiexp_precomputed(&tables, SAMPLES - 1, FullDensity, v).wait().is_err());
+}
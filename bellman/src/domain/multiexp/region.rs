@@ -1,67 +1,140 @@
-use ff::{PrimeField, PrimeFieldRepr, ScalarEngine};
+use ff::{PrimeField, ScalarEngine};
 use group::CurveAffine;
-use std::io;
-use std::sync::Arc;
 
-use crate::error::Result;
-use super::{QueryDensity, Exponents};
+use crate::error::{Result, SynthesisError, LengthMismatch, Operand};
+use super::{planned_cpus, QueryDensity, Exponent};
 
-/// A structure to maintain awareness of the current region
-/// so that the multithreading will consider separate parts 
-/// of the source of bases.
+/// The plan for a bucket-method multi-exponentiation. Exponents are cut
+/// into windows of `cpu` bits, each of which is bucketed by its own task,
+/// and `count` is the bit offset of the current window.
 #[derive(Copy, Clone)]
 pub struct RegionCounter {
     count: u32,
-    cpu: u32,
-    handle_trivial: bool
+    cpu: u32
 }
 
+/// The largest window considered by the planner. Beyond this the buckets
+/// no longer fit comfortably in cache.
+const MAX_WINDOW: u32 = 20;
+
 impl RegionCounter {
-    pub fn try_new<G,Q>(exponents: &Arc<Exponents<G>>, density_query: &Q) -> Result<Self> 
+    pub fn try_new<G,Q>(exponents: &[Exponent<G>], density_query: &Q) -> Result<Self> 
+    where
+        G: CurveAffine,
+        for <'a> &'a Q: QueryDensity
+    {
+        let dense: usize = Self::try_dense::<G,Q>(exponents, density_query)?;
+        let num_bits: u32 = <G::Engine as ScalarEngine>::Fr::NUM_BITS;
+        let cpus: usize = planned_cpus();
+        let cpu: u32 = Self::window_size(dense, num_bits, cpus);
+
+        Ok(RegionCounter {
+            cpu,
+            count: 0
+        })
+    }
+
+    /// A plan for exponents split with the curve endomorphism into halves of
+    /// at most `num_bits` bits. Every dense exponent buckets two bases per
+    /// window, one for each half.
+    #[cfg(feature = "glv")]
+    pub fn try_new_split<G,Q>(exponents: &[Exponent<G>], density_query: &Q, num_bits: u32) -> Result<Self>
+    where
+        G: CurveAffine,
+        for <'a> &'a Q: QueryDensity
+    {
+        let dense: usize = Self::try_dense::<G,Q>(exponents, density_query)?;
+        let cpus: usize = planned_cpus();
+        let cpu: u32 = Self::window_size(2 * dense, num_bits, cpus);
+
+        Ok(RegionCounter {
+            cpu,
+            count: 0
+        })
+    }
+
+    /// The number of exponents present in `density_query`, each of which
+    /// consumes a base.
+    pub(super) fn try_dense<G,Q>(exponents: &[Exponent<G>], density_query: &Q) -> Result<usize>
     where
         G: CurveAffine,
         for <'a> &'a Q: QueryDensity
     {
         if let Some(query_size) = density_query.get_query_size() {
             if query_size != exponents.len() {
-                Err(io::Error::new(io::ErrorKind::InvalidInput, "exected length of exponents to match query size"))?
+                Err(SynthesisError::LengthMismatch(LengthMismatch {
+                    query: None,
+                    operand: Operand::Exponents,
+                    expected: query_size,
+                    actual: exponents.len()
+                }))?
             }
         }
 
-        let cpu = if exponents.len() < 32 {
-            3_u32
-        } else {
-            let casted_size_of_exp: _ = f64::from(exponents.len() as u32);
-            let log_n: _ = casted_size_of_exp.ln();
-            let casted_cpu: u32 = log_n.ceil() as u32;
-            casted_cpu            
-        };
+        // Only the elements present in the query are bucketed, so the window
+        // is planned from the density recorded during synthesis if we have it.
+        Ok(density_query.get_total_density()
+            .unwrap_or_else(|| exponents.len()))
+    }
 
-        Ok(RegionCounter {
-            cpu,
-            handle_trivial: true,
+    /// A plan which uses windows of `window` bits regardless of the size of
+    /// the multi-exponentiation.
+    pub fn with_window(window: u32) -> Self {
+        assert!(window > 0 && window <= MAX_WINDOW, "window must be between 1 and {} bits", MAX_WINDOW);
+        RegionCounter {
+            cpu: window,
             count: 0
-        })
+        }
     }
 
-    pub fn adjust_exponent_by_region<T>(&self, mut exp: <T::Scalar as ff::PrimeField>::Repr) -> u64 
+    /// The window size, in bits, used for a multi-exponentiation over
+    /// `dense` exponents of `num_bits` bits with `cpus` threads available.
+    ///
+    /// Each window costs one addition per exponent to fill its buckets and
+    /// two per bucket to sum them, and the windows are spread across the
+    /// threads, so the window minimising the cost of the busiest thread is
+    /// chosen.
+    pub fn window_size(dense: usize, num_bits: u32, cpus: usize) -> u32 {
+        let cost = |window: u32| -> u64 {
+            let windows: u64 = u64::from((num_bits + window - 1) / window);
+            let rounds: u64 = (windows + cpus as u64 - 1) / cpus as u64;
+            rounds * (dense as u64 + (2 << window))
+        };
+
+        (1..=MAX_WINDOW)
+            .min_by_key(|window| cost(*window))
+            .expect("the planner considers at least one window")
+    }
+
+    /// The digit of `exp` in the current window.
+    pub fn digit<T>(&self, exp: &<T::Scalar as PrimeField>::Repr) -> usize 
     where
         T: CurveAffine
     {
-        let skip: _ = self.get_count();
-        exp.shr(skip);
+        let limbs: &[u64] = exp.as_ref();
+        let limb: usize = (self.count / 64) as usize;
+        let bit: u32 = self.count % 64;
+        if limb >= limbs.len() {
+            return 0
+        }
 
-        let adjusted: _ = exp.as_ref();
-        let cpu: _ = self.get_cpu();
-        let cpu_adjusted: _ = adjusted[0] % (1 << cpu);
-        cpu_adjusted
+        let mut digit: u64 = limbs[limb] >> bit;
+        if bit + self.cpu > 64 && limb + 1 < limbs.len() {
+            digit |= limbs[limb + 1] << (64 - bit);
+        }
+        (digit & ((1 << self.cpu) - 1)) as usize
     }
 
     pub fn still_more_regions<U>(&self) -> bool 
     where
         U: CurveAffine
     {
-        self.count < <U::Engine as ScalarEngine>::Fr::NUM_BITS
+        self.still_more_bits(<U::Engine as ScalarEngine>::Fr::NUM_BITS)
+    }
+
+    /// Whether the current window starts below `num_bits`.
+    pub fn still_more_bits(&self, num_bits: u32) -> bool {
+        self.count < num_bits
     }
 
     pub fn get_count(&self) -> u32 {
@@ -72,12 +145,7 @@ impl RegionCounter {
         self.cpu
     }
 
-    pub fn handle_trivial(&self) -> bool {
-        self.handle_trivial
-    }
-
     pub fn next_region(&mut self) {
-        self.handle_trivial = false;
         self.count += self.cpu;
     }
 }
@@ -85,7 +153,6 @@ impl RegionCounter {
 impl Default for RegionCounter {
     fn default() -> Self {
         RegionCounter {
-            handle_trivial: true,
             count: 0,
             cpu: 1
         }
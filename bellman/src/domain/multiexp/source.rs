@@ -1,4 +1,4 @@
-use ff::{Field, PrimeField, ScalarEngine};
+use ff::PrimeField;
 use group::{CurveAffine, CurveProjective};
 use std::io;
 use std::sync::Arc;
@@ -22,6 +22,11 @@ impl<'a,G> SourceIter<'a,G> {
         }
     }
 
+    /// The number of bases left in the source.
+    pub fn remaining(&self) -> usize {
+        self.elements.len().saturating_sub(self._count)
+    }
+
     pub fn skip_forward(&mut self, amt: usize) {
         self._count += amt;
     }
@@ -35,40 +40,41 @@ impl<'a,G> SourceIter<'a,G>
 where
     G: CurveAffine
 {
-    pub fn try_sort(&mut self, mut acc: G::Projective, buckets: &mut Vec<G::Projective>, exp: &<G::Scalar as ff::PrimeField>::Repr) -> Result<G::Projective> {
-        let settings: _ = &mut self.rc;
-        let ref zero: _ = Self::repr_zero();
-        let ref one: _ = Self::repr_one();
+    /// Adds the next base into the bucket for the digit of `exp` in the
+    /// current window. Bases whose digit is zero are skipped.
+    pub fn try_into_bucket(&mut self, buckets: &mut [G::Projective], exp: &<G::Scalar as PrimeField>::Repr) -> Result<()> {
+        let digit: usize = self.rc.digit::<G>(exp);
 
-        if exp == zero || (exp == one && !settings.handle_trivial()) {
-            self.skip_forward(1)
-        } else if exp == one && settings.handle_trivial() {
-            try_add_assign_mixed(&mut acc, self)?
-        } else {
-            self.try_into_bucket(buckets, exp)?
-        }
-        Ok(acc)
-    }
-
-    fn try_into_bucket(&mut self, buckets: &mut Vec<G::Projective>, exp: &<G::Scalar as ff::PrimeField>::Repr) -> Result<()> {
-        let adjustment_source: _ = exp.clone();
-        let adjusted_exponent: _ = self.rc.adjust_exponent_by_region::<G>(adjustment_source);
-
-        if adjusted_exponent != 0 {
-            let bucket: _ = &mut buckets[(adjusted_exponent - 1) as usize];
-            try_add_assign_mixed(bucket, self)?
+        if digit != 0 {
+            try_add_assign_mixed(&mut buckets[digit - 1], self)?
         } else {
             self.skip_forward(1)
         };
         Ok(())
     }
 
-    fn repr_zero() -> <G::Scalar as ff::PrimeField>::Repr {
-        <G::Engine as ScalarEngine>::Fr::zero().into_repr()
-    }
+    /// Adds the next base into the bucket for the digit of `k1`, and its
+    /// image under the curve endomorphism into the bucket for the digit of
+    /// `k2`, in the current window.
+    #[cfg(feature = "glv")]
+    pub fn try_into_buckets_split(&mut self, buckets: &mut [G::Projective], k1: &<G::Scalar as PrimeField>::Repr, k2: &<G::Scalar as PrimeField>::Repr) -> Result<()> {
+        let first: usize = self.rc.digit::<G>(k1);
+        let second: usize = self.rc.digit::<G>(k2);
+        if first == 0 && second == 0 {
+            self.skip_forward(1);
+            return Ok(())
+        }
 
-    fn repr_one() -> <G::Scalar as ff::PrimeField>::Repr {
-        <G::Engine as ScalarEngine>::Fr::one().into_repr()
+        let base: &G = try_next_base(self)?;
+        if first != 0 {
+            buckets[first - 1].add_assign_mixed(base);
+        }
+        if second != 0 {
+            let image: G = base.endomorphism()
+                .expect("split exponents require a curve endomorphism");
+            buckets[second - 1].add_assign_mixed(&image);
+        }
+        Ok(())
     }
 }
 
@@ -85,6 +91,13 @@ impl<'a,G> Iterator for SourceIter<'a,G> {
 }
 
 fn try_add_assign_mixed<G>(lhs: &mut G::Projective, bases: &mut SourceIter<'_,G>) -> Result<()> 
+where
+    G: CurveAffine
+{
+    try_next_base(bases).map(|base| lhs.add_assign_mixed(base))
+}
+
+fn try_next_base<'a,G>(bases: &mut SourceIter<'a,G>) -> Result<&'a G>
 where
     G: CurveAffine
 {
@@ -92,8 +105,7 @@ where
         .ok_or(io::Error::new(io::ErrorKind::UnexpectedEof, "expected more bases from source").into())
         .and_then(|base| {
             if !base.is_zero() {
-                lhs.add_assign_mixed(base);
-                Ok(())
+                Ok(base)
             } else { Err(SynthesisError::UnexpectedIdentity) }
         })
 }
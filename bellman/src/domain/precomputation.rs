@@ -0,0 +1,89 @@
+use ff::{Field, ScalarEngine};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::{fft, Domain};
+
+/// The powers of the roots of unity of evaluation domains, kept by domain
+/// size so that proofs over the same domain stop recomputing them. A domain
+/// given the tables with `Domain::with_precomputation` runs its transforms
+/// in place over them. The cache is filled as domains ask for it and can be
+/// shared across threads and proofs behind an `Arc`.
+pub struct DomainPrecomputation<E: ScalarEngine> {
+    tables: Mutex<HashMap<usize, Arc<Twiddles<E>>>>
+}
+
+impl<E> DomainPrecomputation<E>
+where
+    E: ScalarEngine
+{
+    pub fn new() -> Self {
+        DomainPrecomputation {
+            tables: Mutex::new(HashMap::new())
+        }
+    }
+
+    /// The number of domain sizes with tables.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The tables for `domain`, computed on first use of its size.
+    pub(crate) fn twiddles<G>(&self, domain: &Domain<E,G>) -> Arc<Twiddles<E>> {
+        let size: usize = domain.coeffs.len();
+        if let Some(twiddles) = self.lock().get(&size) {
+            return twiddles.clone();
+        }
+
+        // Computed without the lock, so domains of other sizes are not held
+        // up. If two threads race, the first to finish is kept.
+        let twiddles: Arc<Twiddles<E>> = Arc::new(Twiddles::new(domain));
+        self.lock().entry(size).or_insert(twiddles).clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Arc<Twiddles<E>>>> {
+        self.tables.lock().expect("domain precomputation lock was poisoned")
+    }
+}
+
+impl<E> Default for DomainPrecomputation<E>
+where
+    E: ScalarEngine
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The powers of omega and its inverse that the transforms of a domain
+/// read: half the domain of them for a power of two and two thirds of it
+/// for three times one, which the radix-3 butterflies need.
+pub(crate) struct Twiddles<E: ScalarEngine> {
+    pub(crate) omega: Vec<E::Fr>,
+    pub(crate) omegainv: Vec<E::Fr>
+}
+
+impl<E> Twiddles<E>
+where
+    E: ScalarEngine
+{
+    fn new<G>(domain: &Domain<E,G>) -> Self {
+        let size: usize = domain.coeffs.len();
+        let len: usize = if domain.radix_3 { 2 * size / 3 } else { size / 2 };
+        Twiddles {
+            omega: powers_of::<E>(domain.omega, len),
+            omegainv: powers_of::<E>(domain.omegainv, len)
+        }
+    }
+}
+
+fn powers_of<E: ScalarEngine>(base: E::Fr, len: usize) -> Vec<E::Fr> {
+    let mut powers: Vec<E::Fr> = vec![E::Fr::zero(); len];
+    fft::fill_powers(&mut powers, base);
+    powers
+}
@@ -1,7 +1,8 @@
+use std::io::{self, Read, Write};
 use std::ops;
 
-use ff::{ScalarEngine, Field, PrimeField};
-use group::CurveProjective;
+use ff::{ScalarEngine, Field, PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective, EncodedPoint};
 
 pub trait Group<'a,E>: Sized 
     + Copy 
@@ -16,6 +17,18 @@ where
     E: ScalarEngine    
 {
     fn zero() -> Self;
+
+    /// Multiplies each element of `values` by the corresponding scalar of
+    /// `by`, which must yield at least as many scalars as there are values.
+    fn batch_mul_assign<I>(values: &mut [Self], by: I)
+    where
+        I: IntoIterator<Item = &'a E::Fr>
+    {
+        let mut by = by.into_iter();
+        for value in values.iter_mut() {
+            *value *= by.next().expect("expected a scalar for every value");
+        }
+    }
 }
 
 pub struct Point<G>(pub G);
@@ -93,6 +106,28 @@ where
     fn zero() -> Self {
         Scalar(E::Fr::zero())
     }
+
+    /// Gathers the scalars four at a time so that fields with a vectorised
+    /// `Field::batch_mul_assign` can use it.
+    fn batch_mul_assign<I>(values: &mut [Self], by: I)
+    where
+        I: IntoIterator<Item = &'a E::Fr>
+    {
+        let mut by = by.into_iter();
+        let mut lhs: [E::Fr; 4] = [E::Fr::zero(); 4];
+        let mut rhs: [E::Fr; 4] = [E::Fr::zero(); 4];
+        for chunk in values.chunks_mut(4) {
+            for ((l, r), value) in lhs.iter_mut().zip(rhs.iter_mut()).zip(chunk.iter()) {
+                *l = value.0;
+                *r = *by.next().expect("expected a scalar for every value");
+            }
+
+            E::Fr::batch_mul_assign(&mut lhs[..chunk.len()], &rhs[..chunk.len()]);
+            for (value, l) in chunk.iter_mut().zip(lhs.iter()) {
+                value.0 = *l;
+            }
+        }
+    }
 }
 
 impl<'a,E> ops::MulAssign<&'a E::Fr> for Scalar<E> 
@@ -120,4 +155,56 @@ where
     fn sub_assign(&mut self, rhs: &'a Self) {
         self.0.sub_assign(&rhs.0);
     }
-}
\ No newline at end of file
+}
+/// An element of a `Domain` which can be written out and read back, so
+/// that a domain's contents can be cached between runs.
+pub trait Encodable: Sized {
+    fn write<W: Write>(&self, writer: W) -> io::Result<()>;
+
+    /// Reads an element. If `checked`, elements which are expensive to
+    /// validate, such as curve points, are validated too.
+    fn read<R: Read>(reader: R, checked: bool) -> io::Result<Self>;
+}
+
+impl<E> Encodable for Scalar<E>
+where
+    E: ScalarEngine
+{
+    fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.0.into_repr().write_be(writer)
+    }
+
+    /// Scalars are always checked to be canonical, which is cheap.
+    fn read<R: Read>(reader: R, _: bool) -> io::Result<Self> {
+        let mut repr: <E::Fr as PrimeField>::Repr = Default::default();
+        repr.read_be(reader)?;
+
+        E::Fr::from_repr(repr)
+            .map(Scalar)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<G> Encodable for Point<G>
+where
+    G: CurveProjective
+{
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.0.into_affine().into_uncompressed().as_ref())
+    }
+
+    /// Unlike the queries of the parameters, a domain may hold the point at
+    /// infinity, so it is accepted here.
+    fn read<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
+        let mut repr: <G::Affine as CurveAffine>::Uncompressed = EncodedPoint::empty();
+        reader.read_exact(repr.as_mut())?;
+
+        let point: G::Affine = if checked {
+            repr.into_affine()
+        } else {
+            repr.into_affine_unchecked()
+        }
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Point(point.into_projective()))
+    }
+}
@@ -1,8 +1,10 @@
+use std::fmt;
 use std::ops::{Add, Sub};
 use ff::{ScalarEngine, Field};
 
-/// Represents a variable in our constraint system.
-#[derive(Copy, Clone, Debug)]
+/// Represents a variable in our constraint system. Variables are ordered
+/// by their index, with every input before every auxiliary variable.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Coefficient(Index);
 
 impl Coefficient {
@@ -21,12 +23,71 @@ impl Coefficient {
 
 /// Represents the index of either an input variable or
 /// auxiliary variable.
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum Index {
     Input(usize),
     Aux(usize),
 }
 
+impl fmt::Display for Coefficient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Index::Input(i) => write!(f, "input({})", i),
+            Index::Aux(i) => write!(f, "aux({})", i)
+        }
+    }
+}
+
+/// A constant term of a linear combination, represented as a multiple of
+/// the "one" input rather than as a variable of its own.
+pub struct Constant<E: ScalarEngine> {
+    value: E::Fr,
+    one: Coefficient
+}
+
+impl<E> Constant<E>
+where
+    E: ScalarEngine
+{
+    pub fn new(value: E::Fr, one: Coefficient) -> Self {
+        Constant { value, one }
+    }
+
+    pub fn get_value(&self) -> E::Fr {
+        self.value
+    }
+}
+
+impl<E: ScalarEngine> Clone for Constant<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: ScalarEngine> Copy for Constant<E> {}
+
+/// Constants are equal when their values are, since every constant is a
+/// multiple of the same "one" input.
+impl<E: ScalarEngine> PartialEq for Constant<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.one == other.one
+    }
+}
+
+impl<E: ScalarEngine> Eq for Constant<E> {}
+
+impl<E: ScalarEngine> fmt::Debug for Constant<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Constant({:?} * {})", self.value, self.one.get_unchecked())
+    }
+}
+
 /// This represents a linear combination of some variables, with coefficients
 /// in the scalar field of a pairing-friendly elliptic curve group.
 #[derive(Clone)]
@@ -98,6 +159,28 @@ where
     }
 }
 
+impl<E> Add<Constant<E>> for LinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(self, constant: Constant<E>) -> LinearCombination<E> {
+        self + (constant.value, constant.one)
+    }
+}
+
+impl<E> Sub<Constant<E>> for LinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(self, constant: Constant<E>) -> LinearCombination<E> {
+        self - (constant.value, constant.one)
+    }
+}
+
 impl<'a, E> Add<&'a LinearCombination<E>> for LinearCombination<E> 
 where
     E: ScalarEngine
@@ -161,3 +244,20 @@ where
         self
     }
 }
+
+#[test]
+fn test_coefficient_ordering_and_display() {
+    use std::collections::BTreeSet;
+
+    let vars: BTreeSet<Coefficient> = vec![
+        Coefficient::new_unchecked(Index::Aux(17)),
+        Coefficient::new_unchecked(Index::Input(3)),
+        Coefficient::new_unchecked(Index::Aux(2)),
+        Coefficient::new_unchecked(Index::Input(3)),
+    ].into_iter().collect();
+
+    let printed: Vec<String> = vars.iter()
+        .map(|var| var.to_string())
+        .collect();
+    assert_eq!(printed, vec!["input(3)", "aux(2)", "aux(17)"]);
+}
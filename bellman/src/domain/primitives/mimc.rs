@@ -0,0 +1,152 @@
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::sponge::{self, RoundConstants};
+
+/// BLAKE2s personalization used when deriving MiMC round constants. The
+/// derivation is specific to this crate.
+pub const MIMC_PERSONALIZATION: &[u8; 8] = b"MiMC_rc_";
+
+/// The parameters of the MiMC block cipher over the scalar field: the
+/// exponent of its round function and its round constants, the first of
+/// which is zero. Each round adds the key and a constant to the state and
+/// raises it to the exponent.
+///
+/// `encrypt` is MiMC-n/n, which needs an exponent coprime to p - 1 and
+/// ceil(log_e p) rounds. `feistel_encrypt` is MiMC-2n/n, a Feistel network
+/// over pairs of elements which is invertible for any exponent and needs
+/// twice the rounds. With a zero key and x^3 it is the `LongsightF322p3`
+/// construction of the MiMC paper.
+///
+/// The round constants are drawn from BLAKE2s under `MIMC_PERSONALIZATION`,
+/// not from the Keccak chain of other MiMC implementations such as
+/// circomlib's. This is deliberate, as the published constants are for
+/// other fields, but it means ciphertexts and gadget outputs are not
+/// interoperable with them even for the same exponent and round count.
+pub struct MimcParams<E: ScalarEngine> {
+    exponent: u64,
+    round_constants: Vec<E::Fr>
+}
+
+impl<E> MimcParams<E>
+where
+    E: ScalarEngine
+{
+    /// MiMC-7, for MiMC-n/n over fields where x^7 is a permutation, such
+    /// as the BLS12-381 scalar field.
+    pub fn mimc7() -> Self {
+        assert!(sponge::is_permutation::<E>(7), "x^7 does not permute this field");
+        Self::with_rounds(7, Self::rounds_for(7))
+    }
+
+    /// MiMC-2n/n with x^3, for use with `feistel_encrypt`.
+    pub fn feistel() -> Self {
+        Self::with_rounds(3, 2 * Self::rounds_for(3))
+    }
+
+    /// Parameters with `rounds` constants drawn from BLAKE2s, the first
+    /// replaced by zero. These are this crate's own constants; see the
+    /// type's documentation.
+    pub fn with_rounds(exponent: u64, rounds: usize) -> Self {
+        assert!(exponent > 1, "the round function must be nonlinear");
+
+        let mut constants = RoundConstants::<E>::new(MIMC_PERSONALIZATION, &[exponent as usize, rounds]);
+        let mut round_constants: Vec<E::Fr> = constants.take(rounds, 1)
+            .into_iter()
+            .map(|round| round[0])
+            .collect();
+        if let Some(first) = round_constants.first_mut() {
+            *first = E::Fr::zero();
+        }
+
+        MimcParams {
+            exponent,
+            round_constants
+        }
+    }
+
+    pub fn exponent(&self) -> u64 {
+        self.exponent
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.round_constants.len()
+    }
+
+    pub fn round_constants(&self) -> &[E::Fr] {
+        &self.round_constants
+    }
+
+    /// Encrypts `x` under `key` with MiMC-n/n, adding the key once more
+    /// after the last round. Keyed with a secret it is a PRF.
+    pub fn encrypt(&self, x: E::Fr, key: E::Fr) -> E::Fr {
+        let mut x: E::Fr = x;
+        for constant in self.round_constants.iter() {
+            x.add_assign(&key);
+            x.add_assign(constant);
+            x = x.pow(&[self.exponent]);
+        }
+        x.add_assign(&key);
+        x
+    }
+
+    /// Encrypts `(xl, xr)` under `key` with MiMC-2n/n, each round mapping
+    /// it to (xr + (xl + key + c)^e, xl).
+    pub fn feistel_encrypt(&self, xl: E::Fr, xr: E::Fr, key: E::Fr) -> (E::Fr, E::Fr) {
+        let (mut xl, mut xr): (E::Fr, E::Fr) = (xl, xr);
+        for constant in self.round_constants.iter() {
+            let mut tmp: E::Fr = xl;
+            tmp.add_assign(&key);
+            tmp.add_assign(constant);
+            tmp = tmp.pow(&[self.exponent]);
+            tmp.add_assign(&xr);
+            xr = xl;
+            xl = tmp;
+        }
+        (xl, xr)
+    }
+
+    /// The number of rounds which take the degree of the cipher past the
+    /// size of the field, ceil(log_e p).
+    fn rounds_for(exponent: u64) -> usize {
+        (E::Fr::NUM_BITS as f64 / (exponent as f64).log2()).ceil() as usize
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_mimc_native() {
+    use pairing::bls12_381::{Bls12, Fr};
+
+    let rng = &mut rand::thread_rng();
+
+    let mimc7 = MimcParams::<Bls12>::mimc7();
+    assert_eq!(mimc7.rounds(), 91);
+    let feistel = MimcParams::<Bls12>::feistel();
+    assert_eq!(feistel.rounds(), 322);
+    assert!(feistel.round_constants()[0].is_zero());
+
+    // With a zero key the Feistel network is LongsightF322p3.
+    let (xl, xr) = (Fr::random(rng), Fr::random(rng));
+    let (mut l, mut r) = (xl, xr);
+    for constant in feistel.round_constants() {
+        let mut tmp = l;
+        tmp.add_assign(constant);
+        let mut cube = tmp;
+        cube.square();
+        cube.mul_assign(&tmp);
+        cube.add_assign(&r);
+        r = l;
+        l = cube;
+    }
+    assert_eq!(feistel.feistel_encrypt(xl, xr, Fr::zero()), (l, r));
+
+    // MiMC-7 is a permutation for each key, so distinct plaintexts under
+    // one key and one plaintext under distinct keys encrypt differently.
+    let key = Fr::random(rng);
+    let x = Fr::random(rng);
+    let mut y = x;
+    y.add_assign(&Fr::one());
+    assert_eq!(mimc7.encrypt(x, key), MimcParams::<Bls12>::mimc7().encrypt(x, key));
+    assert!(mimc7.encrypt(x, key) != mimc7.encrypt(y, key));
+    assert!(mimc7.encrypt(x, key) != mimc7.encrypt(x, Fr::zero()));
+}
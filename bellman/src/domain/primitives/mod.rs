@@ -1,5 +1,17 @@
 mod group;
 mod linear;
+mod mimc;
+mod poseidon;
+mod rescue;
+mod sponge;
+mod sparse;
+mod sparse_merkle;
 
 pub use self::group::*;
 pub use linear::*;
+pub use mimc::*;
+pub use poseidon::*;
+pub use rescue::*;
+pub use sponge::AlgebraicSponge;
+pub use sparse::*;
+pub use sparse_merkle::*;
@@ -0,0 +1,329 @@
+use ff::{Field, PrimeField, PrimeFieldRepr, ScalarEngine};
+
+use super::sponge::{self, AlgebraicSponge};
+
+/// The parameters of a Poseidon permutation over the scalar field: its
+/// width, the number of full and partial rounds, the exponent of the
+/// S-box, the round constants and the MDS matrix of the linear layer.
+///
+/// The round constants and the Cauchy MDS matrix are drawn from the Grain
+/// LFSR exactly as the reference implementation draws them, so the
+/// permutation agrees with other Poseidon instances of the same shape.
+pub struct PoseidonParams<E: ScalarEngine> {
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+    round_constants: Vec<Vec<E::Fr>>,
+    mds: Vec<Vec<E::Fr>>
+}
+
+impl<E> PoseidonParams<E>
+where
+    E: ScalarEngine
+{
+    /// Parameters for widths 2 to 9, with the round counts given for
+    /// 128-bit security by the Poseidon paper for x^5 over a 255-bit field
+    /// such as the BLS12-381 scalar field. For BLS12-381 these match the
+    /// reference parameters, whose first candidate MDS matrix passes its
+    /// subspace trail checks at every one of these widths.
+    pub fn new(width: usize) -> Self {
+        let partial_rounds: usize = match width {
+            2 => 56,
+            3 => 57,
+            4 => 56,
+            5 | 6 => 60,
+            7 | 9 => 63,
+            8 => 64,
+            _ => panic!("no Poseidon round counts are known for width {}", width)
+        };
+        Self::with_rounds(width, 8, partial_rounds)
+    }
+
+    /// Parameters with the given round counts. `full_rounds` is split
+    /// evenly either side of the partial rounds, so it must be even.
+    ///
+    /// The MDS matrix is the first one the Grain LFSR yields; unlike the
+    /// reference script, no subspace trail checks are run to reject it.
+    pub fn with_rounds(width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        assert!(width >= 2, "a Poseidon permutation needs a capacity and a rate element");
+        assert!(full_rounds % 2 == 0, "full rounds are split evenly around the partial rounds");
+
+        let mut grain = Grain::new(E::Fr::NUM_BITS as usize, width, full_rounds, partial_rounds);
+        let round_constants: Vec<Vec<E::Fr>> = (0..full_rounds + partial_rounds)
+            .map(|_| (0..width).map(|_| grain.next_element::<E>()).collect())
+            .collect();
+        let mds: Vec<Vec<E::Fr>> = grain.next_mds::<E>(width);
+
+        PoseidonParams {
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha: sponge::sbox_exponent::<E>(),
+            round_constants,
+            mds
+        }
+    }
+
+    pub fn full_rounds(&self) -> usize {
+        self.full_rounds
+    }
+
+    pub fn partial_rounds(&self) -> usize {
+        self.partial_rounds
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+
+    /// The S-box is x^alpha, with alpha the smallest odd prime coprime to
+    /// the order of the multiplicative group, so it permutes the field.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Whether every element of the state goes through the S-box in
+    /// `round`, rather than only the first.
+    pub fn is_full_round(&self, round: usize) -> bool {
+        let half: usize = self.full_rounds / 2;
+        round < half || round >= half + self.partial_rounds
+    }
+
+    /// The constants added to the state at the start of `round`.
+    pub fn round_constants(&self, round: usize) -> &[E::Fr] {
+        &self.round_constants[round]
+    }
+
+    pub fn mds(&self) -> &[Vec<E::Fr>] {
+        &self.mds
+    }
+}
+
+impl<E> AlgebraicSponge<E> for PoseidonParams<E>
+where
+    E: ScalarEngine
+{
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn permute(&self, state: &mut [E::Fr]) {
+        assert_eq!(state.len(), self.width, "the state must be as wide as the permutation");
+
+        for round in 0..self.rounds() {
+            for (element, constant) in state.iter_mut().zip(self.round_constants(round)) {
+                element.add_assign(constant);
+            }
+
+            let sboxes: usize = if self.is_full_round(round) { self.width } else { 1 };
+            for element in state[..sboxes].iter_mut() {
+                *element = element.pow(&[self.alpha]);
+            }
+
+            sponge::mix::<E>(&self.mds, state);
+        }
+    }
+}
+
+/// The 80-bit Grain LFSR the reference implementation samples its
+/// parameters from, seeded with the field, S-box and round counts.
+struct Grain {
+    bits: [bool; 80],
+    head: usize
+}
+
+impl Grain {
+    fn new(num_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // A prime field (1) with an x^alpha S-box (0), then the field size
+        // and the shape, each most significant bit first, padded with ones.
+        let fields: [(usize, usize); 6] = [
+            (1, 2),
+            (0, 4),
+            (num_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10)
+        ];
+
+        let mut grain = Grain { bits: [true; 80], head: 0 };
+        let mut i: usize = 0;
+        for (value, len) in fields.iter() {
+            for shift in (0..*len).rev() {
+                grain.bits[i] = (value >> shift) & 1 == 1;
+                i += 1;
+            }
+        }
+
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let bit: bool = [62, 51, 38, 23, 13, 0]
+            .iter()
+            .fold(false, |acc, tap| acc ^ self.bits[(self.head + tap) % 80]);
+        self.bits[self.head] = bit;
+        self.head = (self.head + 1) % 80;
+        bit
+    }
+
+    /// Bits are drawn in pairs, keeping the second only when the first is
+    /// set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep: bool = self.step();
+            let bit: bool = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    fn next_repr<E: ScalarEngine>(&mut self) -> <E::Fr as PrimeField>::Repr {
+        let mut repr: <E::Fr as PrimeField>::Repr = Default::default();
+        for _ in 0..E::Fr::NUM_BITS {
+            repr.shl(1);
+            if self.next_bit() {
+                repr.as_mut()[0] |= 1;
+            }
+        }
+        repr
+    }
+
+    /// A round constant, drawing again whenever the bits do not encode an
+    /// element of the field.
+    fn next_element<E: ScalarEngine>(&mut self) -> E::Fr {
+        loop {
+            if let Ok(element) = E::Fr::from_repr(self.next_repr::<E>()) {
+                return element;
+            }
+        }
+    }
+
+    /// An element for the MDS matrix, which is reduced rather than
+    /// redrawn.
+    fn next_reduced<E: ScalarEngine>(&mut self) -> E::Fr {
+        let modulus: <E::Fr as PrimeField>::Repr = E::Fr::char();
+        let mut repr: <E::Fr as PrimeField>::Repr = self.next_repr::<E>();
+        while repr >= modulus {
+            repr.sub_noborrow(&modulus);
+        }
+        E::Fr::from_repr(repr).expect("the representation is reduced")
+    }
+
+    /// The Cauchy matrix 1 / (x_i + y_j) on `width` xs followed by `width`
+    /// ys, all distinct, drawing all of them again until no x + y vanishes.
+    fn next_mds<E: ScalarEngine>(&mut self, width: usize) -> Vec<Vec<E::Fr>> {
+        loop {
+            let points: Vec<E::Fr> = loop {
+                let points: Vec<E::Fr> = (0..2 * width).map(|_| self.next_reduced::<E>()).collect();
+                let distinct: bool = points
+                    .iter()
+                    .enumerate()
+                    .all(|(i, a)| points[i + 1..].iter().all(|b| a != b));
+                if distinct {
+                    break points;
+                }
+            };
+            let (xs, ys) = points.split_at(width);
+
+            let mds: Option<Vec<Vec<E::Fr>>> = xs
+                .iter()
+                .map(|x| {
+                    ys.iter()
+                        .map(|y| {
+                            let mut sum: E::Fr = *x;
+                            sum.add_assign(y);
+                            sum.inverse()
+                        })
+                        .collect()
+                })
+                .collect();
+            if let Some(mds) = mds {
+                return mds;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_poseidon_native() {
+    use pairing::bls12_381::{Bls12, Fr};
+
+    let rng = &mut rand::thread_rng();
+
+    // The S-box is a permutation of the BLS12-381 scalar field for x^5,
+    // as 3 divides r - 1.
+    let params = PoseidonParams::<Bls12>::new(3);
+    assert_eq!(params.alpha(), 5);
+    assert_eq!(params.rounds(), 65);
+    assert_eq!((0..65).filter(|round| params.is_full_round(*round)).count(), 8);
+
+    // Constants are deterministic, and differ across widths.
+    let again = PoseidonParams::<Bls12>::new(3);
+    assert!(params.round_constants(0) == again.round_constants(0));
+    assert!(params.round_constants(0)[0] != PoseidonParams::<Bls12>::new(5).round_constants(0)[0]);
+
+    // No entry of the MDS matrix vanishes.
+    for row in params.mds() {
+        assert!(row.iter().all(|coeff| !coeff.is_zero()));
+    }
+
+    let inputs: Vec<Fr> = (0..4).map(|_| Fr::random(rng)).collect();
+    let digest = params.hash(&inputs);
+    assert_eq!(digest, again.hash(&inputs));
+    assert!(digest != params.hash(&inputs[..3]));
+
+    // A trailing zero is not absorbed silently.
+    let mut padded = inputs.clone();
+    padded.push(Fr::zero());
+    assert!(digest != params.hash(&padded));
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_poseidon_reference_vectors() {
+    use pairing::bls12_381::{Bls12, Fr, FrRepr};
+
+    fn fr(bytes: [u8; 32]) -> Fr {
+        let mut repr: FrRepr = FrRepr::default();
+        repr.read_be(&bytes[..]).unwrap();
+        Fr::from_repr(repr).unwrap()
+    }
+
+    fn count(n: u64) -> Vec<Fr> {
+        (0..n).map(|i| Fr::from_repr(FrRepr::from(i)).unwrap()).collect()
+    }
+
+    // The first round constant and MDS entry, and the permutation of
+    // 0, 1, ..., as generated by the reference script for BLS12-381.
+    let params = PoseidonParams::<Bls12>::new(3);
+    assert_eq!(params.round_constants(0)[0], fr(hex!("6c4ffa723eaf1a7bf74905cc7dae4ca9ff4a2c3bc81d42e09540d1f250910880")));
+    assert_eq!(params.mds()[0][0], fr(hex!("3d955d6c02fe4d7cb500e12f2b55eff668a7b4386bd27413766713c93f2acfcd")));
+
+    let mut state: Vec<Fr> = count(3);
+    params.permute(&mut state);
+    assert_eq!(state, vec![
+        fr(hex!("28ce19420fc246a05553ad1e8c98f5c9d67166be2c18e9e4cb4b4e317dd2a78a")),
+        fr(hex!("51f3e312c95343a896cfd8945ea82ba956c1118ce9b9859b6ea56637b4b1ddc4")),
+        fr(hex!("3b2b69139b235626a0bfb56c9527ae66a7bf486ad8c11c14d1da0c69bbe0f79a"))
+    ]);
+
+    let params = PoseidonParams::<Bls12>::new(5);
+    assert_eq!(params.round_constants(0)[0], fr(hex!("5ee52b2f39e240a4006e97a15a7609dce42fa9aa510d11586a56db98fa925158")));
+    assert_eq!(params.mds()[0][0], fr(hex!("354423b163d1078b0dd645be56316e34a9b98e52dcf9f469be44b108be46c107")));
+
+    let mut state: Vec<Fr> = count(5);
+    params.permute(&mut state);
+    assert_eq!(state, vec![
+        fr(hex!("2a918b9c9f9bd7bb509331c81e297b5707f6fc7393dcee1b13901a0b22202e18")),
+        fr(hex!("65ebf8671739eeb11fb217f2d5c5bf4a0c3f210e3f3cd3b08b5db75675d797f7")),
+        fr(hex!("2cc176fc26bc70737a696a9dfd1b636ce360ee76926d182390cdb7459cf585ce")),
+        fr(hex!("4dc4e29d283afd2a491fe6aef122b9a968e74eff05341f3cc23fda1781dcb566")),
+        fr(hex!("03ff622da276830b9451b88b85e6184fd6ae15c8ab3ee25a5667be8592cce3b1"))
+    ]);
+}
@@ -0,0 +1,130 @@
+use ff::{Field, ScalarEngine};
+
+use super::sponge::{self, AlgebraicSponge, RoundConstants};
+
+/// BLAKE2s personalization used when deriving Rescue-Prime round constants.
+pub const RESCUE_PERSONALIZATION: &[u8; 8] = b"RescuePr";
+
+/// The parameters of a Rescue-Prime permutation over the scalar field.
+/// Each round raises the whole state to alpha, mixes it and adds
+/// constants, then does the same with the inverse power. The inverse power
+/// is cheap to constrain, as its output is checked by raising it to alpha,
+/// so rounds cost twice the S-boxes of a full Poseidon round but far fewer
+/// rounds are needed.
+///
+/// Unlike `PoseidonParams`, the round constants are drawn from BLAKE2s
+/// and the MDS matrix is a fixed Cauchy matrix, so outputs do not match
+/// the reference parameters.
+pub struct RescueParams<E: ScalarEngine> {
+    width: usize,
+    rounds: usize,
+    alpha: u64,
+    alpha_inv: Vec<u64>,
+    round_constants: Vec<Vec<E::Fr>>,
+    mds: Vec<Vec<E::Fr>>
+}
+
+impl<E> RescueParams<E>
+where
+    E: ScalarEngine
+{
+    /// Parameters with 8 rounds, the count the Rescue-Prime round formula
+    /// gives for 128-bit security with x^5 over a 255-bit field such as the
+    /// BLS12-381 scalar field.
+    pub fn new(width: usize) -> Self {
+        Self::with_rounds(width, 8)
+    }
+
+    pub fn with_rounds(width: usize, rounds: usize) -> Self {
+        assert!(width >= 2, "a Rescue-Prime permutation needs a capacity and a rate element");
+
+        let alpha: u64 = sponge::sbox_exponent::<E>();
+        let mut constants = RoundConstants::<E>::new(RESCUE_PERSONALIZATION, &[width, rounds]);
+        RescueParams {
+            width,
+            rounds,
+            alpha,
+            alpha_inv: sponge::inverse_exponent::<E>(alpha),
+            round_constants: constants.take(2 * rounds, width),
+            mds: sponge::cauchy_matrix::<E>(width)
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// The exponent of the forward S-box, chosen as for Poseidon.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// The limbs, least significant first, of the inverse of `alpha`
+    /// modulo p - 1, which is the exponent of the backward S-box.
+    pub fn alpha_inv(&self) -> &[u64] {
+        &self.alpha_inv
+    }
+
+    /// The constants added after the forward half of `round` when `step`
+    /// is 0, and after its backward half when it is 1.
+    pub fn round_constants(&self, round: usize, step: usize) -> &[E::Fr] {
+        &self.round_constants[2 * round + step]
+    }
+
+    pub fn mds(&self) -> &[Vec<E::Fr>] {
+        &self.mds
+    }
+}
+
+impl<E> AlgebraicSponge<E> for RescueParams<E>
+where
+    E: ScalarEngine
+{
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn permute(&self, state: &mut [E::Fr]) {
+        assert_eq!(state.len(), self.width, "the state must be as wide as the permutation");
+
+        for round in 0..self.rounds {
+            for step in 0..2 {
+                let exponent: &[u64] = if step == 0 { &[self.alpha] } else { &self.alpha_inv };
+                for element in state.iter_mut() {
+                    *element = element.pow(exponent);
+                }
+
+                sponge::mix::<E>(&self.mds, state);
+                for (element, constant) in state.iter_mut().zip(self.round_constants(round, step)) {
+                    element.add_assign(constant);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_rescue_native() {
+    use pairing::bls12_381::{Bls12, Fr};
+    use super::PoseidonParams;
+
+    let rng = &mut rand::thread_rng();
+
+    let params = RescueParams::<Bls12>::new(3);
+    assert_eq!(params.alpha(), 5);
+
+    // The backward S-box undoes the forward one.
+    for _ in 0..10 {
+        let x = Fr::random(rng);
+        assert_eq!(x.pow(&[5]).pow(params.alpha_inv()), x);
+    }
+
+    // Digests are deterministic, separate lengths, and differ from
+    // Poseidon's through the same trait.
+    let inputs: Vec<Fr> = (0..5).map(|_| Fr::random(rng)).collect();
+    let digest = params.hash(&inputs);
+    assert_eq!(digest, RescueParams::<Bls12>::new(3).hash(&inputs));
+    assert!(digest != params.hash(&inputs[..4]));
+    assert!(digest != PoseidonParams::<Bls12>::new(3).hash(&inputs));
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::ops::{Add, Sub};
+use ff::{ScalarEngine, Field};
+
+use super::{Coefficient, Index, LinearCombination};
+
+/// A linear combination keyed by variable index, so that repeated terms are
+/// folded together as they are added. This suits gadgets which accumulate
+/// hundreds of terms into a single combination, where the `Vec` backed
+/// `LinearCombination` would carry every duplicate through to synthesis.
+#[derive(Clone)]
+pub struct SparseLinearCombination<E: ScalarEngine>(BTreeMap<Index, E::Fr>);
+
+impl<E> SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    pub fn zero() -> Self {
+        SparseLinearCombination(BTreeMap::new())
+    }
+
+    /// Returns the number of distinct variables with a non-zero coefficient.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the coefficient of `var`, which is zero if it is absent.
+    pub fn get(&self, var: Coefficient) -> E::Fr {
+        self.0
+            .get(&var.get_unchecked())
+            .copied()
+            .unwrap_or_else(E::Fr::zero)
+    }
+
+    /// Iterates over the terms in index order, inputs before auxiliary variables.
+    pub fn iter(&self) -> impl Iterator<Item=(Coefficient, E::Fr)> + '_ {
+        self.0
+            .iter()
+            .map(|(index, coeff)| (Coefficient::new_unchecked(*index), *coeff))
+    }
+}
+
+impl<E> From<SparseLinearCombination<E>> for LinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    fn from(sparse: SparseLinearCombination<E>) -> LinearCombination<E> {
+        LinearCombination(sparse.iter().collect())
+    }
+}
+
+impl<'a, E> From<&'a LinearCombination<E>> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    fn from(lc: &'a LinearCombination<E>) -> SparseLinearCombination<E> {
+        SparseLinearCombination::zero() + lc
+    }
+}
+
+impl<E> Add<(E::Fr, Coefficient)> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(mut self, (coeff, var): (E::Fr, Coefficient)) -> SparseLinearCombination<E> {
+        let index: Index = var.get_unchecked();
+        let mut acc: E::Fr = self.0.remove(&index).unwrap_or_else(E::Fr::zero);
+        acc.add_assign(&coeff);
+
+        // Cancelled terms are dropped so the map only holds live variables.
+        if !acc.is_zero() {
+            self.0.insert(index, acc);
+        }
+
+        self
+    }
+}
+
+impl<E> Sub<(E::Fr, Coefficient)> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(self, (mut coeff, var): (E::Fr, Coefficient)) -> SparseLinearCombination<E> {
+        coeff.negate();
+
+        self + (coeff, var)
+    }
+}
+
+impl<E> Add<Coefficient> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(self, other: Coefficient) -> SparseLinearCombination<E> {
+        self + (E::Fr::one(), other)
+    }
+}
+
+impl<E> Sub<Coefficient> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(self, other: Coefficient) -> SparseLinearCombination<E> {
+        self - (E::Fr::one(), other)
+    }
+}
+
+impl<'a, E> Add<&'a SparseLinearCombination<E>> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(mut self, other: &'a SparseLinearCombination<E>) -> SparseLinearCombination<E> {
+        for (var, coeff) in other.iter() {
+            self = self + (coeff, var);
+        }
+
+        self
+    }
+}
+
+impl<'a, E> Sub<&'a SparseLinearCombination<E>> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(mut self, other: &'a SparseLinearCombination<E>) -> SparseLinearCombination<E> {
+        for (var, coeff) in other.iter() {
+            self = self - (coeff, var);
+        }
+
+        self
+    }
+}
+
+impl<'a, E> Add<(E::Fr, &'a SparseLinearCombination<E>)> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(mut self, (coeff, other): (E::Fr, &'a SparseLinearCombination<E>)) -> SparseLinearCombination<E> {
+        for (var, mut tmp) in other.iter() {
+            tmp.mul_assign(&coeff);
+            self = self + (tmp, var);
+        }
+
+        self
+    }
+}
+
+impl<'a, E> Sub<(E::Fr, &'a SparseLinearCombination<E>)> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(mut self, (coeff, other): (E::Fr, &'a SparseLinearCombination<E>)) -> SparseLinearCombination<E> {
+        for (var, mut tmp) in other.iter() {
+            tmp.mul_assign(&coeff);
+            self = self - (tmp, var);
+        }
+
+        self
+    }
+}
+
+impl<'a, E> Add<&'a LinearCombination<E>> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn add(mut self, other: &'a LinearCombination<E>) -> SparseLinearCombination<E> {
+        for (var, coeff) in other.as_ref() {
+            self = self + (*coeff, *var);
+        }
+
+        self
+    }
+}
+
+impl<'a, E> Sub<&'a LinearCombination<E>> for SparseLinearCombination<E> 
+where
+    E: ScalarEngine
+{
+    type Output = Self;
+
+    fn sub(mut self, other: &'a LinearCombination<E>) -> SparseLinearCombination<E> {
+        for (var, coeff) in other.as_ref() {
+            self = self - (*coeff, *var);
+        }
+
+        self
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_sparse_folds_terms() {
+    use pairing::bls12_381::Bls12;
+    use ff::PrimeField;
+
+    type Fr = <Bls12 as ScalarEngine>::Fr;
+
+    let x = Coefficient::new_unchecked(Index::Aux(0));
+    let y = Coefficient::new_unchecked(Index::Aux(1));
+    let one = Coefficient::new_unchecked(Index::Input(0));
+
+    let mut lc = SparseLinearCombination::<Bls12>::zero();
+    for _ in 0..100 {
+        lc = lc + x + one;
+    }
+    lc = lc + y - y;
+
+    assert_eq!(lc.len(), 2);
+    assert_eq!(lc.get(x), Fr::from_str("100").unwrap());
+    assert_eq!(lc.get(y), Fr::zero());
+
+    let doubled = lc.clone() + &lc;
+    assert_eq!(doubled.get(one), Fr::from_str("200").unwrap());
+    assert!((doubled - (Fr::from_str("2").unwrap(), &lc)).is_empty());
+
+    let dense: LinearCombination<Bls12> = lc.into();
+    let terms: Vec<Index> = dense.as_ref()
+        .iter()
+        .map(|(var, _)| var.get_unchecked())
+        .collect();
+    assert_eq!(terms, vec![Index::Input(0), Index::Aux(0)]);
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+
+use ff::{Field, PrimeField, PrimeFieldRepr, ScalarEngine};
+
+use super::sponge::AlgebraicSponge;
+
+/// A sparse Merkle tree mapping keys below 2^depth to field elements, each
+/// node hashing its children with the sponge of `hasher`.
+///
+/// The leaf of a key is its value, and a key is absent when its value is
+/// zero, so absent keys need no proof of their own: a read of zero proves
+/// non-membership. Every subtree holding only absent keys has the same
+/// root at a given height, so only the nodes above some present key are
+/// stored, and the rest are read from a table of these default roots.
+pub struct SparseMerkleTree<'a, E: ScalarEngine, H> {
+    hasher: &'a H,
+    depth: usize,
+    defaults: Vec<E::Fr>,
+    nodes: BTreeMap<(usize, <E::Fr as PrimeField>::Repr), E::Fr>
+}
+
+impl<'a, E, H> SparseMerkleTree<'a, E, H>
+where
+    E: ScalarEngine,
+    H: AlgebraicSponge<E>
+{
+    /// An empty tree with leaves at height zero and the root at `depth`.
+    pub fn new(hasher: &'a H, depth: usize) -> Self {
+        assert!(depth <= E::Fr::NUM_BITS as usize, "keys are field elements");
+
+        let mut defaults: Vec<E::Fr> = Vec::with_capacity(depth + 1);
+        defaults.push(E::Fr::zero());
+        for height in 0..depth {
+            let child: E::Fr = defaults[height];
+            defaults.push(hasher.hash(&[child, child]));
+        }
+
+        SparseMerkleTree {
+            hasher,
+            depth,
+            defaults,
+            nodes: BTreeMap::new()
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn root(&self) -> E::Fr {
+        self.node(self.depth, Default::default())
+    }
+
+    /// The root of an empty subtree of the given height.
+    pub fn default_root(&self, height: usize) -> E::Fr {
+        self.defaults[height]
+    }
+
+    /// The value of `key`, zero if it is absent.
+    pub fn get(&self, key: E::Fr) -> E::Fr {
+        self.node(0, self.leaf_index(key))
+    }
+
+    /// Sets the value of `key`, returning the old one. Inserting, updating
+    /// and, with a zero value, removing are all the same operation.
+    pub fn insert(&mut self, key: E::Fr, value: E::Fr) -> E::Fr {
+        let mut index: <E::Fr as PrimeField>::Repr = self.leaf_index(key);
+        let old: E::Fr = self.node(0, index);
+        self.set_node(0, index, value);
+
+        for height in 1..=self.depth {
+            index.shr(1);
+
+            let mut left: <E::Fr as PrimeField>::Repr = index;
+            left.shl(1);
+            let mut right: <E::Fr as PrimeField>::Repr = left;
+            right.as_mut()[0] |= 1;
+
+            let parent: E::Fr = self.hasher.hash(&[
+                self.node(height - 1, left),
+                self.node(height - 1, right)
+            ]);
+            self.set_node(height, index, parent);
+        }
+
+        old
+    }
+
+    /// The siblings of the nodes from the leaf of `key` up to the root,
+    /// leaf first. The bits of the key, least significant first, say which
+    /// side of its sibling each node is on.
+    pub fn path(&self, key: E::Fr) -> Vec<E::Fr> {
+        let mut index: <E::Fr as PrimeField>::Repr = self.leaf_index(key);
+
+        (0..self.depth)
+            .map(|height| {
+                let mut sibling: <E::Fr as PrimeField>::Repr = index;
+                sibling.as_mut()[0] ^= 1;
+                index.shr(1);
+                self.node(height, sibling)
+            })
+            .collect()
+    }
+
+    /// The root reached by hashing `value` up `path` at `key`, which is
+    /// the root of the tree when `path` is the path of `key` and `value`
+    /// is its value.
+    pub fn compute_root(&self, key: E::Fr, value: E::Fr, path: &[E::Fr]) -> E::Fr {
+        assert_eq!(path.len(), self.depth);
+
+        let mut index: <E::Fr as PrimeField>::Repr = self.leaf_index(key);
+        path.iter().fold(value, |cur, sibling| {
+            let is_right: bool = index.is_odd();
+            index.shr(1);
+            if is_right {
+                self.hasher.hash(&[*sibling, cur])
+            } else {
+                self.hasher.hash(&[cur, *sibling])
+            }
+        })
+    }
+
+    fn leaf_index(&self, key: E::Fr) -> <E::Fr as PrimeField>::Repr {
+        let index: <E::Fr as PrimeField>::Repr = key.into_repr();
+        let mut high: <E::Fr as PrimeField>::Repr = index;
+        high.shr(self.depth as u32);
+        assert!(
+            self.depth == E::Fr::NUM_BITS as usize || high.is_zero(),
+            "the key does not fit in the depth of the tree"
+        );
+        index
+    }
+
+    fn node(&self, height: usize, index: <E::Fr as PrimeField>::Repr) -> E::Fr {
+        self.nodes
+            .get(&(height, index))
+            .copied()
+            .unwrap_or(self.defaults[height])
+    }
+
+    fn set_node(&mut self, height: usize, index: <E::Fr as PrimeField>::Repr, node: E::Fr) {
+        if node == self.defaults[height] {
+            self.nodes.remove(&(height, index));
+        } else {
+            self.nodes.insert((height, index), node);
+        }
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_sparse_merkle_native() {
+    use pairing::bls12_381::{Bls12, Fr};
+
+    use super::PoseidonParams;
+
+    let rng = &mut rand::thread_rng();
+    let params = PoseidonParams::<Bls12>::new(3);
+
+    let mut tree = SparseMerkleTree::new(&params, 16);
+    let empty_root: Fr = tree.root();
+    assert_eq!(empty_root, tree.default_root(16));
+
+    let keys: Vec<Fr> = (0..8u64)
+        .map(|i| Fr::from_str(&(i * 4099 % 65536).to_string()).unwrap())
+        .collect();
+    let values: Vec<Fr> = (0..8).map(|_| Fr::random(rng)).collect();
+
+    for (key, value) in keys.iter().zip(values.iter()) {
+        assert!(tree.insert(*key, *value).is_zero());
+    }
+    for (key, value) in keys.iter().zip(values.iter()) {
+        assert_eq!(tree.get(*key), *value);
+        assert_eq!(tree.compute_root(*key, *value, &tree.path(*key)), tree.root());
+    }
+
+    // An absent key reads as zero, which its path proves.
+    let absent: Fr = Fr::from_str("12345").unwrap();
+    assert!(tree.get(absent).is_zero());
+    assert_eq!(tree.compute_root(absent, Fr::zero(), &tree.path(absent)), tree.root());
+
+    // Updates return the old value, and only the stored nodes change.
+    let updated: Fr = Fr::random(rng);
+    assert_eq!(tree.insert(keys[3], updated), values[3]);
+    assert_eq!(tree.compute_root(keys[3], updated, &tree.path(keys[3])), tree.root());
+
+    // The root depends only on the contents, not on the order of the
+    // operations, and removing everything empties the tree.
+    let mut other = SparseMerkleTree::new(&params, 16);
+    for (i, key) in keys.iter().enumerate().rev() {
+        other.insert(*key, if i == 3 { updated } else { values[i] });
+    }
+    assert_eq!(other.root(), tree.root());
+
+    for key in keys.iter() {
+        tree.insert(*key, Fr::zero());
+    }
+    assert_eq!(tree.root(), empty_root);
+    assert!(tree.nodes.is_empty());
+}
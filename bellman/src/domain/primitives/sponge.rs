@@ -0,0 +1,206 @@
+use blake2s_simd::Params as Blake2sParams;
+use ff::{Field, PrimeField, PrimeFieldRepr, ScalarEngine};
+
+/// A permutation of a vector of field elements, used as a sponge with a
+/// single capacity element. Hashes built on one, such as Poseidon and
+/// Rescue-Prime, only differ in the permutation, so code hashing through
+/// this trait can switch between them.
+pub trait AlgebraicSponge<E: ScalarEngine> {
+    /// The number of elements the permutation acts on.
+    fn width(&self) -> usize;
+
+    /// Applies the permutation to `state`, which must hold `width`
+    /// elements.
+    fn permute(&self, state: &mut [E::Fr]);
+
+    /// The number of inputs absorbed per permutation.
+    fn rate(&self) -> usize {
+        self.width() - 1
+    }
+
+    /// Hashes `inputs` with the permutation as a sponge. The capacity
+    /// element starts as the number of inputs, which are then added into
+    /// the rest of the state `rate` at a time with a permutation after
+    /// each, and the first rate element is squeezed out.
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        let mut state: Vec<E::Fr> = vec![E::Fr::zero(); self.width()];
+        state[0] = field_element::<E>(inputs.len() as u64);
+
+        if inputs.is_empty() {
+            self.permute(&mut state);
+        }
+        for chunk in inputs.chunks(self.rate()) {
+            for (element, input) in state[1..].iter_mut().zip(chunk) {
+                element.add_assign(input);
+            }
+            self.permute(&mut state);
+        }
+
+        state[1]
+    }
+}
+
+/// A stream of field elements drawn from BLAKE2s under `personal`, keyed
+/// by the shape of the permutation and a counter. Digests which do not
+/// encode an element of the field are skipped.
+pub(super) struct RoundConstants<E: ScalarEngine> {
+    personal: &'static [u8; 8],
+    tag: Vec<u8>,
+    counter: u32,
+    _marker: std::marker::PhantomData<E>
+}
+
+impl<E> RoundConstants<E>
+where
+    E: ScalarEngine
+{
+    pub(super) fn new(personal: &'static [u8; 8], shape: &[usize]) -> Self {
+        let mut tag: Vec<u8> = Vec::with_capacity(4 * shape.len());
+        for param in shape.iter() {
+            tag.extend_from_slice(&(*param as u32).to_le_bytes());
+        }
+
+        RoundConstants {
+            personal,
+            tag,
+            counter: 0,
+            _marker: std::marker::PhantomData
+        }
+    }
+
+    /// `rounds` vectors of `width` constants.
+    pub(super) fn take(&mut self, rounds: usize, width: usize) -> Vec<Vec<E::Fr>> {
+        (0..rounds)
+            .map(|_| (0..width).map(|_| self.next()).collect())
+            .collect()
+    }
+
+    fn next(&mut self) -> E::Fr {
+        let mut repr: <E::Fr as PrimeField>::Repr = Default::default();
+        let len: usize = repr.as_ref().len() * 8;
+        let shave: u32 = (len * 8) as u32 - E::Fr::NUM_BITS;
+
+        loop {
+            // A candidate is read from as many digests, of consecutive
+            // counters, as it takes to fill the representation.
+            let mut bytes: Vec<u8> = Vec::with_capacity(len + 32);
+            while bytes.len() < len {
+                let digest: _ = Blake2sParams::new()
+                    .hash_length(32)
+                    .personal(self.personal)
+                    .to_state()
+                    .update(&self.tag)
+                    .update(&self.counter.to_le_bytes())
+                    .finalize();
+                bytes.extend_from_slice(digest.as_bytes());
+                self.counter += 1;
+            }
+
+            repr.read_le(&bytes[..len]).expect("the digest fills the representation");
+            repr.shr(shave);
+            if let Ok(element) = E::Fr::from_repr(repr) {
+                return element;
+            }
+        }
+    }
+}
+
+/// The smallest odd prime which does not divide p - 1, so that x^alpha
+/// permutes the field.
+pub(super) fn sbox_exponent<E: ScalarEngine>() -> u64 {
+    [3, 5, 7, 11, 13, 17, 19, 23]
+        .iter()
+        .copied()
+        .find(|alpha| is_permutation::<E>(*alpha))
+        .expect("the multiplicative group has order coprime to a small prime")
+}
+
+/// Whether x^alpha permutes the field, for a prime `alpha`.
+pub(super) fn is_permutation<E: ScalarEngine>(alpha: u64) -> bool {
+    div_small(&group_order::<E>(), alpha).1 != 0
+}
+
+/// The limbs of the inverse of `alpha` modulo p - 1, so that raising to
+/// them undoes x^alpha. It is (k (p - 1) + 1) / alpha for whichever k
+/// below alpha makes the division exact.
+pub(super) fn inverse_exponent<E: ScalarEngine>(alpha: u64) -> Vec<u64> {
+    let order: Vec<u64> = group_order::<E>();
+
+    (1..alpha)
+        .find_map(|k| {
+            let mut carry: u128 = 1;
+            let mut multiple: Vec<u64> = Vec::with_capacity(order.len() + 1);
+            for limb in order.iter() {
+                let acc: u128 = *limb as u128 * k as u128 + carry;
+                multiple.push(acc as u64);
+                carry = acc >> 64;
+            }
+            multiple.push(carry as u64);
+
+            match div_small(&multiple, alpha) {
+                (quotient, 0) => Some(quotient),
+                _ => None
+            }
+        })
+        .expect("alpha is coprime to the order of the multiplicative group")
+}
+
+/// The Cauchy matrix 1 / (x_i + y_j) with x_i = i and y_j = width + j,
+/// which is MDS as the x_i and the y_j are distinct and no sum is zero.
+pub(super) fn cauchy_matrix<E: ScalarEngine>(width: usize) -> Vec<Vec<E::Fr>> {
+    (0..width)
+        .map(|i| {
+            (0..width)
+                .map(|j| {
+                    field_element::<E>((i + j + width) as u64)
+                        .inverse()
+                        .expect("the sums are small and nonzero")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Multiplies `state` by `matrix` in place.
+pub(super) fn mix<E: ScalarEngine>(matrix: &[Vec<E::Fr>], state: &mut [E::Fr]) {
+    let mixed: Vec<E::Fr> = matrix
+        .iter()
+        .map(|row| {
+            let mut acc: E::Fr = E::Fr::zero();
+            for (coeff, element) in row.iter().zip(state.iter()) {
+                let mut term: E::Fr = *coeff;
+                term.mul_assign(element);
+                acc.add_assign(&term);
+            }
+            acc
+        })
+        .collect();
+    state.copy_from_slice(&mixed);
+}
+
+fn field_element<E: ScalarEngine>(value: u64) -> E::Fr {
+    E::Fr::from_repr(value.into()).expect("small integers are in the field")
+}
+
+fn group_order<E: ScalarEngine>() -> Vec<u64> {
+    let mut order: <E::Fr as PrimeField>::Repr = E::Fr::char();
+    order.sub_noborrow(&1.into());
+    order.as_ref().to_vec()
+}
+
+/// Divides little-endian limbs by `divisor`, returning the quotient with
+/// its high zero limbs trimmed and the remainder.
+fn div_small(limbs: &[u64], divisor: u64) -> (Vec<u64>, u64) {
+    let mut quotient: Vec<u64> = vec![0; limbs.len()];
+    let mut remainder: u128 = 0;
+    for (q, limb) in quotient.iter_mut().zip(limbs.iter()).rev() {
+        let acc: u128 = (remainder << 64) | *limb as u128;
+        *q = (acc / divisor as u128) as u64;
+        remainder = acc % divisor as u128;
+    }
+    while quotient.len() > 1 && quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+
+    (quotient, remainder as u64)
+}
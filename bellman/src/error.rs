@@ -3,6 +3,8 @@ use std::fmt;
 use std::io;
 use std::option;
 
+use crate::Index;
+
 pub type Result<T> = std::result::Result<T, SynthesisError>;
 
 /// This is an error that could occur during circuit synthesis contexts,
@@ -15,8 +17,6 @@ pub enum SynthesisError {
     DivisionByZero,
     /// During synthesis, we constructed an unsatisfiable constraint system.
     Unsatisfiable,
-    /// During synthesis, our polynomials ended up being too high of degree
-    PolynomialDegreeTooLarge,
     /// During proof generation, we encountered an identity in the CRS
     UnexpectedIdentity,
     /// During proof generation, we encountered an I/O error with the CRS
@@ -28,7 +28,94 @@ pub enum SynthesisError {
     /// During CRS generation, we observed an unconstrained auxiliary variable
     UnconstrainedVariable,
     /// During synthesis, we called an operation on a None.
-    Null
+    Null,
+    /// During proof generation, a replayed witness had no value for a
+    /// variable.
+    UnassignedVariable(Index),
+    /// During synthesis, a public input name was allocated more than once.
+    DuplicateInput(String),
+    /// During verification, no value was given for a labelled public input.
+    MissingInput(String),
+    /// During synthesis, a sub-circuit exposed a port name more than once.
+    DuplicatePort(String),
+    /// During synthesis, a sub-circuit was wired by a port it does not
+    /// expose.
+    UnknownPort(String),
+    /// During verification, a value was given for a label the circuit does
+    /// not have.
+    UnexpectedInput(String),
+    /// During proof generation, middleware refused to run a stage.
+    Vetoed(String),
+    /// During proof generation, a multi-exponentiation was given fewer
+    /// bases, or a different number of exponents, than its query expects.
+    LengthMismatch(LengthMismatch),
+    /// A polynomial needed an evaluation domain larger than any the scalar
+    /// field has roots of unity for.
+    DomainTooLarge(DomainTooLarge)
+}
+
+/// A query of the proving key which the prover multi-exponentiates over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Query {
+    A,
+    B1,
+    B2,
+    H,
+    L
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Query::A => write!(f, "a"),
+            Query::B1 => write!(f, "b1"),
+            Query::B2 => write!(f, "b2"),
+            Query::H => write!(f, "h"),
+            Query::L => write!(f, "l")
+        }
+    }
+}
+
+/// The operand of a multi-exponentiation whose length was wrong.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Exponents,
+    Bases
+}
+
+/// Describes a multi-exponentiation whose exponents or bases did not have
+/// the length its query expects. The query is unknown to the
+/// multi-exponentiation itself, so it is filled in by the prover with
+/// `SynthesisError::in_query`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthMismatch {
+    pub query: Option<Query>,
+    pub operand: Operand,
+    pub expected: usize,
+    pub actual: usize
+}
+
+/// The number of points a polynomial needed its evaluation domain to have,
+/// and the most that any domain over the scalar field can have. That limit
+/// is set by the two-adicity of the field, and is three times larger when
+/// the field has a cube root of unity too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DomainTooLarge {
+    pub required: usize,
+    pub available: usize
+}
+
+impl SynthesisError {
+    /// Attributes a `LengthMismatch` which has no query yet to `query`.
+    /// Other errors are returned unchanged.
+    pub fn in_query(self, query: Query) -> Self {
+        match self {
+            SynthesisError::LengthMismatch(mismatch) if mismatch.query.is_none() => {
+                SynthesisError::LengthMismatch(LengthMismatch { query: Some(query), ..mismatch })
+            },
+            e => e
+        }
+    }
 }
 
 impl From<option::NoneError> for SynthesisError {
@@ -51,24 +138,60 @@ impl Error for SynthesisError {
             }
             SynthesisError::DivisionByZero => "division by zero",
             SynthesisError::Unsatisfiable => "unsatisfiable constraint system",
-            SynthesisError::PolynomialDegreeTooLarge => "polynomial degree is too large",
             SynthesisError::UnexpectedIdentity => "encountered an identity element in the CRS",
             SynthesisError::IoError(_) => "encountered an I/O error",
             SynthesisError::MalformedVerifyingKey => "malformed verifying key",
             SynthesisError::MalformedWireSize => "malformed wire size",
             SynthesisError::UnconstrainedVariable => "auxiliary variable was unconstrained",
-            SynthesisError::Null => "encountered an operation on a None"
+            SynthesisError::Null => "encountered an operation on a None",
+            SynthesisError::UnassignedVariable(_) => "no value was assigned",
+            SynthesisError::DuplicateInput(_) => "the public input was allocated more than once",
+            SynthesisError::MissingInput(_) => "no value was given for the public input",
+            SynthesisError::DuplicatePort(_) => "the port was exposed more than once",
+            SynthesisError::UnknownPort(_) => "the sub-circuit does not expose the port",
+            SynthesisError::UnexpectedInput(_) => "a value was given for an unknown public input",
+            SynthesisError::Vetoed(_) => "proof generation was vetoed",
+            SynthesisError::LengthMismatch(_) => "multi-exponentiation length mismatch",
+            SynthesisError::DomainTooLarge(_) => "evaluation domain is too large for the scalar field"
         }
     }
 }
 
 impl fmt::Display for SynthesisError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let &SynthesisError::IoError(ref e) = self {
-            write!(f, "I/O error: ")?;
-            e.fmt(f)
-        } else {
-            write!(f, "{}", self.description())
+        match self {
+            SynthesisError::IoError(e) => {
+                write!(f, "I/O error: ")?;
+                e.fmt(f)
+            },
+            SynthesisError::UnassignedVariable(index) => {
+                write!(f, "{} to {}", self.description(), index)
+            },
+            SynthesisError::DuplicateInput(label)
+            | SynthesisError::MissingInput(label)
+            | SynthesisError::UnexpectedInput(label)
+            | SynthesisError::DuplicatePort(label)
+            | SynthesisError::UnknownPort(label) => {
+                write!(f, "{} `{}`", self.description(), label)
+            },
+            SynthesisError::Vetoed(reason) => {
+                write!(f, "{}: {}", self.description(), reason)
+            },
+            SynthesisError::LengthMismatch(mismatch) => {
+                let operand: &str = match mismatch.operand {
+                    Operand::Exponents => "exponents",
+                    Operand::Bases => "bases"
+                };
+                match mismatch.query {
+                    Some(query) => write!(f, "{} in the {} query: ", self.description(), query)?,
+                    None => write!(f, "{}: ", self.description())?
+                }
+                write!(f, "expected {} {} but found {}", mismatch.expected, operand, mismatch.actual)
+            },
+            SynthesisError::DomainTooLarge(size) => {
+                write!(f, "{}: {} points are needed but at most {} are available", self.description(), size.required, size.available)
+            },
+            _ => write!(f, "{}", self.description())
         }
     }
 }
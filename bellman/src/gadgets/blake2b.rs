@@ -0,0 +1,356 @@
+use ff::ScalarEngine;
+
+use crate::{ConstraintSystem, SynthesisError};
+
+use super::blake2s::SIGMA;
+
+use super::boolean::Boolean;
+
+use super::uint64::UInt64;
+
+use super::multieq::MultiEq;
+
+// See blake2s.rs for the RFC 7693 pseudocode, which BLAKE2b shares with
+// BLAKE2s up to the word size, the rotation constants, the number of
+// rounds and the block size.
+
+const R1: usize = 32;
+const R2: usize = 24;
+const R3: usize = 16;
+const R4: usize = 63;
+
+const IV: [u64; 8] = [
+    0x6A09E667F3BCC908,
+    0xBB67AE8584CAA73B,
+    0x3C6EF372FE94F82B,
+    0xA54FF53A5F1D36F1,
+    0x510E527FADE682D1,
+    0x9B05688C2B3E6C1F,
+    0x1F83D9ABFB41BD6B,
+    0x5BE0CD19137E2179,
+];
+
+fn mixing_g<E: ScalarEngine, CS: ConstraintSystem<E>, M>(
+    mut cs: M,
+    v: &mut [UInt64],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    x: &UInt64,
+    y: &UInt64,
+) -> Result<(), SynthesisError>
+where
+    M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
+{
+    v[a] = UInt64::addmany(
+        cs.namespace(|| "mixing step 1"),
+        &[v[a].clone(), v[b].clone(), x.clone()],
+    )?;
+    v[d] = v[d].xor(cs.namespace(|| "mixing step 2"), &v[a])?.rotr(R1);
+    v[c] = UInt64::addmany(
+        cs.namespace(|| "mixing step 3"),
+        &[v[c].clone(), v[d].clone()],
+    )?;
+    v[b] = v[b].xor(cs.namespace(|| "mixing step 4"), &v[c])?.rotr(R2);
+    v[a] = UInt64::addmany(
+        cs.namespace(|| "mixing step 5"),
+        &[v[a].clone(), v[b].clone(), y.clone()],
+    )?;
+    v[d] = v[d].xor(cs.namespace(|| "mixing step 6"), &v[a])?.rotr(R3);
+    v[c] = UInt64::addmany(
+        cs.namespace(|| "mixing step 7"),
+        &[v[c].clone(), v[d].clone()],
+    )?;
+    v[b] = v[b].xor(cs.namespace(|| "mixing step 8"), &v[c])?.rotr(R4);
+
+    Ok(())
+}
+
+fn blake2b_compression<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    h: &mut [UInt64],
+    m: &[UInt64],
+    t: u128,
+    f: bool,
+) -> Result<(), SynthesisError> {
+    assert_eq!(h.len(), 8);
+    assert_eq!(m.len(), 16);
+
+    let mut v = Vec::with_capacity(16);
+    v.extend_from_slice(h);
+    v.extend(IV.iter().map(|&word| UInt64::constant(word)));
+
+    assert_eq!(v.len(), 16);
+
+    v[12] = v[12].xor(cs.namespace(|| "first xor"), &UInt64::constant(t as u64))?;
+    v[13] = v[13].xor(
+        cs.namespace(|| "second xor"),
+        &UInt64::constant((t >> 64) as u64),
+    )?;
+
+    if f {
+        v[14] = v[14].xor(
+            cs.namespace(|| "third xor"),
+            &UInt64::constant(u64::max_value()),
+        )?;
+    }
+
+    {
+        let mut cs = MultiEq::new(&mut cs);
+
+        for i in 0..12 {
+            let mut cs = cs.namespace(|| format!("round {}", i));
+
+            let s = SIGMA[i % 10];
+
+            let invocations = [
+                (0, 4, 8, 12),
+                (1, 5, 9, 13),
+                (2, 6, 10, 14),
+                (3, 7, 11, 15),
+                (0, 5, 10, 15),
+                (1, 6, 11, 12),
+                (2, 7, 8, 13),
+                (3, 4, 9, 14),
+            ];
+
+            for (j, &(a, b, c, d)) in invocations.iter().enumerate() {
+                mixing_g(
+                    cs.namespace(|| format!("mixing invocation {}", j + 1)),
+                    &mut v,
+                    a,
+                    b,
+                    c,
+                    d,
+                    &m[s[2 * j]],
+                    &m[s[2 * j + 1]],
+                )?;
+            }
+        }
+    }
+
+    for i in 0..8 {
+        let mut cs = cs.namespace(|| format!("h[{i}] ^ v[{i}] ^ v[{i} + 8]", i = i));
+
+        h[i] = h[i].xor(cs.namespace(|| "first xor"), &v[i])?;
+        h[i] = h[i].xor(cs.namespace(|| "second xor"), &v[i + 8])?;
+    }
+
+    Ok(())
+}
+
+/// BLAKE2b-512 of `input` under a 16-byte `personalization`, with the
+/// input and output bytes given least significant bit first as in
+/// `blake2s`.
+pub fn blake2b<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    cs: CS,
+    input: &[Boolean],
+    personalization: &[u8],
+) -> Result<Vec<Boolean>, SynthesisError> {
+    blake2b_with_length(cs, input, 64, personalization)
+}
+
+/// BLAKE2b with a digest of `hash_length` bytes, such as the 32-byte
+/// digests of Zcash's signature hashes.
+pub fn blake2b_with_length<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    input: &[Boolean],
+    hash_length: usize,
+    personalization: &[u8],
+) -> Result<Vec<Boolean>, SynthesisError> {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    assert!((1..=64).contains(&hash_length));
+    assert_eq!(personalization.len(), 16);
+    assert!(input.len() % 8 == 0);
+
+    let mut h = Vec::with_capacity(8);
+    h.push(UInt64::constant(IV[0] ^ 0x01010000 ^ hash_length as u64));
+    h.push(UInt64::constant(IV[1]));
+    h.push(UInt64::constant(IV[2]));
+    h.push(UInt64::constant(IV[3]));
+    h.push(UInt64::constant(IV[4]));
+    h.push(UInt64::constant(IV[5]));
+
+    // Personalization is stored here
+    h.push(UInt64::constant(
+        IV[6] ^ LittleEndian::read_u64(&personalization[0..8]),
+    ));
+    h.push(UInt64::constant(
+        IV[7] ^ LittleEndian::read_u64(&personalization[8..16]),
+    ));
+
+    let mut blocks: Vec<Vec<UInt64>> = vec![];
+
+    for block in input.chunks(1024) {
+        let mut this_block = Vec::with_capacity(16);
+        for word in block.chunks(64) {
+            let mut tmp = word.to_vec();
+            while tmp.len() < 64 {
+                tmp.push(Boolean::constant(false));
+            }
+            this_block.push(UInt64::from_bits(&tmp));
+        }
+        while this_block.len() < 16 {
+            this_block.push(UInt64::constant(0));
+        }
+        blocks.push(this_block);
+    }
+
+    if blocks.is_empty() {
+        blocks.push((0..16).map(|_| UInt64::constant(0)).collect());
+    }
+
+    for (i, block) in blocks[0..blocks.len() - 1].iter().enumerate() {
+        let cs = cs.namespace(|| format!("block {}", i));
+
+        blake2b_compression(cs, &mut h, block, ((i as u128) + 1) * 128, false)?;
+    }
+
+    {
+        let cs = cs.namespace(|| "final block");
+
+        blake2b_compression(
+            cs,
+            &mut h,
+            &blocks[blocks.len() - 1],
+            (input.len() / 8) as u128,
+            true,
+        )?;
+    }
+
+    Ok(h
+        .iter()
+        .flat_map(|b| b.into_bits())
+        .take(hash_length * 8)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{blake2b, blake2b_with_length};
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::ConstraintSystem;
+
+    // "ZcashSigHash" followed by the little-endian Sapling branch id.
+    const PERSONALIZATION: &[u8; 16] = b"ZcashSigHash\xbb\x09\xb8\x76";
+
+    fn alloc_bytes(cs: &mut TestConstraintSystem<Bls12>, data: &[u8]) -> Vec<Boolean> {
+        let mut input_bits = vec![];
+
+        for (byte_i, input_byte) in data.iter().enumerate() {
+            for bit_i in 0..8 {
+                let cs = cs.namespace(|| format!("input bit {} {}", byte_i, bit_i));
+
+                input_bits.push(
+                    AllocatedBit::alloc(cs, Some((input_byte >> bit_i) & 1u8 == 1u8))
+                        .unwrap()
+                        .into(),
+                );
+            }
+        }
+
+        input_bits
+    }
+
+    fn assert_digest(out: Vec<Boolean>, expected: &[u8]) {
+        assert_eq!(out.len(), expected.len() * 8);
+
+        let mut out = out.into_iter();
+        for b in expected.iter() {
+            for i in 0..8 {
+                let c = out.next().unwrap().get_value().unwrap();
+
+                assert_eq!(c, (b >> i) & 1u8 == 1u8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blank_hash() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let out = blake2b(&mut cs, &[], &[0; 16]).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 0);
+
+        // >>> from hashlib import blake2b
+        // >>> blake2b(b'').hexdigest()
+        let expected = hex!("786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce");
+
+        assert_digest(out, &expected);
+    }
+
+    #[test]
+    fn test_blake2b_constraints() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let input_bits: Vec<_> = (0..1024)
+            .map(|i| {
+                AllocatedBit::alloc(cs.namespace(|| format!("input bit {}", i)), Some(true))
+                    .unwrap()
+                    .into()
+            })
+            .collect();
+        blake2b(&mut cs, &input_bits, PERSONALIZATION).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 50880);
+    }
+
+    #[test]
+    fn test_blake2b_constant_constraints() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let input_bits: Vec<_> = (0..1024)
+            .map(|_| Boolean::constant(rng.next_u32() % 2 != 0))
+            .collect();
+        blake2b(&mut cs, &input_bits, PERSONALIZATION).unwrap();
+        assert_eq!(cs.num_constraints(), 0);
+    }
+
+    #[test]
+    fn test_blake2b() {
+        // >>> from hashlib import blake2b
+        // >>> data = bytes(i * 7 + 3 & 0xff for i in range(n))
+        // >>> blake2b(data, person=b'ZcashSigHash\xbb\x09\xb8\x76').hexdigest()
+        let vectors: [(usize, [u8; 64]); 5] = [
+            (0, hex!("593c9e109f0617c078a13f23ec9c07e0abccf890e7ec5b6d1de36b7e230e991959c62835573a29e622c15d27364c029c716e2823406021324dd07cb998e9da31")),
+            (3, hex!("ada58d6627d964b63fa23d5602a98ca61439737b3b15147c8518fb4801e682d785775e76c6a38789215f3407dd4fb45939087b0547baa17d1ecbe1207acb8cdb")),
+            (127, hex!("42fd28e2b165cf58a84ce383e0baa680bf0b8c3750b74ac899e31a8e25b8fa460d77ebe3be42d6c08659bbdc018fd8723785b35b78d8eece23f8ccd6fcaa4393")),
+            (128, hex!("12cc703ea8a42af74445743344dfba0fe3d725aaaf24da0c468497169001a7410d10dc056762d92e05e33acb015a2bb8064e91f54243331fa9fdea2d6211f943")),
+            (129, hex!("c1467a28c13f76cc166d2206f4ec3e7d104c4d429c3019b7ba6654691361992d8d5aeef759123617290695742f8120983e59df073627fa0c3faee29da6a8b645")),
+        ];
+
+        for (input_len, expected) in vectors.iter() {
+            let data: Vec<u8> = (0..*input_len).map(|i| (i * 7 + 3) as u8).collect();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let input_bits = alloc_bytes(&mut cs, &data);
+            let r = blake2b(&mut cs, &input_bits, PERSONALIZATION).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_digest(r, expected);
+        }
+    }
+
+    #[test]
+    fn test_blake2b_with_length() {
+        // >>> blake2b(data, digest_size=32, person=b'ZcashSigHash\xbb\x09\xb8\x76').hexdigest()
+        let data: Vec<u8> = (0..200).map(|i| (i * 7 + 3) as u8).collect();
+        let expected = hex!("a5dff5b92f38672216b0e7fb72c1c160dcdc1fd61e08cf43343f0e37d7173dd6");
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let input_bits = alloc_bytes(&mut cs, &data);
+        let r = blake2b_with_length(&mut cs, &input_bits, 32, PERSONALIZATION).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_digest(r, &expected);
+    }
+}
@@ -1,4 +1,4 @@
-use pairing::Engine;
+use ff::ScalarEngine;
 
 use crate::{ConstraintSystem, SynthesisError};
 
@@ -46,7 +46,7 @@ const R4: usize = 7;
         ----------+-------------------------------------------------+
 */
 
-const SIGMA: [[usize; 16]; 10] = [
+pub(super) const SIGMA: [[usize; 16]; 10] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
     [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
     [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
@@ -81,7 +81,7 @@ const SIGMA: [[usize; 16]; 10] = [
        END FUNCTION.
 */
 
-fn mixing_g<E: Engine, CS: ConstraintSystem<E>, M>(
+fn mixing_g<E: ScalarEngine, CS: ConstraintSystem<E>, M>(
     mut cs: M,
     v: &mut [UInt32],
     a: usize,
@@ -166,7 +166,7 @@ where
        END FUNCTION.
 */
 
-fn blake2s_compression<E: Engine, CS: ConstraintSystem<E>>(
+fn blake2s_compression<E: ScalarEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     h: &mut [UInt32],
     m: &[UInt32],
@@ -339,7 +339,7 @@ fn blake2s_compression<E: Engine, CS: ConstraintSystem<E>>(
         END FUNCTION.
 */
 
-pub fn blake2s<E: Engine, CS: ConstraintSystem<E>>(
+pub fn blake2s<E: ScalarEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     input: &[Boolean],
     personalization: &[u8],
@@ -1,5 +1,4 @@
-use ff::{BitIterator, Field, PrimeField};
-use pairing::Engine;
+use ff::{BitIterator, Field, PrimeField, ScalarEngine};
 
 use crate::{ConstraintSystem, LinearCombination, SynthesisError, Coefficient, Result};
 
@@ -25,7 +24,7 @@ impl AllocatedBit {
     /// unless the condition is false.
     pub fn alloc_conditionally<E,CS>(mut cs: CS, value: Option<bool>, must_be_false: &AllocatedBit) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let var = cs.alloc(
@@ -62,7 +61,7 @@ impl AllocatedBit {
     /// boolean value.
     pub fn alloc<E, CS>(mut cs: CS, value: Option<bool>) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let var = cs.alloc(
@@ -78,12 +77,7 @@ impl AllocatedBit {
 
         // Constrain: (1 - a) * a = 0
         // This constrains a to be either 0 or 1.
-        cs.enforce(
-            || "boolean constraint",
-            |lc| lc + CS::one() - var,
-            |lc| lc + var,
-            |lc| lc,
-        );
+        cs.enforce_boolean(|| "boolean constraint", var);
 
         Ok(AllocatedBit {
             variable: var,
@@ -95,7 +89,7 @@ impl AllocatedBit {
     /// an `AllocatedBit`.
     pub fn xor<E, CS>(mut cs: CS, a: &Self, b: &Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let mut result_value = None;
@@ -150,7 +144,7 @@ impl AllocatedBit {
     /// an `AllocatedBit`.
     pub fn and<E, CS>(mut cs: CS, a: &Self, b: &Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let mut result_value = None;
@@ -191,7 +185,7 @@ impl AllocatedBit {
     /// Calculates `a AND (NOT b)`.
     pub fn and_not<E, CS>(mut cs: CS, a: &Self, b: &Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let mut result_value = None;
@@ -232,7 +226,7 @@ impl AllocatedBit {
     /// Calculates `(NOT a) AND (NOT b)`.
     pub fn nor<E, CS>(mut cs: CS, a: &Self, b: &Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let mut result_value = None;
@@ -271,7 +265,7 @@ impl AllocatedBit {
     }
 }
 
-pub fn u64_into_boolean_vec_le<E: Engine, CS: ConstraintSystem<E>>(
+pub fn u64_into_boolean_vec_le<E: ScalarEngine, CS: ConstraintSystem<E>>(
     mut cs: CS,
     value: Option<u64>,
 ) -> Result<Vec<Boolean>> {
@@ -302,7 +296,7 @@ pub fn u64_into_boolean_vec_le<E: Engine, CS: ConstraintSystem<E>>(
     Ok(bits)
 }
 
-pub fn field_into_boolean_vec_le<E: Engine, CS: ConstraintSystem<E>, F: PrimeField>(
+pub fn field_into_boolean_vec_le<E: ScalarEngine, CS: ConstraintSystem<E>, F: PrimeField>(
     cs: CS,
     value: Option<F>,
 ) -> Result<Vec<Boolean>> {
@@ -311,7 +305,7 @@ pub fn field_into_boolean_vec_le<E: Engine, CS: ConstraintSystem<E>, F: PrimeFie
     Ok(v.into_iter().map(|e| Boolean::from(e)).collect())
 }
 
-pub fn field_into_allocated_bits_le<E: Engine, CS: ConstraintSystem<E>, F: PrimeField>(
+pub fn field_into_allocated_bits_le<E: ScalarEngine, CS: ConstraintSystem<E>, F: PrimeField>(
     mut cs: CS,
     value: Option<F>,
 ) -> Result<Vec<AllocatedBit>> {
@@ -373,7 +367,7 @@ impl Boolean {
 
     pub fn enforce_equal<E, CS>(mut cs: CS, a: &Self, b: &Self) -> Result<()>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         match (a, b) {
@@ -425,7 +419,7 @@ impl Boolean {
         }
     }
 
-    pub fn lc<E: Engine>(&self, one: Coefficient, coeff: E::Fr) -> LinearCombination<E> {
+    pub fn lc<E: ScalarEngine>(&self, one: Coefficient, coeff: E::Fr) -> LinearCombination<E> {
         match self {
             &Boolean::Constant(c) => {
                 if c {
@@ -458,7 +452,7 @@ impl Boolean {
     /// Perform XOR over two boolean operands
     pub fn xor<'a, E, CS>(cs: CS, a: &'a Self, b: &'a Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         match (a, b) {
@@ -480,7 +474,7 @@ impl Boolean {
     /// Perform AND over two boolean operands
     pub fn and<'a, E, CS>(cs: CS, a: &'a Self, b: &'a Self) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         match (a, b) {
@@ -506,6 +500,64 @@ impl Boolean {
         }
     }
 
+    /// Returns `a` if the condition is true, and `b`
+    /// otherwise.
+    pub fn conditionally_select<E, CS>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Self,
+    ) -> Result<Self>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        match (condition, a, b) {
+            (&Boolean::Constant(true), a, _) => return Ok(a.clone()),
+            (&Boolean::Constant(false), _, b) => return Ok(b.clone()),
+            (condition, &Boolean::Constant(a), &Boolean::Constant(b)) => {
+                return Ok(match (a, b) {
+                    (true, false) => condition.clone(),
+                    (false, true) => condition.not(),
+                    _ => Boolean::Constant(a),
+                });
+            }
+            _ => {}
+        }
+
+        let value = match condition.get_value() {
+            Some(true) => a.get_value(),
+            Some(false) => b.get_value(),
+            None => None,
+        };
+
+        let var = cs.alloc(
+            || "selection",
+            || {
+                if value.ok_or(SynthesisError::AssignmentMissing)? {
+                    Ok(E::Fr::one())
+                } else {
+                    Ok(E::Fr::zero())
+                }
+            },
+        )?;
+
+        // Constrain: (a - b) * condition = r - b
+        // The result is either a or b, so is boolean
+        // without a constraint of its own.
+        cs.enforce(
+            || "selection constraint",
+            |_| a.lc(CS::one(), E::Fr::one()) - &b.lc(CS::one(), E::Fr::one()),
+            |_| condition.lc(CS::one(), E::Fr::one()),
+            |lc| lc + var - &b.lc(CS::one(), E::Fr::one()),
+        );
+
+        Ok(Boolean::Is(AllocatedBit {
+            variable: var,
+            value: value,
+        }))
+    }
+
     /// Computes (a and b) xor ((not a) and c)
     pub fn sha256_ch<'a, E, CS>(
         mut cs: CS,
@@ -514,7 +566,7 @@ impl Boolean {
         c: &'a Self,
     ) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let ch_value = match (a.get_value(), b.get_value(), c.get_value()) {
@@ -625,7 +677,7 @@ impl Boolean {
         c: &'a Self,
     ) -> Result<Self>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let maj_value = match (a.get_value(), b.get_value(), c.get_value()) {
@@ -1526,6 +1578,78 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_boolean_conditionally_select() {
+        let variants = [
+            OperandType::True,
+            OperandType::False,
+            OperandType::AllocatedTrue,
+            OperandType::AllocatedFalse,
+            OperandType::NegatedAllocatedTrue,
+            OperandType::NegatedAllocatedFalse,
+        ];
+
+        for condition in variants.iter().cloned() {
+            for first_operand in variants.iter().cloned() {
+                for second_operand in variants.iter().cloned() {
+                    let mut cs = TestConstraintSystem::<Bls12>::new();
+
+                    let mut dyn_construct = |operand, name| {
+                        let cs = cs.namespace(|| name);
+
+                        match operand {
+                            OperandType::True => Boolean::constant(true),
+                            OperandType::False => Boolean::constant(false),
+                            OperandType::AllocatedTrue => {
+                                Boolean::from(AllocatedBit::alloc(cs, Some(true)).unwrap())
+                            }
+                            OperandType::AllocatedFalse => {
+                                Boolean::from(AllocatedBit::alloc(cs, Some(false)).unwrap())
+                            }
+                            OperandType::NegatedAllocatedTrue => {
+                                Boolean::from(AllocatedBit::alloc(cs, Some(true)).unwrap()).not()
+                            }
+                            OperandType::NegatedAllocatedFalse => {
+                                Boolean::from(AllocatedBit::alloc(cs, Some(false)).unwrap()).not()
+                            }
+                        }
+                    };
+
+                    let c = dyn_construct(condition, "condition");
+                    let a = dyn_construct(first_operand, "a");
+                    let b = dyn_construct(second_operand, "b");
+                    let before = cs.num_constraints();
+
+                    let r = Boolean::conditionally_select(cs.namespace(|| "select"), &a, &b, &c)
+                        .unwrap();
+
+                    assert!(cs.is_satisfied());
+
+                    let expected = if condition.val() {
+                        first_operand.val()
+                    } else {
+                        second_operand.val()
+                    };
+                    assert_eq!(r.get_value(), Some(expected));
+
+                    let free = condition.is_constant()
+                        || (first_operand.is_constant() && second_operand.is_constant());
+                    assert_eq!(cs.num_constraints() - before, if free { 0 } else { 1 });
+
+                    if !free {
+                        // The result is pinned to the selected operand.
+                        let forged = if expected { Fr::zero() } else { Fr::one() };
+                        cs.set("select/selection", forged);
+                        assert_eq!(
+                            cs.which_is_unsatisfied(),
+                            Some("select/selection constraint")
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_u64_into_boolean_vec_le() {
         let mut cs = TestConstraintSystem::<Bls12>::new();
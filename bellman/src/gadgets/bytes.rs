@@ -0,0 +1,305 @@
+//! Gadgets over byte strings, for checking serialized messages.
+//!
+//! Lengths and offsets are fixed by the circuit, so only the contents of
+//! the strings are witnessed. Comparisons pack the bits of both strings
+//! into as few field elements as they fit in, and take the same
+//! constraints whatever the contents.
+
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::boolean::{AllocatedBit, Boolean};
+use super::uint8::UInt8;
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+/// The differences of the strings, packed `CAPACITY` bits at a time, and
+/// the values of these differences.
+fn packed_differences<E, CS>(a: &[UInt8], b: &[UInt8]) -> Vec<(LinearCombination<E>, Option<E::Fr>)>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert_eq!(a.len(), b.len());
+
+    let a_bits: Vec<_> = a.iter().flat_map(|byte| byte.into_bits()).collect();
+    let b_bits: Vec<_> = b.iter().flat_map(|byte| byte.into_bits()).collect();
+
+    a_bits
+        .chunks(E::Fr::CAPACITY as usize)
+        .zip(b_bits.chunks(E::Fr::CAPACITY as usize))
+        .map(|(a, b)| {
+            let mut lc = LinearCombination::zero();
+            let mut value = Some(E::Fr::zero());
+            let mut coeff = E::Fr::one();
+            for (a, b) in a.iter().zip(b.iter()) {
+                lc = lc + &a.lc(CS::one(), coeff) - &b.lc(CS::one(), coeff);
+
+                value = match (value, a.get_value(), b.get_value()) {
+                    (Some(mut value), Some(a), Some(b)) => {
+                        if a {
+                            value.add_assign(&coeff);
+                        }
+                        if b {
+                            value.sub_assign(&coeff);
+                        }
+                        Some(value)
+                    }
+                    _ => None,
+                };
+
+                coeff.double();
+            }
+
+            (lc, value)
+        })
+        .collect()
+}
+
+/// Whether `a` and `b` are the same string. Strings of different lengths
+/// never are, and cost nothing to compare.
+///
+/// Each `CAPACITY` bits of the strings cost three constraints, and all but
+/// the first one more.
+pub fn is_equal<E, CS>(mut cs: CS, a: &[UInt8], b: &[UInt8]) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    if a.len() != b.len() {
+        return Ok(Boolean::constant(false));
+    }
+
+    let mut result = Boolean::constant(true);
+    for (i, (lc, value)) in packed_differences::<E, CS>(a, b).into_iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("chunk {}", i));
+
+        let equal = AllocatedBit::alloc(cs.namespace(|| "equal"), value.map(|v| v.is_zero()))?;
+        let inverse = cs.alloc(
+            || "inverse",
+            || {
+                let value = value.ok_or(SynthesisError::AssignmentMissing)?;
+                Ok(value.inverse().unwrap_or_else(E::Fr::zero))
+            },
+        )?;
+
+        // Constrain: difference * inverse = 1 - equal
+        // A nonzero difference thus has an inverse and is
+        // not equal, and a zero difference is equal.
+        cs.enforce(
+            || "inverse constraint",
+            |_| lc.clone(),
+            |lc| lc + inverse,
+            |lc| lc + CS::one() - equal.get_variable(),
+        );
+
+        // Constrain: difference * equal = 0
+        // A nonzero difference is thus not equal.
+        cs.enforce(
+            || "equality constraint",
+            |_| lc,
+            |lc| lc + equal.get_variable(),
+            |lc| lc,
+        );
+
+        result = Boolean::and(cs.namespace(|| "and"), &result, &Boolean::from(equal))?;
+    }
+
+    Ok(result)
+}
+
+/// Enforces that `a` and `b` are the same string, in one constraint for
+/// each `CAPACITY` bits of them.
+pub fn enforce_equal<E, CS>(mut cs: CS, a: &[UInt8], b: &[UInt8]) -> Result<(), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    if a.len() != b.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    for (i, (lc, _)) in packed_differences::<E, CS>(a, b).into_iter().enumerate() {
+        cs.enforce(
+            || format!("equality constraint {}", i),
+            |_| lc,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether `s` begins with `prefix`.
+pub fn starts_with<E, CS>(cs: CS, s: &[UInt8], prefix: &[UInt8]) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    if prefix.len() > s.len() {
+        return Ok(Boolean::constant(false));
+    }
+
+    is_equal(cs, &s[..prefix.len()], prefix)
+}
+
+/// The `len` bytes of `s` from `offset`. Extracting them adds no
+/// constraints, as the offset is fixed.
+pub fn substring(s: &[UInt8], offset: usize, len: usize) -> Vec<UInt8> {
+    assert!(offset + len <= s.len(), "the substring is out of bounds");
+
+    s[offset..offset + len].to_vec()
+}
+
+/// Enforces that `sub` appears in `s` at `offset`.
+pub fn enforce_substring<E, CS>(
+    cs: CS,
+    s: &[UInt8],
+    offset: usize,
+    sub: &[UInt8],
+) -> Result<(), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    if offset + sub.len() > s.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    enforce_equal(cs, &s[offset..offset + sub.len()], sub)
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{enforce_equal, enforce_substring, is_equal, starts_with, substring};
+    use crate::gadgets::test::*;
+    use crate::gadgets::uint8::UInt8;
+    use crate::{ConstraintSystem, SynthesisError};
+
+    fn alloc<CS: ConstraintSystem<Bls12>>(cs: CS, data: &[u8]) -> Vec<UInt8> {
+        let values: Vec<_> = data.iter().map(|&b| Some(b)).collect();
+        UInt8::alloc_vec(cs, &values).unwrap()
+    }
+
+    #[test]
+    fn test_bytes_is_equal() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &len in [1, 31, 32, 64].iter() {
+            let mut data = vec![0u8; len];
+            rng.fill_bytes(&mut data);
+            let mut other = data.clone();
+            other[rng.next_u32() as usize % len] ^= 1 << (rng.next_u32() % 8);
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a = alloc(cs.namespace(|| "a"), &data);
+            let b = alloc(cs.namespace(|| "b"), &data);
+            let c = alloc(cs.namespace(|| "c"), &other);
+            let before = cs.num_constraints();
+
+            let same = is_equal(cs.namespace(|| "a == b"), &a, &b).unwrap();
+            let chunks = (len * 8 + 253) / 254;
+            assert_eq!(cs.num_constraints() - before, 3 * chunks + chunks - 1);
+
+            let different = is_equal(cs.namespace(|| "a == c"), &a, &c).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(same.get_value(), Some(true));
+            assert_eq!(different.get_value(), Some(false));
+
+            // The result cannot be flipped.
+            cs.set("a == b/chunk 0/equal/boolean", Fr::zero());
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_bytes_enforce_equal() {
+        let message = b"\x05zcash\x00\x01payload";
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = alloc(cs.namespace(|| "a"), message);
+        enforce_equal(cs.namespace(|| "equal"), &a, &UInt8::constant_vec(message)).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 8 * message.len() + 1);
+
+        let mut other = message.to_vec();
+        other[3] = b'X';
+        enforce_equal(
+            cs.namespace(|| "not equal"),
+            &a,
+            &UInt8::constant_vec(&other),
+        )
+        .unwrap();
+        assert_eq!(
+            cs.which_is_unsatisfied(),
+            Some("not equal/equality constraint 0")
+        );
+
+        match enforce_equal(cs.namespace(|| "lengths"), &a, &a[1..]) {
+            Err(SynthesisError::Unsatisfiable) => {}
+            _ => panic!("strings of different lengths cannot be equal"),
+        }
+    }
+
+    #[test]
+    fn test_bytes_prefix_and_substring() {
+        let message = b"\x05zcash\x00\x01payload";
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let s = alloc(cs.namespace(|| "s"), message);
+
+        let r = starts_with(
+            cs.namespace(|| "prefix"),
+            &s,
+            &UInt8::constant_vec(b"\x05zcash"),
+        )
+        .unwrap();
+        assert_eq!(r.get_value(), Some(true));
+        let r = starts_with(
+            cs.namespace(|| "wrong prefix"),
+            &s,
+            &UInt8::constant_vec(b"\x05zec"),
+        )
+        .unwrap();
+        assert_eq!(r.get_value(), Some(false));
+        let r = starts_with(
+            cs.namespace(|| "long prefix"),
+            &s,
+            &UInt8::constant_vec(&[0; 32]),
+        )
+        .unwrap();
+        assert_eq!(r.get_value(), Some(false));
+
+        let payload = substring(&s, 8, 7);
+        let bytes: Vec<_> = payload.iter().map(|b| b.get_value().unwrap()).collect();
+        assert_eq!(bytes, b"payload".to_vec());
+
+        let before = cs.num_constraints();
+        enforce_substring(
+            cs.namespace(|| "version"),
+            &s,
+            6,
+            &UInt8::constant_vec(b"\x00\x01"),
+        )
+        .unwrap();
+        assert_eq!(cs.num_constraints() - before, 1);
+        assert!(cs.is_satisfied());
+
+        enforce_substring(
+            cs.namespace(|| "bad version"),
+            &s,
+            6,
+            &UInt8::constant_vec(b"\x00\x02"),
+        )
+        .unwrap();
+        assert!(!cs.is_satisfied());
+    }
+}
@@ -0,0 +1,288 @@
+use ff::ScalarEngine;
+
+use crate::{ConstraintSystem, SynthesisError};
+
+use super::boolean::Boolean;
+
+use super::uint32::UInt32;
+
+use super::multieq::MultiEq;
+
+// The ChaCha20 block function and cipher of RFC 8439, with its 32-bit block
+// counter and 96-bit nonce. Keys, nonces and data are bit strings in the
+// same order as the inputs and outputs of the BLAKE2 gadgets: bytes in
+// order, and the bits of each byte least significant first.
+
+/// "expand 32-byte k", read as little-endian words.
+const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/*
+2.1.  The ChaCha Quarter Round
+   1.  a += b; d ^= a; d <<<= 16;
+   2.  c += d; b ^= c; b <<<= 12;
+   3.  a += b; d ^= a; d <<<= 8;
+   4.  c += d; b ^= c; b <<<= 7;
+*/
+
+fn quarter_round<E: ScalarEngine, CS: ConstraintSystem<E>, M>(
+    mut cs: M,
+    v: &mut [UInt32],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+) -> Result<(), SynthesisError>
+where
+    M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
+{
+    v[a] = UInt32::addmany(cs.namespace(|| "step 1"), &[v[a].clone(), v[b].clone()])?;
+    v[d] = v[d].xor(cs.namespace(|| "step 2"), &v[a])?.rotr(32 - 16);
+    v[c] = UInt32::addmany(cs.namespace(|| "step 3"), &[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(cs.namespace(|| "step 4"), &v[c])?.rotr(32 - 12);
+    v[a] = UInt32::addmany(cs.namespace(|| "step 5"), &[v[a].clone(), v[b].clone()])?;
+    v[d] = v[d].xor(cs.namespace(|| "step 6"), &v[a])?.rotr(32 - 8);
+    v[c] = UInt32::addmany(cs.namespace(|| "step 7"), &[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(cs.namespace(|| "step 8"), &v[c])?.rotr(32 - 7);
+
+    Ok(())
+}
+
+fn into_words(bits: &[Boolean]) -> Vec<UInt32> {
+    bits.chunks(32).map(UInt32::from_bits).collect()
+}
+
+/// The 512 bits of keystream of the block numbered `counter`, under a
+/// 256-bit `key` and a 96-bit `nonce`.
+pub fn chacha20_block<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    key: &[Boolean],
+    nonce: &[Boolean],
+    counter: &UInt32,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    assert_eq!(key.len(), 256);
+    assert_eq!(nonce.len(), 96);
+
+    let mut initial = Vec::with_capacity(16);
+    initial.extend(SIGMA.iter().map(|&w| UInt32::constant(w)));
+    initial.extend(into_words(key));
+    initial.push(counter.clone());
+    initial.extend(into_words(nonce));
+
+    let mut v = initial.clone();
+
+    let mut cs = MultiEq::new(&mut cs);
+
+    for i in 0..10 {
+        let mut cs = cs.namespace(|| format!("double round {}", i));
+
+        // Column round
+        quarter_round(cs.namespace(|| "quarter round 1"), &mut v, 0, 4, 8, 12)?;
+        quarter_round(cs.namespace(|| "quarter round 2"), &mut v, 1, 5, 9, 13)?;
+        quarter_round(cs.namespace(|| "quarter round 3"), &mut v, 2, 6, 10, 14)?;
+        quarter_round(cs.namespace(|| "quarter round 4"), &mut v, 3, 7, 11, 15)?;
+
+        // Diagonal round
+        quarter_round(cs.namespace(|| "quarter round 5"), &mut v, 0, 5, 10, 15)?;
+        quarter_round(cs.namespace(|| "quarter round 6"), &mut v, 1, 6, 11, 12)?;
+        quarter_round(cs.namespace(|| "quarter round 7"), &mut v, 2, 7, 8, 13)?;
+        quarter_round(cs.namespace(|| "quarter round 8"), &mut v, 3, 4, 9, 14)?;
+    }
+
+    let mut block = Vec::with_capacity(512);
+    for (i, (word, initial)) in v.into_iter().zip(initial).enumerate() {
+        let word = UInt32::addmany(
+            cs.namespace(|| format!("final addition {}", i)),
+            &[word, initial],
+        )?;
+        block.extend(word.into_bits());
+    }
+
+    Ok(block)
+}
+
+/// `len` bits of keystream, from the block numbered `counter` onwards.
+pub fn chacha20_keystream<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    key: &[Boolean],
+    nonce: &[Boolean],
+    counter: u32,
+    len: usize,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    let blocks = (len + 511) / 512;
+    assert!(
+        blocks as u64 <= (1 << 32) - counter as u64,
+        "the block counter would wrap around"
+    );
+
+    let mut keystream = Vec::with_capacity(blocks * 512);
+    for i in 0..blocks {
+        let block = chacha20_block(
+            cs.namespace(|| format!("block {}", i)),
+            key,
+            nonce,
+            &UInt32::constant(counter + i as u32),
+        )?;
+        keystream.extend(block);
+    }
+    keystream.truncate(len);
+
+    Ok(keystream)
+}
+
+/// Encrypts or decrypts `input` by XORing it with the keystream from the
+/// block numbered `counter` onwards, which for the payloads of
+/// ChaCha20-Poly1305 is block 1.
+pub fn chacha20_xor<E: ScalarEngine, CS: ConstraintSystem<E>>(
+    mut cs: CS,
+    key: &[Boolean],
+    nonce: &[Boolean],
+    counter: u32,
+    input: &[Boolean],
+) -> Result<Vec<Boolean>, SynthesisError> {
+    let keystream = chacha20_keystream(
+        cs.namespace(|| "keystream"),
+        key,
+        nonce,
+        counter,
+        input.len(),
+    )?;
+
+    input
+        .iter()
+        .zip(keystream.iter())
+        .enumerate()
+        .map(|(i, (a, b))| Boolean::xor(cs.namespace(|| format!("xor of bit {}", i)), a, b))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{chacha20_block, chacha20_xor};
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::gadgets::uint32::UInt32;
+    use crate::ConstraintSystem;
+
+    fn alloc_bytes<CS: ConstraintSystem<Bls12>>(mut cs: CS, data: &[u8]) -> Vec<Boolean> {
+        let mut bits = vec![];
+        for (byte_i, byte) in data.iter().enumerate() {
+            for bit_i in 0..8 {
+                let cs = cs.namespace(|| format!("bit {} {}", byte_i, bit_i));
+
+                bits.push(
+                    AllocatedBit::alloc(cs, Some((byte >> bit_i) & 1u8 == 1u8))
+                        .unwrap()
+                        .into(),
+                );
+            }
+        }
+        bits
+    }
+
+    fn into_bytes(bits: &[Boolean]) -> Vec<u8> {
+        bits.chunks(8)
+            .map(|byte| {
+                byte.iter().enumerate().fold(0u8, |acc, (i, bit)| {
+                    acc | ((bit.get_value().unwrap() as u8) << i)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chacha20_block() {
+        // RFC 8439, section 2.3.2
+        let key: Vec<u8> = (0..32).collect();
+        let nonce = hex!("000000090000004a00000000");
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key = alloc_bytes(cs.namespace(|| "key"), &key);
+        let nonce = alloc_bytes(cs.namespace(|| "nonce"), &nonce);
+        let counter = UInt32::alloc(cs.namespace(|| "counter"), Some(1)).unwrap();
+
+        let block = chacha20_block(cs.namespace(|| "block"), &key, &nonce, &counter).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints() - 256 - 96 - 32, 21376);
+        assert_eq!(
+            into_bytes(&block),
+            hex!("10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4ed2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e").to_vec()
+        );
+    }
+
+    #[test]
+    fn test_chacha20_constant_constraints() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key: Vec<_> = (0..256).map(|i| Boolean::constant(i % 3 == 0)).collect();
+        let nonce: Vec<_> = (0..96).map(|i| Boolean::constant(i % 5 == 0)).collect();
+        chacha20_xor(&mut cs, &key, &nonce, 1, &key).unwrap();
+        assert_eq!(cs.num_constraints(), 0);
+    }
+
+    #[test]
+    fn test_chacha20_xor() {
+        // RFC 8439, section 2.4.2
+        let key: Vec<u8> = (0..32).collect();
+        let nonce = hex!("000000000000004a00000000");
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key = alloc_bytes(cs.namespace(|| "key"), &key);
+        let nonce = alloc_bytes(cs.namespace(|| "nonce"), &nonce);
+        let input = alloc_bytes(cs.namespace(|| "plaintext"), plaintext);
+
+        let ciphertext = chacha20_xor(cs.namespace(|| "encrypt"), &key, &nonce, 1, &input).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(
+            into_bytes(&ciphertext),
+            hex!("6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0bf91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d807ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab77937365af90bbf74a35be6b40b8eedf2785e42874d").to_vec()
+        );
+
+        // Decryption inverts encryption.
+        let decrypted =
+            chacha20_xor(cs.namespace(|| "decrypt"), &key, &nonce, 1, &ciphertext).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(into_bytes(&decrypted), plaintext.to_vec());
+    }
+
+    #[test]
+    fn test_chacha20_keystream_lengths() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        // Lengths across block boundaries must agree with each other.
+        let mut key = [0u8; 32];
+        rng.fill_bytes(&mut key);
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut nonce);
+        let mut data = [0u8; 130];
+        rng.fill_bytes(&mut data);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key = alloc_bytes(cs.namespace(|| "key"), &key);
+        let nonce = alloc_bytes(cs.namespace(|| "nonce"), &nonce);
+        let input = alloc_bytes(cs.namespace(|| "input"), &data);
+
+        let full =
+            into_bytes(&chacha20_xor(cs.namespace(|| "full"), &key, &nonce, 7, &input).unwrap());
+        for &len in [0, 1, 63, 64, 65, 128].iter() {
+            let part = chacha20_xor(
+                cs.namespace(|| format!("length {}", len)),
+                &key,
+                &nonce,
+                7,
+                &input[..len * 8],
+            )
+            .unwrap();
+            assert_eq!(into_bytes(&part), full[..len].to_vec());
+        }
+        assert!(cs.is_satisfied());
+    }
+}
@@ -0,0 +1,192 @@
+//! Comparisons between allocated numbers read as integers below 2^`bits`.
+//!
+//! The operands are not range checked here: a comparison is only sound
+//! when both are known to lie below 2^`bits`, whether from the way they
+//! were built or from `AllocatedNum::assert_in_range`. Each comparison
+//! takes `bits + 1` constraints.
+
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::boolean::{AllocatedBit, Boolean};
+use super::num::AllocatedNum;
+use super::range;
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+/// Whether `a < b`.
+pub fn lt<E, CS>(
+    cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    Ok(ge(cs, a, b, bits)?.not())
+}
+
+/// Whether `a <= b`.
+pub fn le<E, CS>(
+    cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    ge(cs, b, a, bits)
+}
+
+/// Whether `a > b`.
+pub fn gt<E, CS>(
+    cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    lt(cs, b, a, bits)
+}
+
+/// Whether `a >= b`.
+///
+/// a - b + 2^bits lies in [1, 2^(bits + 1)), and is at least 2^bits
+/// exactly when a >= b. That top bit is allocated, and the rest of the sum
+/// is checked to lie below 2^bits.
+pub fn ge<E, CS>(
+    mut cs: CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(bits < E::Fr::CAPACITY as usize);
+
+    let mut shift = E::Fr::one();
+    for _ in 0..bits {
+        shift.double();
+    }
+
+    let ge_value = match (a.get_value(), b.get_value()) {
+        (Some(a), Some(b)) => Some(a.into_repr() >= b.into_repr()),
+        _ => None,
+    };
+    let ge = AllocatedBit::alloc(cs.namespace(|| "a >= b"), ge_value)?;
+
+    // a - b + 2^bits without its top bit
+    let rest_value = match (a.get_value(), b.get_value(), ge_value) {
+        (Some(a), Some(b), Some(ge)) => {
+            let mut tmp = a;
+            tmp.sub_assign(&b);
+            if !ge {
+                tmp.add_assign(&shift);
+            }
+            Some(tmp)
+        }
+        _ => None,
+    };
+    let rest = LinearCombination::zero() + a.get_variable() - b.get_variable()
+        + (shift, CS::one())
+        - (shift, ge.get_variable());
+
+    range::enforce_lc_in_range(cs.namespace(|| "difference"), rest, rest_value, bits)?;
+
+    Ok(Boolean::from(ge))
+}
+
+#[cfg(test)]
+mod test {
+    use ff::{Field, PrimeField};
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{ge, gt, le, lt};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::test::*;
+    use crate::ConstraintSystem;
+
+    #[test]
+    fn test_comparisons() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let mut pairs: Vec<(u64, u64)> =
+            vec![(0, 0), (0, 1), (1, 0), (0, 1 << 63), (!0, !0), (!0, 0)];
+        pairs.extend((0..50).map(|_| (rng.next_u64(), rng.next_u64())));
+        pairs.extend((0..10).map(|_| {
+            let a = rng.next_u64();
+            (a, a)
+        }));
+
+        for (a, b) in pairs {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a_num = AllocatedNum::alloc(cs.namespace(|| "a"), || {
+                Ok(Fr::from_str(&a.to_string()).unwrap())
+            })
+            .unwrap();
+            let b_num = AllocatedNum::alloc(cs.namespace(|| "b"), || {
+                Ok(Fr::from_str(&b.to_string()).unwrap())
+            })
+            .unwrap();
+
+            let results = [
+                lt(cs.namespace(|| "lt"), &a_num, &b_num, 64).unwrap(),
+                le(cs.namespace(|| "le"), &a_num, &b_num, 64).unwrap(),
+                gt(cs.namespace(|| "gt"), &a_num, &b_num, 64).unwrap(),
+                ge(cs.namespace(|| "ge"), &a_num, &b_num, 64).unwrap(),
+            ];
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 4 * (64 + 1));
+
+            let expected = [a < b, a <= b, a > b, a >= b];
+            for (result, expected) in results.iter().zip(expected.iter()) {
+                assert_eq!(result.get_value(), Some(*expected));
+            }
+
+            // Claiming the opposite is caught.
+            cs.set(
+                "ge/a >= b/boolean",
+                if a >= b { Field::zero() } else { Field::one() },
+            );
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_comparison_widths() {
+        // Operands right at the bound of a narrow width, and of the widest.
+        for &bits in [1, 8, Fr::CAPACITY as usize - 1].iter() {
+            let mut max = Fr::one();
+            for _ in 0..bits {
+                max.double();
+            }
+            max.sub_assign(&Fr::one());
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let zero = AllocatedNum::alloc(cs.namespace(|| "zero"), || Ok(Fr::zero())).unwrap();
+            let max = AllocatedNum::alloc(cs.namespace(|| "max"), || Ok(max)).unwrap();
+
+            let r = lt(cs.namespace(|| "0 < max"), &zero, &max, bits).unwrap();
+            assert_eq!(r.get_value(), Some(true));
+            let r = lt(cs.namespace(|| "max < 0"), &max, &zero, bits).unwrap();
+            assert_eq!(r.get_value(), Some(false));
+            let r = ge(cs.namespace(|| "max >= max"), &max, &max, bits).unwrap();
+            assert_eq!(r.get_value(), Some(true));
+
+            assert!(cs.is_satisfied());
+        }
+    }
+}
@@ -0,0 +1,924 @@
+//! A uniform interface to the bundled gadgets.
+//!
+//! A `Gadget` declares the types of its inputs and outputs, synthesizes
+//! outputs from inputs, and states up front how many constraints it adds.
+//! Gadgets whose outputs are the inputs of others compose with `Chain`,
+//! `CostReport` measures gadgets against what they declare, and
+//! `check_gadget` runs one in a test constraint system.
+//!
+//! Costs are for inputs that are allocated rather than constant, and are
+//! exact for those: every gadget here has a shape fixed by its parameters.
+//! Constant inputs can only make a gadget cheaper.
+
+use std::fmt;
+
+use ff::ScalarEngine;
+
+use super::boolean::{AllocatedBit, Boolean};
+use super::chacha20::chacha20_block;
+use super::comparison;
+use super::lookup::lookup;
+use super::mux::mux;
+use super::num::{AllocatedNum, Decomposition};
+use super::sha256::sha256_block_no_padding;
+use super::test::TestConstraintSystem;
+use super::uint32::UInt32;
+use super::uint8::UInt8;
+use crate::{Circuit, CircuitStats, ConstraintSystem, SynthesisError};
+
+/// A gadget value that can be allocated from, and read back as, a native
+/// value.
+pub trait GadgetIo<E: ScalarEngine>: Sized {
+    type Value;
+
+    /// Allocates `value` in the constraint system.
+    fn alloc<CS>(cs: CS, value: &Self::Value) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>;
+
+    fn get_value(&self) -> Option<Self::Value>;
+}
+
+impl<E: ScalarEngine> GadgetIo<E> for () {
+    type Value = ();
+
+    fn alloc<CS>(_: CS, _: &()) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        Ok(())
+    }
+
+    fn get_value(&self) -> Option<()> {
+        Some(())
+    }
+}
+
+impl<E: ScalarEngine> GadgetIo<E> for AllocatedNum<E> {
+    type Value = E::Fr;
+
+    fn alloc<CS>(cs: CS, value: &E::Fr) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        AllocatedNum::alloc(cs, || Ok(*value))
+    }
+
+    fn get_value(&self) -> Option<E::Fr> {
+        self.get_value()
+    }
+}
+
+impl<E: ScalarEngine> GadgetIo<E> for Boolean {
+    type Value = bool;
+
+    fn alloc<CS>(cs: CS, value: &bool) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        Ok(Boolean::from(AllocatedBit::alloc(cs, Some(*value))?))
+    }
+
+    fn get_value(&self) -> Option<bool> {
+        self.get_value()
+    }
+}
+
+impl<E: ScalarEngine> GadgetIo<E> for UInt8 {
+    type Value = u8;
+
+    fn alloc<CS>(cs: CS, value: &u8) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        UInt8::alloc(cs, Some(*value))
+    }
+
+    fn get_value(&self) -> Option<u8> {
+        self.get_value()
+    }
+}
+
+impl<E: ScalarEngine> GadgetIo<E> for UInt32 {
+    type Value = u32;
+
+    fn alloc<CS>(cs: CS, value: &u32) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        UInt32::alloc(cs, Some(*value))
+    }
+
+    fn get_value(&self) -> Option<u32> {
+        self.get_value()
+    }
+}
+
+impl<E: ScalarEngine, T: GadgetIo<E>> GadgetIo<E> for Vec<T> {
+    type Value = Vec<T::Value>;
+
+    fn alloc<CS>(mut cs: CS, value: &Vec<T::Value>) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        value
+            .iter()
+            .enumerate()
+            .map(|(i, v)| T::alloc(cs.namespace(|| format!("element {}", i)), v))
+            .collect()
+    }
+
+    fn get_value(&self) -> Option<Vec<T::Value>> {
+        self.iter().map(|v| v.get_value()).collect()
+    }
+}
+
+impl<E: ScalarEngine, A: GadgetIo<E>, B: GadgetIo<E>> GadgetIo<E> for (A, B) {
+    type Value = (A::Value, B::Value);
+
+    fn alloc<CS>(mut cs: CS, value: &Self::Value) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        Ok((
+            A::alloc(cs.namespace(|| "first"), &value.0)?,
+            B::alloc(cs.namespace(|| "second"), &value.1)?,
+        ))
+    }
+
+    fn get_value(&self) -> Option<Self::Value> {
+        Some((self.0.get_value()?, self.1.get_value()?))
+    }
+}
+
+impl<E: ScalarEngine, A: GadgetIo<E>, B: GadgetIo<E>, C: GadgetIo<E>> GadgetIo<E> for (A, B, C) {
+    type Value = (A::Value, B::Value, C::Value);
+
+    fn alloc<CS>(mut cs: CS, value: &Self::Value) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        Ok((
+            A::alloc(cs.namespace(|| "first"), &value.0)?,
+            B::alloc(cs.namespace(|| "second"), &value.1)?,
+            C::alloc(cs.namespace(|| "third"), &value.2)?,
+        ))
+    }
+
+    fn get_value(&self) -> Option<Self::Value> {
+        Some((
+            self.0.get_value()?,
+            self.1.get_value()?,
+            self.2.get_value()?,
+        ))
+    }
+}
+
+/// A gadget with declared inputs, outputs and cost.
+pub trait Gadget<E: ScalarEngine> {
+    type Input: GadgetIo<E>;
+    type Output: GadgetIo<E>;
+
+    /// A short description of the gadget and its parameters, for reports.
+    fn name(&self) -> String;
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>;
+
+    /// The number of constraints `synthesize` adds when none of the input
+    /// is constant.
+    fn cost(&self) -> usize;
+}
+
+/// Multiplies two numbers.
+#[derive(Clone, Copy, Debug)]
+pub struct Multiply;
+
+impl<E: ScalarEngine> Gadget<E> for Multiply {
+    type Input = (AllocatedNum<E>, AllocatedNum<E>);
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "multiply".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.0.mul(cs, &input.1)
+    }
+
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Squares a number.
+#[derive(Clone, Copy, Debug)]
+pub struct Square;
+
+impl<E: ScalarEngine> Gadget<E> for Square {
+    type Input = AllocatedNum<E>;
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "square".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.square(cs)
+    }
+
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Inverts a nonzero number.
+#[derive(Clone, Copy, Debug)]
+pub struct Inverse;
+
+impl<E: ScalarEngine> Gadget<E> for Inverse {
+    type Input = AllocatedNum<E>;
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "inverse".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.inverse(cs)
+    }
+
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Divides a number by a nonzero number.
+#[derive(Clone, Copy, Debug)]
+pub struct Divide;
+
+impl<E: ScalarEngine> Gadget<E> for Divide {
+    type Input = (AllocatedNum<E>, AllocatedNum<E>);
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "divide".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.0.div(cs, &input.1)
+    }
+
+    fn cost(&self) -> usize {
+        2
+    }
+}
+
+/// Selects the first of two numbers if the condition is true, and the
+/// second otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct Select;
+
+impl<E: ScalarEngine> Gadget<E> for Select {
+    type Input = (AllocatedNum<E>, AllocatedNum<E>, Boolean);
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "select".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        AllocatedNum::conditionally_select(cs, &input.0, &input.1, &input.2)
+    }
+
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Enforces that a number is less than 2^`bits`.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheck {
+    pub bits: usize,
+}
+
+impl<E: ScalarEngine> Gadget<E> for RangeCheck {
+    type Input = AllocatedNum<E>;
+    type Output = ();
+
+    fn name(&self) -> String {
+        format!("range check to {} bits", self.bits)
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.assert_in_range(cs, self.bits)
+    }
+
+    fn cost(&self) -> usize {
+        self.bits
+    }
+}
+
+/// The comparisons of the `comparison` module.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Compares two numbers below 2^`bits`.
+#[derive(Clone, Copy, Debug)]
+pub struct Compare {
+    pub comparison: Comparison,
+    pub bits: usize,
+}
+
+impl<E: ScalarEngine> Gadget<E> for Compare {
+    type Input = (AllocatedNum<E>, AllocatedNum<E>);
+    type Output = Boolean;
+
+    fn name(&self) -> String {
+        format!("{:?} comparison of {} bits", self.comparison, self.bits)
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let compare = match self.comparison {
+            Comparison::Lt => comparison::lt,
+            Comparison::Le => comparison::le,
+            Comparison::Gt => comparison::gt,
+            Comparison::Ge => comparison::ge,
+        };
+
+        compare(cs, &input.0, &input.1, self.bits)
+    }
+
+    fn cost(&self) -> usize {
+        self.bits + 1
+    }
+}
+
+/// Decomposes a number below 2^`bits` into that many bits, least
+/// significant first.
+#[derive(Clone, Copy, Debug)]
+pub struct Unpack {
+    pub bits: usize,
+}
+
+impl<E: ScalarEngine> Gadget<E> for Unpack {
+    type Input = AllocatedNum<E>;
+    type Output = Vec<Boolean>;
+
+    fn name(&self) -> String {
+        format!("unpack into {} bits", self.bits)
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        input.unpack_into_bits_le(cs, Decomposition::Bounded(self.bits))
+    }
+
+    fn cost(&self) -> usize {
+        self.bits + 1
+    }
+}
+
+/// Packs bits, least significant first, into a number.
+#[derive(Clone, Copy, Debug)]
+pub struct Pack;
+
+impl<E: ScalarEngine> Gadget<E> for Pack {
+    type Input = Vec<Boolean>;
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        "pack".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        AllocatedNum::pack_bits_le(cs, input)
+    }
+
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Looks up the entry of a table of 2^k field elements indexed by k
+/// little-endian bits.
+#[derive(Clone, Debug)]
+pub struct Lookup<E: ScalarEngine> {
+    pub table: Vec<E::Fr>,
+}
+
+impl<E: ScalarEngine> Lookup<E> {
+    fn index_bits(&self) -> usize {
+        assert!(self.table.len().is_power_of_two() && self.table.len() > 1);
+
+        self.table.len().trailing_zeros() as usize
+    }
+}
+
+impl<E: ScalarEngine> Gadget<E> for Lookup<E> {
+    type Input = Vec<Boolean>;
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        format!("lookup in {} entries", self.table.len())
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        lookup(cs, input, &self.table)
+    }
+
+    fn cost(&self) -> usize {
+        let k = self.index_bits();
+
+        (1 << (k - 1)) - k + 1
+    }
+}
+
+/// Selects one of 2^`selector_bits` numbers by the little-endian bits of
+/// its index.
+#[derive(Clone, Copy, Debug)]
+pub struct Mux {
+    pub selector_bits: usize,
+}
+
+impl<E: ScalarEngine> Gadget<E> for Mux {
+    type Input = (Vec<AllocatedNum<E>>, Vec<Boolean>);
+    type Output = AllocatedNum<E>;
+
+    fn name(&self) -> String {
+        format!("mux of {} options", 1 << self.selector_bits)
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        assert_eq!(input.1.len(), self.selector_bits);
+
+        mux(cs, &input.0, &input.1)
+    }
+
+    fn cost(&self) -> usize {
+        (1 << self.selector_bits) - 1
+    }
+}
+
+/// The SHA-256 compression function over one 512-bit block, from the
+/// initial hash value.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Block;
+
+impl<E: ScalarEngine> Gadget<E> for Sha256Block {
+    type Input = Vec<Boolean>;
+    type Output = Vec<Boolean>;
+
+    fn name(&self) -> String {
+        "sha256 block".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        sha256_block_no_padding(cs, input)
+    }
+
+    fn cost(&self) -> usize {
+        25840
+    }
+}
+
+/// The ChaCha20 block function, over a key, a nonce and a block counter.
+#[derive(Clone, Copy, Debug)]
+pub struct ChaCha20Block;
+
+impl<E: ScalarEngine> Gadget<E> for ChaCha20Block {
+    type Input = (Vec<Boolean>, Vec<Boolean>, UInt32);
+    type Output = Vec<Boolean>;
+
+    fn name(&self) -> String {
+        "chacha20 block".to_string()
+    }
+
+    fn synthesize<CS>(&self, cs: CS, input: &Self::Input) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        chacha20_block(cs, &input.0, &input.1, &input.2)
+    }
+
+    fn cost(&self) -> usize {
+        21376
+    }
+}
+
+/// Two gadgets in sequence, the outputs of the first being the inputs of
+/// the second.
+#[derive(Clone, Copy, Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Chain<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Chain {
+            first: first,
+            second: second,
+        }
+    }
+}
+
+impl<E, A, B> Gadget<E> for Chain<A, B>
+where
+    E: ScalarEngine,
+    A: Gadget<E>,
+    B: Gadget<E, Input = A::Output>,
+{
+    type Input = A::Input;
+    type Output = B::Output;
+
+    fn name(&self) -> String {
+        format!("{} then {}", self.first.name(), self.second.name())
+    }
+
+    fn synthesize<CS>(
+        &self,
+        mut cs: CS,
+        input: &Self::Input,
+    ) -> Result<Self::Output, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let middle = self.first.synthesize(cs.namespace(|| "first"), input)?;
+
+        self.second.synthesize(cs.namespace(|| "second"), &middle)
+    }
+
+    fn cost(&self) -> usize {
+        self.first.cost() + self.second.cost()
+    }
+}
+
+/// Allocates the input of a gadget and, if `synthesize` is set, runs it.
+struct GadgetCircuit<'a, E: ScalarEngine, G: Gadget<E>> {
+    gadget: &'a G,
+    input: &'a <G::Input as GadgetIo<E>>::Value,
+    synthesize: bool,
+}
+
+impl<'a, E: ScalarEngine, G: Gadget<E>> Circuit<E> for GadgetCircuit<'a, E, G> {
+    fn synthesize<CS>(self, cs: &mut CS) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let input = <G::Input as GadgetIo<E>>::alloc(cs.namespace(|| "input"), self.input)?;
+        if self.synthesize {
+            self.gadget.synthesize(cs.namespace(|| "gadget"), &input)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The cost a gadget declares, next to what synthesizing it takes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostEntry {
+    pub name: String,
+    pub declared: usize,
+    /// Constraints added beyond those allocating the input.
+    pub measured: usize,
+    /// Auxiliary variables allocated beyond those of the input.
+    pub aux: usize,
+}
+
+/// A table of gadget costs, measured without evaluating any assignments.
+#[derive(Clone, Debug, Default)]
+pub struct CostReport {
+    pub entries: Vec<CostEntry>,
+}
+
+impl CostReport {
+    pub fn new() -> Self {
+        CostReport::default()
+    }
+
+    /// Measures `gadget` over an input shaped like `input`, whose values
+    /// are never read.
+    pub fn add<E, G>(
+        &mut self,
+        gadget: &G,
+        input: &<G::Input as GadgetIo<E>>::Value,
+    ) -> Result<&CostEntry, SynthesisError>
+    where
+        E: ScalarEngine,
+        G: Gadget<E>,
+    {
+        let measure = |synthesize| {
+            CircuitStats::measure(GadgetCircuit {
+                gadget: gadget,
+                input: input,
+                synthesize: synthesize,
+            })
+        };
+        let before = measure(false)?;
+        let after = measure(true)?;
+
+        self.entries.push(CostEntry {
+            name: gadget.name(),
+            declared: gadget.cost(),
+            measured: after.constraints - before.constraints,
+            aux: after.aux - before.aux,
+        });
+
+        Ok(self.entries.last().expect("an entry was added"))
+    }
+
+    /// The entries whose measured cost differs from the declared one.
+    pub fn mismatches(&self) -> Vec<&CostEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.declared != e.measured)
+            .collect()
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40} {:>12} {:>12} {:>12}",
+            "gadget", "declared", "measured", "aux"
+        )?;
+        for e in &self.entries {
+            writeln!(
+                f,
+                "{:<40} {:>12} {:>12} {:>12}",
+                e.name, e.declared, e.measured, e.aux
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Allocates `input`, synthesizes `gadget` over it in a test constraint
+/// system and returns the value of the output.
+///
+/// Panics if the constraints are not satisfied or if the gadget adds a
+/// different number of constraints from the cost it declares.
+pub fn check_gadget<E, G>(
+    gadget: &G,
+    input: &<G::Input as GadgetIo<E>>::Value,
+) -> Option<<G::Output as GadgetIo<E>>::Value>
+where
+    E: ScalarEngine,
+    G: Gadget<E>,
+{
+    let mut cs = TestConstraintSystem::<E>::new();
+    let input = <G::Input as GadgetIo<E>>::alloc(cs.namespace(|| "input"), input)
+        .expect("the input allocates");
+    let before = cs.num_constraints();
+
+    let output = gadget
+        .synthesize(cs.namespace(|| "gadget"), &input)
+        .expect("the gadget synthesizes");
+
+    if let Some(constraint) = cs.which_is_unsatisfied() {
+        panic!(
+            "{}: constraint {} is unsatisfied",
+            gadget.name(),
+            constraint
+        );
+    }
+    assert_eq!(
+        cs.num_constraints() - before,
+        gadget.cost(),
+        "{}: cost differs from the declared cost",
+        gadget.name()
+    );
+
+    output.get_value()
+}
+
+#[cfg(test)]
+mod test {
+    use ff::{Field, PrimeField};
+    use pairing::bls12_381::{Bls12, Fr, FrRepr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::*;
+
+    fn bits(rng: &mut XorShiftRng, len: usize) -> Vec<bool> {
+        (0..len).map(|_| rng.next_u32() % 2 == 1).collect()
+    }
+
+    #[test]
+    fn test_arithmetic_gadgets() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..10 {
+            let a = Fr::random(&mut rng);
+            let b = Fr::random(&mut rng);
+
+            let mut product = a;
+            product.mul_assign(&b);
+            assert_eq!(check_gadget::<Bls12, _>(&Multiply, &(a, b)), Some(product));
+
+            let mut square = a;
+            square.square();
+            assert_eq!(check_gadget::<Bls12, _>(&Square, &a), Some(square));
+
+            assert_eq!(check_gadget::<Bls12, _>(&Inverse, &a), a.inverse());
+
+            let mut quotient = b.inverse().unwrap();
+            quotient.mul_assign(&a);
+            assert_eq!(check_gadget::<Bls12, _>(&Divide, &(a, b)), Some(quotient));
+
+            assert_eq!(check_gadget::<Bls12, _>(&Select, &(a, b, true)), Some(a));
+            assert_eq!(check_gadget::<Bls12, _>(&Select, &(a, b, false)), Some(b));
+        }
+    }
+
+    #[test]
+    fn test_bit_gadgets() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &bit_len in [1, 8, 32, 63].iter() {
+            let a = rng.next_u64() & ((1 << bit_len) - 1);
+            let b = rng.next_u64() & ((1 << bit_len) - 1);
+            let a_num = Fr::from_repr(FrRepr::from(a)).unwrap();
+            let b_num = Fr::from_repr(FrRepr::from(b)).unwrap();
+
+            check_gadget::<Bls12, _>(&RangeCheck { bits: bit_len }, &a_num).unwrap();
+
+            let gadget = Chain::new(Unpack { bits: bit_len }, Pack);
+            assert_eq!(check_gadget::<Bls12, _>(&gadget, &a_num), Some(a_num));
+
+            for &(comparison, expected) in [
+                (Comparison::Lt, a < b),
+                (Comparison::Le, a <= b),
+                (Comparison::Gt, a > b),
+                (Comparison::Ge, a >= b),
+            ]
+            .iter()
+            {
+                let gadget = Compare {
+                    comparison: comparison,
+                    bits: bit_len,
+                };
+                assert_eq!(
+                    check_gadget::<Bls12, _>(&gadget, &(a_num, b_num)),
+                    Some(expected)
+                );
+                assert_eq!(
+                    check_gadget::<Bls12, _>(&gadget, &(a_num, a_num)),
+                    Some(comparison == Comparison::Le || comparison == Comparison::Ge)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_selection_gadgets() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for k in 1..5 {
+            let table: Vec<Fr> = (0..1 << k).map(|_| Fr::random(&mut rng)).collect();
+            let index = rng.next_u32() as usize % table.len();
+            let index_bits: Vec<_> = (0..k).map(|i| (index >> i) & 1 == 1).collect();
+
+            let gadget = Lookup::<Bls12> {
+                table: table.clone(),
+            };
+            assert_eq!(
+                check_gadget::<Bls12, _>(&gadget, &index_bits),
+                Some(table[index])
+            );
+
+            let gadget = Mux { selector_bits: k };
+            assert_eq!(
+                check_gadget::<Bls12, _>(&gadget, &(table.clone(), index_bits)),
+                Some(table[index])
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_gadgets() {
+        // FIPS 180-2, the padded block of "abc"
+        let mut block = b"abc\x80".to_vec();
+        block.resize(63, 0);
+        block.push(24);
+        let input: Vec<_> = block
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+
+        let digest = check_gadget::<Bls12, _>(&Sha256Block, &input).unwrap();
+        let expected = hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        let expected: Vec<_> = expected
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        assert_eq!(digest, expected);
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let input = (bits(&mut rng, 256), bits(&mut rng, 96), rng.next_u32());
+        let block = check_gadget::<Bls12, _>(&ChaCha20Block, &input).unwrap();
+        assert_eq!(block.len(), 512);
+    }
+
+    #[test]
+    fn test_chained_gadgets() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let gadget = Chain::new(Multiply, Chain::new(Square, Square));
+        assert_eq!(Gadget::<Bls12>::cost(&gadget), 3);
+        assert_eq!(
+            Gadget::<Bls12>::name(&gadget),
+            "multiply then square then square"
+        );
+
+        let a = Fr::random(&mut rng);
+        let b = Fr::random(&mut rng);
+        let mut expected = a;
+        expected.mul_assign(&b);
+        expected.square();
+        expected.square();
+        assert_eq!(check_gadget::<Bls12, _>(&gadget, &(a, b)), Some(expected));
+    }
+
+    #[test]
+    fn test_cost_report() {
+        let zero = Fr::zero();
+        let mut report = CostReport::new();
+
+        report.add::<Bls12, _>(&Multiply, &(zero, zero)).unwrap();
+        report
+            .add::<Bls12, _>(&RangeCheck { bits: 64 }, &zero)
+            .unwrap();
+        report
+            .add::<Bls12, _>(&Mux { selector_bits: 3 }, &(vec![zero; 8], vec![false; 3]))
+            .unwrap();
+        let entry = report
+            .add::<Bls12, _>(&Sha256Block, &vec![false; 512])
+            .unwrap();
+        assert_eq!(entry.measured, 25840);
+
+        assert_eq!(report.entries.len(), 4);
+        assert!(report.mismatches().is_empty());
+        assert!(report.to_string().contains("range check to 64 bits"));
+    }
+}
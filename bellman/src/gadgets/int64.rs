@@ -0,0 +1,381 @@
+use ff::{Field, ScalarEngine};
+
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+use super::boolean::{AllocatedBit, Boolean};
+
+/// Represents an interpretation of 64 `Boolean` objects as a signed
+/// integer in two's complement.
+///
+/// Arithmetic is checked rather than wrapping: the sum or difference of
+/// two `Int64`s is enforced to equal the exact integer result, so a witness
+/// which overflows cannot satisfy the constraint system.
+#[derive(Clone)]
+pub struct Int64 {
+    // Least significant bit first, the last being the sign bit
+    bits: Vec<Boolean>,
+    value: Option<i64>,
+}
+
+impl Int64 {
+    /// Construct a constant `Int64` from an `i64`
+    pub fn constant(value: i64) -> Self {
+        let bits = (0..64)
+            .map(|i| Boolean::constant((value >> i) & 1 == 1))
+            .collect();
+
+        Int64 { bits, value: Some(value) }
+    }
+
+    /// Allocate an `Int64` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<i64>) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let bits = (0..64)
+            .map(|i| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("allocated bit {}", i)),
+                    value.map(|v| (v >> i) & 1 == 1),
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(Int64 { bits, value })
+    }
+
+    pub fn get_value(&self) -> Option<i64> {
+        self.value
+    }
+
+    /// The two's complement bits, least significant first.
+    pub fn into_bits(&self) -> Vec<Boolean> {
+        self.bits.clone()
+    }
+
+    /// Reads two's complement bits, least significant first.
+    pub fn from_bits(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let mut value = Some(0u64);
+        for b in bits.iter().rev() {
+            value = match (value, b.get_value()) {
+                (Some(v), Some(b)) => Some((v << 1) | b as u64),
+                _ => None,
+            };
+        }
+
+        Int64 {
+            bits: bits.to_vec(),
+            value: value.map(|v| v as i64),
+        }
+    }
+
+    /// Whether this is below zero, which is its sign bit and costs no
+    /// constraints.
+    pub fn is_negative(&self) -> Boolean {
+        self.bits[63].clone()
+    }
+
+    /// Checked addition. Returns `SynthesisError::Unsatisfiable` if the
+    /// operands are known and their sum does not fit in 64 bits.
+    pub fn add<E, CS>(&self, cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a.checked_add(b).ok_or(SynthesisError::Unsatisfiable)?),
+            _ => None,
+        };
+
+        Self::enforce_sum(cs, value, self.lc::<E, CS>() + &other.lc::<E, CS>(), &[self, other])
+    }
+
+    /// Checked subtraction. Returns `SynthesisError::Unsatisfiable` if the
+    /// operands are known and their difference does not fit in 64 bits.
+    pub fn sub<E, CS>(&self, cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a.checked_sub(b).ok_or(SynthesisError::Unsatisfiable)?),
+            _ => None,
+        };
+
+        Self::enforce_sum(cs, value, self.lc::<E, CS>() - &other.lc::<E, CS>(), &[self, other])
+    }
+
+    /// Checked negation, which only fails for `i64::MIN`.
+    pub fn neg<E, CS>(&self, cs: CS) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        Int64::constant(0).sub(cs, self)
+    }
+
+    /// Whether `self < other`. Swap the operands for `>`, and negate the
+    /// result for `>=` and `<=`.
+    pub fn lt<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Boolean, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        if let (Some(a), Some(b)) = (self.value, other.value) {
+            if self.is_constant() && other.is_constant() {
+                return Ok(Boolean::constant(a < b));
+            }
+        }
+
+        // self - other + 2^64 lies in (0, 2^65), and its top bit is set
+        // exactly when self >= other.
+        let shifted = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some((a as i128 - b as i128 + (1 << 64)) as u128),
+            _ => None,
+        };
+
+        let mut result_bits = vec![];
+        let mut result_lc = LinearCombination::zero();
+        let mut coeff = E::Fr::one();
+        let mut shift = E::Fr::zero();
+        for i in 0..65 {
+            if i == 64 {
+                shift = coeff;
+            }
+
+            let b = AllocatedBit::alloc(
+                cs.namespace(|| format!("difference bit {}", i)),
+                shifted.map(|v| (v >> i) & 1 == 1),
+            )?;
+
+            result_lc = result_lc + (coeff, b.get_variable());
+
+            result_bits.push(Boolean::from(b));
+
+            coeff.double();
+        }
+
+        let difference = self.lc::<E, CS>() - &other.lc::<E, CS>() + (shift, CS::one());
+        cs.enforce(
+            || "difference",
+            |lc| lc + CS::one(),
+            |lc| lc + &difference,
+            |lc| lc + &result_lc,
+        );
+
+        Ok(result_bits.pop().unwrap().not())
+    }
+
+    fn is_constant(&self) -> bool {
+        self.bits.iter().all(|b| b.is_constant())
+    }
+
+    /// The integer these bits represent, the sign bit weighing -2^63.
+    fn lc<E, CS>(&self) -> LinearCombination<E>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let mut lc = LinearCombination::zero();
+        let mut coeff = E::Fr::one();
+        for (i, bit) in self.bits.iter().enumerate() {
+            if i == 63 {
+                let mut neg = coeff;
+                neg.negate();
+                lc = lc + &bit.lc(CS::one(), neg);
+            } else {
+                lc = lc + &bit.lc(CS::one(), coeff);
+            }
+
+            coeff.double();
+        }
+
+        lc
+    }
+
+    /// Allocates `value` and enforces that it equals `sum`, the exact
+    /// integer result of an operation on `operands`.
+    fn enforce_sum<E, CS>(
+        mut cs: CS,
+        value: Option<i64>,
+        sum: LinearCombination<E>,
+        operands: &[&Self],
+    ) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        if operands.iter().all(|op| op.is_constant()) {
+            if let Some(v) = value {
+                return Ok(Int64::constant(v));
+            }
+        }
+
+        let result = Int64::alloc(cs.namespace(|| "result"), value)?;
+        let result_lc = result.lc::<E, CS>();
+
+        // Both sides lie far inside the field, so the equality holds over
+        // the integers and the result cannot have wrapped.
+        cs.enforce(
+            || "sum",
+            |lc| lc + CS::one(),
+            |lc| lc + &sum,
+            |lc| lc + &result_lc,
+        );
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Int64;
+    use crate::gadgets::boolean::Boolean;
+    use crate::gadgets::test::*;
+    use crate::{ConstraintSystem, SynthesisError};
+    use ff::Field;
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    fn check_bits(n: &Int64, expected: i64) {
+        assert_eq!(n.get_value(), Some(expected));
+        for (i, b) in n.into_bits().iter().enumerate() {
+            assert_eq!(b.get_value().unwrap(), (expected >> i) & 1 == 1);
+        }
+    }
+
+    #[test]
+    fn test_int64_from_bits() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let v = rng.next_u64() as i64;
+            let b = Int64::constant(v);
+
+            assert_eq!(Int64::from_bits(&b.into_bits()).get_value(), Some(v));
+            assert_eq!(b.is_negative().get_value(), Some(v < 0));
+            check_bits(&b, v);
+        }
+    }
+
+    #[test]
+    fn test_int64_add_sub_neg() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            // Halved so that neither the sum nor the difference overflows.
+            let (a, b) = ((rng.next_u64() as i64) >> 1, (rng.next_u64() as i64) >> 1);
+            let a_num = Int64::alloc(cs.namespace(|| "a"), Some(a)).unwrap();
+            let b_num = Int64::alloc(cs.namespace(|| "b"), Some(b)).unwrap();
+
+            let sum = a_num.add(cs.namespace(|| "add"), &b_num).unwrap();
+            let difference = a_num.sub(cs.namespace(|| "sub"), &Int64::constant(b)).unwrap();
+            let negation = a_num.neg(cs.namespace(|| "neg")).unwrap();
+
+            assert!(cs.is_satisfied());
+            check_bits(&sum, a + b);
+            check_bits(&difference, a - b);
+            check_bits(&negation, -a);
+
+            // Each operation allocates the 64 bits of its result and adds
+            // one constraint.
+            assert_eq!(cs.num_constraints(), 2 * 64 + 3 * 65);
+
+            // Flipping the sign of the sum makes it wrong by 2^64, which
+            // the field does not wrap around.
+            if cs.get("add/result/allocated bit 63/boolean").is_zero() {
+                cs.set("add/result/allocated bit 63/boolean", Field::one());
+            } else {
+                cs.set("add/result/allocated bit 63/boolean", Field::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_int64_overflow() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+
+        let max = Int64::alloc(cs.namespace(|| "max"), Some(i64::max_value())).unwrap();
+        let min = Int64::alloc(cs.namespace(|| "min"), Some(i64::min_value())).unwrap();
+        let one = Int64::constant(1);
+
+        match max.add(cs.namespace(|| "max + 1"), &one) {
+            Err(SynthesisError::Unsatisfiable) => {}
+            _ => panic!("addition should overflow"),
+        }
+        match min.sub(cs.namespace(|| "min - 1"), &one) {
+            Err(SynthesisError::Unsatisfiable) => {}
+            _ => panic!("subtraction should overflow"),
+        }
+        match min.neg(cs.namespace(|| "-min")) {
+            Err(SynthesisError::Unsatisfiable) => {}
+            _ => panic!("negation should overflow"),
+        }
+
+        // Right at the edges of the range nothing overflows.
+        let r = max.add(cs.namespace(|| "max + min"), &min).unwrap();
+        check_bits(&r, -1);
+        let r = r.add(cs.namespace(|| "-1 + max"), &max).unwrap();
+        let r = min.add(cs.namespace(|| "min + max - 1"), &r).unwrap();
+        check_bits(&r, -2);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_int64_lt() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let edges = [i64::min_value(), -1, 0, 1, i64::max_value()];
+        let mut pairs: Vec<(i64, i64)> = edges
+            .iter()
+            .flat_map(|a| edges.iter().map(move |b| (*a, *b)))
+            .collect();
+        pairs.extend((0..50).map(|_| (rng.next_u64() as i64, rng.next_u64() as i64)));
+
+        for (a, b) in pairs {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let a_num = Int64::alloc(cs.namespace(|| "a"), Some(a)).unwrap();
+            let b_num = Int64::alloc(cs.namespace(|| "b"), Some(b)).unwrap();
+
+            let lt = a_num.lt(cs.namespace(|| "a < b"), &b_num).unwrap();
+            let gt = b_num.lt(cs.namespace(|| "a > b"), &a_num).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(lt.get_value(), Some(a < b));
+            assert_eq!(gt.get_value(), Some(a > b));
+            assert_eq!(gt.not().get_value(), Some(a <= b));
+
+            // Claiming the opposite ordering is caught.
+            cs.set(
+                "a < b/difference bit 64/boolean",
+                if a < b { Field::one() } else { Field::zero() },
+            );
+            assert!(!cs.is_satisfied());
+        }
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let lt = Int64::constant(-3).lt(&mut cs, &Int64::constant(2)).unwrap();
+        match lt {
+            Boolean::Constant(true) => {}
+            _ => panic!("constant operands should compare to a constant"),
+        }
+        assert_eq!(cs.num_constraints(), 0);
+    }
+}
@@ -0,0 +1,193 @@
+use super::boolean::Boolean;
+use super::uint64::UInt64;
+use crate::{ConstraintSystem, SynthesisError};
+use ff::ScalarEngine;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// The left rotation of the lane at (x, y), indexed `[x][y]`.
+const ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// The rate of Keccak-256 in bits, the part of the state each block of
+/// the padded message is absorbed into.
+const KECCAK256_RATE: usize = 1088;
+
+/// Keccak-256 as used by Ethereum, with the original Keccak padding
+/// rather than that of SHA3-256. The input is a whole number of bytes and
+/// each byte, like each byte of the 256-bit output, is given least
+/// significant bit first.
+pub fn keccak256<E, CS>(mut cs: CS, input: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(input.len() % 8 == 0);
+
+    // pad10*1: the bytes 0x01, then zeros, then 0x80, up to a whole block
+    let mut padded = input.to_vec();
+    padded.push(Boolean::constant(true));
+    while padded.len() % KECCAK256_RATE != KECCAK256_RATE - 1 {
+        padded.push(Boolean::constant(false));
+    }
+    padded.push(Boolean::constant(true));
+
+    let mut state: Vec<UInt64> = (0..25).map(|_| UInt64::constant(0)).collect();
+    for (i, block) in padded.chunks(KECCAK256_RATE).enumerate() {
+        let mut cs = cs.namespace(|| format!("block {}", i));
+
+        for (j, lane) in block.chunks(64).enumerate() {
+            state[j] = state[j].xor(cs.namespace(|| format!("absorb lane {}", j)), &UInt64::from_bits(lane))?;
+        }
+        state = keccak_f1600(cs.namespace(|| "keccak-f"), &state)?;
+    }
+
+    Ok(state.iter().take(4).flat_map(|lane| lane.into_bits()).collect())
+}
+
+/// The Keccak-f[1600] permutation of 25 lanes, the lane at (x, y) being
+/// at index x + 5 y.
+pub fn keccak_f1600<E, CS>(mut cs: CS, input: &[UInt64]) -> Result<Vec<UInt64>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert_eq!(input.len(), 25);
+
+    let mut a = input.to_vec();
+    for (round, rc) in ROUND_CONSTANTS.iter().enumerate() {
+        let cs = &mut cs.namespace(|| format!("round {}", round));
+
+        // theta: each lane is xored with the parities of two nearby columns
+        let mut c = vec![];
+        for x in 0..5 {
+            let mut parity = a[x].clone();
+            for y in 1..5 {
+                parity = parity.xor(cs.namespace(|| format!("column {} parity {}", x, y)), &a[x + 5 * y])?;
+            }
+            c.push(parity);
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5].xor(cs.namespace(|| format!("theta {}", x)), &c[(x + 1) % 5].rotl(1))?;
+            for y in 0..5 {
+                a[x + 5 * y] = a[x + 5 * y].xor(cs.namespace(|| format!("theta {} {}", x, y)), &d)?;
+            }
+        }
+
+        // rho and pi: B[y, 2x + 3y] = A[x, y] rotated left by r[x, y]
+        let mut b = a.clone();
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotl(ROTATIONS[x][y]);
+            }
+        }
+
+        // chi: A[x, y] = B[x, y] xor ((not B[x + 1, y]) and B[x + 2, y])
+        for x in 0..5 {
+            for y in 0..5 {
+                let cs = &mut cs.namespace(|| format!("chi {} {}", x, y));
+                let masked = b[(x + 1) % 5 + 5 * y].not().and(cs.namespace(|| "and not"), &b[(x + 2) % 5 + 5 * y])?;
+                a[x + 5 * y] = b[x + 5 * y].xor(cs.namespace(|| "xor"), &masked)?;
+            }
+        }
+
+        // iota: constants xor into the bits of a lane for free
+        a[0] = a[0].xor(cs.namespace(|| "iota"), &UInt64::constant(*rc))?;
+    }
+
+    Ok(a)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gadgets::boolean::AllocatedBit;
+    use crate::gadgets::test::TestConstraintSystem;
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_blank_hash() {
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let out = keccak256(&mut cs, &[]).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 0);
+
+        let expected = hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+
+        let mut out = out.into_iter();
+        for b in expected.iter() {
+            for i in 0..8 {
+                let c = out.next().unwrap().get_value().unwrap();
+
+                assert_eq!(c, (b >> i) & 1u8 == 1u8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_against_vectors() {
+        use sha3::{Digest, Keccak256};
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        // Lengths either side of where the padding spills into a second block.
+        for input_len in (0..4).chain([32, 135, 136, 200].iter().cloned()) {
+            let mut h = Keccak256::new();
+            let data: Vec<u8> = (0..input_len).map(|_| rng.next_u32() as u8).collect();
+            h.input(&data);
+            let hash_result = h.result();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let mut input_bits = vec![];
+
+            for (byte_i, input_byte) in data.into_iter().enumerate() {
+                for bit_i in 0..8 {
+                    let cs = cs.namespace(|| format!("input bit {} {}", byte_i, bit_i));
+
+                    input_bits.push(
+                        AllocatedBit::alloc(cs, Some((input_byte >> bit_i) & 1u8 == 1u8))
+                            .unwrap()
+                            .into(),
+                    );
+                }
+            }
+
+            let r = keccak256(&mut cs, &input_bits).unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let mut s = hash_result
+                .as_ref()
+                .iter()
+                .flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8));
+
+            for b in r {
+                assert_eq!(s.next().unwrap(), b.get_value().unwrap());
+            }
+
+            // A single block, as for a 32-byte storage key, costs the
+            // permutation alone.
+            if input_len == 32 {
+                assert_eq!(cs.num_constraints() - 256, 150848);
+            }
+        }
+    }
+}
@@ -1,5 +1,4 @@
-use ff::Field;
-use pairing::Engine;
+use ff::{Field, ScalarEngine};
 
 use super::boolean::Boolean;
 use super::num::{AllocatedNum, Num};
@@ -7,7 +6,7 @@ use super::*;
 use crate::ConstraintSystem;
 
 // Synthesize the constants for each base pattern.
-fn synth<'a, E: Engine, I>(window_size: usize, constants: I, assignment: &mut [E::Fr])
+fn synth<'a, E: ScalarEngine, I>(window_size: usize, constants: I, assignment: &mut [E::Fr])
 where
     I: IntoIterator<Item = &'a E::Fr>,
 {
@@ -28,7 +27,7 @@ where
 
 /// Performs a 3-bit window table lookup. `bits` is in
 /// little-endian order.
-pub fn lookup3_xy<E: Engine, CS>(
+pub fn lookup3_xy<E: ScalarEngine, CS>(
     mut cs: CS,
     bits: &[Boolean],
     coords: &[(E::Fr, E::Fr)],
@@ -124,7 +123,7 @@ where
 
 /// Performs a 3-bit window table lookup, where
 /// one of the bits is a sign bit.
-pub fn lookup3_xy_with_conditional_negation<E: Engine, CS>(
+pub fn lookup3_xy_with_conditional_negation<E: ScalarEngine, CS>(
     mut cs: CS,
     bits: &[Boolean],
     coords: &[(E::Fr, E::Fr)],
@@ -193,6 +192,104 @@ where
     Ok((x, y.into()))
 }
 
+/// Performs a lookup into a table of `2^bits.len()` field elements.
+/// `bits` is in little-endian order, and is the index of the entry
+/// looked up.
+pub fn lookup<E: ScalarEngine, CS>(
+    cs: CS,
+    bits: &[Boolean],
+    table: &[E::Fr],
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    CS: ConstraintSystem<E>,
+{
+    let mut res = lookup_columns(cs, bits, &[table])?;
+
+    Ok(res.pop().expect("one column was looked up"))
+}
+
+/// Performs a lookup into several tables of `2^bits.len()` field
+/// elements at once, such as the coordinates of a table of points.
+/// `bits` is in little-endian order.
+///
+/// As in `lookup3_xy`, each entry is a polynomial in the bits, and each
+/// column costs one constraint over the products of all but the lowest
+/// bit. Those products are shared by the columns, and there are
+/// `2^(bits.len() - 1) - bits.len()` of them to allocate, fewer if some
+/// bits are constant, so wide windows quickly grow expensive.
+pub fn lookup_columns<E: ScalarEngine, CS>(
+    mut cs: CS,
+    bits: &[Boolean],
+    columns: &[&[E::Fr]],
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    CS: ConstraintSystem<E>,
+{
+    assert!(!bits.is_empty());
+    for column in columns {
+        assert_eq!(column.len(), 1 << bits.len());
+    }
+
+    // Calculate the index into the columns
+    let i = bits.iter().rev().try_fold(0, |acc, bit| {
+        bit.get_value().map(|b| (acc << 1) | b as usize)
+    });
+
+    // The products of each subset of the bits above the lowest,
+    // indexed by the subset shifted down by one.
+    let mut products = vec![Boolean::constant(true)];
+    for (j, bit) in bits.iter().enumerate().skip(1) {
+        for m in 0..products.len() {
+            let product = if m == 0 {
+                bit.clone()
+            } else {
+                Boolean::and(
+                    cs.namespace(|| format!("product {}", m | (1 << (j - 1)))),
+                    &products[m],
+                    bit,
+                )?
+            };
+            products.push(product);
+        }
+    }
+
+    let one = CS::one();
+
+    columns
+        .iter()
+        .enumerate()
+        .map(|(n, column)| {
+            let res = AllocatedNum::alloc(cs.namespace(|| format!("column {}", n)), || {
+                Ok(column[i.ok_or(SynthesisError::AssignmentMissing)?])
+            })?;
+
+            // Compute the coefficients for the lookup constraint
+            let mut coeffs = vec![E::Fr::zero(); column.len()];
+            synth::<E, _>(bits.len(), column.iter(), &mut coeffs);
+
+            cs.enforce(
+                || format!("column {} lookup", n),
+                |lc| {
+                    products.iter().enumerate().fold(lc, |lc, (m, product)| {
+                        lc + &product.lc::<E>(one, coeffs[(m << 1) | 1])
+                    })
+                },
+                |lc| lc + &bits[0].lc::<E>(one, E::Fr::one()),
+                |lc| {
+                    products
+                        .iter()
+                        .enumerate()
+                        .fold(lc + res.get_variable(), |lc, (m, product)| {
+                            lc - &product.lc::<E>(one, coeffs[m << 1])
+                        })
+                },
+            );
+
+            Ok(res)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -293,6 +390,99 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lookup() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &window in [1, 2, 3, 4, 6].iter() {
+            let table: Vec<_> = (0..(1 << window)).map(|_| Fr::random(&mut rng)).collect();
+
+            for index in 0..(1 << window) {
+                let mut cs = TestConstraintSystem::<Bls12>::new();
+
+                let bits: Vec<_> = (0..window)
+                    .map(|j| {
+                        Boolean::from(
+                            AllocatedBit::alloc(
+                                cs.namespace(|| format!("bit {}", j)),
+                                Some((index >> j) & 1 == 1),
+                            )
+                            .unwrap(),
+                        )
+                    })
+                    .collect();
+
+                let res = lookup(cs.namespace(|| "lookup"), &bits, &table).unwrap();
+
+                assert!(cs.is_satisfied());
+                assert_eq!(res.get_value().unwrap(), table[index]);
+                assert_eq!(
+                    cs.num_constraints(),
+                    window + (1 << (window - 1)) - window + 1
+                );
+
+                cs.set("lookup/column 0/num", Fr::random(&mut rng));
+                assert_eq!(cs.which_is_unsatisfied(), Some("lookup/column 0 lookup"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_columns() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let bits: Vec<_> = (0..3)
+                .map(|j| {
+                    Boolean::from(
+                        AllocatedBit::alloc(
+                            cs.namespace(|| format!("bit {}", j)),
+                            Some(rng.next_u32() % 2 != 0),
+                        )
+                        .unwrap(),
+                    )
+                })
+                .collect();
+            let points: Vec<(Fr, Fr)> = (0..8)
+                .map(|_| (Fr::random(&mut rng), Fr::random(&mut rng)))
+                .collect();
+            let xs: Vec<_> = points.iter().map(|p| p.0).collect();
+            let ys: Vec<_> = points.iter().map(|p| p.1).collect();
+
+            // Three bits and two columns cost the same as lookup3_xy.
+            let res = lookup_columns(cs.namespace(|| "lookup"), &bits, &[&xs, &ys]).unwrap();
+            assert_eq!(cs.num_constraints(), 3 + 3);
+
+            let (x, y) = lookup3_xy(cs.namespace(|| "lookup3_xy"), &bits, &points).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(res[0].get_value(), x.get_value());
+            assert_eq!(res[1].get_value(), y.get_value());
+        }
+
+        // Constant bits need no products.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let table: Vec<_> = (0..16).map(|_| Fr::random(&mut rng)).collect();
+        let bits = [
+            Boolean::from(AllocatedBit::alloc(cs.namespace(|| "bit 0"), Some(true)).unwrap()),
+            Boolean::constant(false),
+            Boolean::constant(true),
+            Boolean::constant(true),
+        ];
+        let res = lookup(cs.namespace(|| "lookup"), &bits, &table).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 1 + 1);
+        assert_eq!(res.get_value().unwrap(), table[0b1101]);
+    }
+
     #[test]
     fn test_synth() {
         let mut rng = XorShiftRng::from_seed([
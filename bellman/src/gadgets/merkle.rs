@@ -0,0 +1,502 @@
+//! Merkle authentication paths over any two-to-one hash with a gadget,
+//! and reads and updates of a `SparseMerkleTree` built on them.
+
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::boolean::{AllocatedBit, Boolean};
+use super::num::{AllocatedNum, Decomposition};
+use super::sponge::{self, SpongeGadget};
+use crate::{ConstraintSystem, SynthesisError};
+
+/// Hashes a left and a right child into their parent inside a circuit.
+/// Every `SpongeGadget` is one, hashing the pair as a sponge.
+pub trait NodeHashGadget<E: ScalarEngine> {
+    fn hash_nodes<CS>(
+        &self,
+        cs: CS,
+        left: &AllocatedNum<E>,
+        right: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>;
+}
+
+impl<E: ScalarEngine, P: SpongeGadget<E>> NodeHashGadget<E> for P {
+    fn hash_nodes<CS>(
+        &self,
+        cs: CS,
+        left: &AllocatedNum<E>,
+        right: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        sponge::sponge_hash(cs, self, &[left.clone(), right.clone()])
+    }
+}
+
+/// Authenticates leaves of a Merkle tree of a given depth against its
+/// root. The depth is a value rather than a type, so one gadget serves
+/// trees of any depth, but it is fixed for a circuit: a path always has
+/// exactly `depth` levels, each a sibling and a bit which is set when the
+/// node being authenticated is the right child at that level.
+pub struct MerklePathGadget<'a, E: ScalarEngine, H> {
+    hasher: &'a H,
+    depth: usize,
+    _marker: PhantomData<E>,
+}
+
+impl<'a, E, H> MerklePathGadget<'a, E, H>
+where
+    E: ScalarEngine,
+    H: NodeHashGadget<E>,
+{
+    pub fn new(hasher: &'a H, depth: usize) -> Self {
+        MerklePathGadget {
+            hasher,
+            depth,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Allocates a path from its siblings and position bits, leaf first,
+    /// or an unassigned path of the right shape when `path` is `None`.
+    pub fn alloc_path<CS>(
+        &self,
+        mut cs: CS,
+        path: Option<&[(E::Fr, bool)]>,
+    ) -> Result<Vec<(AllocatedNum<E>, Boolean)>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        if let Some(path) = path {
+            assert_eq!(path.len(), self.depth);
+        }
+
+        (0..self.depth)
+            .map(|i| {
+                let mut cs = cs.namespace(|| format!("level {}", i));
+                let level = path.map(|path| path[i]);
+
+                let sibling = AllocatedNum::alloc(cs.namespace(|| "sibling"), || {
+                    level.map(|l| l.0).ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                let is_right = Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| "position bit"),
+                    level.map(|l| l.1),
+                )?);
+
+                Ok((sibling, is_right))
+            })
+            .collect()
+    }
+
+    /// Hashes `leaf` up `path` to the root it authenticates against.
+    pub fn compute_root<CS>(
+        &self,
+        mut cs: CS,
+        leaf: &AllocatedNum<E>,
+        path: &[(AllocatedNum<E>, Boolean)],
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        assert_eq!(path.len(), self.depth, "the path must be as long as the tree is deep");
+
+        let mut cur = leaf.clone();
+        for (i, (sibling, is_right)) in path.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("level {}", i));
+
+            // Swap the node and its sibling when the node is on the right
+            let (left, right) = AllocatedNum::conditionally_reverse(
+                cs.namespace(|| "conditional reversal"),
+                &cur,
+                sibling,
+                is_right,
+            )?;
+
+            cur = self
+                .hasher
+                .hash_nodes(cs.namespace(|| "node hash"), &left, &right)?;
+        }
+
+        Ok(cur)
+    }
+
+    /// Enforces that `leaf` is authenticated by `path` against `root`.
+    pub fn enforce_root<CS>(
+        &self,
+        mut cs: CS,
+        leaf: &AllocatedNum<E>,
+        path: &[(AllocatedNum<E>, Boolean)],
+        root: &AllocatedNum<E>,
+    ) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let computed = self.compute_root(cs.namespace(|| "path"), leaf, path)?;
+
+        cs.enforce_equal(
+            || "root equality",
+            |lc| lc + computed.get_variable(),
+            |lc| lc + root.get_variable(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Reads and updates a `SparseMerkleTree` of a given depth inside a
+/// circuit. The position bits of the path of a key are the bits of the key,
+/// so a read authenticates the key as well as its value, and a read of
+/// zero proves that the key is absent.
+pub struct SparseMerkleGadget<'a, E: ScalarEngine, H> {
+    path: MerklePathGadget<'a, E, H>,
+}
+
+impl<'a, E, H> SparseMerkleGadget<'a, E, H>
+where
+    E: ScalarEngine,
+    H: NodeHashGadget<E>,
+{
+    pub fn new(hasher: &'a H, depth: usize) -> Self {
+        assert!(depth <= E::Fr::NUM_BITS as usize, "keys are field elements");
+
+        SparseMerkleGadget {
+            path: MerklePathGadget::new(hasher, depth),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.path.depth()
+    }
+
+    /// Decomposes `key` into the position bits of its path, least
+    /// significant first, enforcing that it fits in the depth of the tree.
+    pub fn key_bits<CS>(&self, cs: CS, key: &AllocatedNum<E>) -> Result<Vec<Boolean>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let decomposition = if self.depth() == E::Fr::NUM_BITS as usize {
+            Decomposition::Strict
+        } else {
+            Decomposition::Bounded(self.depth())
+        };
+
+        key.unpack_into_bits_le(cs, decomposition)
+    }
+
+    /// Allocates the siblings of a path, leaf first, or unassigned
+    /// siblings when `siblings` is `None`.
+    pub fn alloc_siblings<CS>(
+        &self,
+        mut cs: CS,
+        siblings: Option<&[E::Fr]>,
+    ) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        if let Some(siblings) = siblings {
+            assert_eq!(siblings.len(), self.depth());
+        }
+
+        (0..self.depth())
+            .map(|i| {
+                AllocatedNum::alloc(cs.namespace(|| format!("sibling {}", i)), || {
+                    siblings
+                        .map(|siblings| siblings[i])
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect()
+    }
+
+    /// The root of a tree with `value` at the key with `key_bits`, given
+    /// the siblings of its path.
+    pub fn compute_root<CS>(
+        &self,
+        cs: CS,
+        key_bits: &[Boolean],
+        value: &AllocatedNum<E>,
+        siblings: &[AllocatedNum<E>],
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        assert_eq!(key_bits.len(), self.depth());
+
+        let path: Vec<_> = siblings.iter().cloned().zip(key_bits.iter().cloned()).collect();
+
+        self.path.compute_root(cs, value, &path)
+    }
+
+    /// Enforces that the key with `key_bits` has `value` in the tree with
+    /// `root`.
+    pub fn enforce_read<CS>(
+        &self,
+        cs: CS,
+        key_bits: &[Boolean],
+        value: &AllocatedNum<E>,
+        siblings: &[AllocatedNum<E>],
+        root: &AllocatedNum<E>,
+    ) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let path: Vec<_> = siblings.iter().cloned().zip(key_bits.iter().cloned()).collect();
+
+        self.path.enforce_root(cs, value, &path, root)
+    }
+
+    /// Enforces that the key with `key_bits` is absent from the tree with
+    /// `root`.
+    pub fn enforce_non_membership<CS>(
+        &self,
+        mut cs: CS,
+        key_bits: &[Boolean],
+        siblings: &[AllocatedNum<E>],
+        root: &AllocatedNum<E>,
+    ) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let empty = Self::empty_leaf(cs.namespace(|| "empty leaf"))?;
+
+        self.enforce_read(cs.namespace(|| "read"), key_bits, &empty, siblings, root)
+    }
+
+    /// Enforces that the key with `key_bits` has `old_value` in the tree
+    /// with `old_root`, returning the root once it is set to `new_value`.
+    /// The siblings are the same before and after.
+    pub fn update<CS>(
+        &self,
+        mut cs: CS,
+        key_bits: &[Boolean],
+        old_value: &AllocatedNum<E>,
+        new_value: &AllocatedNum<E>,
+        siblings: &[AllocatedNum<E>],
+        old_root: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        self.enforce_read(cs.namespace(|| "old"), key_bits, old_value, siblings, old_root)?;
+
+        self.compute_root(cs.namespace(|| "new"), key_bits, new_value, siblings)
+    }
+
+    /// `update` of a key which must be absent from the tree with
+    /// `old_root`.
+    pub fn insert<CS>(
+        &self,
+        mut cs: CS,
+        key_bits: &[Boolean],
+        value: &AllocatedNum<E>,
+        siblings: &[AllocatedNum<E>],
+        old_root: &AllocatedNum<E>,
+    ) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let empty = Self::empty_leaf(cs.namespace(|| "empty leaf"))?;
+
+        self.update(cs.namespace(|| "update"), key_bits, &empty, value, siblings, old_root)
+    }
+
+    fn empty_leaf<CS>(mut cs: CS) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let empty = AllocatedNum::alloc(cs.namespace(|| "leaf"), || Ok(E::Fr::zero()))?;
+
+        cs.enforce(
+            || "leaf is zero",
+            |lc| lc + empty.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        Ok(empty)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ff::{Field, PrimeField};
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{MerklePathGadget, SparseMerkleGadget};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::sponge::SpongeGadget;
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::{AlgebraicSponge, ConstraintSystem, PoseidonParams, RescueParams, SparseMerkleTree};
+
+    fn native_root<P: AlgebraicSponge<Bls12>>(params: &P, leaf: Fr, path: &[(Fr, bool)]) -> Fr {
+        path.iter().fold(leaf, |cur, &(sibling, is_right)| {
+            if is_right {
+                params.hash(&[sibling, cur])
+            } else {
+                params.hash(&[cur, sibling])
+            }
+        })
+    }
+
+    fn check_paths<P: SpongeGadget<Bls12>>(params: &P) {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &depth in [0, 1, 5].iter() {
+            let gadget = MerklePathGadget::new(params, depth);
+
+            let leaf = Fr::random(&mut rng);
+            let path: Vec<(Fr, bool)> = (0..depth)
+                .map(|_| (Fr::random(&mut rng), rng.next_u32() % 2 == 1))
+                .collect();
+            let root = native_root(params, leaf, &path);
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let leaf_num = AllocatedNum::alloc(cs.namespace(|| "leaf"), || Ok(leaf)).unwrap();
+            let root_num = AllocatedNum::alloc(cs.namespace(|| "root"), || Ok(root)).unwrap();
+            let path_nums = gadget.alloc_path(cs.namespace(|| "auth path"), Some(&path)).unwrap();
+
+            let computed = gadget
+                .compute_root(cs.namespace(|| "compute"), &leaf_num, &path_nums)
+                .unwrap();
+            assert_eq!(computed.get_value(), Some(root));
+
+            gadget
+                .enforce_root(cs.namespace(|| "membership"), &leaf_num, &path_nums, &root_num)
+                .unwrap();
+            assert!(cs.is_satisfied());
+
+            if depth > 0 {
+                // Moving the leaf to the other side of its sibling changes
+                // the root.
+                let flipped = if path[0].1 { Fr::zero() } else { Fr::one() };
+                cs.set("auth path/level 0/position bit/boolean", flipped);
+                assert!(!cs.is_satisfied());
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_path() {
+        check_paths(&PoseidonParams::<Bls12>::new(3));
+        check_paths(&RescueParams::<Bls12>::new(3));
+    }
+
+    #[test]
+    fn test_merkle_path_constraints() {
+        let params = PoseidonParams::<Bls12>::new(3);
+        let depth = 4;
+        let gadget = MerklePathGadget::new(&params, depth);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let leaf = AllocatedNum::alloc(cs.namespace(|| "leaf"), || Ok(Fr::one())).unwrap();
+        let path = gadget
+            .alloc_path(cs.namespace(|| "auth path"), Some(&vec![(Fr::one(), false); depth]))
+            .unwrap();
+        gadget.compute_root(cs.namespace(|| "compute"), &leaf, &path).unwrap();
+
+        // Each level is a position bit, two constraints to order the pair
+        // and the 244 of a width 3 Poseidon hash of two elements.
+        assert_eq!(cs.num_constraints(), depth * (1 + 2 + 244));
+    }
+
+    #[test]
+    fn test_sparse_merkle() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let params = PoseidonParams::<Bls12>::new(3);
+        let depth = 8;
+
+        let mut tree = SparseMerkleTree::new(&params, depth);
+        for _ in 0..10 {
+            let key = Fr::from_str(&(rng.next_u32() % 256).to_string()).unwrap();
+            tree.insert(key, Fr::random(&mut rng));
+        }
+        let gadget = SparseMerkleGadget::new(&params, depth);
+
+        let key = Fr::from_str(&(rng.next_u32() % 256).to_string()).unwrap();
+        tree.insert(key, Fr::zero());
+        let old_root = tree.root();
+        let siblings = tree.path(key);
+        let value = Fr::random(&mut rng);
+        let updated = Fr::random(&mut rng);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key_num = AllocatedNum::alloc(cs.namespace(|| "key"), || Ok(key)).unwrap();
+        let old_root_num = AllocatedNum::alloc(cs.namespace(|| "old root"), || Ok(old_root)).unwrap();
+        let value_num = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(value)).unwrap();
+        let updated_num = AllocatedNum::alloc(cs.namespace(|| "updated"), || Ok(updated)).unwrap();
+
+        let key_bits = gadget.key_bits(cs.namespace(|| "key bits"), &key_num).unwrap();
+        let sibling_nums = gadget
+            .alloc_siblings(cs.namespace(|| "siblings"), Some(&siblings))
+            .unwrap();
+
+        // The key is absent, so it can be inserted.
+        gadget
+            .enforce_non_membership(cs.namespace(|| "absent"), &key_bits, &sibling_nums, &old_root_num)
+            .unwrap();
+        let inserted_root = gadget
+            .insert(cs.namespace(|| "insert"), &key_bits, &value_num, &sibling_nums, &old_root_num)
+            .unwrap();
+        tree.insert(key, value);
+        assert_eq!(inserted_root.get_value(), Some(tree.root()));
+
+        // Its siblings are unchanged by the insertion, so it can then be
+        // read and updated along the same path.
+        gadget
+            .enforce_read(cs.namespace(|| "read"), &key_bits, &value_num, &sibling_nums, &inserted_root)
+            .unwrap();
+        let updated_root = gadget
+            .update(
+                cs.namespace(|| "update"),
+                &key_bits,
+                &value_num,
+                &updated_num,
+                &sibling_nums,
+                &inserted_root,
+            )
+            .unwrap();
+        tree.insert(key, updated);
+        assert_eq!(updated_root.get_value(), Some(tree.root()));
+
+        assert!(cs.is_satisfied());
+
+        // A present key cannot be passed off as absent.
+        cs.set("old root/num", tree.root());
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_sparse_merkle_key_range() {
+        let params = PoseidonParams::<Bls12>::new(3);
+        let gadget = SparseMerkleGadget::new(&params, 8);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key = AllocatedNum::alloc(cs.namespace(|| "key"), || Ok(Fr::from_str("255").unwrap())).unwrap();
+        gadget.key_bits(cs.namespace(|| "key bits"), &key).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 8 + 1);
+
+        // A key past the depth of the tree has no leaf.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let key = AllocatedNum::alloc(cs.namespace(|| "key"), || Ok(Fr::from_str("256").unwrap())).unwrap();
+        gadget.key_bits(cs.namespace(|| "key bits"), &key).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+}
@@ -0,0 +1,130 @@
+//! Circuit implementation of the MiMC block cipher, matching
+//! `MimcParams::encrypt` and `MimcParams::feistel_encrypt`, and so sharing
+//! their round constants, which are specific to this crate.
+
+use ff::{Field, ScalarEngine};
+
+use super::num::AllocatedNum;
+use super::sponge::Element;
+use crate::{ConstraintSystem, MimcParams, SynthesisError};
+
+/// Encrypts `x` under `key` with MiMC-n/n. With MiMC-7 each round takes
+/// four constraints.
+pub fn mimc<E, CS>(
+    mut cs: CS,
+    params: &MimcParams<E>,
+    x: &AllocatedNum<E>,
+    key: &AllocatedNum<E>,
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    let key = Element::from(key);
+    let mut x = Element::from(x);
+
+    for (i, constant) in params.round_constants().iter().enumerate() {
+        x.add(E::Fr::one(), &key);
+        x.add(E::Fr::one(), &Element::constant::<CS>(*constant));
+        x = x.pow(cs.namespace(|| format!("round {}", i)), params.exponent())?;
+    }
+    x.add(E::Fr::one(), &key);
+
+    x.allocate(cs.namespace(|| "ciphertext"))
+}
+
+/// Encrypts `(xl, xr)` under `key` with MiMC-2n/n. With x^3 each round
+/// takes two constraints.
+pub fn mimc_feistel<E, CS>(
+    mut cs: CS,
+    params: &MimcParams<E>,
+    xl: &AllocatedNum<E>,
+    xr: &AllocatedNum<E>,
+    key: &AllocatedNum<E>,
+) -> Result<(AllocatedNum<E>, AllocatedNum<E>), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    let key = Element::from(key);
+    let mut xl = Element::from(xl);
+    let mut xr = Element::from(xr);
+
+    for (i, constant) in params.round_constants().iter().enumerate() {
+        let mut tmp = xl.clone();
+        tmp.add(E::Fr::one(), &key);
+        tmp.add(E::Fr::one(), &Element::constant::<CS>(*constant));
+
+        let mut tmp = tmp.pow(cs.namespace(|| format!("round {}", i)), params.exponent())?;
+        tmp.add(E::Fr::one(), &xr);
+        xr = xl;
+        xl = tmp;
+    }
+
+    Ok((
+        xl.allocate(cs.namespace(|| "ciphertext xl"))?,
+        xr.allocate(cs.namespace(|| "ciphertext xr"))?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::{mimc, mimc_feistel};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::{ConstraintSystem, MimcParams};
+
+    #[test]
+    fn test_mimc() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let params = MimcParams::<Bls12>::mimc7();
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let (x, key) = (Fr::random(&mut rng), Fr::random(&mut rng));
+        let x_num = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(x)).unwrap();
+        let key_num = AllocatedNum::alloc(cs.namespace(|| "key"), || Ok(key)).unwrap();
+
+        let out = mimc(cs.namespace(|| "mimc"), &params, &x_num, &key_num).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(out.get_value().unwrap(), params.encrypt(x, key));
+
+        // Four constraints for x^7 in each of the 91 rounds, and one to
+        // allocate the ciphertext.
+        assert_eq!(cs.num_constraints(), 4 * 91 + 1);
+
+        cs.set("mimc/ciphertext/num", Fr::one());
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_mimc_feistel() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let params = MimcParams::<Bls12>::feistel();
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let (xl, xr, key) = (Fr::random(&mut rng), Fr::random(&mut rng), Fr::random(&mut rng));
+        let xl_num = AllocatedNum::alloc(cs.namespace(|| "xl"), || Ok(xl)).unwrap();
+        let xr_num = AllocatedNum::alloc(cs.namespace(|| "xr"), || Ok(xr)).unwrap();
+        let key_num = AllocatedNum::alloc(cs.namespace(|| "key"), || Ok(key)).unwrap();
+
+        let (out_l, out_r) =
+            mimc_feistel(cs.namespace(|| "mimc"), &params, &xl_num, &xr_num, &key_num).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(
+            (out_l.get_value().unwrap(), out_r.get_value().unwrap()),
+            params.feistel_encrypt(xl, xr, key)
+        );
+        assert_eq!(cs.num_constraints(), 2 * 322 + 2);
+    }
+}
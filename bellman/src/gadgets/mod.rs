@@ -1,12 +1,31 @@
 pub mod test;
 
+pub mod blake2b;
 pub mod blake2s;
 pub mod boolean;
+pub mod bytes;
+pub mod chacha20;
+pub mod comparison;
+pub mod gadget;
+pub mod int64;
+pub mod keccak;
 pub mod lookup;
+pub mod merkle;
+pub mod mimc;
 pub mod multieq;
 pub mod multipack;
+pub mod mux;
 pub mod num;
+pub mod poseidon;
+pub mod range;
+pub mod rescue;
 pub mod sha256;
+pub mod sha512;
+pub mod sponge;
 pub mod uint32;
+pub mod uint64;
+pub mod uint8;
+#[cfg(feature = "groth16")]
+pub mod verifying_key;
 
 use crate::SynthesisError;
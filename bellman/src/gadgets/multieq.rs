@@ -1,9 +1,8 @@
-use ff::{Field, PrimeField};
-use pairing::Engine;
+use ff::{Field, PrimeField, ScalarEngine};
 
 use crate::{ConstraintSystem, LinearCombination, SynthesisError, Coefficient};
 
-pub struct MultiEq<E: Engine, CS: ConstraintSystem<E>> {
+pub struct MultiEq<E: ScalarEngine, CS: ConstraintSystem<E>> {
     cs: CS,
     ops: usize,
     bits_used: usize,
@@ -11,7 +10,7 @@ pub struct MultiEq<E: Engine, CS: ConstraintSystem<E>> {
     rhs: LinearCombination<E>,
 }
 
-impl<E: Engine, CS: ConstraintSystem<E>> MultiEq<E, CS> {
+impl<E: ScalarEngine, CS: ConstraintSystem<E>> MultiEq<E, CS> {
     pub fn new(cs: CS) -> Self {
         MultiEq {
             cs: cs,
@@ -58,7 +57,7 @@ impl<E: Engine, CS: ConstraintSystem<E>> MultiEq<E, CS> {
     }
 }
 
-impl<E: Engine, CS: ConstraintSystem<E>> Drop for MultiEq<E, CS> {
+impl<E: ScalarEngine, CS: ConstraintSystem<E>> Drop for MultiEq<E, CS> {
     fn drop(&mut self) {
         if self.bits_used > 0 {
             self.accumulate();
@@ -66,7 +65,7 @@ impl<E: Engine, CS: ConstraintSystem<E>> Drop for MultiEq<E, CS> {
     }
 }
 
-impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for MultiEq<E, CS> {
+impl<E: ScalarEngine, CS: ConstraintSystem<E>> ConstraintSystem<E> for MultiEq<E, CS> {
     type Root = Self;
 
     fn one() -> Coefficient {
@@ -91,6 +90,14 @@ impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for MultiEq<E, CS>
         self.cs.alloc_input(annotation, f)
     }
 
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient, SynthesisError>
+    where
+        F: FnOnce() -> Result<E::Fr, SynthesisError>,
+        N: Into<String>,
+    {
+        self.cs.alloc_input_named(name, f)
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -102,6 +109,33 @@ impl<E: Engine, CS: ConstraintSystem<E>> ConstraintSystem<E> for MultiEq<E, CS>
         self.cs.enforce(annotation, a, b, c)
     }
 
+    fn enforce_zero<A, AR, LA>(&mut self, annotation: A, a: LA)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.cs.enforce_zero(annotation, a)
+    }
+
+    fn enforce_equal<A, AR, LA, LB>(&mut self, annotation: A, a: LA, b: LB)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.cs.enforce_equal(annotation, a, b)
+    }
+
+    fn enforce_boolean<A, AR>(&mut self, annotation: A, var: Coefficient)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enforce_boolean(annotation, var)
+    }
+
     fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
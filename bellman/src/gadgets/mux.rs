@@ -0,0 +1,248 @@
+//! Selection between gadget values under boolean conditions: two-way
+//! selects over numbers, booleans, words and arrays of any of these, and
+//! an n-way multiplexer driven by the bits of an index.
+
+use ff::ScalarEngine;
+
+use super::boolean::Boolean;
+use super::num::AllocatedNum;
+use super::uint32::UInt32;
+use crate::{ConstraintSystem, SynthesisError};
+
+/// A value that can be selected between in a circuit.
+pub trait ConditionalSelect<E: ScalarEngine>: Sized {
+    /// Returns `a` if the condition is true, and `b` otherwise.
+    fn conditionally_select<CS>(
+        cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>;
+}
+
+impl<E: ScalarEngine> ConditionalSelect<E> for AllocatedNum<E> {
+    fn conditionally_select<CS>(
+        cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        AllocatedNum::conditionally_select(cs, a, b, condition)
+    }
+}
+
+impl<E: ScalarEngine> ConditionalSelect<E> for Boolean {
+    fn conditionally_select<CS>(
+        cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        Boolean::conditionally_select(cs, a, b, condition)
+    }
+}
+
+impl<E: ScalarEngine> ConditionalSelect<E> for UInt32 {
+    fn conditionally_select<CS>(
+        cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        UInt32::conditionally_select(cs, a, b, condition)
+    }
+}
+
+/// Arrays are selected between element by element, and must be of the
+/// same length.
+impl<E: ScalarEngine, T: ConditionalSelect<E>> ConditionalSelect<E> for Vec<T> {
+    fn conditionally_select<CS>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        assert_eq!(a.len(), b.len());
+
+        a.iter()
+            .zip(b.iter())
+            .enumerate()
+            .map(|(i, (a, b))| {
+                T::conditionally_select(cs.namespace(|| format!("element {}", i)), a, b, condition)
+            })
+            .collect()
+    }
+}
+
+/// Selects `options[i]`, where `i` is the integer with the little-endian
+/// bits `selector`. There must be exactly 2^`selector.len()` options.
+///
+/// The options are halved once per bit, each bit choosing between the
+/// pairs of those that remain, so the multiplexer takes one selection
+/// less than there are options.
+pub fn mux<E, CS, T>(mut cs: CS, options: &[T], selector: &[Boolean]) -> Result<T, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+    T: ConditionalSelect<E> + Clone,
+{
+    assert_eq!(options.len(), 1 << selector.len());
+
+    let mut level = options.to_vec();
+    for (i, bit) in selector.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("selector bit {}", i));
+
+        level = level
+            .chunks(2)
+            .enumerate()
+            .map(|(j, pair)| {
+                T::conditionally_select(
+                    cs.namespace(|| format!("pair {}", j)),
+                    &pair[1],
+                    &pair[0],
+                    bit,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    Ok(level.pop().expect("one option remains"))
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{mux, ConditionalSelect};
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::test::*;
+    use crate::gadgets::uint32::UInt32;
+    use crate::ConstraintSystem;
+
+    #[test]
+    fn test_mux() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        let values: Vec<Fr> = (0..8).map(|_| Fr::random(&mut rng)).collect();
+
+        for index in 0..8 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let options: Vec<_> = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    AllocatedNum::alloc(cs.namespace(|| format!("option {}", i)), || Ok(*v))
+                        .unwrap()
+                })
+                .collect();
+            let selector: Vec<_> = (0..3)
+                .map(|i| {
+                    Boolean::from(
+                        AllocatedBit::alloc(
+                            cs.namespace(|| format!("selector {}", i)),
+                            Some((index >> i) & 1 == 1),
+                        )
+                        .unwrap(),
+                    )
+                })
+                .collect();
+
+            let r = mux(cs.namespace(|| "mux"), &options, &selector).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 3 + 7);
+            assert_eq!(r.get_value(), Some(values[index]));
+
+            cs.set(
+                "mux/selector bit 2/pair 0/conditional selection result/num",
+                Fr::one(),
+            );
+            assert_eq!(
+                cs.which_is_unsatisfied(),
+                Some("mux/selector bit 2/pair 0/conditional selection")
+            );
+        }
+
+        // A constant selector picks its option for free.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let options: Vec<_> = (0..4)
+            .map(|i| UInt32::alloc(cs.namespace(|| format!("option {}", i)), Some(i)).unwrap())
+            .collect();
+        let selector = [Boolean::constant(true), Boolean::constant(false)];
+        let before = cs.num_constraints();
+        let r = mux(cs.namespace(|| "mux"), &options, &selector).unwrap();
+        assert_eq!(cs.num_constraints(), before);
+        assert_eq!(r.get_value(), Some(1));
+    }
+
+    #[test]
+    fn test_select_arrays() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &condition in [true, false].iter() {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a: Vec<u32> = (0..4).map(|_| rng.next_u32()).collect();
+            let b: Vec<u32> = (0..4).map(|_| rng.next_u32()).collect();
+            let alloc = |cs: &mut TestConstraintSystem<Bls12>, name: &str, words: &[u32]| {
+                words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| {
+                        UInt32::alloc(cs.namespace(|| format!("{} {}", name, i)), Some(*w)).unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let a_words = alloc(&mut cs, "a", &a);
+            let b_words = alloc(&mut cs, "b", &b);
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(condition)).unwrap(),
+            );
+
+            let r = Vec::conditionally_select(
+                cs.namespace(|| "select"),
+                &a_words,
+                &b_words,
+                &condition,
+            )
+            .unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 2 * 4 * 32 + 1 + 4 * 32);
+
+            let expected = if condition.get_value().unwrap() {
+                &a
+            } else {
+                &b
+            };
+            for (word, expected) in r.iter().zip(expected.iter()) {
+                assert_eq!(word.get_value(), Some(*expected));
+            }
+        }
+    }
+}
@@ -1,16 +1,33 @@
-use ff::{BitIterator, Field, PrimeField, PrimeFieldRepr};
-use pairing::Engine;
+use std::ops::{Add, Mul, Sub};
 
-use crate::{ConstraintSystem, LinearCombination, SynthesisError, Coefficient};
+use ff::{BitIterator, Field, PrimeField, PrimeFieldRepr, ScalarEngine};
+
+use crate::{ConstraintSystem, Constant, LinearCombination, SynthesisError, Coefficient};
 
 use super::boolean::{self, AllocatedBit, Boolean};
+use super::range;
+
+/// How an allocated number is decomposed into bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decomposition {
+    /// All `NUM_BITS` bits, constrained to encode a value below the
+    /// modulus, so that each number has exactly one decomposition.
+    Strict,
+    /// All `NUM_BITS` bits without that constraint, so that a number whose
+    /// sum with the modulus still fits in `NUM_BITS` bits has a second.
+    NonStrict,
+    /// The given number of bits, up to `CAPACITY`, which also enforces
+    /// that the number lies below 2^bits. Such a decomposition cannot wrap
+    /// around the modulus, and is always canonical.
+    Bounded(usize),
+}
 
-pub struct AllocatedNum<E: Engine> {
+pub struct AllocatedNum<E: ScalarEngine> {
     value: Option<E::Fr>,
     variable: Coefficient,
 }
 
-impl<E: Engine> Clone for AllocatedNum<E> {
+impl<E: ScalarEngine> Clone for AllocatedNum<E> {
     fn clone(&self) -> Self {
         AllocatedNum {
             value: self.value,
@@ -19,7 +36,7 @@ impl<E: Engine> Clone for AllocatedNum<E> {
     }
 }
 
-impl<E: Engine> AllocatedNum<E> {
+impl<E: ScalarEngine> AllocatedNum<E> {
     pub fn alloc<CS, F>(mut cs: CS, value: F) -> Result<Self, SynthesisError>
     where
         CS: ConstraintSystem<E>,
@@ -52,10 +69,9 @@ impl<E: Engine> AllocatedNum<E> {
             || Ok(self.value.ok_or(SynthesisError::AssignmentMissing)?)
         )?;
 
-        cs.enforce(
+        cs.enforce_equal(
             || "enforce input is correct",
             |lc| lc + input,
-            |lc| lc + CS::one(),
             |lc| lc + self.variable,
         );
 
@@ -76,7 +92,7 @@ impl<E: Engine> AllocatedNum<E> {
             v: &[AllocatedBit],
         ) -> Result<AllocatedBit, SynthesisError>
         where
-            E: Engine,
+            E: ScalarEngine,
             CS: ConstraintSystem<E>,
         {
             assert!(v.len() > 0);
@@ -212,6 +228,96 @@ impl<E: Engine> AllocatedNum<E> {
         Ok(bits.into_iter().map(|b| Boolean::from(b)).collect())
     }
 
+    /// Allocates the number whose bits are `bits`, least
+    /// significant first, in a single constraint. The
+    /// bits are trusted to be boolean already, and there
+    /// may be at most `CAPACITY` of them so that the sum
+    /// cannot wrap around the modulus.
+    pub fn pack_bits_le<CS>(mut cs: CS, bits: &[Boolean]) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let mut value = Some(E::Fr::zero());
+        let mut coeff = E::Fr::one();
+        for bit in bits {
+            value = match (value, bit.get_value()) {
+                (Some(mut value), Some(bit)) => {
+                    if bit {
+                        value.add_assign(&coeff);
+                    }
+                    Some(value)
+                }
+                _ => None,
+            };
+
+            coeff.double();
+        }
+
+        let num = Self::alloc(&mut cs, || value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        range::enforce_packing(&mut cs, &num, bits)?;
+
+        Ok(num)
+    }
+
+    /// `pack_bits_le` for bits most significant first.
+    pub fn pack_bits_be<CS>(cs: CS, bits: &[Boolean]) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let bits: Vec<_> = bits.iter().rev().cloned().collect();
+
+        Self::pack_bits_le(cs, &bits)
+    }
+
+    /// Decomposes this number into bits, least significant
+    /// first, as `decomposition` says.
+    pub fn unpack_into_bits_le<CS>(
+        &self,
+        mut cs: CS,
+        decomposition: Decomposition,
+    ) -> Result<Vec<Boolean>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let bits = match decomposition {
+            Decomposition::Strict => return self.into_bits_le_strict(cs),
+            Decomposition::NonStrict => return self.into_bits_le(cs),
+            Decomposition::Bounded(bits) => bits,
+        };
+
+        let repr = self.value.map(|v| v.into_repr());
+        let bits = (0..bits)
+            .map(|i| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("bit {}", i)),
+                    repr.as_ref()
+                        .map(|repr| (repr.as_ref()[i / 64] >> (i % 64)) & 1 == 1),
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        range::enforce_packing(&mut cs, self, &bits)?;
+
+        Ok(bits)
+    }
+
+    /// `unpack_into_bits_le` for bits most significant
+    /// first.
+    pub fn unpack_into_bits_be<CS>(
+        &self,
+        cs: CS,
+        decomposition: Decomposition,
+    ) -> Result<Vec<Boolean>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let mut bits = self.unpack_into_bits_le(cs, decomposition)?;
+        bits.reverse();
+
+        Ok(bits)
+    }
+
     pub fn mul<CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
     where
         CS: ConstraintSystem<E>,
@@ -276,6 +382,94 @@ impl<E: Engine> AllocatedNum<E> {
         })
     }
 
+    /// Allocates the inverse of this number, which must be
+    /// nonzero. Takes a single constraint, which no zero
+    /// number can satisfy.
+    pub fn inverse<CS>(&self, mut cs: CS) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let mut value = None;
+
+        let var = cs.alloc(
+            || "inverse num",
+            || {
+                let tmp = self.value.ok_or(SynthesisError::AssignmentMissing)?;
+                let tmp = tmp.inverse().ok_or(SynthesisError::DivisionByZero)?;
+
+                value = Some(tmp);
+
+                Ok(tmp)
+            },
+        )?;
+
+        // Constrain: a * inv = 1
+        cs.enforce(
+            || "inverse constraint",
+            |lc| lc + self.variable,
+            |lc| lc + var,
+            |lc| lc + CS::one(),
+        );
+
+        Ok(AllocatedNum {
+            value: value,
+            variable: var,
+        })
+    }
+
+    /// Divides this number by `other`, which is constrained
+    /// to be nonzero so that the quotient is unique. Takes
+    /// two constraints.
+    pub fn div<CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        other.assert_nonzero(&mut cs)?;
+
+        self.div_unchecked(&mut cs, other)
+    }
+
+    /// Divides this number by `other` in a single constraint,
+    /// quotient * other = self. Nothing stops `other` being
+    /// zero: a zero over zero is satisfied by any quotient,
+    /// so this is only sound where `other` is already known
+    /// to be nonzero.
+    pub fn div_unchecked<CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let mut value = None;
+
+        let var = cs.alloc(
+            || "quotient num",
+            || {
+                let mut tmp = other
+                    .value
+                    .ok_or(SynthesisError::AssignmentMissing)?
+                    .inverse()
+                    .ok_or(SynthesisError::DivisionByZero)?;
+                tmp.mul_assign(&self.value.ok_or(SynthesisError::AssignmentMissing)?);
+
+                value = Some(tmp);
+
+                Ok(tmp)
+            },
+        )?;
+
+        // Constrain: q * b = a
+        cs.enforce(
+            || "division constraint",
+            |lc| lc + var,
+            |lc| lc + other.variable,
+            |lc| lc + self.variable,
+        );
+
+        Ok(AllocatedNum {
+            value: value,
+            variable: var,
+        })
+    }
+
     pub fn assert_nonzero<CS>(&self, mut cs: CS) -> Result<(), SynthesisError>
     where
         CS: ConstraintSystem<E>,
@@ -306,6 +500,15 @@ impl<E: Engine> AllocatedNum<E> {
         Ok(())
     }
 
+    /// Enforces that this number is less than 2^`bits`,
+    /// in `bits` constraints. See `range::enforce_in_range`.
+    pub fn assert_in_range<CS>(&self, cs: CS, bits: usize) -> Result<(), SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        range::enforce_in_range(cs, self, bits)
+    }
+
     /// Takes two allocated numbers (a, b) and returns
     /// (b, a) if the condition is true, and (a, b)
     /// otherwise.
@@ -351,6 +554,42 @@ impl<E: Engine> AllocatedNum<E> {
         Ok((c, d))
     }
 
+    /// Returns `a` if the condition is true, and `b`
+    /// otherwise.
+    pub fn conditionally_select<CS>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        match *condition {
+            Boolean::Constant(true) => return Ok(a.clone()),
+            Boolean::Constant(false) => return Ok(b.clone()),
+            _ => {}
+        }
+
+        let c = Self::alloc(cs.namespace(|| "conditional selection result"), || {
+            if condition.get_value().ok_or(SynthesisError::AssignmentMissing)? {
+                Ok(a.value.ok_or(SynthesisError::AssignmentMissing)?)
+            } else {
+                Ok(b.value.ok_or(SynthesisError::AssignmentMissing)?)
+            }
+        })?;
+
+        // Constrain: (a - b) * condition = c - b
+        cs.enforce(
+            || "conditional selection",
+            |lc| lc + a.variable - b.variable,
+            |_| condition.lc(CS::one(), E::Fr::one()),
+            |lc| lc + c.variable - b.variable,
+        );
+
+        Ok(c)
+    }
+
     pub fn get_value(&self) -> Option<E::Fr> {
         self.value
     }
@@ -360,12 +599,25 @@ impl<E: Engine> AllocatedNum<E> {
     }
 }
 
-pub struct Num<E: Engine> {
+/// A linear combination of allocated numbers and constants, with its
+/// value. Sums, differences and scalar multiples of `AllocatedNum`s are
+/// `Num`s, and cost nothing until they are constrained, which usually
+/// happens as one side of some other constraint.
+pub struct Num<E: ScalarEngine> {
     value: Option<E::Fr>,
     lc: LinearCombination<E>,
 }
 
-impl<E: Engine> From<AllocatedNum<E>> for Num<E> {
+impl<E: ScalarEngine> Clone for Num<E> {
+    fn clone(&self) -> Self {
+        Num {
+            value: self.value,
+            lc: self.lc.clone(),
+        }
+    }
+}
+
+impl<E: ScalarEngine> From<AllocatedNum<E>> for Num<E> {
     fn from(num: AllocatedNum<E>) -> Num<E> {
         Num {
             value: num.value,
@@ -374,7 +626,7 @@ impl<E: Engine> From<AllocatedNum<E>> for Num<E> {
     }
 }
 
-impl<E: Engine> Num<E> {
+impl<E: ScalarEngine> Num<E> {
     pub fn zero() -> Self {
         Num {
             value: Some(E::Fr::zero()),
@@ -407,17 +659,207 @@ impl<E: Engine> Num<E> {
             lc: self.lc + &bit.lc(one, coeff),
         }
     }
+
+    /// Allocates a number equal to this one, in a single
+    /// constraint.
+    pub fn into_allocated<CS>(self, mut cs: CS) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let num = AllocatedNum::alloc(&mut cs, || {
+            self.value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Constrain: lc * 1 = num
+        cs.enforce(
+            || "allocation constraint",
+            |_| self.lc,
+            |lc| lc + CS::one(),
+            |lc| lc + num.variable,
+        );
+
+        Ok(num)
+    }
+
+    /// Allocates the product of this number and `other`,
+    /// in a single constraint whatever their linear
+    /// combinations. Scaling by a constant is free with
+    /// the `*` operator instead.
+    pub fn mul_allocated<CS>(&self, mut cs: CS, other: &Self) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let value = match (self.value, other.value) {
+            (Some(mut a), Some(b)) => {
+                a.mul_assign(&b);
+                Some(a)
+            }
+            _ => None,
+        };
+
+        let product = AllocatedNum::alloc(cs.namespace(|| "product num"), || {
+            value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Constrain: a * b = ab
+        cs.enforce(
+            || "multiplication constraint",
+            |_| self.lc.clone(),
+            |_| other.lc.clone(),
+            |lc| lc + product.variable,
+        );
+
+        Ok(product)
+    }
+
+    /// Enforces that this number equals `other`, in a single
+    /// constraint.
+    pub fn enforce_equal<CS>(&self, mut cs: CS, other: &Self)
+    where
+        CS: ConstraintSystem<E>,
+    {
+        cs.enforce(
+            || "equality constraint",
+            |_| self.lc.clone() - &other.lc,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+    }
+}
+
+fn add_values<F: Field>(a: Option<F>, b: Option<F>) -> Option<F> {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            a.add_assign(&b);
+            Some(a)
+        }
+        _ => None,
+    }
+}
+
+fn sub_values<F: Field>(a: Option<F>, b: Option<F>) -> Option<F> {
+    match (a, b) {
+        (Some(mut a), Some(b)) => {
+            a.sub_assign(&b);
+            Some(a)
+        }
+        _ => None,
+    }
+}
+
+impl<'a, E: ScalarEngine> From<&'a AllocatedNum<E>> for Num<E> {
+    fn from(num: &'a AllocatedNum<E>) -> Num<E> {
+        Num::from(num.clone())
+    }
+}
+
+impl<E: ScalarEngine> Add<Num<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn add(self, other: Num<E>) -> Num<E> {
+        Num {
+            value: add_values(self.value, other.value),
+            lc: self.lc + &other.lc,
+        }
+    }
+}
+
+impl<E: ScalarEngine> Sub<Num<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn sub(self, other: Num<E>) -> Num<E> {
+        Num {
+            value: sub_values(self.value, other.value),
+            lc: self.lc - &other.lc,
+        }
+    }
+}
+
+impl<'a, E: ScalarEngine> Add<&'a AllocatedNum<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn add(self, other: &'a AllocatedNum<E>) -> Num<E> {
+        self + Num::from(other)
+    }
+}
+
+impl<'a, E: ScalarEngine> Sub<&'a AllocatedNum<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn sub(self, other: &'a AllocatedNum<E>) -> Num<E> {
+        self - Num::from(other)
+    }
+}
+
+impl<E: ScalarEngine> Add<Constant<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn add(self, constant: Constant<E>) -> Num<E> {
+        Num {
+            value: add_values(self.value, Some(constant.get_value())),
+            lc: self.lc + constant,
+        }
+    }
+}
+
+impl<E: ScalarEngine> Sub<Constant<E>> for Num<E> {
+    type Output = Num<E>;
+
+    fn sub(self, constant: Constant<E>) -> Num<E> {
+        Num {
+            value: sub_values(self.value, Some(constant.get_value())),
+            lc: self.lc - constant,
+        }
+    }
+}
+
+impl<E: ScalarEngine> Mul<E::Fr> for Num<E> {
+    type Output = Num<E>;
+
+    fn mul(self, scalar: E::Fr) -> Num<E> {
+        Num {
+            value: self.value.map(|mut v| {
+                v.mul_assign(&scalar);
+                v
+            }),
+            lc: self.lc(scalar),
+        }
+    }
+}
+
+impl<'a, 'b, E: ScalarEngine> Add<&'b AllocatedNum<E>> for &'a AllocatedNum<E> {
+    type Output = Num<E>;
+
+    fn add(self, other: &'b AllocatedNum<E>) -> Num<E> {
+        Num::from(self) + other
+    }
+}
+
+impl<'a, 'b, E: ScalarEngine> Sub<&'b AllocatedNum<E>> for &'a AllocatedNum<E> {
+    type Output = Num<E>;
+
+    fn sub(self, other: &'b AllocatedNum<E>) -> Num<E> {
+        Num::from(self) - other
+    }
+}
+
+impl<'a, E: ScalarEngine> Mul<E::Fr> for &'a AllocatedNum<E> {
+    type Output = Num<E>;
+
+    fn mul(self, scalar: E::Fr) -> Num<E> {
+        Num::from(self) * scalar
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::ConstraintSystem;
+    use crate::{ConstraintSystem, Constant, SynthesisError};
     use ff::{BitIterator, Field, PrimeField};
     use pairing::bls12_381::{Bls12, Fr};
-    use rand_core::SeedableRng;
+    use rand_core::{RngCore, SeedableRng};
     use rand_xorshift::XorShiftRng;
 
-    use super::{AllocatedNum, Boolean};
+    use super::{AllocatedBit, AllocatedNum, Boolean, Decomposition, Num};
     use crate::gadgets::test::*;
 
     #[test]
@@ -515,6 +957,242 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_num_inverse() {
+        {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let n = AllocatedNum::alloc(&mut cs, || Ok(Fr::from_str("3").unwrap())).unwrap();
+            let inv = n.inverse(&mut cs).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 1);
+
+            let mut product = inv.value.unwrap();
+            product.mul_assign(&Fr::from_str("3").unwrap());
+            assert_eq!(product, Fr::one());
+
+            cs.set("inverse num", Fr::from_str("3").unwrap());
+            assert_eq!(cs.which_is_unsatisfied(), Some("inverse constraint"));
+        }
+        {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let n = AllocatedNum::alloc(&mut cs, || Ok(Fr::zero())).unwrap();
+            match n.inverse(&mut cs) {
+                Err(SynthesisError::DivisionByZero) => {}
+                _ => panic!("zero has no inverse"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_num_division() {
+        {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a =
+                AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::from_str("12").unwrap())).unwrap();
+            let b =
+                AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::from_str("4").unwrap())).unwrap();
+            let q = a.div(cs.namespace(|| "div"), &b).unwrap();
+            let r = a.div_unchecked(cs.namespace(|| "div unchecked"), &b).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 2 + 1);
+            assert_eq!(q.value.unwrap(), Fr::from_str("3").unwrap());
+            assert_eq!(r.value.unwrap(), Fr::from_str("3").unwrap());
+
+            cs.set("div/quotient num", Fr::from_str("4").unwrap());
+            assert_eq!(cs.which_is_unsatisfied(), Some("div/division constraint"));
+        }
+        {
+            // With a zero divisor there is no quotient to witness, and a
+            // zero divisor cannot pass the checked division.
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::zero())).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::one())).unwrap();
+            match b.div(cs.namespace(|| "div by zero"), &a) {
+                Err(SynthesisError::DivisionByZero) => {}
+                _ => panic!("division by zero should fail"),
+            }
+            match b.div_unchecked(cs.namespace(|| "div unchecked by zero"), &a) {
+                Err(SynthesisError::DivisionByZero) => {}
+                _ => panic!("division by zero should fail"),
+            }
+
+            // Zero over a nonzero divisor is fine.
+            let q = a.div(cs.namespace(|| "zero over one"), &b).unwrap();
+            assert!(cs.is_satisfied());
+            assert!(q.value.unwrap().is_zero());
+        }
+    }
+
+    #[test]
+    fn test_num_conditional_select() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        for &condition in [true, false].iter() {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::random(&mut rng))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::random(&mut rng))).unwrap();
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(condition)).unwrap(),
+            );
+            let c = AllocatedNum::conditionally_select(&mut cs, &a, &b, &condition).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 1 + 1);
+            if condition.get_value().unwrap() {
+                assert_eq!(c.value, a.value);
+            } else {
+                assert_eq!(c.value, b.value);
+            }
+
+            cs.set("conditional selection result/num", Fr::random(&mut rng));
+            assert_eq!(cs.which_is_unsatisfied(), Some("conditional selection"));
+        }
+
+        {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::random(&mut rng))).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::random(&mut rng))).unwrap();
+            let condition = Boolean::constant(false);
+            let c = AllocatedNum::conditionally_select(&mut cs, &a, &b, &condition).unwrap();
+
+            assert_eq!(cs.num_constraints(), 0);
+            assert_eq!(c.value, b.value);
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_bits() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..20 {
+            let v = rng.next_u64();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let n = AllocatedNum::alloc(cs.namespace(|| "n"), || {
+                Ok(Fr::from_str(&v.to_string()).unwrap())
+            })
+            .unwrap();
+
+            let le = n
+                .unpack_into_bits_le(cs.namespace(|| "le"), Decomposition::Bounded(64))
+                .unwrap();
+            let be = n
+                .unpack_into_bits_be(cs.namespace(|| "be"), Decomposition::Bounded(64))
+                .unwrap();
+            assert_eq!(cs.num_constraints(), 2 * (64 + 1));
+
+            for (i, (le, be)) in le.iter().zip(be.iter().rev()).enumerate() {
+                assert_eq!(le.get_value(), Some((v >> i) & 1 == 1));
+                assert_eq!(be.get_value(), le.get_value());
+            }
+
+            // Packing either order back gives the same number.
+            let packed_le = AllocatedNum::pack_bits_le(cs.namespace(|| "pack le"), &le).unwrap();
+            let packed_be = AllocatedNum::pack_bits_be(cs.namespace(|| "pack be"), &be).unwrap();
+            assert_eq!(cs.num_constraints(), 2 * (64 + 1) + 2);
+            assert_eq!(packed_le.get_value(), n.get_value());
+            assert_eq!(packed_be.get_value(), n.get_value());
+
+            assert!(cs.is_satisfied());
+            cs.set("pack be/num", Fr::zero());
+            assert_eq!(cs.which_is_unsatisfied(), Some("pack be/packing constraint"));
+        }
+
+        // A bounded decomposition rejects numbers out of range.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "n"), || Ok(Fr::from_str("256").unwrap()))
+            .unwrap();
+        n.unpack_into_bits_le(cs.namespace(|| "bits"), Decomposition::Bounded(8))
+            .unwrap();
+        assert_eq!(cs.which_is_unsatisfied(), Some("bits/packing constraint"));
+
+        // Full decompositions are strict only when asked to be.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "n"), || Ok(Fr::random(&mut rng))).unwrap();
+        let strict = n
+            .unpack_into_bits_le(cs.namespace(|| "strict"), Decomposition::Strict)
+            .unwrap();
+        let nonstrict = n
+            .unpack_into_bits_be(cs.namespace(|| "nonstrict"), Decomposition::NonStrict)
+            .unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(strict.len(), Fr::NUM_BITS as usize);
+        assert_eq!(nonstrict.len(), Fr::NUM_BITS as usize);
+        for (a, b) in strict.iter().zip(nonstrict.iter().rev()) {
+            assert_eq!(a.get_value(), b.get_value());
+        }
+    }
+
+    #[test]
+    fn test_num_operators() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..10 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let (a_val, b_val, c_val, k) = (
+                Fr::random(&mut rng),
+                Fr::random(&mut rng),
+                Fr::random(&mut rng),
+                Fr::random(&mut rng),
+            );
+            let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(a_val)).unwrap();
+            let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(b_val)).unwrap();
+            let c = AllocatedNum::alloc(cs.namespace(|| "c"), || Ok(c_val)).unwrap();
+
+            // (a + b - 3) * (k * c - a) costs a single constraint.
+            let three = Fr::from_str("3").unwrap();
+            let left = &a + &b - Constant::new(three, TestConstraintSystem::<Bls12>::one());
+            let right = &c * k - &a;
+
+            let mut expected_left = a_val;
+            expected_left.add_assign(&b_val);
+            expected_left.sub_assign(&three);
+            let mut expected_right = c_val;
+            expected_right.mul_assign(&k);
+            expected_right.sub_assign(&a_val);
+            assert_eq!(left.get_value(), Some(expected_left));
+            assert_eq!(right.get_value(), Some(expected_right));
+            assert_eq!(cs.num_constraints(), 0);
+
+            let product = left.mul_allocated(cs.namespace(|| "product"), &right).unwrap();
+            let mut expected = expected_left;
+            expected.mul_assign(&expected_right);
+            assert_eq!(product.get_value(), Some(expected));
+            assert_eq!(cs.num_constraints(), 1);
+
+            // Allocating a sum, and comparing it with another, cost one
+            // constraint each.
+            let sum = (&a + &b).into_allocated(cs.namespace(|| "sum")).unwrap();
+            (&b + &a).enforce_equal(cs.namespace(|| "commutes"), &Num::from(&sum));
+            assert_eq!(cs.num_constraints(), 3);
+            assert!(cs.is_satisfied());
+
+            cs.set("sum/num", a_val);
+            assert_eq!(cs.which_is_unsatisfied(), Some("sum/allocation constraint"));
+            cs.set("sum/num", sum.get_value().unwrap());
+
+            (&a - &b).enforce_equal(cs.namespace(|| "difference"), &Num::from(&sum));
+            assert_eq!(cs.which_is_unsatisfied(), Some("difference/equality constraint"));
+        }
+    }
+
     #[test]
     fn test_into_bits_strict() {
         let mut negone = Fr::one();
@@ -0,0 +1,143 @@
+//! Circuit implementation of the Poseidon permutation and of the sponge
+//! hash built on it, matching `PoseidonParams`.
+
+use ff::{Field, ScalarEngine};
+
+use super::num::AllocatedNum;
+use super::sponge::{self, Element, SpongeGadget};
+use crate::{ConstraintSystem, PoseidonParams, SynthesisError};
+
+impl<E: ScalarEngine> SpongeGadget<E> for PoseidonParams<E> {
+    fn permute_elements<CS>(&self, mut cs: CS, mut state: Vec<Element<E>>) -> Result<Vec<Element<E>>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        for round in 0..self.rounds() {
+            let mut cs = cs.namespace(|| format!("round {}", round));
+
+            for (element, constant) in state.iter_mut().zip(self.round_constants(round)) {
+                element.add(E::Fr::one(), &Element::constant::<CS>(*constant));
+            }
+
+            let sboxes = if self.is_full_round(round) { state.len() } else { 1 };
+            for (i, element) in state[..sboxes].iter_mut().enumerate() {
+                *element = element.pow(cs.namespace(|| format!("s-box {}", i)), self.alpha())?;
+            }
+
+            state = sponge::mix(self.mds(), &state);
+        }
+
+        Ok(state)
+    }
+}
+
+/// Applies the permutation to `state`, which must hold `params.width()`
+/// numbers, constraining each output.
+pub fn poseidon_permutation<E, CS>(
+    cs: CS,
+    params: &PoseidonParams<E>,
+    state: &[AllocatedNum<E>],
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    sponge::sponge_permutation(cs, params, state)
+}
+
+/// Hashes `inputs` with the permutation as a sponge, the same way as
+/// `AlgebraicSponge::hash`.
+pub fn poseidon_hash<E, CS>(
+    cs: CS,
+    params: &PoseidonParams<E>,
+    inputs: &[AllocatedNum<E>],
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    sponge::sponge_hash(cs, params, inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::{poseidon_hash, poseidon_permutation};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::{AlgebraicSponge, ConstraintSystem, PoseidonParams};
+
+    #[test]
+    fn test_poseidon_hash() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &width in [3, 5, 9].iter() {
+            let params = PoseidonParams::<Bls12>::new(width);
+
+            for len in 0..2 * width + 1 {
+                let mut cs = TestConstraintSystem::<Bls12>::new();
+                let values: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+                let inputs: Vec<AllocatedNum<Bls12>> = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        AllocatedNum::alloc(cs.namespace(|| format!("input {}", i)), || Ok(*value))
+                            .unwrap()
+                    })
+                    .collect();
+
+                let digest = poseidon_hash(cs.namespace(|| "hash"), &params, &inputs).unwrap();
+                assert!(cs.is_satisfied());
+                assert_eq!(digest.get_value().unwrap(), params.hash(&values));
+            }
+        }
+
+        // Two field elements take a single width 3 permutation: three
+        // constraints per S-box, over 8 full and 57 partial rounds, and one
+        // to allocate the digest.
+        let params = PoseidonParams::<Bls12>::new(3);
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::one())).unwrap();
+        let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::one())).unwrap();
+        poseidon_hash(cs.namespace(|| "hash"), &params, &[a, b]).unwrap();
+        assert_eq!(cs.num_constraints(), 3 * (8 * 3 + 57) + 1);
+
+        // A wrong digest is caught.
+        cs.set("hash/digest/num", Fr::one());
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_poseidon_permutation() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let params = PoseidonParams::<Bls12>::new(5);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut values: Vec<Fr> = (0..5).map(|_| Fr::random(&mut rng)).collect();
+        let state: Vec<AllocatedNum<Bls12>> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("state {}", i)), || Ok(*value)).unwrap()
+            })
+            .collect();
+
+        let output = poseidon_permutation(cs.namespace(|| "permutation"), &params, &state).unwrap();
+        assert!(cs.is_satisfied());
+
+        params.permute(&mut values);
+        for (num, value) in output.iter().zip(values.iter()) {
+            assert_eq!(num.get_value().unwrap(), *value);
+        }
+    }
+}
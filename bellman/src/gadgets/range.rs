@@ -0,0 +1,221 @@
+//! Range checks on allocated numbers.
+//!
+//! `enforce_in_range` decomposes a number into bits it allocates itself,
+//! and costs one constraint per bit. Where the bits of a number are already
+//! at hand and constrained to be boolean, as those of a `UInt32` or of
+//! another decomposition are, `enforce_packing` relies on them and costs a
+//! single constraint.
+
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::boolean::{AllocatedBit, Boolean};
+use super::num::AllocatedNum;
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+/// Enforces that `num` is less than 2^`bits`, for `bits` up to the
+/// capacity of the field so that the bits cannot wrap around the modulus.
+///
+/// All but the top bit are allocated and constrained to be boolean. The
+/// top bit is what remains of `num` once they are taken away, scaled down,
+/// and is never allocated: constraining that to be boolean as well also
+/// enforces the decomposition, for `bits` constraints in all.
+pub fn enforce_in_range<E, CS>(
+    cs: CS,
+    num: &AllocatedNum<E>,
+    bits: usize,
+) -> Result<(), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    enforce_lc_in_range(
+        cs,
+        LinearCombination::zero() + num.get_variable(),
+        num.get_value(),
+        bits,
+    )
+}
+
+/// `enforce_in_range` for a linear combination whose value is `value`.
+pub(super) fn enforce_lc_in_range<E, CS>(
+    mut cs: CS,
+    lc: LinearCombination<E>,
+    value: Option<E::Fr>,
+    bits: usize,
+) -> Result<(), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(bits <= E::Fr::CAPACITY as usize);
+
+    if bits == 0 {
+        cs.enforce(
+            || "zero",
+            |_| lc,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        return Ok(());
+    }
+
+    let repr = value.map(|v| v.into_repr());
+
+    let mut remainder = lc;
+    let mut coeff = E::Fr::one();
+    for i in 0..bits - 1 {
+        let bit = AllocatedBit::alloc(
+            cs.namespace(|| format!("bit {}", i)),
+            repr.as_ref()
+                .map(|repr| (repr.as_ref()[i / 64] >> (i % 64)) & 1 == 1),
+        )?;
+
+        remainder = remainder - (coeff, bit.get_variable());
+
+        coeff.double();
+    }
+
+    // coeff is now 2^(bits - 1), and the remainder is the top bit times it
+    let top = LinearCombination::zero()
+        + (
+            coeff.inverse().expect("powers of two are nonzero"),
+            &remainder,
+        );
+
+    cs.enforce(
+        || "top bit boolean constraint",
+        |lc| lc + &top,
+        |lc| lc + &top - CS::one(),
+        |lc| lc,
+    );
+
+    Ok(())
+}
+
+/// Enforces that `bits`, least significant first, are the binary
+/// representation of `num`, which therefore lies below 2^`bits.len()`.
+///
+/// The bits are trusted to be boolean already, and only the packing
+/// constraint is added.
+pub fn enforce_packing<E, CS>(
+    mut cs: CS,
+    num: &AllocatedNum<E>,
+    bits: &[Boolean],
+) -> Result<(), SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(bits.len() <= E::Fr::CAPACITY as usize);
+
+    let mut lc = LinearCombination::zero();
+    let mut coeff = E::Fr::one();
+    for bit in bits {
+        lc = lc + &bit.lc(CS::one(), coeff);
+
+        coeff.double();
+    }
+
+    cs.enforce(
+        || "packing constraint",
+        |lc| lc,
+        |lc| lc,
+        |_| lc - num.get_variable(),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ff::{Field, PrimeField};
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    use super::{enforce_in_range, enforce_packing};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::test::*;
+    use crate::gadgets::uint32::UInt32;
+    use crate::ConstraintSystem;
+
+    fn power_of_two(exp: usize) -> Fr {
+        let mut value = Fr::one();
+        for _ in 0..exp {
+            value.double();
+        }
+        value
+    }
+
+    #[test]
+    fn test_enforce_in_range() {
+        for &bits in [1, 2, 8, 64, 200, Fr::CAPACITY as usize].iter() {
+            // The largest value in range, and the smallest out of it.
+            let mut max = power_of_two(bits);
+            max.sub_assign(&Fr::one());
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let n = AllocatedNum::alloc(cs.namespace(|| "max"), || Ok(max)).unwrap();
+            enforce_in_range(cs.namespace(|| "range"), &n, bits).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), bits);
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let n = AllocatedNum::alloc(cs.namespace(|| "max + 1"), || Ok(power_of_two(bits)))
+                .unwrap();
+            enforce_in_range(cs.namespace(|| "range"), &n, bits).unwrap();
+            assert_eq!(
+                cs.which_is_unsatisfied(),
+                Some("range/top bit boolean constraint")
+            );
+        }
+
+        // Zero bits pin the number to zero.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let n = AllocatedNum::alloc(cs.namespace(|| "zero"), || Ok(Fr::zero())).unwrap();
+        enforce_in_range(cs.namespace(|| "range"), &n, 0).unwrap();
+        assert!(cs.is_satisfied());
+        cs.set("zero/num", Fr::one());
+        assert!(!cs.is_satisfied());
+
+        // Nor can a value below the modulus but far out of range pass.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut negone = Fr::one();
+        negone.negate();
+        let n = AllocatedNum::alloc(cs.namespace(|| "-1"), || Ok(negone)).unwrap();
+        n.assert_in_range(cs.namespace(|| "range"), 64).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_packing() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..20 {
+            let v = rng.next_u32();
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let word = UInt32::alloc(cs.namespace(|| "word"), Some(v)).unwrap();
+            let n = AllocatedNum::alloc(cs.namespace(|| "num"), || {
+                Ok(Fr::from_str(&v.to_string()).unwrap())
+            })
+            .unwrap();
+
+            // The word already constrains its bits, so only one constraint
+            // is added to its 32.
+            enforce_packing(cs.namespace(|| "range"), &n, &word.into_bits()).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 32 + 1);
+
+            cs.set("num/num", Fr::from_str(&(v as u64 + 1).to_string()).unwrap());
+            assert_eq!(
+                cs.which_is_unsatisfied(),
+                Some("range/packing constraint")
+            );
+        }
+    }
+}
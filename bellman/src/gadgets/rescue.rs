@@ -0,0 +1,181 @@
+//! Circuit implementation of the Rescue-Prime permutation and of the
+//! sponge hash built on it, matching `RescueParams`.
+
+use ff::{Field, ScalarEngine};
+
+use super::num::AllocatedNum;
+use super::sponge::{self, Element, SpongeGadget};
+use crate::{ConstraintSystem, RescueParams, SynthesisError};
+
+impl<E: ScalarEngine> SpongeGadget<E> for RescueParams<E> {
+    fn permute_elements<CS>(&self, mut cs: CS, mut state: Vec<Element<E>>) -> Result<Vec<Element<E>>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        for round in 0..self.rounds() {
+            let mut cs = cs.namespace(|| format!("round {}", round));
+
+            for step in 0..2 {
+                for (i, element) in state.iter_mut().enumerate() {
+                    let cs = cs.namespace(|| format!("s-box {} {}", step, i));
+                    *element = if step == 0 {
+                        element.pow(cs, self.alpha())?
+                    } else {
+                        element.pow_inverse(cs, self.alpha(), self.alpha_inv())?
+                    };
+                }
+
+                state = sponge::mix(self.mds(), &state);
+                for (element, constant) in state.iter_mut().zip(self.round_constants(round, step)) {
+                    element.add(E::Fr::one(), &Element::constant::<CS>(*constant));
+                }
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+/// Applies the permutation to `state`, which must hold `params.width()`
+/// numbers, constraining each output.
+pub fn rescue_permutation<E, CS>(
+    cs: CS,
+    params: &RescueParams<E>,
+    state: &[AllocatedNum<E>],
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    sponge::sponge_permutation(cs, params, state)
+}
+
+/// Hashes `inputs` with the permutation as a sponge, the same way as
+/// `AlgebraicSponge::hash`.
+pub fn rescue_hash<E, CS>(
+    cs: CS,
+    params: &RescueParams<E>,
+    inputs: &[AllocatedNum<E>],
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    sponge::sponge_hash(cs, params, inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand_core::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::{rescue_hash, rescue_permutation};
+    use crate::gadgets::num::AllocatedNum;
+    use crate::gadgets::sponge::{sponge_hash, SpongeGadget};
+    use crate::gadgets::test::TestConstraintSystem;
+    use crate::{AlgebraicSponge, ConstraintSystem, PoseidonParams, RescueParams};
+
+    #[test]
+    fn test_rescue_hash() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for &width in [3, 5].iter() {
+            let params = RescueParams::<Bls12>::new(width);
+
+            for len in 0..2 * width + 1 {
+                let mut cs = TestConstraintSystem::<Bls12>::new();
+                let values: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+                let inputs: Vec<AllocatedNum<Bls12>> = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        AllocatedNum::alloc(cs.namespace(|| format!("input {}", i)), || Ok(*value))
+                            .unwrap()
+                    })
+                    .collect();
+
+                let digest = rescue_hash(cs.namespace(|| "hash"), &params, &inputs).unwrap();
+                assert!(cs.is_satisfied());
+                assert_eq!(digest.get_value().unwrap(), params.hash(&values));
+            }
+        }
+
+        // Two field elements take a single width 3 permutation: both halves
+        // of each of the 8 rounds put all three elements through an S-box
+        // of three constraints, and one more allocates the digest.
+        let params = RescueParams::<Bls12>::new(3);
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(Fr::one())).unwrap();
+        let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(Fr::one())).unwrap();
+        rescue_hash(cs.namespace(|| "hash"), &params, &[a, b]).unwrap();
+        assert_eq!(cs.num_constraints(), 8 * 2 * 3 * 3 + 1);
+
+        // A wrong root from the backward S-box is caught.
+        cs.set("hash/permutation 0/round 0/s-box 1 0/root/num", Fr::one());
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_rescue_permutation() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let params = RescueParams::<Bls12>::new(4);
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let mut values: Vec<Fr> = (0..4).map(|_| Fr::random(&mut rng)).collect();
+        let state: Vec<AllocatedNum<Bls12>> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("state {}", i)), || Ok(*value)).unwrap()
+            })
+            .collect();
+
+        let output = rescue_permutation(cs.namespace(|| "permutation"), &params, &state).unwrap();
+        assert!(cs.is_satisfied());
+
+        params.permute(&mut values);
+        for (num, value) in output.iter().zip(values.iter()) {
+            assert_eq!(num.get_value().unwrap(), *value);
+        }
+    }
+
+    #[test]
+    fn test_switching_sponges() {
+        // A gadget written once against `SpongeGadget`, here hashing a leaf
+        // up two levels of a Merkle path, runs with either permutation.
+        fn root<P: SpongeGadget<Bls12>>(params: &P, leaf: Fr, path: &[Fr]) -> (Fr, bool) {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let mut cur = AllocatedNum::alloc(cs.namespace(|| "leaf"), || Ok(leaf)).unwrap();
+            for (i, sibling) in path.iter().enumerate() {
+                let sibling =
+                    AllocatedNum::alloc(cs.namespace(|| format!("sibling {}", i)), || Ok(*sibling)).unwrap();
+                cur = sponge_hash(cs.namespace(|| format!("level {}", i)), params, &[cur, sibling]).unwrap();
+            }
+            (cur.get_value().unwrap(), cs.is_satisfied())
+        }
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let leaf = Fr::random(&mut rng);
+        let path = [Fr::random(&mut rng), Fr::random(&mut rng)];
+
+        let native = |params: &dyn AlgebraicSponge<Bls12>| {
+            path.iter().fold(leaf, |cur, sibling| params.hash(&[cur, *sibling]))
+        };
+
+        let poseidon = PoseidonParams::<Bls12>::new(3);
+        let rescue = RescueParams::<Bls12>::new(3);
+        assert_eq!(root(&poseidon, leaf, &path), (native(&poseidon), true));
+        assert_eq!(root(&rescue, leaf, &path), (native(&rescue), true));
+    }
+}
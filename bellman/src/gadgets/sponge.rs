@@ -0,0 +1,245 @@
+//! Circuit implementation of the sponge construction of
+//! `AlgebraicSponge`, for any permutation with a gadget. Merkle trees and
+//! commitments written against `SpongeGadget` switch hash functions by
+//! switching parameters.
+
+use ff::{Field, PrimeField, ScalarEngine};
+
+use super::num::AllocatedNum;
+use crate::{AlgebraicSponge, ConstraintSystem, LinearCombination, SparseLinearCombination, SynthesisError};
+
+/// An element of a permutation state. The linear layers and the round
+/// constants of algebraic permutations only ever combine elements
+/// linearly, so an element is kept as a linear combination and only the
+/// S-boxes allocate.
+#[derive(Clone)]
+pub struct Element<E: ScalarEngine> {
+    value: Option<E::Fr>,
+    lc: SparseLinearCombination<E>,
+}
+
+impl<E: ScalarEngine> Element<E> {
+    pub fn zero() -> Self {
+        Element {
+            value: Some(E::Fr::zero()),
+            lc: SparseLinearCombination::zero(),
+        }
+    }
+
+    pub fn constant<CS: ConstraintSystem<E>>(value: E::Fr) -> Self {
+        Element {
+            value: Some(value),
+            lc: SparseLinearCombination::zero() + (value, CS::one()),
+        }
+    }
+
+    pub fn get_value(&self) -> Option<E::Fr> {
+        self.value
+    }
+
+    pub fn lc(&self) -> LinearCombination<E> {
+        self.lc.clone().into()
+    }
+
+    /// Adds `coeff` times `other` to this element.
+    pub fn add(&mut self, coeff: E::Fr, other: &Self) {
+        self.value = match (self.value, other.value) {
+            (Some(mut acc), Some(mut term)) => {
+                term.mul_assign(&coeff);
+                acc.add_assign(&term);
+                Some(acc)
+            }
+            _ => None,
+        };
+        self.lc = self.lc.clone() + (coeff, &other.lc);
+    }
+
+    /// Raises this element to `alpha` by square-and-multiply, at one
+    /// constraint per step: x^5 takes three.
+    pub fn pow<CS>(&self, cs: CS, alpha: u64) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        pow(cs, alpha, self, None)
+    }
+
+    /// Raises this element to `alpha_inv`, the inverse of `alpha` modulo
+    /// p - 1, by allocating the result and constraining that it raised to
+    /// `alpha` gives this element back. It costs the same as `pow`.
+    pub fn pow_inverse<CS>(&self, mut cs: CS, alpha: u64, alpha_inv: &[u64]) -> Result<Self, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let root = AllocatedNum::alloc(cs.namespace(|| "root"), || {
+            Ok(self.value.ok_or(SynthesisError::AssignmentMissing)?.pow(alpha_inv))
+        })?;
+        let root = Element::from(&root);
+
+        pow(cs, alpha, &root, Some(self))?;
+        Ok(root)
+    }
+
+    /// Allocates a number equal to this element.
+    pub fn allocate<CS>(&self, mut cs: CS) -> Result<AllocatedNum<E>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>,
+    {
+        let num = AllocatedNum::alloc(&mut cs, || self.value.ok_or(SynthesisError::AssignmentMissing))?;
+
+        cs.enforce_equal(|| "allocation constraint", |_| self.lc(), |lc| lc + num.get_variable());
+
+        Ok(num)
+    }
+}
+
+impl<'a, E: ScalarEngine> From<&'a AllocatedNum<E>> for Element<E> {
+    fn from(num: &'a AllocatedNum<E>) -> Self {
+        Element {
+            value: num.get_value(),
+            lc: SparseLinearCombination::zero() + num.get_variable(),
+        }
+    }
+}
+
+/// A permutation which can be applied inside a circuit, so that
+/// `sponge_hash` can hash with it.
+pub trait SpongeGadget<E: ScalarEngine>: AlgebraicSponge<E> {
+    /// Constrains the permutation of `state`, which holds `width`
+    /// elements, returning the permuted state.
+    fn permute_elements<CS>(&self, cs: CS, state: Vec<Element<E>>) -> Result<Vec<Element<E>>, SynthesisError>
+    where
+        CS: ConstraintSystem<E>;
+}
+
+/// Applies `permutation` to `state`, constraining each output.
+pub fn sponge_permutation<E, CS, P>(
+    mut cs: CS,
+    permutation: &P,
+    state: &[AllocatedNum<E>],
+) -> Result<Vec<AllocatedNum<E>>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+    P: SpongeGadget<E>,
+{
+    assert_eq!(state.len(), permutation.width(), "the state must be as wide as the permutation");
+
+    let state = state.iter().map(Element::from).collect();
+    let state = permutation.permute_elements(cs.namespace(|| "permutation"), state)?;
+
+    state
+        .iter()
+        .enumerate()
+        .map(|(i, element)| element.allocate(cs.namespace(|| format!("output {}", i))))
+        .collect()
+}
+
+/// Hashes `inputs` with `permutation` as a sponge, the same way as
+/// `AlgebraicSponge::hash`.
+pub fn sponge_hash<E, CS, P>(
+    mut cs: CS,
+    permutation: &P,
+    inputs: &[AllocatedNum<E>],
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+    P: SpongeGadget<E>,
+{
+    let len = E::Fr::from_repr((inputs.len() as u64).into())
+        .expect("small integers are in the field");
+    let mut state = vec![Element::zero(); permutation.width()];
+    state[0] = Element::constant::<CS>(len);
+
+    if inputs.is_empty() {
+        state = permutation.permute_elements(cs.namespace(|| "permutation 0"), state)?;
+    }
+    for (i, chunk) in inputs.chunks(permutation.rate()).enumerate() {
+        for (element, input) in state[1..].iter_mut().zip(chunk) {
+            element.add(E::Fr::one(), &Element::from(input));
+        }
+        state = permutation.permute_elements(cs.namespace(|| format!("permutation {}", i)), state)?;
+    }
+
+    state[1].allocate(cs.namespace(|| "digest"))
+}
+
+/// Multiplies `state` by `matrix`, which costs no constraints.
+pub fn mix<E: ScalarEngine>(matrix: &[Vec<E::Fr>], state: &[Element<E>]) -> Vec<Element<E>> {
+    matrix
+        .iter()
+        .map(|row| {
+            let mut acc = Element::zero();
+            for (coeff, element) in row.iter().zip(state.iter()) {
+                acc.add(*coeff, element);
+            }
+            acc
+        })
+        .collect()
+}
+
+/// Raises `x` to `alpha`. When `output` is given the last step is
+/// constrained to equal it rather than allocating.
+fn pow<E, CS>(
+    mut cs: CS,
+    alpha: u64,
+    x: &Element<E>,
+    output: Option<&Element<E>>,
+) -> Result<Element<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    assert!(alpha > 1, "an S-box exponent is at least two");
+
+    // The factors of each step, the square of the accumulator or its
+    // product with x, in order.
+    let mut steps = vec![];
+    for i in (0..63 - alpha.leading_zeros()).rev() {
+        steps.push((format!("square {}", i), false));
+        if (alpha >> i) & 1 == 1 {
+            steps.push((format!("multiply {}", i), true));
+        }
+    }
+
+    let last = steps.len() - 1;
+    let mut acc = x.clone();
+    for (step, (name, by_x)) in steps.into_iter().enumerate() {
+        let factor = if by_x { x.clone() } else { acc.clone() };
+        acc = match output {
+            Some(output) if step == last => {
+                cs.enforce(|| name, |_| acc.lc(), |_| factor.lc(), |_| output.lc());
+                output.clone()
+            }
+            _ => mul(cs.namespace(|| name), &acc, &factor)?,
+        };
+    }
+
+    Ok(acc)
+}
+
+fn mul<E, CS>(mut cs: CS, a: &Element<E>, b: &Element<E>) -> Result<Element<E>, SynthesisError>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+{
+    let mut value = None;
+    let var = cs.alloc(
+        || "product",
+        || {
+            let mut tmp = a.value.ok_or(SynthesisError::AssignmentMissing)?;
+            tmp.mul_assign(&b.value.ok_or(SynthesisError::AssignmentMissing)?);
+
+            value = Some(tmp);
+
+            Ok(tmp)
+        },
+    )?;
+
+    cs.enforce(|| "multiplication constraint", |_| a.lc(), |_| b.lc(), |lc| lc + var);
+
+    Ok(Element {
+        value,
+        lc: SparseLinearCombination::zero() + var,
+    })
+}
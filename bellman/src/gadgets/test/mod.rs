@@ -1,5 +1,4 @@
-use ff::{Field, PrimeField, PrimeFieldRepr};
-use pairing::Engine;
+use ff::{Field, PrimeField, PrimeFieldRepr, ScalarEngine};
 
 use crate::{ConstraintSystem, Index, LinearCombination, SynthesisError, Coefficient};
 
@@ -7,7 +6,6 @@ use std::collections::HashMap;
 use std::fmt::Write;
 
 use byteorder::{BigEndian, ByteOrder};
-use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
 use blake2s_simd::{Params as Blake2sParams, State as Blake2sState};
@@ -20,7 +18,7 @@ enum NamedObject {
 }
 
 /// Constraint system for testing purposes.
-pub struct TestConstraintSystem<E: Engine> {
+pub struct TestConstraintSystem<E: ScalarEngine> {
     named_objects: HashMap<String, NamedObject>,
     current_namespace: Vec<String>,
     constraints: Vec<(
@@ -33,39 +31,10 @@ pub struct TestConstraintSystem<E: Engine> {
     aux: Vec<(E::Fr, String)>,
 }
 
-#[derive(Clone, Copy)]
-struct OrderedVariable(Coefficient);
-
-impl Eq for OrderedVariable {}
-impl PartialEq for OrderedVariable {
-    fn eq(&self, other: &OrderedVariable) -> bool {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a == b,
-            (Index::Aux(ref a), Index::Aux(ref b)) => a == b,
-            _ => false,
-        }
-    }
-}
-impl PartialOrd for OrderedVariable {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-impl Ord for OrderedVariable {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.0.get_unchecked(), other.0.get_unchecked()) {
-            (Index::Input(ref a), Index::Input(ref b)) => a.cmp(b),
-            (Index::Aux(ref a), Index::Aux(ref b)) => a.cmp(b),
-            (Index::Input(_), Index::Aux(_)) => Ordering::Less,
-            (Index::Aux(_), Index::Input(_)) => Ordering::Greater,
-        }
-    }
-}
-
-fn proc_lc<E: Engine>(terms: &[(Coefficient, E::Fr)]) -> BTreeMap<OrderedVariable, E::Fr> {
+fn proc_lc<E: ScalarEngine>(terms: &[(Coefficient, E::Fr)]) -> BTreeMap<Coefficient, E::Fr> {
     let mut map = BTreeMap::new();
     for &(var, coeff) in terms {
-        map.entry(OrderedVariable(var))
+        map.entry(var)
             .or_insert(E::Fr::zero())
             .add_assign(&coeff);
     }
@@ -85,7 +54,7 @@ fn proc_lc<E: Engine>(terms: &[(Coefficient, E::Fr)]) -> BTreeMap<OrderedVariabl
     map
 }
 
-fn hash_lc<E: Engine>(terms: &[(Coefficient, E::Fr)], h: &mut Blake2sState) {
+fn hash_lc<E: ScalarEngine>(terms: &[(Coefficient, E::Fr)], h: &mut Blake2sState) {
     let map = proc_lc::<E>(terms);
 
     let mut buf = [0u8; 9 + 32];
@@ -93,7 +62,7 @@ fn hash_lc<E: Engine>(terms: &[(Coefficient, E::Fr)], h: &mut Blake2sState) {
     h.update(&buf[0..8]);
 
     for (var, coeff) in map {
-        match var.0.get_unchecked() {
+        match var.get_unchecked() {
             Index::Input(i) => {
                 buf[0] = b'I';
                 BigEndian::write_u64(&mut buf[1..9], i as u64);
@@ -110,7 +79,7 @@ fn hash_lc<E: Engine>(terms: &[(Coefficient, E::Fr)], h: &mut Blake2sState) {
     }
 }
 
-fn eval_lc<E: Engine>(
+fn eval_lc<E: ScalarEngine>(
     terms: &[(Coefficient, E::Fr)],
     inputs: &[(E::Fr, String)],
     aux: &[(E::Fr, String)],
@@ -130,7 +99,7 @@ fn eval_lc<E: Engine>(
     acc
 }
 
-impl<E: Engine> TestConstraintSystem<E> {
+impl<E: ScalarEngine> TestConstraintSystem<E> {
     pub fn new() -> TestConstraintSystem<E> {
         let mut map = HashMap::new();
         map.insert(
@@ -182,7 +151,7 @@ impl<E: Engine> TestConstraintSystem<E> {
                     write!(s, "{} . ", coeff).unwrap();
                 }
 
-                match var.0.get_unchecked() {
+                match var.get_unchecked() {
                     Index::Input(i) => {
                         write!(s, "`{}`", &self.inputs[i].1).unwrap();
                     }
@@ -344,7 +313,7 @@ fn compute_path(ns: &[String], this: String) -> String {
     name
 }
 
-impl<E: Engine> ConstraintSystem<E> for TestConstraintSystem<E> {
+impl<E: ScalarEngine> ConstraintSystem<E> for TestConstraintSystem<E> {
     type Root = Self;
 
     fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Coefficient, SynthesisError>
@@ -1,5 +1,4 @@
-use ff::{Field, PrimeField};
-use pairing::Engine;
+use ff::{Field, PrimeField, ScalarEngine};
 
 use crate::{ConstraintSystem, LinearCombination, SynthesisError};
 
@@ -41,7 +40,7 @@ impl UInt32 {
     /// Allocate a `UInt32` in the constraint system
     pub fn alloc<E, CS>(mut cs: CS, value: Option<u32>) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let values = match value {
@@ -75,6 +74,10 @@ impl UInt32 {
         })
     }
 
+    pub fn get_value(&self) -> Option<u32> {
+        self.value
+    }
+
     pub fn into_bits_be(&self) -> Vec<Boolean> {
         self.bits.iter().rev().cloned().collect()
     }
@@ -195,7 +198,7 @@ impl UInt32 {
         circuit_fn: U,
     ) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
         F: Fn(u32, u32, u32) -> u32,
         U: Fn(&mut CS, usize, &Boolean, &Boolean, &Boolean) -> Result<Boolean, SynthesisError>,
@@ -224,7 +227,7 @@ impl UInt32 {
     /// during SHA256.
     pub fn sha256_maj<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         Self::triop(
@@ -241,7 +244,7 @@ impl UInt32 {
     /// during SHA256.
     pub fn sha256_ch<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         Self::triop(
@@ -257,7 +260,7 @@ impl UInt32 {
     /// XOR this `UInt32` with another `UInt32`
     pub fn xor<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
     {
         let new_value = match (self.value, other.value) {
@@ -279,10 +282,50 @@ impl UInt32 {
         })
     }
 
+    /// Returns `a` if the condition is true, and `b`
+    /// otherwise, at one constraint per bit that is
+    /// not a constant in both.
+    pub fn conditionally_select<E, CS>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        condition: &Boolean,
+    ) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match condition.get_value() {
+            Some(true) => a.value,
+            Some(false) => b.value,
+            None => None,
+        };
+
+        let bits = a
+            .bits
+            .iter()
+            .zip(b.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| {
+                Boolean::conditionally_select(
+                    cs.namespace(|| format!("selection of bit {}", i)),
+                    a,
+                    b,
+                    condition,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt32 {
+            bits: bits,
+            value: new_value,
+        })
+    }
+
     /// Perform modular addition of several `UInt32` objects.
     pub fn addmany<E, CS, M>(mut cs: M, operands: &[Self]) -> Result<Self, SynthesisError>
     where
-        E: Engine,
+        E: ScalarEngine,
         CS: ConstraintSystem<E>,
         M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
     {
@@ -384,7 +427,7 @@ impl UInt32 {
 #[cfg(test)]
 mod test {
     use super::UInt32;
-    use crate::gadgets::boolean::Boolean;
+    use crate::gadgets::boolean::{AllocatedBit, Boolean};
     use crate::gadgets::multieq::MultiEq;
     use crate::gadgets::test::*;
     use crate::ConstraintSystem;
@@ -508,6 +551,62 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_uint32_conditionally_select() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let a = rng.next_u32();
+            let b = rng.next_u32();
+            let condition = rng.next_u32() % 2 == 1;
+
+            let a_bit = UInt32::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt32::alloc(cs.namespace(|| "b_bit"), Some(b)).unwrap();
+            let condition = Boolean::from(
+                AllocatedBit::alloc(cs.namespace(|| "condition"), Some(condition)).unwrap(),
+            );
+
+            let r = UInt32::conditionally_select(
+                cs.namespace(|| "select"),
+                &a_bit,
+                &b_bit,
+                &condition,
+            )
+            .unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 32 + 32 + 1 + 32);
+
+            let mut expected = if condition.get_value().unwrap() { a } else { b };
+            assert!(r.value == Some(expected));
+
+            for b in r.bits.iter() {
+                assert_eq!(b.get_value(), Some(expected & 1 == 1));
+                expected >>= 1;
+            }
+
+            // Selecting between two constants is free.
+            let r = UInt32::conditionally_select(
+                cs.namespace(|| "select constants"),
+                &UInt32::constant(a),
+                &UInt32::constant(b),
+                &condition,
+            )
+            .unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 32 + 32 + 1 + 32);
+            assert_eq!(
+                r.value,
+                Some(if condition.get_value().unwrap() { a } else { b })
+            );
+        }
+    }
+
     #[test]
     fn test_uint32_addmany_constants() {
         let mut rng = XorShiftRng::from_seed([
@@ -0,0 +1,759 @@
+use ff::{Field, PrimeField, ScalarEngine};
+
+use crate::{ConstraintSystem, LinearCombination, SynthesisError};
+
+use super::boolean::{AllocatedBit, Boolean};
+
+use super::multieq::MultiEq;
+
+/// Represents an interpretation of 64 `Boolean` objects as an
+/// unsigned integer.
+#[derive(Clone)]
+pub struct UInt64 {
+    // Least significant bit first
+    bits: Vec<Boolean>,
+    value: Option<u64>,
+}
+
+impl UInt64 {
+    /// Construct a constant `UInt64` from a `u64`
+    pub fn constant(value: u64) -> Self {
+        let mut bits = Vec::with_capacity(64);
+
+        let mut tmp = value;
+        for _ in 0..64 {
+            if tmp & 1 == 1 {
+                bits.push(Boolean::constant(true))
+            } else {
+                bits.push(Boolean::constant(false))
+            }
+
+            tmp >>= 1;
+        }
+
+        UInt64 {
+            bits: bits,
+            value: Some(value),
+        }
+    }
+
+    /// Allocate a `UInt64` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<u64>) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let values = match value {
+            Some(mut val) => {
+                let mut v = Vec::with_capacity(64);
+
+                for _ in 0..64 {
+                    v.push(Some(val & 1 == 1));
+                    val >>= 1;
+                }
+
+                v
+            }
+            None => vec![None; 64],
+        };
+
+        let bits = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("allocated bit {}", i)),
+                    v,
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(UInt64 {
+            bits: bits,
+            value: value,
+        })
+    }
+
+    pub fn into_bits_be(&self) -> Vec<Boolean> {
+        self.bits.iter().rev().cloned().collect()
+    }
+
+    pub fn from_bits_be(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let mut value = Some(0u64);
+        for b in bits {
+            value.as_mut().map(|v| *v <<= 1);
+
+            match b.get_value() {
+                Some(true) => {
+                    value.as_mut().map(|v| *v |= 1);
+                }
+                Some(false) => {}
+                None => {
+                    value = None;
+                }
+            }
+        }
+
+        UInt64 {
+            value: value,
+            bits: bits.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Turns this `UInt64` into its little-endian byte order representation.
+    pub fn into_bits(&self) -> Vec<Boolean> {
+        self.bits.clone()
+    }
+
+    /// Converts a little-endian byte order representation of bits into a
+    /// `UInt64`.
+    pub fn from_bits(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let new_bits = bits.to_vec();
+
+        let mut value = Some(0u64);
+        for b in new_bits.iter().rev() {
+            value.as_mut().map(|v| *v <<= 1);
+
+            match b {
+                &Boolean::Constant(b) => {
+                    if b {
+                        value.as_mut().map(|v| *v |= 1);
+                    }
+                }
+                &Boolean::Is(ref b) => match b.get_value() {
+                    Some(true) => {
+                        value.as_mut().map(|v| *v |= 1);
+                    }
+                    Some(false) => {}
+                    None => value = None,
+                },
+                &Boolean::Not(ref b) => match b.get_value() {
+                    Some(false) => {
+                        value.as_mut().map(|v| *v |= 1);
+                    }
+                    Some(true) => {}
+                    None => value = None,
+                },
+            }
+        }
+
+        UInt64 {
+            value: value,
+            bits: new_bits,
+        }
+    }
+
+    pub fn rotr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let new_bits = self
+            .bits
+            .iter()
+            .skip(by)
+            .chain(self.bits.iter())
+            .take(64)
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v.rotate_right(by as u32)),
+        }
+    }
+
+    pub fn shr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = self
+            .bits
+            .iter() // The bits are least significant first
+            .skip(by) // Skip the bits that will be lost during the shift
+            .chain(Some(&fill).into_iter().cycle()) // Rest will be zeros
+            .take(64) // Only 64 bits needed!
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v >> by as u32),
+        }
+    }
+
+    pub fn rotl(&self, by: usize) -> Self {
+        self.rotr((64 - by % 64) % 64)
+    }
+
+    pub fn shl(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = Some(&fill)
+            .into_iter()
+            .cycle()
+            .take(by) // The low bits are zeros
+            .chain(self.bits.iter()) // Followed by the bits that are kept
+            .take(64)
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v << by as u32),
+        }
+    }
+
+    /// Flips every bit, which costs no constraints.
+    pub fn not(&self) -> Self {
+        UInt64 {
+            bits: self.bits.iter().map(|b| b.not()).collect(),
+            value: self.value.map(|v| !v),
+        }
+    }
+
+    fn triop<E, CS, F, U>(
+        mut cs: CS,
+        a: &Self,
+        b: &Self,
+        c: &Self,
+        tri_fn: F,
+        circuit_fn: U,
+    ) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+        F: Fn(u64, u64, u64) -> u64,
+        U: Fn(&mut CS, usize, &Boolean, &Boolean, &Boolean) -> Result<Boolean, SynthesisError>,
+    {
+        let new_value = match (a.value, b.value, c.value) {
+            (Some(a), Some(b), Some(c)) => Some(tri_fn(a, b, c)),
+            _ => None,
+        };
+
+        let bits = a
+            .bits
+            .iter()
+            .zip(b.bits.iter())
+            .zip(c.bits.iter())
+            .enumerate()
+            .map(|(i, ((a, b), c))| circuit_fn(&mut cs, i, a, b, c))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits: bits,
+            value: new_value,
+        })
+    }
+
+    /// Compute the `maj` value (a and b) xor (a and c) xor (b and c)
+    /// during SHA512.
+    pub fn sha512_maj<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        Self::triop(
+            cs,
+            a,
+            b,
+            c,
+            |a, b, c| (a & b) ^ (a & c) ^ (b & c),
+            |cs, i, a, b, c| Boolean::sha256_maj(cs.namespace(|| format!("maj {}", i)), a, b, c),
+        )
+    }
+
+    /// Compute the `ch` value `(a and b) xor ((not a) and c)`
+    /// during SHA512.
+    pub fn sha512_ch<E, CS>(cs: CS, a: &Self, b: &Self, c: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        Self::triop(
+            cs,
+            a,
+            b,
+            c,
+            |a, b, c| (a & b) ^ ((!a) & c),
+            |cs, i, a, b, c| Boolean::sha256_ch(cs.namespace(|| format!("ch {}", i)), a, b, c),
+        )
+    }
+
+    /// XOR this `UInt64` with another `UInt64`
+    pub fn xor<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a ^ b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::xor(cs.namespace(|| format!("xor of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits: bits,
+            value: new_value,
+        })
+    }
+
+    /// AND this `UInt64` with another `UInt64`
+    pub fn and<E, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a & b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::and(cs.namespace(|| format!("and of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits: bits,
+            value: new_value,
+        })
+    }
+
+    /// Adds `other` and a `carry` bit to this `UInt64`, returning the sum
+    /// modulo 2^64 and the carry out of it, so that words can be chained
+    /// into wider integers. Takes 66 constraints: one for the sum and one
+    /// for each of the 65 result bits.
+    pub fn add_with_carry<E, CS>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+        carry: &Boolean,
+    ) -> Result<(Self, Boolean), SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        assert!(E::Fr::NUM_BITS >= 66);
+
+        let result_value = match (self.value, other.value, carry.get_value()) {
+            (Some(a), Some(b), Some(c)) => Some(a as u128 + b as u128 + c as u128),
+            _ => None,
+        };
+
+        // The sum of the operands and the carry in
+        let mut sum = carry.lc(CS::one(), E::Fr::one());
+        let mut all_constants = carry.is_constant();
+        for op in [self, other].iter() {
+            let mut coeff = E::Fr::one();
+            for bit in &op.bits {
+                sum = sum + &bit.lc(CS::one(), coeff);
+
+                all_constants &= bit.is_constant();
+
+                coeff.double();
+            }
+        }
+
+        if let Some(v) = result_value.filter(|_| all_constants) {
+            return Ok((UInt64::constant(v as u64), Boolean::constant(v >> 64 == 1)));
+        }
+
+        // The sum fits in 65 bits, the last of which is the carry out
+        let mut result_bits = vec![];
+        let mut result_lc = LinearCombination::zero();
+        let mut coeff = E::Fr::one();
+        for i in 0..65 {
+            let b = AllocatedBit::alloc(
+                cs.namespace(|| format!("result bit {}", i)),
+                result_value.map(|v| (v >> i) & 1 == 1),
+            )?;
+
+            result_lc = result_lc + (coeff, b.get_variable());
+
+            result_bits.push(Boolean::from(b));
+
+            coeff.double();
+        }
+
+        cs.enforce(
+            || "sum",
+            |lc| lc + CS::one(),
+            |lc| lc + &sum,
+            |lc| lc + &result_lc,
+        );
+
+        let carry_out = result_bits.pop().unwrap();
+
+        Ok((
+            UInt64 {
+                bits: result_bits,
+                value: result_value.map(|v| v as u64),
+            },
+            carry_out,
+        ))
+    }
+
+    /// Perform modular addition of several `UInt64` objects.
+    pub fn addmany<E, CS, M>(mut cs: M, operands: &[Self]) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+        M: ConstraintSystem<E, Root = MultiEq<E, CS>>,
+    {
+        // Make some arbitrary bounds for ourselves to avoid overflows
+        // in the scalar field
+        assert!(E::Fr::NUM_BITS >= 128);
+        assert!(operands.len() >= 2); // Weird trivial cases that should never happen
+        assert!(operands.len() <= 10);
+
+        // Compute the maximum value of the sum so we allocate enough bits for
+        // the result
+        let mut max_value = (operands.len() as u128) * (u64::max_value() as u128);
+
+        // Keep track of the resulting value
+        let mut result_value = Some(0u128);
+
+        // This is a linear combination that we will enforce to equal the
+        // output
+        let mut lc = LinearCombination::zero();
+
+        let mut all_constants = true;
+
+        // Iterate over the operands
+        for op in operands {
+            // Accumulate the value
+            match op.value {
+                Some(val) => {
+                    result_value.as_mut().map(|v| *v += val as u128);
+                }
+                None => {
+                    // If any of our operands have unknown value, we won't
+                    // know the value of the result
+                    result_value = None;
+                }
+            }
+
+            // Iterate over each bit of the operand and add the operand to
+            // the linear combination
+            let mut coeff = E::Fr::one();
+            for bit in &op.bits {
+                lc = lc + &bit.lc(CS::one(), coeff);
+
+                all_constants &= bit.is_constant();
+
+                coeff.double();
+            }
+        }
+
+        // The value of the actual result is modulo 2^64
+        let modular_value = result_value.map(|v| v as u64);
+
+        if all_constants && modular_value.is_some() {
+            // We can just return a constant, rather than
+            // unpacking the result into allocated bits.
+
+            return Ok(UInt64::constant(modular_value.unwrap()));
+        }
+
+        // Storage area for the resulting bits
+        let mut result_bits = vec![];
+
+        // Linear combination representing the output,
+        // for comparison with the sum of the operands
+        let mut result_lc = LinearCombination::zero();
+
+        // Allocate each bit of the result
+        let mut coeff = E::Fr::one();
+        let mut i = 0;
+        while max_value != 0 {
+            // Allocate the bit
+            let b = AllocatedBit::alloc(
+                cs.namespace(|| format!("result bit {}", i)),
+                result_value.map(|v| (v >> i) & 1 == 1),
+            )?;
+
+            // Add this bit to the result combination
+            result_lc = result_lc + (coeff, b.get_variable());
+
+            result_bits.push(b.into());
+
+            max_value >>= 1;
+            i += 1;
+            coeff.double();
+        }
+
+        // Enforce equality between the sum and result
+        cs.get_root().enforce_equal(i, &lc, &result_lc);
+
+        // Discard carry bits that we don't care about
+        result_bits.truncate(64);
+
+        Ok(UInt64 {
+            bits: result_bits,
+            value: modular_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UInt64;
+    use crate::gadgets::boolean::Boolean;
+    use crate::gadgets::multieq::MultiEq;
+    use crate::gadgets::test::*;
+    use crate::ConstraintSystem;
+    use ff::Field;
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_uint64_from_bits_be() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let v = rng.next_u64();
+            let b = UInt64::constant(v);
+            let bits = b.into_bits_be();
+
+            assert_eq!(UInt64::from_bits_be(&bits).value, Some(v));
+            assert_eq!(UInt64::from_bits(&b.into_bits()).value, Some(v));
+        }
+    }
+
+    #[test]
+    fn test_uint64_addmany() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            // Ten operands near the top of the range carry well past 64 bits.
+            let values: Vec<u64> = (0..10).map(|_| rng.next_u64() | (1 << 63)).collect();
+            let mut expected = values.iter().fold(0u64, |acc, v| acc.wrapping_add(*v));
+
+            let operands: Vec<UInt64> = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    if i % 2 == 0 {
+                        UInt64::alloc(cs.namespace(|| format!("operand {}", i)), Some(*v)).unwrap()
+                    } else {
+                        UInt64::constant(*v)
+                    }
+                })
+                .collect();
+
+            let r = {
+                let mut cs = MultiEq::new(&mut cs);
+                UInt64::addmany(cs.namespace(|| "addition"), &operands).unwrap()
+            };
+
+            assert!(cs.is_satisfied());
+            assert!(r.value == Some(expected));
+
+            for b in r.bits.iter() {
+                assert_eq!(b.get_value().unwrap(), expected & 1 == 1);
+                expected >>= 1;
+            }
+
+            // Flip a bit and see if the addition constraint still works
+            if cs.get("addition/result bit 0/boolean").is_zero() {
+                cs.set("addition/result bit 0/boolean", Field::one());
+            } else {
+                cs.set("addition/result bit 0/boolean", Field::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_uint64_rotr_shr() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..20 {
+            let num = rng.next_u64();
+            let a = UInt64::constant(num);
+
+            for i in 0..64 {
+                let expected = [num.rotate_right(i as u32), num.wrapping_shr(i as u32)];
+                for (b, expected) in [a.rotr(i), a.shr(i)].iter().zip(expected.iter()) {
+                    assert_eq!(b.value, Some(*expected));
+                    assert_eq!(b.bits.len(), 64);
+                    for (j, bit) in b.bits.iter().enumerate() {
+                        assert_eq!(bit.get_value().unwrap(), (expected >> j) & 1 == 1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint64_rotl_shl() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..20 {
+            let num = rng.next_u64();
+            let a = UInt64::constant(num);
+
+            for i in 0..64 {
+                let expected = [num.rotate_left(i as u32), num.wrapping_shl(i as u32)];
+                for (b, expected) in [a.rotl(i), a.shl(i)].iter().zip(expected.iter()) {
+                    assert_eq!(b.value, Some(*expected));
+                    assert_eq!(b.bits.len(), 64);
+                    for (j, bit) in b.bits.iter().enumerate() {
+                        assert_eq!(bit.get_value().unwrap(), (expected >> j) & 1 == 1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint64_and_not() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let (a, b, c) = (rng.next_u64(), rng.next_u64(), rng.next_u64());
+            let a_bit = UInt64::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let c_bit = UInt64::alloc(cs.namespace(|| "c_bit"), Some(c)).unwrap();
+
+            let r = a_bit.and(cs.namespace(|| "first and"), &b_bit).unwrap();
+            let r = r.not().and(cs.namespace(|| "second and"), &c_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let mut expected = !(a & b) & c;
+            assert!(r.value == Some(expected));
+
+            for b in r.bits.iter() {
+                assert_eq!(b.get_value().unwrap(), expected & 1 == 1);
+                expected >>= 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint64_add_with_carry() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            // Two words each make a 128-bit integer.
+            let (a, b) = (
+                ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128,
+                ((rng.next_u64() as u128) << 64) | rng.next_u64() as u128,
+            );
+            let (a_lo, a_hi) = (
+                UInt64::alloc(cs.namespace(|| "a lo"), Some(a as u64)).unwrap(),
+                UInt64::alloc(cs.namespace(|| "a hi"), Some((a >> 64) as u64)).unwrap(),
+            );
+            let (b_lo, b_hi) = (
+                UInt64::constant(b as u64),
+                UInt64::alloc(cs.namespace(|| "b hi"), Some((b >> 64) as u64)).unwrap(),
+            );
+
+            let (lo, carry) = a_lo
+                .add_with_carry(cs.namespace(|| "lo"), &b_lo, &Boolean::constant(false))
+                .unwrap();
+            let (hi, carry) = a_hi.add_with_carry(cs.namespace(|| "hi"), &b_hi, &carry).unwrap();
+
+            assert!(cs.is_satisfied());
+
+            let (expected, overflowed) = a.overflowing_add(b);
+            assert_eq!(lo.value, Some(expected as u64));
+            assert_eq!(hi.value, Some((expected >> 64) as u64));
+            assert_eq!(carry.get_value(), Some(overflowed));
+            for (i, b) in lo.bits.iter().chain(hi.bits.iter()).enumerate() {
+                assert_eq!(b.get_value().unwrap(), (expected >> i) & 1 == 1);
+            }
+
+            assert_eq!(cs.num_constraints() - 3 * 64, 2 * (65 + 1));
+
+            // A wrong carry between the words is caught
+            if cs.get("lo/result bit 64/boolean").is_zero() {
+                cs.set("lo/result bit 64/boolean", Field::one());
+            } else {
+                cs.set("lo/result bit 64/boolean", Field::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+
+        // Constant operands give constant results.
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let (sum, carry) = UInt64::constant(u64::max_value())
+            .add_with_carry(&mut cs, &UInt64::constant(1), &Boolean::constant(true))
+            .unwrap();
+        assert_eq!(cs.num_constraints(), 0);
+        assert_eq!(sum.value, Some(1));
+        assert_eq!(carry.get_value(), Some(true));
+    }
+
+    #[test]
+    fn test_uint64_sha512_maj_ch() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+
+            let (a, b, c) = (rng.next_u64(), rng.next_u64(), rng.next_u64());
+            let a_bit = UInt64::alloc(cs.namespace(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let c_bit = UInt64::alloc(cs.namespace(|| "c_bit"), Some(c)).unwrap();
+
+            let maj = UInt64::sha512_maj(cs.namespace(|| "maj"), &a_bit, &b_bit, &c_bit).unwrap();
+            let ch = UInt64::sha512_ch(cs.namespace(|| "ch"), &a_bit, &b_bit, &c_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(maj.value, Some((a & b) ^ (a & c) ^ (b & c)));
+            assert_eq!(ch.value, Some((a & b) ^ ((!a) & c)));
+            for (i, bit) in ch.bits.iter().enumerate() {
+                assert_eq!(bit.get_value().unwrap(), (((a & b) ^ ((!a) & c)) >> i) & 1 == 1);
+            }
+        }
+
+    }
+}
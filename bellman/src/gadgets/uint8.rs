@@ -0,0 +1,131 @@
+use ff::ScalarEngine;
+
+use crate::{ConstraintSystem, SynthesisError};
+
+use super::boolean::{AllocatedBit, Boolean};
+
+/// Represents an interpretation of 8 `Boolean` objects as an
+/// unsigned integer.
+#[derive(Clone)]
+pub struct UInt8 {
+    // Least significant bit first
+    bits: Vec<Boolean>,
+    value: Option<u8>,
+}
+
+impl UInt8 {
+    /// Construct a constant `UInt8` from a `u8`
+    pub fn constant(value: u8) -> Self {
+        let bits = (0..8)
+            .map(|i| Boolean::constant((value >> i) & 1 == 1))
+            .collect();
+
+        UInt8 {
+            bits: bits,
+            value: Some(value),
+        }
+    }
+
+    /// Construct a constant byte string
+    pub fn constant_vec(values: &[u8]) -> Vec<Self> {
+        values.iter().map(|&v| UInt8::constant(v)).collect()
+    }
+
+    /// Allocate a `UInt8` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<u8>) -> Result<Self, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        let bits = (0..8)
+            .map(|i| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.namespace(|| format!("allocated bit {}", i)),
+                    value.map(|v| (v >> i) & 1 == 1),
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(UInt8 {
+            bits: bits,
+            value: value,
+        })
+    }
+
+    /// Allocate a byte string of `values.len()` bytes in the
+    /// constraint system
+    pub fn alloc_vec<E, CS>(mut cs: CS, values: &[Option<u8>]) -> Result<Vec<Self>, SynthesisError>
+    where
+        E: ScalarEngine,
+        CS: ConstraintSystem<E>,
+    {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| UInt8::alloc(cs.namespace(|| format!("byte {}", i)), v))
+            .collect()
+    }
+
+    pub fn get_value(&self) -> Option<u8> {
+        self.value
+    }
+
+    /// Turns this `UInt8` into its bits, least significant first.
+    pub fn into_bits(&self) -> Vec<Boolean> {
+        self.bits.clone()
+    }
+
+    /// Converts bits, least significant first, into a `UInt8`.
+    pub fn from_bits(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 8);
+
+        let value = bits
+            .iter()
+            .rev()
+            .try_fold(0u8, |acc, b| b.get_value().map(|b| (acc << 1) | b as u8));
+
+        UInt8 {
+            bits: bits.to_vec(),
+            value: value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UInt8;
+    use crate::gadgets::boolean::Boolean;
+    use crate::gadgets::test::*;
+    use crate::ConstraintSystem;
+    use pairing::bls12_381::Bls12;
+    use rand_core::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    #[test]
+    fn test_uint8_from_bits() {
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let v = rng.next_u32() as u8;
+
+            let mut cs = TestConstraintSystem::<Bls12>::new();
+            let b = UInt8::alloc(cs.namespace(|| "b"), Some(v)).unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), 8);
+
+            for (i, bit) in b.into_bits().iter().enumerate() {
+                assert_eq!(bit.get_value(), Some((v >> i) & 1 == 1));
+            }
+            assert_eq!(UInt8::from_bits(&b.into_bits()).get_value(), Some(v));
+            assert_eq!(UInt8::constant(v).get_value(), Some(v));
+
+            let bits: Vec<_> = (0..8)
+                .map(|i| Boolean::constant((v >> i) & 1 == 1).not())
+                .collect();
+            assert_eq!(UInt8::from_bits(&bits).get_value(), Some(!v));
+        }
+    }
+}
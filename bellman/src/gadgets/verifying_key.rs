@@ -0,0 +1,117 @@
+use ff::{Field, PrimeField};
+use pairing::Engine;
+
+use crate::{ConstraintSystem, SynthesisError};
+use crate::groth16::{VerifyingKey, VERIFYING_KEY_PERSONALIZATION};
+
+use super::blake2s::blake2s;
+use super::boolean::{AllocatedBit, Boolean};
+use super::num::{AllocatedNum, Num};
+
+/// Witnesses the canonical encoding of `vk` as little-endian bits per byte,
+/// matching the input expected by `hash_verifying_key`. The key must have
+/// `num_ic` elements in `ic`, which fixes the shape of the circuit.
+pub fn witness_verifying_key<E, CS>(
+    mut cs: CS,
+    vk: Option<&VerifyingKey<E>>,
+    num_ic: usize,
+) -> Result<Vec<Boolean>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let len = VerifyingKey::<E>::encoded_len(num_ic);
+
+    let bytes: Option<Vec<u8>> = match vk {
+        Some(vk) => {
+            if vk.ic.len() != num_ic {
+                return Err(SynthesisError::MalformedVerifyingKey);
+            }
+            let mut bytes = Vec::with_capacity(len);
+            vk.write(&mut bytes)?;
+            Some(bytes)
+        }
+        None => None,
+    };
+
+    let mut bits = Vec::with_capacity(len * 8);
+    for i in 0..len {
+        for j in 0..8 {
+            let value = bytes.as_ref().map(|bytes| (bytes[i] >> j) & 1 == 1);
+            let bit = AllocatedBit::alloc(cs.namespace(|| format!("bit {} of byte {}", j, i)), value)?;
+            bits.push(Boolean::from(bit));
+        }
+    }
+
+    Ok(bits)
+}
+
+/// Hashes the canonical encoding of a verifying key into a field element,
+/// matching `VerifyingKey::hash`.
+pub fn hash_verifying_key<E, CS>(
+    mut cs: CS,
+    vk_bits: &[Boolean],
+) -> Result<AllocatedNum<E>, SynthesisError>
+where
+    E: Engine,
+    CS: ConstraintSystem<E>,
+{
+    let digest = blake2s(cs.namespace(|| "hash"), vk_bits, VERIFYING_KEY_PERSONALIZATION)?;
+
+    let mut num = Num::<E>::zero();
+    let mut coeff = E::Fr::one();
+    for bit in digest.iter().take(E::Fr::CAPACITY as usize) {
+        num = num.add_bool_with_coeff(CS::one(), bit, coeff);
+
+        coeff.double();
+    }
+
+    let packed = AllocatedNum::alloc(cs.namespace(|| "packed"), || {
+        Ok(num.get_value().ok_or(SynthesisError::AssignmentMissing)?)
+    })?;
+
+    // num * 1 = packed
+    cs.enforce_equal(
+        || "packing constraint",
+        |_| num.lc(E::Fr::one()),
+        |lc| lc + packed.get_variable(),
+    );
+
+    Ok(packed)
+}
+
+#[cfg(test)]
+mod test {
+    use pairing::bls12_381::Bls12;
+    use pairing::Engine;
+    use rand::rngs::StdRng;
+
+    use super::{hash_verifying_key, witness_verifying_key};
+    use crate::gadgets::test::*;
+    use crate::groth16::{generate_random_parameters, ProverRng};
+    use crate::{Circuit, ConstraintSystem, SynthesisError};
+
+    struct Empty;
+
+    impl<E: Engine> Circuit<E> for Empty {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            let x = cs.alloc_input(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+            cs.enforce_zero(|| "x = 0", |lc| lc + x);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hash_verifying_key() {
+        let rng = &mut ProverRng::<StdRng>::from_seed([3; 32]);
+        let params = generate_random_parameters::<Bls12, _, _>(Empty, rng).unwrap();
+        let vk = params.vk;
+
+        let mut cs = TestConstraintSystem::<Bls12>::new();
+        let bits = witness_verifying_key(cs.namespace(|| "vk"), Some(&vk), vk.ic.len()).unwrap();
+        let hash = hash_verifying_key(cs.namespace(|| "hash vk"), &bits).unwrap();
+
+        assert!(cs.is_satisfied());
+        assert_eq!(hash.get_value().unwrap(), vk.hash());
+    }
+}
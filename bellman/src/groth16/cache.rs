@@ -0,0 +1,213 @@
+use ff::{PrimeField, PrimeFieldRepr};
+use pairing::Engine;
+
+use blake2s_simd::Params as Blake2sParams;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use super::{prepare_verifying_key, verify_proof, PreparedVerifyingKey, Proof, Result, VerifyingKey};
+
+/// BLAKE2s personalization used when hashing the public inputs of a proof.
+pub const CACHE_INPUTS_PERSONALIZATION: &[u8; 8] = b"G16Input";
+
+/// BLAKE2s personalization used when hashing a proof.
+pub const CACHE_PROOF_PERSONALIZATION: &[u8; 8] = b"G16Proof";
+
+/// Identifies a verification by the hashes of its inputs and proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    inputs: [u8; 32],
+    proof: [u8; 32],
+}
+
+/// Counters describing how a `VerificationCache` has been used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheMetrics {
+    /// Verifications answered from the cache.
+    pub hits: u64,
+    /// Verifications which ran the pairing check.
+    pub misses: u64,
+    /// Results dropped to stay within the capacity.
+    pub evictions: u64,
+}
+
+impl CacheMetrics {
+    /// The fraction of verifications answered from the cache, or zero
+    /// before any have been made.
+    pub fn hit_rate(&self) -> f64 {
+        let total: u64 = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.hits as f64 / total as f64
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Each result, with the tick at which it was last used.
+    results: HashMap<CacheKey, (bool, u64)>,
+    /// The keys of `results` by the tick at which they were last used.
+    recency: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    metrics: CacheMetrics,
+}
+
+impl CacheState {
+    fn get(&mut self, key: &CacheKey) -> Option<bool> {
+        self.tick += 1;
+        let tick: u64 = self.tick;
+
+        let (valid, last_used): (bool, u64) = {
+            let entry: &mut (bool, u64) = self.results.get_mut(key)?;
+            let last_used: u64 = entry.1;
+            entry.1 = tick;
+            (entry.0, last_used)
+        };
+        self.recency.remove(&last_used);
+        self.recency.insert(tick, *key);
+
+        Some(valid)
+    }
+
+    fn insert(&mut self, key: CacheKey, valid: bool, capacity: usize) {
+        self.tick += 1;
+        let tick: u64 = self.tick;
+
+        if let Some((_, last_used)) = self.results.insert(key, (valid, tick)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(tick, key);
+
+        while self.results.len() > capacity {
+            let oldest: u64 = *self.recency.keys().next().expect("the cache is not empty");
+            let key: CacheKey = self.recency.remove(&oldest).expect("the tick was just found");
+            self.results.remove(&key);
+            self.metrics.evictions += 1;
+        }
+    }
+}
+
+/// A bounded, least-recently-used memo of verification results, for
+/// long-lived verifiers which see the same proofs submitted repeatedly.
+///
+/// A cache verifies against the single key it is created with, which it
+/// prepares itself, so that a result can never be answered for a key other
+/// than the one it was checked against. Results are keyed by BLAKE2s hashes
+/// of the public inputs and of the compressed proof, so a retried
+/// submission is answered without any pairing work. Both valid and invalid
+/// results are remembered; errors, such as inputs which do not match the
+/// key, are not. The cache may be shared between threads.
+pub struct VerificationCache<E: Engine> {
+    pvk: PreparedVerifyingKey<E>,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl<E> VerificationCache<E>
+where
+    E: Engine
+{
+    /// Creates a cache of results verified against `vk`, holding at most
+    /// `capacity` of them.
+    pub fn new(vk: &VerifyingKey<E>, capacity: usize) -> Self {
+        assert!(capacity > 0, "a verification cache must hold at least one result");
+
+        VerificationCache {
+            pvk: prepare_verifying_key(vk),
+            capacity: capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// `verify_proof` against the key of this cache, answered from the
+    /// cache when this proof and these inputs have been verified before.
+    pub fn verify_proof(&self, proof: &Proof<E>, public_inputs: &[E::Fr]) -> Result<bool> {
+        let key: CacheKey = CacheKey {
+            inputs: hash_inputs::<E>(public_inputs),
+            proof: hash_proof(proof),
+        };
+
+        {
+            let mut state = self.state.lock().expect("the cache lock is not poisoned");
+            if let Some(valid) = state.get(&key) {
+                state.metrics.hits += 1;
+                return Ok(valid);
+            }
+        }
+
+        // The lock is not held while verifying, so that distinct proofs
+        // are verified concurrently. Identical proofs arriving together may
+        // both be verified, which is harmless.
+        let valid: bool = verify_proof(&self.pvk, proof, public_inputs)?;
+
+        let mut state = self.state.lock().expect("the cache lock is not poisoned");
+        state.metrics.misses += 1;
+        state.insert(key, valid, self.capacity);
+
+        Ok(valid)
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        self.state.lock().expect("the cache lock is not poisoned").metrics
+    }
+
+    /// The number of results held.
+    pub fn len(&self) -> usize {
+        self.state.lock().expect("the cache lock is not poisoned").results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Forgets every result, keeping the metrics.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("the cache lock is not poisoned");
+        state.results.clear();
+        state.recency.clear();
+    }
+}
+
+fn hash_inputs<E>(public_inputs: &[E::Fr]) -> [u8; 32]
+where
+    E: Engine
+{
+    let mut encoded: Vec<u8> = Vec::with_capacity(8 + 32 * public_inputs.len());
+    encoded.extend_from_slice(&(public_inputs.len() as u64).to_le_bytes());
+    for input in public_inputs {
+        input.into_repr()
+            .write_le(&mut encoded)
+            .expect("writing to a vector does not fail");
+    }
+
+    digest(CACHE_INPUTS_PERSONALIZATION, &encoded)
+}
+
+fn hash_proof<E>(proof: &Proof<E>) -> [u8; 32]
+where
+    E: Engine
+{
+    let mut encoded: Vec<u8> = vec![];
+    proof.write(&mut encoded)
+        .expect("writing to a vector does not fail");
+
+    digest(CACHE_PROOF_PERSONALIZATION, &encoded)
+}
+
+fn digest(personalization: &[u8; 8], data: &[u8]) -> [u8; 32] {
+    let hash: _ = Blake2sParams::new()
+        .hash_length(32)
+        .personal(personalization)
+        .hash(data);
+
+    let mut digest: [u8; 32] = [0; 32];
+    digest.copy_from_slice(hash.as_bytes());
+    digest
+}
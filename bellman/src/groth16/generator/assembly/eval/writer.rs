@@ -8,7 +8,7 @@ use domain::Scalar;
 use super::super::{key_pair, windows, parameters};
 use parameters::Elements;
 use key_pair::{KeyPairWires, FlatKeyPairWires};
-use windows::BasedWindows;
+use windows::Windows;
 
 pub struct Writer<'a, E: Engine> {
     a: &'a mut [E::G1],
@@ -37,7 +37,7 @@ where
         self.a.len() == self.ext.len()
     }
 
-    pub fn eval(self, wnaf: &BasedWindows<'_,E>, coeffs: &[Scalar<E>], qap: KeyPairWires<E>, inverse_coeff: &E::Fr, trapdoors: &Elements<E>) {
+    pub fn eval(self, wnaf: &Windows<E>, coeffs: &[Scalar<E>], qap: KeyPairWires<E>, inverse_coeff: &E::Fr, trapdoors: &Elements<E>) {
 
         let coeff_len: usize = self.a.len();
         let mut flat_writer: FlatWriter<E> = self.flatten();
@@ -46,9 +46,6 @@ where
         multi_thread!(coeff_len, iter(flat_writer, flat_poly) => {
             for ((a, b_g1, b_g2, ext), (at, bt, ct)) in writer, poly => {
 
-                let mut g1_wnaf = wnaf.g1.shared();
-                let mut g2_wnaf = wnaf.g2.shared();
-
                 // Evaluate QAP polynomials at tau
                 let mut at = eval_at_tau(coeffs, at);
                 let mut bt = eval_at_tau(coeffs, bt);
@@ -56,14 +53,14 @@ where
 
                 // Compute A query (in G1)
                 if !at.is_zero() {
-                    **a = g1_wnaf.scalar(at.into_repr());
+                    **a = wnaf.g1.mul(at.into_repr());
                 }
 
                 // Compute B query (in G1/G2)
                 if !bt.is_zero() {
                     let bt_repr = bt.into_repr();
-                    **b_g1 = g1_wnaf.scalar(bt_repr);
-                    **b_g2 = g2_wnaf.scalar(bt_repr);
+                    **b_g1 = wnaf.g1.mul(bt_repr);
+                    **b_g2 = wnaf.g2.mul(bt_repr);
                 }
 
                 at.mul_assign(&trapdoors.beta);
@@ -74,7 +71,7 @@ where
                 e.add_assign(&ct);
                 e.mul_assign(inverse_coeff);
 
-                **ext = g1_wnaf.scalar(e.into_repr());
+                **ext = wnaf.g1.mul(e.into_repr());
             }
         });
 
@@ -89,11 +86,21 @@ fn eval_at_tau<E>(powers_of_tau: &[Scalar<E>], wires: &[(E::Fr, usize)]) -> E::F
 where
     E: Engine
 {
-    wires.iter()
-        .fold(E::Fr::zero(), |mut acc, (coeff, idx)| {
-            let Scalar(mut exp): Scalar<E> = powers_of_tau[*idx];
-            exp.mul_assign(coeff);
-            acc.add_assign(&exp);
+    // Multiplied four at a time so fields with a vectorised
+    // `Field::batch_mul_assign` can use it.
+    let mut exps: [E::Fr; 4] = [E::Fr::zero(); 4];
+    let mut coeffs: [E::Fr; 4] = [E::Fr::zero(); 4];
+    wires.chunks(4)
+        .fold(E::Fr::zero(), |mut acc, chunk| {
+            for ((exp, coeff), (wire_coeff, idx)) in exps.iter_mut().zip(coeffs.iter_mut()).zip(chunk) {
+                *exp = powers_of_tau[*idx].0;
+                *coeff = *wire_coeff;
+            }
+
+            E::Fr::batch_mul_assign(&mut exps[..chunk.len()], &coeffs[..chunk.len()]);
+            for exp in exps[..chunk.len()].iter() {
+                acc.add_assign(exp);
+            }
             acc
         })
 }
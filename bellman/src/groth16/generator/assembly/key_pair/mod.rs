@@ -1,26 +1,41 @@
 use ff::Field;
 use pairing::Engine;
 
-use crate::{ConstraintSystem, Circuit, Index, LinearCombination, Coefficient};
+use crate::{ConstraintSystem, Circuit, Index, LinearCombination, Coefficient, PublicInputLayout};
 use crate::{domain, error};
 use domain::{Domain, Scalar};
 use error::Result;
 
+use super::super::ConstraintSymbols;
+
 pub mod wires;
 pub use wires::*;
 
+mod r1cs;
+
 /// This is our assembly structure that we'll use to synthesize the
 /// circuit into a QAP.
 pub struct KeyPairAssembly<E: Engine> {
     pub num: KeyPairNum,
     pub inputs: KeyPairWires<E>,
-    pub aux: KeyPairWires<E>
+    pub aux: KeyPairWires<E>,
+    pub symbols: Option<ConstraintSymbols>,
+    pub layout: PublicInputLayout,
+    namespace: Vec<String>
 }
 
 impl<E> KeyPairAssembly<E>
 where
     E: Engine
 {
+    /// An assembly which records the namespace path of every constraint.
+    pub fn with_symbols() -> Self {
+        KeyPairAssembly {
+            symbols: Some(ConstraintSymbols::new()),
+            ..Self::default()
+        }
+    }
+
     pub fn allocate_input_one(&mut self) -> Result<()> {
         self.alloc_input(
             || "", 
@@ -98,7 +113,30 @@ where
         Ok(Coefficient::new_unchecked(Index::Input(index)))
     }
 
-    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        N: Into<String>,
+    {
+        let name: String = name.into();
+        let var: Coefficient = self.alloc_input(|| name.clone(), f)?;
+
+        // Inputs are named by their full namespace path, so gadgets used
+        // more than once under different namespaces do not collide. The
+        // verifier's input vector does not include the "one" input.
+        let path: String = self.namespace
+            .iter()
+            .chain(Some(&name))
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        if let Index::Input(index) = var.get_unchecked() {
+            self.layout.insert(path, index - 1)?;
+        }
+        Ok(var)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
         AR: Into<String>,
@@ -107,51 +145,55 @@ where
         LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
     {
         fn eval<E: Engine>(
-            l: LinearCombination<E>,
+            l: &LinearCombination<E>,
             inputs: &mut [Vec<(E::Fr, usize)>],
             aux: &mut [Vec<(E::Fr, usize)>],
             this_constraint: usize,
         ) {
-            for (index, coeff) in l.0 {
+            // Constant terms are folded into a single term on the "one"
+            // input, so repeated constants cost nothing during evaluation
+            // and constants which cancel out leave no term at all.
+            let mut constant: E::Fr = E::Fr::zero();
+            for (index, coeff) in l.as_ref() {
                 match index.get_unchecked() {
-                    Index::Input(id) => inputs[id].push((coeff, this_constraint)),
-                    Index::Aux(id) => aux[id].push((coeff, this_constraint)),
+                    Index::Input(0) => constant.add_assign(coeff),
+                    Index::Input(id) => inputs[id].push((*coeff, this_constraint)),
+                    Index::Aux(id) => aux[id].push((*coeff, this_constraint)),
                 }
             }
+            if !constant.is_zero() {
+                inputs[0].push((constant, this_constraint));
+            }
         }
 
-        eval(
-            a(LinearCombination::zero()),
-            &mut self.inputs.at,
-            &mut self.aux.at,
-            self.num.constraints,
-        );
-        eval(
-            b(LinearCombination::zero()),
-            &mut self.inputs.bt,
-            &mut self.aux.bt,
-            self.num.constraints,
-        );
-        eval(
-            c(LinearCombination::zero()),
-            &mut self.inputs.ct,
-            &mut self.aux.ct,
-            self.num.constraints,
-        );
+        eval(&a(LinearCombination::zero()), &mut self.inputs.at, &mut self.aux.at, self.num.constraints);
+        eval(&b(LinearCombination::zero()), &mut self.inputs.bt, &mut self.aux.bt, self.num.constraints);
+        eval(&c(LinearCombination::zero()), &mut self.inputs.ct, &mut self.aux.ct, self.num.constraints);
+
+        if let Some(symbols) = self.symbols.as_mut() {
+            symbols.record(annotation().into());
+        }
 
         self.num.constraints += 1;
     }
 
-    fn push_namespace<NR, N>(&mut self, _: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about namespaces in this context.
+        let name: String = name_fn().into();
+        if let Some(symbols) = self.symbols.as_mut() {
+            symbols.push_namespace(name.clone());
+        }
+        self.namespace.push(name);
     }
 
     fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
+        if let Some(symbols) = self.symbols.as_mut() {
+            symbols.pop_namespace();
+        }
+        self.namespace.pop();
     }
 
     fn get_root(&mut self) -> &mut Self::Root {
@@ -167,7 +209,10 @@ where
         KeyPairAssembly {
             num: KeyPairNum::default(),
             inputs: KeyPairWires::default(),
-            aux: KeyPairWires::default()
+            aux: KeyPairWires::default(),
+            symbols: None,
+            layout: PublicInputLayout::new(),
+            namespace: Vec::new()
         }
     }
 }
@@ -0,0 +1,132 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use pairing::Engine;
+
+use std::io::{self, Write};
+
+use super::KeyPairAssembly;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const R1CS_VERSION: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINT_SECTION: u32 = 2;
+const WIRE_TO_LABEL_SECTION: u32 = 3;
+
+type Row<E> = Vec<(u32, <E as ff::ScalarEngine>::Fr)>;
+
+impl<E> KeyPairAssembly<E>
+where
+    E: Engine
+{
+    /// Writes the synthesized constraint system in the binary `.r1cs` format
+    /// read by the circom/snarkjs toolchain. Wire `0` is the constant one,
+    /// followed by the remaining public inputs and then the auxiliary variables.
+    pub fn write_r1cs<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let header: Vec<u8> = self.r1cs_header()?;
+        let constraints: Vec<u8> = self.r1cs_constraints()?;
+        let labels: Vec<u8> = self.r1cs_wire_labels()?;
+
+        writer.write_all(R1CS_MAGIC)?;
+        writer.write_u32::<LittleEndian>(R1CS_VERSION)?;
+        writer.write_u32::<LittleEndian>(3)?;
+
+        for (kind, section) in [
+            (HEADER_SECTION, header),
+            (CONSTRAINT_SECTION, constraints),
+            (WIRE_TO_LABEL_SECTION, labels)
+        ].iter() {
+            writer.write_u32::<LittleEndian>(*kind)?;
+            writer.write_u64::<LittleEndian>(section.len() as u64)?;
+            writer.write_all(section)?;
+        }
+
+        Ok(())
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num.inputs + self.num.aux
+    }
+
+    fn field_size() -> usize {
+        E::Fr::char().as_ref().len() * 8
+    }
+
+    fn r1cs_header(&self) -> io::Result<Vec<u8>> {
+        let mut buf: Vec<u8> = Vec::new();
+
+        buf.write_u32::<LittleEndian>(Self::field_size() as u32)?;
+        E::Fr::char().write_le(&mut buf)?;
+        buf.write_u32::<LittleEndian>(self.num_wires() as u32)?;
+
+        // Public outputs are not distinguished from public inputs, and every
+        // auxiliary variable is treated as an internal signal.
+        buf.write_u32::<LittleEndian>(0)?;
+        buf.write_u32::<LittleEndian>(self.num.inputs.saturating_sub(1) as u32)?;
+        buf.write_u32::<LittleEndian>(0)?;
+
+        buf.write_u64::<LittleEndian>(self.num_wires() as u64)?;
+        buf.write_u32::<LittleEndian>(self.num.constraints as u32)?;
+
+        Ok(buf)
+    }
+
+    fn r1cs_constraints(&self) -> io::Result<Vec<u8>> {
+        let a: Vec<Row<E>> = self.rows(&self.inputs.at, &self.aux.at);
+        let b: Vec<Row<E>> = self.rows(&self.inputs.bt, &self.aux.bt);
+        let c: Vec<Row<E>> = self.rows(&self.inputs.ct, &self.aux.ct);
+
+        let mut buf: Vec<u8> = Vec::new();
+        for ((a, b), c) in a.iter()
+            .zip(b.iter())
+            .zip(c.iter())
+        {
+            write_row::<E>(&mut buf, a)?;
+            write_row::<E>(&mut buf, b)?;
+            write_row::<E>(&mut buf, c)?;
+        }
+
+        Ok(buf)
+    }
+
+    fn r1cs_wire_labels(&self) -> io::Result<Vec<u8>> {
+        let mut buf: Vec<u8> = Vec::new();
+        for label in 0..self.num_wires() {
+            buf.write_u64::<LittleEndian>(label as u64)?;
+        }
+        Ok(buf)
+    }
+
+    // The assembly stores each matrix column-wise (per variable), whereas the
+    // `.r1cs` format is written row-wise (per constraint) with each wire
+    // appearing at most once, so repeated terms are folded together.
+    fn rows(&self, inputs: &[Vec<(E::Fr, usize)>], aux: &[Vec<(E::Fr, usize)>]) -> Vec<Row<E>> {
+        let mut rows: Vec<Row<E>> = vec![Vec::new(); self.num.constraints];
+
+        let columns: _ = inputs.iter()
+            .chain(aux.iter())
+            .enumerate();
+        for (wire, column) in columns {
+            for (coeff, constraint) in column.iter() {
+                let row: &mut Row<E> = &mut rows[*constraint];
+                match row.last_mut() {
+                    Some((last, acc)) if *last == wire as u32 => acc.add_assign(coeff),
+                    _ => row.push((wire as u32, *coeff))
+                }
+            }
+        }
+        rows
+    }
+}
+
+fn write_row<E>(buf: &mut Vec<u8>, row: &Row<E>) -> io::Result<()>
+where
+    E: Engine
+{
+    buf.write_u32::<LittleEndian>(row.len() as u32)?;
+    for (wire, coeff) in row.iter() {
+        buf.write_u32::<LittleEndian>(*wire)?;
+        coeff.into_repr().write_le(&mut *buf)?;
+    }
+    Ok(())
+}
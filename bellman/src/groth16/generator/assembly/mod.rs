@@ -4,15 +4,12 @@ mod eval;
 mod windows;
 
 use pairing::Engine;
-use group::Wnaf;
-
 use crate::{domain, Circuit};
-use domain::Scalar;
+use domain::{Scalar, FixedBaseMultiexp};
 
 use parameters::ParameterAssembly;
-use key_pair::KeyPairAssembly;
+pub use key_pair::KeyPairAssembly;
 use eval::Evaluation;
-use windows::BasedWindows;
 
 use super::*;
 
@@ -34,9 +31,9 @@ where
     E: Engine,
     C: Circuit<E>
 {
-    pub fn new(circuit: C, g1: E::G1, g2: E::G2, alpha: E::Fr, beta: E::Fr, gamma: E::Fr, delta: E::Fr, tau: E::Fr) -> Result<Self> {
+    pub fn new(circuit: C, g1: E::G1, g2: E::G2, alpha: E::Fr, beta: E::Fr, gamma: E::Fr, delta: E::Fr, tau: E::Fr, capture_symbols: bool) -> Result<Self> {
         let mut param: _ = ParameterAssembly::new(circuit, g1, g2, alpha, beta, gamma, delta, tau)?;
-        let key_pair: KeyPairAssembly<E> = param.key_assembly()?;
+        let key_pair: KeyPairAssembly<E> = param.key_assembly(capture_symbols)?;
         let result: _ = Evaluation::new(&key_pair);
 
         Ok(Self { 
@@ -52,13 +49,28 @@ where
             .blind_evaluation_base()
     }
 
-    pub fn h(&mut self, domain: &mut Domain<E, Scalar<E>>, based_g1: &Wnaf<usize, &[E::G1], &mut Vec<i64>>) -> Result<Vec<E::G1Affine>> {
+    pub fn take_symbols(&mut self) -> Result<Option<ConstraintSymbols>> {
+        let symbols: _ = self.key_pair
+            .as_mut()?
+            .symbols
+            .take();
+        Ok(symbols)
+    }
+
+    pub fn take_layout(&mut self) -> Result<PublicInputLayout> {
+        let layout: _ = std::mem::take(&mut self.key_pair
+            .as_mut()?
+            .layout);
+        Ok(layout)
+    }
+
+    pub fn h(&mut self, domain: &mut Domain<E, Scalar<E>>, based_g1: &FixedBaseMultiexp<E::G1>) -> Result<Vec<E::G1Affine>> {
         self.param
             .as_mut()?
             .h(domain, based_g1)
     }
 
-    pub fn evaluate(&mut self, win: &BasedWindows<'_,E>, coeffs: &[Scalar<E>]) -> Result<()> {
+    pub fn evaluate(&mut self, win: &Windows<E>, coeffs: &[Scalar<E>]) -> Result<()> {
         self.param
             .as_mut()?
             .evaluate(
@@ -1,9 +1,9 @@
 use pairing::Engine;
 use ff::{PrimeField, Field};
-use group::{CurveProjective, Wnaf};
+use group::CurveProjective;
 
-use crate::{multi_thread, domain, error};
-use domain::{Scalar, Domain};
+use crate::{arith, multi_thread, domain, error};
+use domain::{Scalar, Domain, FixedBaseMultiexp};
 use error::{SynthesisError, Result};
 
 pub struct ParameterGroups<E>
@@ -58,12 +58,11 @@ where
     }
 
     // Set values of the H query to g1^{(tau^i * t(tau)) / delta}
-    pub fn map_exponent_of_tau(&self, h: &mut Vec<E::G1>, domain: &[Scalar<E>],  based_g1: &Wnaf<usize, &[E::G1], &mut Vec<i64>>) {
+    pub fn map_exponent_of_tau(&self, h: &mut Vec<E::G1>, domain: &[Scalar<E>],  based_g1: &FixedBaseMultiexp<E::G1>) {
         multi_thread!(h.len(), iter(h, domain) => {
             for (value, power) in h_iter, domain_iter => {
-                let mut g1_wnaf: _ = based_g1.shared();
                 let exponent: _ = self.exponentiate_tau(power).into_repr();
-                *value = g1_wnaf.scalar(exponent);
+                *value = based_g1.mul(exponent);
             }
         });
         E::G1::batch_normalization(h);
@@ -89,12 +88,12 @@ where
     E: Engine
 {
     pub fn new(delta: &E::Fr, gamma: &E::Fr) -> Result<Self> {
-        let gamma_inv: E::Fr = gamma.inverse().ok_or(SynthesisError::UnexpectedIdentity)?;
-        let delta_inv: E::Fr = delta.inverse().ok_or(SynthesisError::UnexpectedIdentity)?; 
+        let mut inverses: [E::Fr; 2] = [*delta, *gamma];
+        arith::batch_inverse::<E>(&mut inverses).map_err(|_| SynthesisError::UnexpectedIdentity)?;
 
         Ok(Self { 
-            delta: delta_inv, 
-            gamma: gamma_inv 
+            delta: inverses[0], 
+            gamma: inverses[1] 
         })
     }
 }
\ No newline at end of file
@@ -1,16 +1,16 @@
 use pairing::Engine;
-use group::{Wnaf, CurveProjective, CurveAffine};
+use group::{CurveProjective, CurveAffine};
 
 use crate::{error, domain, groth16};
 use crate::Circuit;
 use error::{SynthesisError, Result};
-use domain::{Domain, Scalar};
+use domain::{Domain, Scalar, FixedBaseMultiexp};
 use groth16::VerifyingKey;
 
 use super::{eval, key_pair, windows};
 use eval::{Evaluation, Writer};
 use key_pair::{KeyPairWires, KeyPairAssembly};
-use windows::BasedWindows;
+use windows::Windows;
 
 mod elements;
 pub use elements::{Elements, InverseElements, ParameterGroups};
@@ -43,8 +43,12 @@ where
         })
     }
 
-    pub fn key_assembly(&mut self) -> Result<KeyPairAssembly<E>> {
-        let mut key_assembly: _ = KeyPairAssembly::default();
+    pub fn key_assembly(&mut self, capture_symbols: bool) -> Result<KeyPairAssembly<E>> {
+        let mut key_assembly: _ = if capture_symbols {
+            KeyPairAssembly::with_symbols()
+        } else {
+            KeyPairAssembly::default()
+        };
 
         key_assembly.allocate_input_one()?;
         key_assembly.synthesize_circuit(self.circuit.take()?)?;
@@ -53,7 +57,7 @@ where
         Ok(key_assembly)
     }
 
-    pub fn h(&mut self, domain: &mut Domain<E, Scalar<E>>, based_g1: &Wnaf<usize, &[E::G1], &mut Vec<i64>>) -> Result<Vec<E::G1Affine>> {  
+    pub fn h(&mut self, domain: &mut Domain<E, Scalar<E>>, based_g1: &FixedBaseMultiexp<E::G1>) -> Result<Vec<E::G1Affine>> {  
         let mut h: Vec<E::G1> = vec![E::G1::zero(); domain.as_ref().len() - 1];
 
         self.elements.map_powers_of_tau(domain.as_mut());        
@@ -67,7 +71,7 @@ where
         Ok(into_affine)
     }
 
-    pub fn evaluate(&self, result: &mut Evaluation<E>, kp: KeyPairAssembly<E>, win: &BasedWindows<'_,E>, coeffs: &[Scalar<E>]) -> Result<()> {
+    pub fn evaluate(&self, result: &mut Evaluation<E>, kp: KeyPairAssembly<E>, win: &Windows<E>, coeffs: &[Scalar<E>]) -> Result<()> {
         let input_size: usize = kp.num.inputs;
         let input_result_writer: _ = result.as_inputs(input_size);
         self.input_eval(input_result_writer, kp.inputs, win, coeffs)?;
@@ -78,7 +82,7 @@ where
         Ok(())
     }
 
-    fn input_eval(&self, input_results: Writer<E>, input_wires: KeyPairWires<E>, win: &BasedWindows<'_,E>, coeffs: &[Scalar<E>]) -> Result<()> {
+    fn input_eval(&self, input_results: Writer<E>, input_wires: KeyPairWires<E>, win: &Windows<E>, coeffs: &[Scalar<E>]) -> Result<()> {
         if input_results.sanity_check(&input_wires) {
             input_results.eval(win, coeffs, input_wires, &self.inverse.gamma, &self.elements);
             Ok(())  
@@ -87,7 +91,7 @@ where
         }
     }
 
-    fn aux_eval(&self, aux_results: Writer<E>, aux_wires: KeyPairWires<E>, win: &BasedWindows<'_,E>, coeffs: &[Scalar<E>]) -> Result<()> {
+    fn aux_eval(&self, aux_results: Writer<E>, aux_wires: KeyPairWires<E>, win: &Windows<E>, coeffs: &[Scalar<E>]) -> Result<()> {
         if aux_results.sanity_check(&aux_wires) {
             aux_results.eval(win, coeffs, aux_wires, &self.inverse.delta, &self.elements);
             Ok(())
@@ -0,0 +1,82 @@
+use blake2s_simd::Params as Blake2sParams;
+use pairing::Engine;
+use rand_core::{RngCore, SeedableRng};
+
+use super::generate_random_parameters;
+use crate::groth16::Parameters;
+use crate::Circuit;
+use crate::error::Result;
+
+/// BLAKE2s personalization used when hashing parameters for an audit.
+const PARAMETERS_PERSONALIZATION: &[u8; 8] = b"G16Param";
+
+/// The outcome of a determinism audit.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Determinism {
+    /// Both runs matched the golden digest recorded for this version.
+    Matches,
+    /// Both runs agreed, but no golden digest is recorded for this version.
+    Unrecorded([u8; 32]),
+    /// Both runs agreed, but differ from the golden digest for this version.
+    Mismatch {
+        expected: [u8; 32],
+        actual: [u8; 32]
+    },
+    /// Two runs from the same seed disagreed on this platform.
+    Unstable {
+        first: [u8; 32],
+        second: [u8; 32]
+    }
+}
+
+/// Returns the BLAKE2s-256 digest of the serialized `params`.
+pub fn parameters_digest<E: Engine>(params: &Parameters<E>) -> [u8; 32] {
+    let mut encoded: Vec<u8> = Vec::new();
+    params.write(&mut encoded)
+        .expect("writing to a vector does not fail");
+
+    let digest: _ = Blake2sParams::new()
+        .hash_length(32)
+        .personal(PARAMETERS_PERSONALIZATION)
+        .hash(&encoded);
+
+    let mut out: [u8; 32] = [0; 32];
+    out.copy_from_slice(digest.as_bytes());
+    out
+}
+
+/// Generates parameters for the circuit built by `circuit` twice from
+/// `seed`, and compares their digests with each other and with the golden
+/// digest recorded in `golden` for this crate version. This catches
+/// nondeterminism from parallel reduction order or platform-dependent
+/// arithmetic before parameters are published.
+pub fn audit_determinism<E,C,R,F>(circuit: F, seed: R::Seed, golden: &[(&str, [u8; 32])]) -> Result<Determinism>
+where
+    E: Engine,
+    C: Circuit<E>,
+    R: RngCore + SeedableRng,
+    R::Seed: Clone,
+    F: Fn() -> C
+{
+    let mut digests: Vec<[u8; 32]> = Vec::with_capacity(2);
+    for _ in 0..2 {
+        let mut rng: R = R::from_seed(seed.clone());
+        let params: Parameters<E> = generate_random_parameters(circuit(), &mut rng)?;
+        digests.push(parameters_digest(&params));
+    }
+
+    let (first, second): ([u8; 32], [u8; 32]) = (digests[0], digests[1]);
+    if first != second {
+        return Ok(Determinism::Unstable { first, second })
+    }
+
+    let recorded: Option<[u8; 32]> = golden.iter()
+        .find(|(version, _)| *version == env!("CARGO_PKG_VERSION"))
+        .map(|(_, digest)| *digest);
+
+    Ok(match recorded {
+        Some(expected) if expected == first => Determinism::Matches,
+        Some(expected) => Determinism::Mismatch { expected, actual: first },
+        None => Determinism::Unrecorded(first)
+    })
+}
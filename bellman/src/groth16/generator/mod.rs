@@ -1,5 +1,6 @@
 use rand_core::RngCore;
 
+use std::io::Write;
 use std::sync::Arc;
 
 use ff::Field;
@@ -7,13 +8,19 @@ use group::CurveProjective;
 use pairing::Engine;
 
 use super::{Parameters, VerifyingKey};
-use crate::{Circuit, SynthesisError};
+use crate::{Circuit, PublicInputLayout, SynthesisError};
 use crate::domain::Domain;
 use crate::error::Result;
 
 mod assembly;
 use assembly::Assembly;
 
+mod symbols;
+pub use symbols::ConstraintSymbols;
+
+mod audit;
+pub use audit::{audit_determinism, parameters_digest, Determinism};
+
 /// Generates a random common reference string for
 /// a circuit.
 pub fn generate_random_parameters<E,C,R>(circuit: C, rng: &mut R) -> Result<Parameters<E>>
@@ -48,17 +55,79 @@ where
     E: Engine,
     C: Circuit<E>,
 {
-    let mut assembly: _ = Assembly::new(circuit, g1, g2, alpha, beta, gamma, delta, tau)?;
+    let (params, _, _): _ = generate(circuit, g1, g2, alpha, beta, gamma, delta, tau, false)?;
+    Ok(params)
+}
+
+/// Create parameters for a circuit, given some toxic waste, along with the
+/// layout of the public inputs it allocated with `alloc_input_named`. The
+/// layout is recorded by the same synthesis as the parameters, so it always
+/// describes their verifying key.
+pub fn generate_parameters_with_layout<E,C>(
+    circuit: C,
+    g1: E::G1,
+    g2: E::G2,
+    alpha: E::Fr,
+    beta: E::Fr,
+    gamma: E::Fr,
+    delta: E::Fr,
+    tau: E::Fr,
+) -> Result<(Parameters<E>, PublicInputLayout)>
+where
+    E: Engine,
+    C: Circuit<E>,
+{
+    let (params, _, layout): _ = generate(circuit, g1, g2, alpha, beta, gamma, delta, tau, false)?;
+    Ok((params, layout))
+}
+
+/// Create parameters for a circuit, given some toxic waste, and capture
+/// the namespace path of every constraint into a symbol table.
+pub fn generate_parameters_with_symbols<E,C>(
+    circuit: C,
+    g1: E::G1,
+    g2: E::G2,
+    alpha: E::Fr,
+    beta: E::Fr,
+    gamma: E::Fr,
+    delta: E::Fr,
+    tau: E::Fr,
+) -> Result<(Parameters<E>, ConstraintSymbols)>
+where
+    E: Engine,
+    C: Circuit<E>,
+{
+    let (params, symbols, _): _ = generate(circuit, g1, g2, alpha, beta, gamma, delta, tau, true)?;
+    Ok((params, symbols?))
+}
+
+fn generate<E,C>(
+    circuit: C,
+    g1: E::G1,
+    g2: E::G2,
+    alpha: E::Fr,
+    beta: E::Fr,
+    gamma: E::Fr,
+    delta: E::Fr,
+    tau: E::Fr,
+    capture_symbols: bool
+) -> Result<(Parameters<E>, Option<ConstraintSymbols>, PublicInputLayout)>
+where
+    E: Engine,
+    C: Circuit<E>,
+{
+    let mut assembly: _ = Assembly::new(circuit, g1, g2, alpha, beta, gamma, delta, tau, capture_symbols)?;
+    let symbols: Option<ConstraintSymbols> = assembly.take_symbols()?;
+    let layout: PublicInputLayout = assembly.take_layout()?;
     let mut evaluation_domain: Domain<_,_> = assembly.evaluation_domain()?; 
 
-    let mut windows: _ = assembly::Windows::default();
-    let based: _ = windows.as_based(&assembly, &evaluation_domain)?;
+    let windows: _ = assembly::Windows::new(&assembly, &evaluation_domain)?;
 
-    let h: Vec<E::G1Affine> = assembly.h(&mut evaluation_domain, &based.g1)?;
+    let h: Vec<E::G1Affine> = assembly.h(&mut evaluation_domain, &windows.g1)?;
 
     let lagrange_coeffs = assembly::into_lagrange_coefficients(evaluation_domain);
 
-    assembly.evaluate(&based, &lagrange_coeffs)?;
+    assembly.evaluate(&windows, &lagrange_coeffs)?;
     
     if assembly.result_is_unconstrained()? {
         return Err(SynthesisError::UnconstrainedVariable)
@@ -68,12 +137,42 @@ where
     
     let (l, a, b_g1, b_g2): _ = assembly.results().filter_into_affine();
 
-    Ok(Parameters {
+    let params: _ = Parameters {
         vk,
         h: Arc::new(h),
         l: Arc::new(l),
         a: Arc::new(a),
         b_g1: Arc::new(b_g1),
         b_g2: Arc::new(b_g2)
-    })
+    };
+    Ok((params, symbols, layout))
+}
+
+/// Synthesizes a circuit and writes its rank-1 constraint system in the
+/// binary `.r1cs` format used by circom/snarkjs, so that circuits can be
+/// cross-checked against that toolchain.
+pub fn write_r1cs<E,C,W>(circuit: C, writer: W) -> Result<()>
+where
+    E: Engine,
+    C: Circuit<E>,
+    W: Write,
+{
+    let mut assembly: _ = assembly::KeyPairAssembly::default();
+    assembly.allocate_input_one()?;
+    assembly.synthesize_circuit(circuit)?;
+    assembly.write_r1cs(writer)?;
+    Ok(())
+}
+
+/// Synthesizes a circuit and returns the layout of the public inputs it
+/// allocated with `alloc_input_named`.
+pub fn public_input_layout<E,C>(circuit: C) -> Result<PublicInputLayout>
+where
+    E: Engine,
+    C: Circuit<E>,
+{
+    let mut assembly: _ = assembly::KeyPairAssembly::default();
+    assembly.allocate_input_one()?;
+    assembly.synthesize_circuit(circuit)?;
+    Ok(assembly.layout)
 }
@@ -0,0 +1,86 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{self, Read, Write};
+
+/// A symbol table mapping the index of each constraint to the namespace
+/// path and annotation it was enforced under. Capturing the table during
+/// setup lets tooling resolve failing constraint indices later on without
+/// re-running synthesis with annotations enabled.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConstraintSymbols {
+    names: Vec<String>,
+    current_namespace: Vec<String>
+}
+
+impl ConstraintSymbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the path of the constraint at `index`, if one was recorded.
+    pub fn resolve(&self, index: usize) -> Option<&str> {
+        self.names
+            .get(index)
+            .map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub(crate) fn push_namespace(&mut self, name: String) {
+        self.current_namespace.push(name);
+    }
+
+    pub(crate) fn pop_namespace(&mut self) {
+        self.current_namespace.pop();
+    }
+
+    pub(crate) fn record(&mut self, annotation: String) {
+        let path: String = self.current_namespace
+            .iter()
+            .chain(Some(&annotation))
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.names.push(path);
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u32::<BigEndian>(self.names.len() as u32)?;
+        for name in &self.names {
+            writer.write_u32::<BigEndian>(name.len() as u32)?;
+            writer.write_all(name.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let len = reader.read_u32::<BigEndian>()? as usize;
+
+        // Lengths are untrusted, so the table and each name grow as they
+        // are read rather than being allocated up front.
+        let mut names = vec![];
+        for _ in 0..len {
+            let name_len = reader.read_u32::<BigEndian>()? as u64;
+            let mut buf = vec![];
+            reader.by_ref().take(name_len).read_to_end(&mut buf)?;
+            if (buf.len() as u64) < name_len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "name is truncated"));
+            }
+
+            let name = String::from_utf8(buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            names.push(name);
+        }
+
+        Ok(ConstraintSymbols {
+            names,
+            current_namespace: Vec::new()
+        })
+    }
+}
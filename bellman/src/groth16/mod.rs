@@ -2,25 +2,35 @@ use group::{CurveAffine, EncodedPoint};
 use pairing::{Engine, PairingCurveAffine};
 
 use crate::SynthesisError;
+use crate::gadgets::multipack;
 
+use blake2s_simd::Params as Blake2sParams;
+use ff::PrimeField;
+
+use crate::multicore::MULTI_THREAD;
 use crate::multiexp::SourceBuilder;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::io::{self, Read, Write};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 #[cfg(test)]
 mod tests;
 
+mod cache;
 mod generator;
 mod prover;
 mod verifier;
 
+pub use self::cache::*;
 pub use self::generator::*;
 pub use self::prover::*;
 pub use self::verifier::*;
 
 pub type Result<T> = std::result::Result<T, SynthesisError>;
 
+/// BLAKE2s personalization used when hashing a verifying key.
+pub const VERIFYING_KEY_PERSONALIZATION: &[u8; 8] = b"G16VKey_";
+
 #[derive(Clone)]
 pub struct Proof<E: Engine> {
     pub a: E::G1Affine,
@@ -137,6 +147,34 @@ impl<E: Engine> PartialEq for VerifyingKey<E> {
 }
 
 impl<E: Engine> VerifyingKey<E> {
+    /// Hashes the canonical encoding of this key into a field element, so
+    /// that a circuit can commit to the key of an inner proof. The canonical
+    /// encoding is the uncompressed form produced by `write`; it is hashed
+    /// with BLAKE2s-256 under `VERIFYING_KEY_PERSONALIZATION` and the digest
+    /// bits, taken little-endian, are truncated to the field's capacity.
+    pub fn hash(&self) -> E::Fr {
+        let mut encoded: Vec<u8> = Vec::with_capacity(Self::encoded_len(self.ic.len()));
+        self.write(&mut encoded)
+            .expect("writing to a vector does not fail");
+
+        let digest: _ = Blake2sParams::new()
+            .hash_length(32)
+            .personal(VERIFYING_KEY_PERSONALIZATION)
+            .hash(&encoded);
+
+        let bits: Vec<bool> = multipack::bytes_to_bits_le(digest.as_bytes());
+        multipack::compute_multipacking::<E>(&bits[..E::Fr::CAPACITY as usize])[0]
+    }
+
+    /// Returns the length in bytes of the canonical encoding of a key
+    /// with `num_ic` elements in `ic`.
+    pub fn encoded_len(num_ic: usize) -> usize {
+        let g1: usize = <E::G1Affine as CurveAffine>::Uncompressed::size();
+        let g2: usize = <E::G2Affine as CurveAffine>::Uncompressed::size();
+
+        g1 * (3 + num_ic) + g2 * 3 + 4
+    }
+
     pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
         writer.write_all(self.alpha_g1.into_uncompressed().as_ref())?;
         writer.write_all(self.beta_g1.into_uncompressed().as_ref())?;
@@ -289,93 +327,27 @@ impl<E: Engine> Parameters<E> {
         Ok(())
     }
 
-    pub fn read<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
-        let read_g1 = |reader: &mut R| -> io::Result<E::G1Affine> {
-            let mut repr = <E::G1Affine as CurveAffine>::Uncompressed::empty();
-            reader.read_exact(repr.as_mut())?;
-
-            if checked {
-                repr.into_affine()
-            } else {
-                repr.into_affine_unchecked()
-            }
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
-            .and_then(|e| {
-                if e.is_zero() {
-                    Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "point at infinity",
-                    ))
-                } else {
-                    Ok(e)
-                }
-            })
-        };
-
-        let read_g2 = |reader: &mut R| -> io::Result<E::G2Affine> {
-            let mut repr = <E::G2Affine as CurveAffine>::Uncompressed::empty();
-            reader.read_exact(repr.as_mut())?;
-
-            if checked {
-                repr.into_affine()
-            } else {
-                repr.into_affine_unchecked()
-            }
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
-            .and_then(|e| {
-                if e.is_zero() {
-                    Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "point at infinity",
-                    ))
-                } else {
-                    Ok(e)
-                }
-            })
-        };
+    /// Reads only the verifying key from the front of serialized parameters,
+    /// leaving the proving elements unread. Verifier-only deployments can use
+    /// this to avoid loading the full set of proving parameters into memory,
+    /// and `capabilities` to check which features their build includes.
+    pub fn read_verifying_key<R: Read>(reader: R) -> io::Result<VerifyingKey<E>> {
+        VerifyingKey::read(reader)
+    }
 
+    /// Reads serialized parameters. If `checked`, every point is validated
+    /// to be on the curve and in the prime order subgroup. With the `glv`
+    /// feature the prover's multiexps assume the subgroup, so every point
+    /// is validated whatever `checked` is. The points of each query are
+    /// decoded and validated in parallel across the worker pool.
+    pub fn read<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
         let vk = VerifyingKey::<E>::read(&mut reader)?;
 
-        let mut h = vec![];
-        let mut l = vec![];
-        let mut a = vec![];
-        let mut b_g1 = vec![];
-        let mut b_g2 = vec![];
-
-        {
-            let len = reader.read_u32::<BigEndian>()? as usize;
-            for _ in 0..len {
-                h.push(read_g1(&mut reader)?);
-            }
-        }
-
-        {
-            let len = reader.read_u32::<BigEndian>()? as usize;
-            for _ in 0..len {
-                l.push(read_g1(&mut reader)?);
-            }
-        }
-
-        {
-            let len = reader.read_u32::<BigEndian>()? as usize;
-            for _ in 0..len {
-                a.push(read_g1(&mut reader)?);
-            }
-        }
-
-        {
-            let len = reader.read_u32::<BigEndian>()? as usize;
-            for _ in 0..len {
-                b_g1.push(read_g1(&mut reader)?);
-            }
-        }
-
-        {
-            let len = reader.read_u32::<BigEndian>()? as usize;
-            for _ in 0..len {
-                b_g2.push(read_g2(&mut reader)?);
-            }
-        }
+        let h = read_points::<E::G1Affine, _>(&mut reader, checked)?;
+        let l = read_points::<E::G1Affine, _>(&mut reader, checked)?;
+        let a = read_points::<E::G1Affine, _>(&mut reader, checked)?;
+        let b_g1 = read_points::<E::G1Affine, _>(&mut reader, checked)?;
+        let b_g2 = read_points::<E::G2Affine, _>(&mut reader, checked)?;
 
         Ok(Parameters {
             vk: vk,
@@ -388,6 +360,75 @@ impl<E: Engine> Parameters<E> {
     }
 }
 
+/// Reads a length prefixed query of uncompressed points, none of which may
+/// be the point at infinity.
+///
+/// Subgroup membership is checked point by point rather than with a random
+/// linear combination of the query: the cofactors of BLS12-381 have small
+/// prime factors, so a combined check would miss a torsion component with
+/// high probability.
+fn read_points<G, R>(reader: &mut R, checked: bool) -> io::Result<Vec<G>>
+where
+    G: CurveAffine,
+    R: Read
+{
+    let len = reader.read_u32::<BigEndian>()? as usize;
+
+    // The length is untrusted, so the query grows as points are read
+    // rather than being allocated up front.
+    let mut reprs: Vec<G::Uncompressed> = vec![];
+    for _ in 0..len {
+        let mut repr = G::Uncompressed::empty();
+        reader.read_exact(repr.as_mut())?;
+        reprs.push(repr);
+    }
+
+    let mut points: Vec<G> = vec![G::zero(); len];
+    let error: Mutex<Option<io::Error>> = Mutex::new(None);
+
+    MULTI_THREAD.scope(len, |scope, chunk_size| {
+        let chunk_size: usize = chunk_size.max(1);
+        for (reprs, points) in reprs.chunks(chunk_size).zip(points.chunks_mut(chunk_size)) {
+            let error = &error;
+            scope.spawn(move || {
+                for (repr, point) in reprs.iter().zip(points.iter_mut()) {
+                    // A base outside the subgroup would silently give a
+                    // wrong multiexp once its exponent is split.
+                    let decoded = if checked || cfg!(feature = "glv") {
+                        repr.into_affine()
+                    } else {
+                        repr.into_affine_unchecked()
+                    }
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                    .and_then(|e| {
+                        if e.is_zero() {
+                            Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "point at infinity",
+                            ))
+                        } else {
+                            Ok(e)
+                        }
+                    });
+
+                    match decoded {
+                        Ok(decoded) => *point = decoded,
+                        Err(e) => {
+                            error.lock().expect("error lock was poisoned").get_or_insert(e);
+                            return
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    match error.into_inner().expect("error lock was poisoned") {
+        Some(e) => Err(e),
+        None => Ok(points)
+    }
+}
+
 pub struct PreparedVerifyingKey<E: Engine> {
     /// Pairing result of alpha*beta
     alpha_g1_beta_g2: E::Fqk,
@@ -457,8 +498,8 @@ mod test_with_bls12_381 {
     use crate::{Circuit, ConstraintSystem, SynthesisError};
 
     use ff::Field;
-    use pairing::bls12_381::{Bls12, Fr};
-    use rand::thread_rng;
+    use pairing::bls12_381::{Bls12, Fr, G1Uncompressed};
+    use rand::rngs::StdRng;
 
     #[test]
     fn serialization() {
@@ -491,7 +532,7 @@ mod test_with_bls12_381 {
             }
         }
 
-        let rng = &mut thread_rng();
+        let rng = &mut ProverRng::<StdRng>::from_entropy();
 
         let params =
             generate_random_parameters::<Bls12, _, _>(MySillyCircuit { a: None, b: None }, rng)
@@ -501,13 +542,50 @@ mod test_with_bls12_381 {
             let mut v = vec![];
 
             params.write(&mut v).unwrap();
-            assert_eq!(v.len(), 2136);
+            assert_eq!(v.len(), 2040);
 
             let de_params = Parameters::read(&v[..], true).unwrap();
             assert!(params == de_params);
 
             let de_params = Parameters::read(&v[..], false).unwrap();
             assert!(params == de_params);
+
+            let de_vk = Parameters::<Bls12>::read_verifying_key(&v[..]).unwrap();
+            assert!(params.vk == de_vk);
+
+            // A point which is not on the curve is only caught by a checked
+            // read, or by any read when split exponents need the subgroup.
+            let last = v.len() - 1;
+            v[last] ^= 1;
+            match Parameters::<Bls12>::read(&v[..], true) {
+                Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+                Ok(_) => panic!("expected an invalid point to be rejected"),
+            }
+            assert_eq!(Parameters::<Bls12>::read(&v[..], false).is_ok(), !cfg!(feature = "glv"));
+            v[last] ^= 1;
+
+            // As is a point on the curve outside the prime order subgroup,
+            // such as (0, 2).
+            let mut encoded = G1Uncompressed::empty();
+            encoded.as_mut()[95] = 2;
+            let mut outside = params.clone();
+            outside.h = Arc::new(vec![encoded.into_affine_unchecked().unwrap(); params.h.len()]);
+            let mut v = vec![];
+            outside.write(&mut v).unwrap();
+            match Parameters::<Bls12>::read(&v[..], true) {
+                Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+                Ok(_) => panic!("expected a point outside the subgroup to be rejected"),
+            }
+            assert_eq!(Parameters::<Bls12>::read(&v[..], false).is_ok(), !cfg!(feature = "glv"));
+
+            // A query claiming far more points than follow is an error,
+            // and is not allocated up front.
+            let mut v = v[..VerifyingKey::<Bls12>::encoded_len(params.vk.ic.len())].to_vec();
+            v.extend_from_slice(&[0xff; 4]);
+            match Parameters::<Bls12>::read(&v[..], false) {
+                Err(e) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+                Ok(_) => panic!("expected a truncated query to be rejected"),
+            }
         }
 
         let pvk = prepare_verifying_key::<Bls12>(&params.vk);
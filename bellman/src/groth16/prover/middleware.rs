@@ -0,0 +1,131 @@
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use crate::error::Result;
+use crate::multicore::Worker;
+
+/// A stage of proof creation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Synthesizing the circuit into its assignment and evaluations.
+    Synthesis,
+    /// Computing the coefficients of the quotient polynomial.
+    Fft,
+    /// The multi-exponentiations over the proving key.
+    Multiexp,
+    /// Combining the multi-exponentiations with the blinding factors.
+    Finalization
+}
+
+/// The size of the proving problem. Every count is zero before the
+/// synthesis stage has completed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StageInfo {
+    /// The number of public inputs, including the "one" input.
+    pub num_inputs: usize,
+    pub num_aux: usize,
+    /// The number of constraints enforced by the circuit.
+    pub num_constraints: usize
+}
+
+/// The settings a stage runs with, which middleware may change before it
+/// starts.
+#[derive(Clone)]
+pub struct ProverConfig {
+    /// The worker whose pool runs the parallel work of the stage.
+    pub worker: Worker,
+    /// Whether the FFT stage transforms with the in-place FFT, which needs
+    /// no scratch buffer the size of the domain but is slower than the FFT
+    /// chosen otherwise on many threads. Off by default.
+    pub in_place_fft: bool
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        ProverConfig {
+            worker: Worker::new(),
+            in_place_fft: false
+        }
+    }
+}
+
+/// Hooks around each stage of proof creation, for telemetry, choosing a
+/// thread pool per stage or aborting a proof without forking the prover.
+pub trait ProverMiddleware {
+    /// Called before `stage` runs. Returning an error vetoes the proof and
+    /// the error is returned by the prover.
+    fn before(&mut self, _stage: Stage, _info: &StageInfo, _config: &mut ProverConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after `stage` completes successfully. The elapsed time is
+    /// always zero on wasm32, which has no clock.
+    fn after(&mut self, _stage: Stage, _info: &StageInfo, _elapsed: Duration) {}
+}
+
+/// No middleware.
+impl ProverMiddleware for () {}
+
+impl<'a, M: ProverMiddleware> ProverMiddleware for &'a mut M {
+    fn before(&mut self, stage: Stage, info: &StageInfo, config: &mut ProverConfig) -> Result<()> {
+        (**self).before(stage, info, config)
+    }
+
+    fn after(&mut self, stage: Stage, info: &StageInfo, elapsed: Duration) {
+        (**self).after(stage, info, elapsed)
+    }
+}
+
+/// Composes two middleware. The outer runs its `before` hook first and its
+/// `after` hook last, so the pair nests around each stage.
+impl<A, B> ProverMiddleware for (A, B)
+where
+    A: ProverMiddleware,
+    B: ProverMiddleware
+{
+    fn before(&mut self, stage: Stage, info: &StageInfo, config: &mut ProverConfig) -> Result<()> {
+        self.0.before(stage, info, config)?;
+        self.1.before(stage, info, config)
+    }
+
+    fn after(&mut self, stage: Stage, info: &StageInfo, elapsed: Duration) {
+        self.1.after(stage, info, elapsed);
+        self.0.after(stage, info, elapsed);
+    }
+}
+
+/// Runs `op` as `stage` with the config left by the hooks of `middleware`,
+/// which surround it.
+pub(crate) fn run_stage<M, F, T>(middleware: &mut M, stage: Stage, info: &StageInfo, op: F) -> Result<T>
+where
+    M: ProverMiddleware,
+    F: FnOnce(&ProverConfig) -> Result<T>
+{
+    let mut config: ProverConfig = ProverConfig::default();
+    middleware.before(stage, info, &mut config)?;
+
+    let (result, elapsed): (Result<T>, Duration) = timed(|| config.worker.enter(|| op(&config)));
+    let result: T = result?;
+    middleware.after(stage, info, elapsed);
+
+    Ok(result)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn timed<F, T>(op: F) -> (T, Duration)
+where
+    F: FnOnce() -> T
+{
+    let start: Instant = Instant::now();
+    let result: T = op();
+    (result, start.elapsed())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn timed<F, T>(op: F) -> (T, Duration)
+where
+    F: FnOnce() -> T
+{
+    (op(), Duration::default())
+}
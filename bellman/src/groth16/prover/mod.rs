@@ -1,5 +1,4 @@
-use futures::Future;
-use rand_core::RngCore;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
 
 use ff::{Field};
 use group::{CurveProjective};
@@ -7,17 +6,25 @@ use pairing::Engine;
 
 use super::{ParameterSource, Proof, Result};
 
-use crate::{Circuit, ConstraintSystem, SynthesisError};
+use crate::{Circuit, ConstraintSystem, DomainPrecomputation, SynthesisError};
 
 mod system;
 use system::*;
 
-pub fn create_random_proof<E,C,R,P>(circuit: C, params: P, rng: &mut R) -> Result<Proof<E>>
+mod rng;
+pub use rng::ProverRng;
+
+mod middleware;
+pub use middleware::{ProverConfig, ProverMiddleware, Stage, StageInfo};
+use middleware::run_stage;
+
+/// Creates a proof blinded with randomness drawn from `rng`.
+pub fn create_random_proof<E,C,R,P>(circuit: C, params: P, rng: &mut ProverRng<R>) -> Result<Proof<E>>
 where
     E: Engine,
     C: Circuit<E>,
     P: ParameterSource<E>,
-    R: RngCore,
+    R: RngCore + CryptoRng + SeedableRng,
 {
     let r = E::Fr::random(rng);
     let s = E::Fr::random(rng);
@@ -25,22 +32,82 @@ where
     create_proof::<E, C, P>(circuit, params, r, s)
 }
 
-pub fn create_proof<E, C, P>(circuit: C, mut params: P, r: E::Fr, s: E::Fr) -> Result<Proof<E>>
+pub fn create_proof<E, C, P>(circuit: C, params: P, r: E::Fr, s: E::Fr) -> Result<Proof<E>>
 where
     E: Engine,
     C: Circuit<E>,
     P: ParameterSource<E>
 {
-    let mut prover: _ = ProvingSystem::default();
-    prover.alloc_input(
-        || "", 
-        || Ok(E::Fr::one())
-    )?;
-    circuit.synthesize(&mut prover)?;
+    create_proof_with_middleware::<E, C, P, _>(circuit, params, r, s, &mut ())
+}
+
+/// Creates a proof blinded with randomness drawn from `rng`, running the
+/// hooks of `middleware` around each stage.
+pub fn create_random_proof_with_middleware<E,C,R,P,M>(circuit: C, params: P, rng: &mut ProverRng<R>, middleware: &mut M) -> Result<Proof<E>>
+where
+    E: Engine,
+    C: Circuit<E>,
+    P: ParameterSource<E>,
+    R: RngCore + CryptoRng + SeedableRng,
+    M: ProverMiddleware
+{
+    let r = E::Fr::random(rng);
+    let s = E::Fr::random(rng);
+
+    create_proof_with_middleware::<E, C, P, M>(circuit, params, r, s, middleware)
+}
+
+/// Creates a proof, running the hooks of `middleware` around each stage.
+pub fn create_proof_with_middleware<E, C, P, M>(circuit: C, params: P, r: E::Fr, s: E::Fr, middleware: &mut M) -> Result<Proof<E>>
+where
+    E: Engine,
+    C: Circuit<E>,
+    P: ParameterSource<E>,
+    M: ProverMiddleware
+{
+    create_proof_with_precomputation::<E, C, P, M>(circuit, params, r, s, None, middleware)
+}
+
+/// Creates a proof blinded with randomness drawn from `rng`, reading the
+/// powers of the roots of unity of its domain from `precomputation`.
+pub fn create_random_proof_with_precomputation<E,C,R,P,M>(circuit: C, params: P, rng: &mut ProverRng<R>, precomputation: Option<&DomainPrecomputation<E>>, middleware: &mut M) -> Result<Proof<E>>
+where
+    E: Engine,
+    C: Circuit<E>,
+    P: ParameterSource<E>,
+    R: RngCore + CryptoRng + SeedableRng,
+    M: ProverMiddleware
+{
+    let r = E::Fr::random(rng);
+    let s = E::Fr::random(rng);
+
+    create_proof_with_precomputation::<E, C, P, M>(circuit, params, r, s, precomputation, middleware)
+}
+
+/// Creates a proof, running the hooks of `middleware` around each stage.
+/// With a `precomputation`, the powers of the roots of unity of the domain
+/// are read from it instead of being recomputed, so proofs sharing one only
+/// pay for them once per domain size.
+pub fn create_proof_with_precomputation<E, C, P, M>(circuit: C, mut params: P, r: E::Fr, s: E::Fr, precomputation: Option<&DomainPrecomputation<E>>, middleware: &mut M) -> Result<Proof<E>>
+where
+    E: Engine,
+    C: Circuit<E>,
+    P: ParameterSource<E>,
+    M: ProverMiddleware
+{
+    let prover: ProvingSystem<E> = run_stage(middleware, Stage::Synthesis, &StageInfo::default(), |_| {
+        let mut prover: _ = ProvingSystem::default();
+        prover.alloc_input(
+            || "", 
+            || Ok(E::Fr::one())
+        )?;
+        circuit.synthesize(&mut prover)?;
+        Ok(prover)
+    })?;
+    let info: StageInfo = prover.info();
     
-    let (ga,gb,gc): _ = prover
-        .prepare(&mut params, r, s)?
-        .try_build()?;
+    let builder: _ = prover.prepare(&mut params, r, s, precomputation, middleware, &info)?;
+    let (ga,gb,gc): _ = run_stage(middleware, Stage::Finalization, &info, |_| builder.try_build())?;
 
     Ok(Proof {
         a: ga.into_affine(),
@@ -0,0 +1,92 @@
+use rand_core::{CryptoRng, Error, OsRng, RngCore, SeedableRng};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::process;
+
+/// The randomness source used to blind proofs. Wrapping the generator
+/// restricts the prover to cryptographically secure RNGs and guards
+/// against a forked child process replaying its parent's stream, which
+/// would reuse the blinding factors `r` and `s` and leak the witness.
+pub struct ProverRng<R> {
+    inner: R,
+    pid: u32,
+    reseed_on_fork: bool,
+}
+
+impl<R> ProverRng<R>
+where
+    R: RngCore + CryptoRng + SeedableRng,
+{
+    /// Seeds the generator from the operating system. The generator is
+    /// reseeded from the operating system whenever it is used from a
+    /// process other than the one that seeded it.
+    pub fn from_entropy() -> Self {
+        ProverRng {
+            inner: R::from_rng(OsRng).expect("the operating system RNG should be available"),
+            pid: process_id(),
+            reseed_on_fork: true,
+        }
+    }
+
+    /// Seeds the generator deterministically. The stream is never reseeded,
+    /// so this is only suitable for tests and reproducible benchmarks.
+    pub fn from_seed(seed: R::Seed) -> Self {
+        ProverRng {
+            inner: R::from_seed(seed),
+            pid: process_id(),
+            reseed_on_fork: false,
+        }
+    }
+
+    /// Reseeds the generator from the operating system.
+    pub fn reseed(&mut self) -> Result<(), Error> {
+        self.inner = R::from_rng(OsRng)?;
+        self.pid = process_id();
+        Ok(())
+    }
+
+    fn check_fork(&mut self) -> Result<(), Error> {
+        if self.reseed_on_fork && self.pid != process_id() {
+            self.reseed()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn process_id() -> u32 {
+    process::id()
+}
+
+/// wasm32 has no processes to fork, and asking for the id panics.
+#[cfg(target_arch = "wasm32")]
+fn process_id() -> u32 {
+    0
+}
+
+impl<R> RngCore for ProverRng<R>
+where
+    R: RngCore + CryptoRng + SeedableRng,
+{
+    fn next_u32(&mut self) -> u32 {
+        self.check_fork().expect("failed to reseed after fork");
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.check_fork().expect("failed to reseed after fork");
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.check_fork().expect("failed to reseed after fork");
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.check_fork()?;
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+impl<R> CryptoRng for ProverRng<R> where R: RngCore + CryptoRng + SeedableRng {}
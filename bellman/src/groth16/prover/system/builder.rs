@@ -1,16 +1,17 @@
-use std::sync::Arc;
-
 use super::{source, fourier};
 use super::{
     PolynomialEvaluation, ParameterSource, Result, 
-    ProvingSystem, Future, SynthesisError, 
+    ProvingSystem, SynthesisError, 
     AssignmentField, ProvingAssignment, 
 };
+use super::super::{run_stage, ProverMiddleware, Stage, StageInfo};
 
 use ff::{Field, PrimeField};
 use pairing::Engine;
 
-use crate::multiexp::{multiexp, FullDensity};
+use crate::{DomainPrecomputation, Query};
+use crate::multicore::MULTI_THREAD;
+use crate::multiexp::{multiexp_slice, FullDensity, SourceBuilder};
 use crate::groth16::VerifyingKey;
 use group::{CurveAffine, CurveProjective};
 
@@ -28,25 +29,51 @@ impl<E> Builder<E>
 where
     E: Engine
 {
-    pub fn try_new<P>(mut prover: ProvingSystem<E>, params: &mut P, r: E::Fr, s: E::Fr) -> Result<Self> 
+    pub fn try_new<P,M>(prover: ProvingSystem<E>, params: &mut P, r: E::Fr, s: E::Fr, precomputation: Option<&DomainPrecomputation<E>>, middleware: &mut M, info: &StageInfo) -> Result<Self> 
     where
-        P: ParameterSource<E>
+        P: ParameterSource<E>,
+        M: ProverMiddleware
     {
         let vk: VerifyingKey<E> = try_vk(params)?;
-        let h: _ = try_h(&mut prover.eval, params)?;
+        // The H query has a base for every point of the domain the
+        // parameters were generated over but one, so proving over that
+        // domain keeps parameters from before mixed-radix domains usable.
+        let domain_size: usize = params.get_h()?.new().remaining() + 1;
+        let eval: PolynomialEvaluation<E> = prover.eval;
+        let linear_coeffs: AssignmentField<E> = run_stage(middleware, Stage::Fft, info, |config| {
+            fourier::evaluate_coefficients(eval, domain_size, precomputation, config.in_place_fft)
+        })?;
         
         let (input_field, aux_field): (AssignmentField<E>, AssignmentField<E>) = into_primefield(prover.assignment);
-        let l: _ = try_l(&aux_field, params)?;
 
-        let (answer, aux): _ = source::SourceFactory::try_new(prover.density, input_field, aux_field, params)?.unpack();
+        let density = prover.density;
+        let (h, l, answer, aux): _ = run_stage(middleware, Stage::Multiexp, info, |_| {
+            // Every base is fetched up front, as the parameters are borrowed
+            // mutably, so that all eight multiexps can then run at once.
+            let h_src: P::G1Builder = params.get_h()?;
+            let l_src: P::G1Builder = params.get_l()?;
+            let sources: source::SourceFactory<P,E> = source::SourceFactory::try_new(density, input_field.len(), params)?;
+
+            let ((h, l), sources): _ = MULTI_THREAD.join(
+                || MULTI_THREAD.join(
+                    || try_h::<E,_>(h_src, &linear_coeffs),
+                    || try_l::<E,_>(l_src, &aux_field)
+                ),
+                || sources.try_multiexp(&input_field, &aux_field)
+            );
+            let (h, l): (E::G1, E::G1) = (h?, l?);
+            let (answer, aux): _ = sources?;
+            Ok((h, l, answer, aux))
+        })?;
+
         let builder: _ = Self {
             vk,
             r,
             s,
             answer,
             aux,
-            h: h.wait()?,
-            l: l.wait()?
+            h,
+            l
         };
         Ok(builder)
     }
@@ -109,40 +136,37 @@ fn into_primefield<E>(assignment: ProvingAssignment<E>) -> (AssignmentField<E>,
 where
     E: Engine
 {
-    let input = Arc::new(
-        assignment.input
-            .into_iter()
-            .map(|s| s.into_repr())
-            .collect::<Vec<_>>(),
-    );
-
-    let aux = Arc::new(
-        assignment.aux
-            .into_iter()
-            .map(|s| s.into_repr())
-            .collect::<Vec<_>>(),
-    );
+    let input = assignment.input
+        .into_iter()
+        .map(|s| s.into_repr())
+        .collect::<Vec<_>>();
+
+    let aux = assignment.aux
+        .into_iter()
+        .map(|s| s.into_repr())
+        .collect::<Vec<_>>();
 
     (input, aux)
 }
 
-fn try_h<E,P>(eval: &mut PolynomialEvaluation<E>, params: &mut P) -> Result<impl Future<Item=E::G1, Error=SynthesisError>>
+fn try_h<E,S>(h: S, coeffs: &[<E::Fr as PrimeField>::Repr]) -> Result<E::G1>
 where
     E: Engine,
-    P: ParameterSource<E>
+    S: SourceBuilder<E::G1Affine>
 {
-    let linear_coeffs: _ = fourier::evaluate_coefficients(eval)?;
-    let multi_exponentiated_coeffs: _ = multiexp(params.get_h()?, FullDensity, linear_coeffs);
-    Ok(multi_exponentiated_coeffs)
+    multiexp_slice(h, &FullDensity, coeffs)
+        .map_err(|e| e.in_query(Query::H))
 }
 
-fn try_l<E,P>(aux: &AssignmentField<E>, params: &mut P) -> Result<impl Future<Item=E::G1, Error=SynthesisError>> 
+fn try_l<E,S>(l: S, aux: &[<E::Fr as PrimeField>::Repr]) -> Result<E::G1>
 where
     E: Engine,
-    P: ParameterSource<E>
+    S: SourceBuilder<E::G1Affine>
 {
-    let l: _ = multiexp(params.get_l()?, FullDensity, aux.clone());
-    Ok(l)
+    // Every auxiliary variable has an L base, so unlike the A and B queries
+    // there is no synthesis-time density to plan from.
+    multiexp_slice(l, &FullDensity, aux)
+        .map_err(|e| e.in_query(Query::L))
 }
 
 fn try_vk<E,P>(params: &mut P) -> Result<VerifyingKey<E>> 
@@ -1,39 +1,39 @@
-use std::sync::Arc;
-
 use ff::PrimeField;
 use pairing::Engine;
 
-use crate::domain::{Domain, Scalar};
+use crate::domain::{Domain, DomainPrecomputation, Scalar};
 use super::{PolynomialEvaluation, AssignmentField, Result};
 
-pub fn evaluate_coefficients<E>(eval: &mut PolynomialEvaluation<E>) -> Result<AssignmentField<E>>
+/// Computes the coefficients of the quotient polynomial over the domain of
+/// `domain_size` points, or the smallest domain that fits the circuit if
+/// that size cannot be used. The transforms read their powers of the roots
+/// of unity from `precomputation` when there is one, and run in place when
+/// `in_place` is set.
+pub fn evaluate_coefficients<E>(eval: PolynomialEvaluation<E>, domain_size: usize, precomputation: Option<&DomainPrecomputation<E>>, in_place: bool) -> Result<AssignmentField<E>>
 where
     E: Engine
 {
-    let fourier_eval_domain: _ = FourierEvaluationDomain::new(eval)?;
-    fourier_eval_domain.coeffs_by_fft()
+    let fourier_eval_domain: _ = FourierEvaluationDomain { domain_size, precomputation, in_place };
+    fourier_eval_domain.coeffs_by_fft(eval)
 }
 
-struct FourierEvaluationDomain<E: Engine> {
-    a: Domain<E,Scalar<E>>,
-    b: Domain<E,Scalar<E>>,
-    c: Domain<E,Scalar<E>>,
+/// Moves the a, b and c evaluations onto the coset one at a time, each
+/// consumed into its domain and folded into the a domain as soon as it is
+/// there. No more than the three evaluations the stage is given are ever
+/// held, plus a scratch buffer for the FFT unless it runs in place.
+struct FourierEvaluationDomain<'a, E: Engine> {
+    domain_size: usize,
+    precomputation: Option<&'a DomainPrecomputation<E>>,
+    in_place: bool
 }
 
-impl<E> FourierEvaluationDomain<E> 
+impl<'a,E> FourierEvaluationDomain<'a,E> 
 where
     E: Engine
 {
-    fn new(eval: &mut PolynomialEvaluation<E>) -> Result<Self> {
-        let a = Domain::new(eval.a.take()?)?;
-        let b = Domain::new(eval.b.take()?)?;
-        let c = Domain::new(eval.c.take()?)?;
-        Ok(FourierEvaluationDomain {a, b, c})
-    }
-
     // The efficiency shortcut for building coefficients from the groth16 paper.
-    fn coeffs_by_fft(self) ->  Result<AssignmentField<E>> {
-        let mut a: _ = self.transform_field().into_coefficients()?;
+    fn coeffs_by_fft(&self, eval: PolynomialEvaluation<E>) ->  Result<AssignmentField<E>> {
+        let mut a: _ = self.into_coefficients(eval)?;
         let new_len = a.len() - 1;
         a.truncate(new_len);
 
@@ -41,33 +41,33 @@ where
             .map(|s| s.0.into_repr())
             .collect();
             
-        Ok(Arc::new(repr))
+        Ok(repr)
     }   
 
-    fn transform_field(mut self) -> Self {
-        self.a.ifft();
-        self.a.coset_fft();
-
-        self.b.ifft();
-        self.b.coset_fft();
-
-        self.c.ifft();
-        self.c.coset_fft();
-
-        self
+    fn onto_coset(&self, evals: Vec<Scalar<E>>) -> Result<Domain<E,Scalar<E>>> {
+        let mut domain: _ = Domain::with_preferred_size(evals, self.domain_size)?;
+        if self.in_place {
+            domain = domain.with_in_place_fft();
+        }
+        if let Some(precomputation) = self.precomputation {
+            domain = domain.with_precomputation(precomputation);
+        }
+        domain.ifft();
+        domain.coset_fft();
+        Ok(domain)
     }
 
-    fn into_coefficients(mut self) -> Result<Vec<Scalar<E>>> {
-        self.a *= &self.b;
-        drop(self.b);
+    fn into_coefficients(&self, eval: PolynomialEvaluation<E>) -> Result<Vec<Scalar<E>>> {
+        let PolynomialEvaluation { a, b, c }: PolynomialEvaluation<E> = eval;
 
-        self.a -= &self.c;
-        drop(self.c);
+        let mut a: _ = self.onto_coset(a?)?;
+        a *= &self.onto_coset(b?)?;
+        a -= &self.onto_coset(c?)?;
 
-        self.a.divide_over_coset()?;
-        self.a.icoset_fft();
+        a.divide_by_z_on_coset()?;
+        a.icoset_fft();
 
-        let coeffs: _ = self.a.into_coeffs();
+        let coeffs: _ = a.into_coeffs();
         Ok(coeffs)
     }
 }
@@ -1,21 +1,20 @@
-use std::sync::Arc;
-use super::{Future, SynthesisError};
+use super::SynthesisError;
 
 use ff::{Field, PrimeField, ScalarEngine};
 use pairing::Engine;
 
-use super::{ParameterSource, Result};
+use super::{ParameterSource, ProverMiddleware, Result, StageInfo};
 
 use crate::{ConstraintSystem, Index, LinearCombination, Coefficient};
 use crate::{domain, multiexp};
-use domain::Scalar;
+use domain::{DomainPrecomputation, Scalar};
 use multiexp::DensityTracker;
 
 mod builder;
 mod source;
 mod fourier;
 
-type AssignmentField<E> = Arc<Vec<<<E as ScalarEngine>::Fr as PrimeField>::Repr>>;
+type AssignmentField<E> = Vec<<<E as ScalarEngine>::Fr as PrimeField>::Repr>;
 
 pub struct ProvingSystem<E: Engine> {
     density: QueryDensity,
@@ -24,9 +23,19 @@ pub struct ProvingSystem<E: Engine> {
 }
 
 impl<E: Engine> ProvingSystem<E> {
-    pub fn prepare<T>(mut self, params: &mut T, r: E::Fr, s: E::Fr) -> Result<builder::Builder<E>>
+    /// The size of the synthesized circuit.
+    pub fn info(&self) -> StageInfo {
+        StageInfo {
+            num_inputs: self.assignment.input.len(),
+            num_aux: self.assignment.aux.len(),
+            num_constraints: self.eval.a.as_ref().map_or(0, |a| a.len())
+        }
+    }
+
+    pub fn prepare<T, M>(mut self, params: &mut T, r: E::Fr, s: E::Fr, precomputation: Option<&DomainPrecomputation<E>>, middleware: &mut M, info: &StageInfo) -> Result<builder::Builder<E>>
     where
-        T: ParameterSource<E>
+        T: ParameterSource<E>,
+        M: ProverMiddleware
     {
         for i in 0..self.assignment.input.len() {
             self.enforce(
@@ -37,7 +46,7 @@ impl<E: Engine> ProvingSystem<E> {
             );
         }
 
-        builder::Builder::try_new(self, params, r, s)
+        builder::Builder::try_new(self, params, r, s, precomputation, middleware, info)
     }
 }
 
@@ -104,6 +113,9 @@ where
         let eval_b: E::Fr = evalaluate_linear_combination(
             &b,
             |index| match index {
+                // The "one" input is only dense if its folded constant is
+                // non-zero, mirroring the parameter assembly.
+                Index::Input(0) => self.assignment.input[0],
                 Index::Input(i) => {
                     self.density.b_input.inc(i);
                     self.assignment.input[i]
@@ -114,6 +126,9 @@ where
                 }
             }
         );
+        if !fold_constant(&b).is_zero() {
+            self.density.b_input.inc(0);
+        }
         self.eval
             .b
             .as_mut()
@@ -168,6 +183,17 @@ where
         })
 }
 
+/// Sums the coefficients of the constant terms in `linear`.
+fn fold_constant<E: Engine>(linear: &LinearCombination<E>) -> E::Fr {
+    linear.0
+        .iter()
+        .filter(|(idx, _)| idx.get_unchecked() == Index::Input(0))
+        .fold(E::Fr::zero(), |mut acc, (_, coeff)| {
+            acc.add_assign(coeff);
+            acc
+        })
+}
+
 impl<E: Engine> Default for ProvingSystem<E> {
     fn default() -> Self {
         ProvingSystem {
@@ -1,36 +1,41 @@
-use std::sync::Arc;
+use ff::PrimeField;
 use pairing::Engine;
 
-use super::{Future, QueryDensity, ParameterSource, AssignmentField, Result};
-use crate::multiexp::{multiexp, FullDensity};
+use super::{QueryDensity, ParameterSource, Result};
+use crate::Query;
+use crate::multiexp::{multiexp_slice, FullDensity};
+use crate::multicore::MULTI_THREAD;
 
 mod source;
 
-pub struct SourceFactory<E>
+/// The A and B bases of a proof, fetched from the parameters ahead of the
+/// multiexps so that the H and L multiexps can run alongside them.
+pub struct SourceFactory<P,E>
 where
+    P: ParameterSource<E>,
     E: Engine
 {
-    answer: Answer<E>,
-    auxiliary: Auxiliary<E>
+    src: source::Source<P,E>
 }
 
-impl<E> SourceFactory<E>
+impl<P,E> SourceFactory<P,E>
 where
+    P: ParameterSource<E>,
     E: Engine
 {
-    pub(super) fn try_new<P>(density: QueryDensity, input: AssignmentField<E>, aux: AssignmentField<E>, params: &mut P) -> Result<Self> 
-    where
-        P: ParameterSource<E>
-    {
-        let mut src: _ = source::Source::try_new(density, input.len(), params)?;
-        Ok(SourceFactory {
-            answer: src.into_answer(input)?,
-            auxiliary: src.into_auxiliary(aux)?
-        })
+    pub(super) fn try_new(density: QueryDensity, num_inputs: usize, params: &mut P) -> Result<Self> {
+        let src: _ = source::Source::try_new(density, num_inputs, params)?;
+        Ok(SourceFactory { src })
     }
 
-    pub fn unpack(self) -> (Answer<E>, Auxiliary<E>) {
-        (self.answer, self.auxiliary)
+    /// Runs the input and auxiliary multiexps concurrently.
+    pub fn try_multiexp(self, input: &[<E::Fr as PrimeField>::Repr], aux: &[<E::Fr as PrimeField>::Repr]) -> Result<(Answer<E>, Auxiliary<E>)> {
+        let (answer_src, aux_src): _ = self.src.unpack();
+        let (answer, auxiliary): _ = MULTI_THREAD.join(
+            || Answer::try_new(answer_src, input),
+            || Auxiliary::try_new(aux_src, aux)
+        );
+        Ok((answer?, auxiliary?))
     }
 }
 
@@ -44,23 +49,24 @@ impl<E> Answer<E>
 where
     E: Engine
 {
-    pub fn try_new<P>(src: source::AnswerSource<P,E>, input: AssignmentField<E>) -> Result<Self> 
+    pub fn try_new<P>(src: source::AnswerSource<P,E>, input: &[<E::Fr as PrimeField>::Repr]) -> Result<Self> 
     where
         P: ParameterSource<E>
     {
-        let a: E::G1 = multiexp(src.a_input_src, FullDensity, input.clone()).wait()?;
+        let a: E::G1 = multiexp_slice(src.a_input_src, &FullDensity, input)
+            .map_err(|e| e.in_query(Query::A))?;
 
-        let b1: E::G1 = multiexp(
+        let b1: E::G1 = multiexp_slice(
             src.b1_input_src,
-            src.b_input_density.clone(),
-            input.clone(),
-        ).wait()?;
+            &src.b_input_density,
+            input,
+        ).map_err(|e| e.in_query(Query::B1))?;
 
-        let b2: E::G2 = multiexp(
+        let b2: E::G2 = multiexp_slice(
             src.b2_input_src,
-            src.b_input_density,
+            &src.b_input_density,
             input
-        ).wait()?;
+        ).map_err(|e| e.in_query(Query::B2))?;
 
         Ok(Answer { a, b1, b2 })
     }
@@ -76,27 +82,27 @@ impl<E> Auxiliary<E>
 where
     E: Engine
 {
-    pub fn try_new<P>(src: source::AuxiliarySource<P,E>, assignment: AssignmentField<E>) -> Result<Self> 
+    pub fn try_new<P>(src: source::AuxiliarySource<P,E>, assignment: &[<E::Fr as PrimeField>::Repr]) -> Result<Self> 
     where
         P: ParameterSource<E>
     {
-        let a: _ = multiexp(
+        let a: _ = multiexp_slice(
             src.a_aux_src,
-            Arc::new(src.a_aux_density),
-            assignment.clone(),
-        ).wait()?;
+            &src.a_aux_density,
+            assignment,
+        ).map_err(|e| e.in_query(Query::A))?;
 
-        let b1: _ = multiexp(
+        let b1: _ = multiexp_slice(
             src.b1_aux_src,
-            src.b_aux_density.clone(),
-            assignment.clone(),
-        ).wait()?;
+            &src.b_aux_density,
+            assignment,
+        ).map_err(|e| e.in_query(Query::B1))?;
 
-        let b2 = multiexp(
+        let b2 = multiexp_slice(
             src.b2_aux_src, 
-            src.b_aux_density, 
+            &src.b_aux_density, 
             assignment
-        ).wait()?;
+        ).map_err(|e| e.in_query(Query::B2))?;
 
         Ok(Auxiliary{ a, b1, b2 })
     }
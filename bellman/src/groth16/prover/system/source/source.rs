@@ -1,12 +1,11 @@
-use std::sync::Arc;
 use pairing::Engine;
 
-use super::{ParameterSource, QueryDensity, AssignmentField, Result, source};
+use super::{ParameterSource, QueryDensity, Result, source};
 use crate::multiexp::DensityTracker;
 
 pub struct Source<P: ParameterSource<E>, E: Engine> {
-    answer: Option<source::AnswerSource<P,E>>,
-    aux: Option<source::AuxiliarySource<P,E>>,
+    answer: source::AnswerSource<P,E>,
+    aux: source::AuxiliarySource<P,E>,
 }
 
 impl<P,E> Source<P,E>
@@ -16,8 +15,8 @@ where
 {
     pub(super) fn try_new(density: QueryDensity, a_input: usize, params: &mut P) -> Result<Self> {
         let a_aux_density: _ = density.a_aux;
-        let b_input_density: _ = Arc::new(density.b_input);
-        let b_aux_density: _ = Arc::new(density.b_aux);
+        let b_input_density: _ = density.b_input;
+        let b_aux_density: _ = density.b_aux;
 
         let b_input_total: usize = b_input_density.get_total_density();
         
@@ -41,17 +40,13 @@ where
         );
         
         Ok(Source {
-            answer: Some(answer_src),
-            aux: Some(aux_src)
+            answer: answer_src,
+            aux: aux_src
         })
     }        
 
-    pub fn into_answer(&mut self, input: AssignmentField<E>) -> Result<super::Answer<E>> {
-        super::Answer::try_new(self.answer.take()?, input)
-    }
-
-    pub fn into_auxiliary(&mut self, aux: AssignmentField<E>) -> Result<super::Auxiliary<E>> {
-        super::Auxiliary::try_new(self.aux.take()?, aux)
+    pub fn unpack(self) -> (source::AnswerSource<P,E>, source::AuxiliarySource<P,E>) {
+        (self.answer, self.aux)
     }
 }
 
@@ -59,8 +54,10 @@ pub struct AnswerSource<P: ParameterSource<E>, E: Engine> {
     pub a_input_src: P::G1Builder,
     pub b1_input_src: P::G1Builder,
     pub b2_input_src: P::G2Builder,
-    pub b_input_density: Arc<DensityTracker>,
-    _marker: std::marker::PhantomData<E>
+    pub b_input_density: DensityTracker,
+    // A marker which is `Send` whatever the engine, as the sources are
+    // handed to the thread which runs their multiexps.
+    _marker: std::marker::PhantomData<fn() -> E>
 }
 
 impl<P,E> AnswerSource<P,E>
@@ -72,7 +69,7 @@ where
         a_input_src: P::G1Builder,
         b1_input_src: P::G1Builder,
         b2_input_src: P::G2Builder,
-        b_input_density: Arc<DensityTracker>
+        b_input_density: DensityTracker
     ) -> Self {
         AnswerSource {
             a_input_src,
@@ -89,8 +86,8 @@ pub struct AuxiliarySource<P: ParameterSource<E>, E: Engine> {
     pub b1_aux_src: P::G1Builder,
     pub b2_aux_src: P::G2Builder,
     pub a_aux_density: DensityTracker,
-    pub b_aux_density: Arc<DensityTracker>,
-    _marker: std::marker::PhantomData<E>
+    pub b_aux_density: DensityTracker,
+    _marker: std::marker::PhantomData<fn() -> E>
 }
 
 impl<P,E> AuxiliarySource<P,E>
@@ -103,7 +100,7 @@ where
         b1_aux_src: P::G1Builder,
         b2_aux_src: P::G2Builder,
         a_aux_density: DensityTracker,
-        b_aux_density: Arc<DensityTracker>
+        b_aux_density: DensityTracker
     ) -> Self {
         AuxiliarySource {
             a_aux_src,
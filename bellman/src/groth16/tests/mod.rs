@@ -8,7 +8,13 @@ use std::marker::PhantomData;
 
 use crate::{Circuit, ConstraintSystem, SynthesisError};
 
-use super::{create_proof, generate_parameters, prepare_verifying_key, verify_proof};
+use super::{
+    create_proof, create_random_proof, generate_parameters, generate_parameters_with_layout,
+    generate_parameters_with_symbols,
+    generate_random_parameters, prepare_verifying_key, public_input_layout, verify_proof,
+    verify_proof_labelled,
+    write_r1cs, ConstraintSymbols, ProverRng,
+};
 
 struct XORDemo<E: Engine> {
     a: Option<bool>,
@@ -119,29 +125,37 @@ fn test_xordemo() {
     //     (a_0) * 0 = 0
     //     (a_1) * 0 = 0
 
-    // The evaluation domain is 8. The H query should
-    // have 7 elements (it's a quotient polynomial)
-    assert_eq!(7, params.h.len());
+    // The evaluation domain is 6, which is smaller than the power of
+    // two 8. The H query should have 5 elements (it's a quotient
+    // polynomial)
+    assert_eq!(5, params.h.len());
 
     let mut root_of_unity = Fr::root_of_unity();
 
     // We expect this to be a 2^10 root of unity
     assert_eq!(Fr::one(), root_of_unity.pow(&[1 << 10]));
 
-    // Let's turn it into a 2^3 root of unity.
-    root_of_unity = root_of_unity.pow(&[1 << 7]);
-    assert_eq!(Fr::one(), root_of_unity.pow(&[1 << 3]));
-    assert_eq!(Fr::from_str("20201").unwrap(), root_of_unity);
+    // Let's turn it into a 2^1 root of unity, which is -1.
+    root_of_unity = root_of_unity.pow(&[1 << 9]);
+    assert_eq!(Fr::from_str("64512").unwrap(), root_of_unity);
+
+    // Multiplying by the cube root of unity (sqrt(-3) - 1) / 2 makes it
+    // a primitive 6th root of unity.
+    let cube_root_of_unity = Fr::from_str("17314").unwrap();
+    assert_eq!(Fr::one(), cube_root_of_unity.pow(&[3]));
+    root_of_unity.mul_assign(&cube_root_of_unity);
+    assert_eq!(Fr::one(), root_of_unity.pow(&[6]));
+    assert_eq!(Fr::from_str("47199").unwrap(), root_of_unity);
 
     // Let's compute all the points in our evaluation domain.
-    let mut points = Vec::with_capacity(8);
-    for i in 0..8 {
+    let mut points = Vec::with_capacity(6);
+    for i in 0..6 {
         points.push(root_of_unity.pow(&[i]));
     }
 
     // Let's compute t(tau) = (tau - p_0)(tau - p_1)...
-    //                      = tau^8 - 1
-    let mut t_at_tau = tau.pow(&[8]);
+    //                      = tau^6 - 1
+    let mut t_at_tau = tau.pow(&[6]);
     t_at_tau.sub_assign(&Fr::one());
     {
         let mut tmp = Fr::one();
@@ -153,7 +167,7 @@ fn test_xordemo() {
         assert_eq!(tmp, t_at_tau);
     }
 
-    // We expect our H query to be 7 elements of the form...
+    // We expect our H query to be 5 elements of the form...
     // {tau^i t(tau) / delta}
     let delta_inverse = delta.inverse().unwrap();
     let gamma_inverse = gamma.inverse().unwrap();
@@ -203,25 +217,25 @@ fn test_xordemo() {
 
     sage: r = 64513
     sage: Fr = GF(r)
-    sage: omega = (Fr(5)^63)^(2^7)
+    sage: omega = Fr(47199)
     sage: tau = Fr(3673)
     sage: R.<x> = PolynomialRing(Fr, 'x')
     sage: def eval(tau, c0, c1, c2, c3, c4):
-    ....:     p = R.lagrange_polynomial([(omega^0, c0), (omega^1, c1), (omega^2, c2), (omega^3, c3), (omega^4, c4), (omega^5, 0), (omega^6, 0), (omega^7, 0)])
+    ....:     p = R.lagrange_polynomial([(omega^0, c0), (omega^1, c1), (omega^2, c2), (omega^3, c3), (omega^4, c4), (omega^5, 0)])
     ....:     return p.substitute(tau)
     sage: eval(tau, 1, 1, 0, 1, 0)
-    59158
+    31156
     */
 
-    let u_i = [59158, 48317, 21767, 10402]
+    let u_i = [31156, 63857, 33279, 33914]
         .iter()
         .map(|e| Fr::from_str(&format!("{}", e)).unwrap())
         .collect::<Vec<Fr>>();
-    let v_i = [0, 0, 60619, 30791]
+    let v_i = [0, 0, 55514, 42739]
         .iter()
         .map(|e| Fr::from_str(&format!("{}", e)).unwrap())
         .collect::<Vec<Fr>>();
-    let w_i = [0, 23320, 41193, 41193]
+    let w_i = [0, 52373, 12140, 12140]
         .iter()
         .map(|e| Fr::from_str(&format!("{}", e)).unwrap())
         .collect::<Vec<Fr>>();
@@ -293,10 +307,10 @@ fn test_xordemo() {
     };
 
     // A(x) =
-    //  a_0 * (44865*x^7 + 56449*x^6 + 44865*x^5 + 8064*x^4 + 3520*x^3 + 56449*x^2 + 3520*x + 40321) +
-    //  a_1 * (8064*x^7 + 56449*x^6 + 8064*x^5 + 56449*x^4 + 8064*x^3 + 56449*x^2 + 8064*x + 56449) +
-    //  a_2 * (16983*x^7 + 24192*x^6 + 63658*x^5 + 56449*x^4 + 16983*x^3 + 24192*x^2 + 63658*x + 56449) +
-    //  a_3 * (5539*x^7 + 27797*x^6 + 6045*x^5 + 56449*x^4 + 58974*x^3 + 36716*x^2 + 58468*x + 8064) +
+    //  a_0 * (40123*x^5 + 29371*x^4 + 10752*x^3 + 2886*x^2 + 13638*x + 32257) +
+    //  a_1 * (24390*x^5 + 50875*x^4 + 53761*x^3 + 24390*x^2 + 50875*x + 53761) +
+    //  a_2 * (47989*x^5 + 59532*x^4 + 53761*x^3 + 47989*x^2 + 59532*x + 53761) +
+    //  a_3 * (24390*x^5 + 13638*x^4 + 53761*x^3 + 40123*x^2 + 50875*x + 10752) +
     {
         // proof A = alpha + A(tau) + delta * r
         let mut expected_a = delta;
@@ -312,8 +326,8 @@ fn test_xordemo() {
     // B(x) =
     // a_0 * (0) +
     // a_1 * (0) +
-    // a_2 * (56449*x^7 + 56449*x^6 + 56449*x^5 + 56449*x^4 + 56449*x^3 + 56449*x^2 + 56449*x + 56449) +
-    // a_3 * (31177*x^7 + 44780*x^6 + 21752*x^5 + 42255*x^3 + 35861*x^2 + 33842*x + 48385)
+    // a_2 * (53761*x^5 + 53761*x^4 + 53761*x^3 + 53761*x^2 + 53761*x + 53761) +
+    // a_3 * (26485*x^5 + 10752*x^4 + 10752*x^2 + 38028*x + 43009)
     {
         // proof B = beta + B(tau) + delta * s
         let mut expected_b = delta;
@@ -328,17 +342,17 @@ fn test_xordemo() {
 
     // C(x) =
     // a_0 * (0) +
-    // a_1 * (27797*x^7 + 56449*x^6 + 36716*x^5 + 8064*x^4 + 27797*x^3 + 56449*x^2 + 36716*x + 8064) +
-    // a_2 * (36716*x^7 + 8064*x^6 + 27797*x^5 + 56449*x^4 + 36716*x^3 + 8064*x^2 + 27797*x + 56449) +
-    // a_3 * (36716*x^7 + 8064*x^6 + 27797*x^5 + 56449*x^4 + 36716*x^3 + 8064*x^2 + 27797*x + 56449)
+    // a_1 * (13638*x^5 + 40123*x^4 + 10752*x^3 + 13638*x^2 + 40123*x + 10752) +
+    // a_2 * (50875*x^5 + 24390*x^4 + 53761*x^3 + 50875*x^2 + 24390*x + 53761) +
+    // a_3 * (50875*x^5 + 24390*x^4 + 53761*x^3 + 50875*x^2 + 24390*x + 53761)
     //
     // If A * B = C at each point in the domain, then the following polynomial...
     // P(x) = A(x) * B(x) - C(x)
-    //      = 49752*x^14 + 13914*x^13 + 29243*x^12 + 27227*x^11 + 62362*x^10 + 35703*x^9 + 4032*x^8 + 14761*x^6 + 50599*x^5 + 35270*x^4 + 37286*x^3 + 2151*x^2 + 28810*x + 60481
+    //      = 61759*x^10 + 63551*x^9 + 61759*x^8 + 63551*x^7 + 8960*x^6 + 2754*x^4 + 962*x^3 + 2754*x^2 + 962*x + 55553
     //
     // ... should be divisible by t(x), producing the quotient polynomial:
     // h(x) = P(x) / t(x)
-    //      = 49752*x^6 + 13914*x^5 + 29243*x^4 + 27227*x^3 + 62362*x^2 + 35703*x + 4032
+    //      = 61759*x^4 + 63551*x^3 + 61759*x^2 + 63551*x + 8960
     {
         let mut expected_c = Fr::zero();
 
@@ -363,7 +377,7 @@ fn test_xordemo() {
         expected_c.add_assign(&params.l[0]);
 
         // H query answer
-        for (i, coeff) in [5040, 11763, 10755, 63633, 128, 9747, 8739]
+        for (i, coeff) in [8960, 63551, 61759, 63551, 61759]
             .iter()
             .enumerate()
         {
@@ -379,3 +393,755 @@ fn test_xordemo() {
 
     assert!(verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
 }
+
+#[test]
+fn test_xordemo_symbols() {
+    let g1 = Fr::one();
+    let g2 = Fr::one();
+    let alpha = Fr::from_str("48577").unwrap();
+    let beta = Fr::from_str("22580").unwrap();
+    let gamma = Fr::from_str("53332").unwrap();
+    let delta = Fr::from_str("5481").unwrap();
+    let tau = Fr::from_str("3673").unwrap();
+
+    let c = XORDemo::<DummyEngine> {
+        a: None,
+        b: None,
+        _marker: PhantomData,
+    };
+
+    let (params, symbols) =
+        generate_parameters_with_symbols(c, g1, g2, alpha, beta, gamma, delta, tau).unwrap();
+    assert_eq!(5, params.h.len());
+
+    // The three circuit constraints, followed by the two input constraints.
+    assert_eq!(symbols.len(), 5);
+    assert_eq!(symbols.resolve(0), Some("a_boolean_constraint"));
+    assert_eq!(symbols.resolve(1), Some("b_boolean_constraint"));
+    assert_eq!(symbols.resolve(2), Some("c_xor_constraint"));
+    assert_eq!(symbols.resolve(5), None);
+
+    let mut v = vec![];
+    symbols.write(&mut v).unwrap();
+    let de_symbols = ConstraintSymbols::read(&v[..]).unwrap();
+    assert_eq!(symbols, de_symbols);
+
+    // Tables and names claiming more than follows are errors.
+    assert!(ConstraintSymbols::read(&[0xff, 0xff, 0xff, 0xff][..]).is_err());
+    assert!(ConstraintSymbols::read(&[0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, b'a'][..]).is_err());
+}
+
+#[test]
+fn test_xordemo_r1cs_export() {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    let c = XORDemo::<DummyEngine> {
+        a: None,
+        b: None,
+        _marker: PhantomData,
+    };
+
+    let mut v = vec![];
+    write_r1cs(c, &mut v).unwrap();
+
+    assert_eq!(&v[0..4], b"r1cs");
+    assert_eq!(LittleEndian::read_u32(&v[4..8]), 1);
+    assert_eq!(LittleEndian::read_u32(&v[8..12]), 3);
+
+    // Header section: an 8 byte field with 4 wires, 1 public input
+    // (excluding ONE) and the 3 constraints of the circuit.
+    assert_eq!(LittleEndian::read_u32(&v[12..16]), 1);
+    let header_len = LittleEndian::read_u64(&v[16..24]) as usize;
+    let header = &v[24..24 + header_len];
+    assert_eq!(LittleEndian::read_u32(&header[0..4]), 8);
+    assert_eq!(LittleEndian::read_u64(&header[4..12]), 64513);
+    assert_eq!(LittleEndian::read_u32(&header[12..16]), 4);
+    assert_eq!(LittleEndian::read_u32(&header[20..24]), 1);
+    assert_eq!(LittleEndian::read_u64(&header[28..36]), 4);
+    assert_eq!(LittleEndian::read_u32(&header[36..40]), 3);
+
+    // The XOR constraint's A term folds `a + a` into a single wire.
+    let rest = &v[24 + header_len..];
+    assert_eq!(LittleEndian::read_u32(&rest[0..4]), 2);
+    let constraints_len = LittleEndian::read_u64(&rest[4..12]) as usize;
+    let constraints = &rest[12..12 + constraints_len];
+    let per_factor = 4 + 8;
+    let first = 4 + 2 * per_factor + 4 + per_factor + 4;
+    let second = 4 + 2 * per_factor + 4 + per_factor + 4;
+    let third = &constraints[first + second..];
+    assert_eq!(LittleEndian::read_u32(&third[0..4]), 1);
+    assert_eq!(LittleEndian::read_u32(&third[4..8]), 2);
+    assert_eq!(LittleEndian::read_u64(&third[8..16]), 2);
+}
+
+#[test]
+fn test_public_input_layout() {
+    struct NamedInputs;
+
+    impl<E: Engine> Circuit<E> for NamedInputs {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            let x = cs.alloc(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+            let square = cs.alloc_input_named("square", || Err(SynthesisError::AssignmentMissing))?;
+            let unnamed = cs.alloc_input(|| "unnamed", || Err(SynthesisError::AssignmentMissing))?;
+            let double = cs
+                .namespace(|| "nested")
+                .alloc_input_named("double", || Err(SynthesisError::AssignmentMissing))?;
+
+            cs.enforce(|| "square", |lc| lc + x, |lc| lc + x, |lc| lc + square);
+            cs.enforce_equal(|| "unnamed", |lc| lc + x, |lc| lc + unnamed);
+            cs.enforce_equal(|| "double", |lc| lc + x + x, |lc| lc + double);
+
+            Ok(())
+        }
+    }
+
+    let layout = public_input_layout::<DummyEngine, _>(NamedInputs).unwrap();
+
+    // Names are qualified by the namespace they were allocated in.
+    assert_eq!(layout.len(), 2);
+    assert_eq!(layout.position("square"), Some(0));
+    assert_eq!(layout.position("nested/double"), Some(2));
+    assert_eq!(layout.position("double"), None);
+    assert_eq!(layout.position("unnamed"), None);
+    assert_eq!(
+        layout.iter().collect::<Vec<_>>(),
+        vec![("square", 0), ("nested/double", 2)]
+    );
+
+    // The same name in two namespaces is two inputs, as when a gadget is
+    // used twice, but a name allocated twice in one namespace is an error
+    // rather than a panic.
+    struct Repeated(bool);
+
+    impl<E: Engine> Circuit<E> for Repeated {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            for i in 0..2 {
+                let name = if self.0 { "gadget".to_string() } else { format!("gadget {}", i) };
+                let mut cs = cs.namespace(|| name);
+                cs.alloc_input_named("x", || Err(SynthesisError::AssignmentMissing))?;
+            }
+
+            Ok(())
+        }
+    }
+
+    let layout = public_input_layout::<DummyEngine, _>(Repeated(false)).unwrap();
+    assert_eq!(
+        layout.iter().collect::<Vec<_>>(),
+        vec![("gadget 0/x", 0), ("gadget 1/x", 1)]
+    );
+
+    match public_input_layout::<DummyEngine, _>(Repeated(true)) {
+        Err(SynthesisError::DuplicateInput(name)) => assert_eq!(name, "gadget/x"),
+        _ => panic!("expected a duplicate input to be rejected"),
+    }
+}
+
+#[test]
+fn test_verify_proof_labelled() {
+    use rand::rngs::StdRng;
+    use std::collections::BTreeMap;
+
+    // Proves knowledge of x such that x^2 = square and x + x = double
+    struct Labelled<E: Engine> {
+        x: Option<E::Fr>,
+    }
+
+    impl<E: Engine> Circuit<E> for Labelled<E> {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            let value = self.x;
+            let x = cs.alloc(|| "x", || value.ok_or(SynthesisError::AssignmentMissing))?;
+            let square = cs.alloc_input_named("square", || {
+                let mut tmp = value.ok_or(SynthesisError::AssignmentMissing)?;
+                tmp.square();
+                Ok(tmp)
+            })?;
+            let double = cs.alloc_input_named("double", || {
+                let mut tmp = value.ok_or(SynthesisError::AssignmentMissing)?;
+                tmp.double();
+                Ok(tmp)
+            })?;
+
+            cs.enforce(|| "square", |lc| lc + x, |lc| lc + x, |lc| lc + square);
+            cs.enforce_equal(|| "double", |lc| lc + x + x, |lc| lc + double);
+
+            Ok(())
+        }
+    }
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x44; 32]);
+    let (params, layout) = generate_parameters_with_layout::<DummyEngine, _>(
+        Labelled { x: None },
+        Fr::one(),
+        Fr::one(),
+        Fr::from_str("48577").unwrap(),
+        Fr::from_str("22580").unwrap(),
+        Fr::from_str("53332").unwrap(),
+        Fr::from_str("5481").unwrap(),
+        Fr::from_str("3673").unwrap(),
+    ).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    assert_eq!(layout, public_input_layout::<DummyEngine, _>(Labelled { x: None }).unwrap());
+
+    let x = Fr::from_str("7").unwrap();
+    let proof = create_random_proof(Labelled::<DummyEngine> { x: Some(x) }, &params, rng).unwrap();
+
+    let mut inputs = BTreeMap::new();
+    inputs.insert("double".to_string(), Fr::from_str("14").unwrap());
+    inputs.insert("square".to_string(), Fr::from_str("49").unwrap());
+    assert!(verify_proof_labelled(&pvk, &proof, &layout, &inputs).unwrap());
+
+    // Swapping the values between labels is caught.
+    let mut swapped = BTreeMap::new();
+    swapped.insert("double".to_string(), Fr::from_str("49").unwrap());
+    swapped.insert("square".to_string(), Fr::from_str("14").unwrap());
+    assert!(!verify_proof_labelled(&pvk, &proof, &layout, &swapped).unwrap());
+
+    let mut missing = inputs.clone();
+    missing.remove("double");
+    match verify_proof_labelled(&pvk, &proof, &layout, &missing) {
+        Err(SynthesisError::MissingInput(label)) => assert_eq!(label, "double"),
+        _ => panic!("expected a missing input"),
+    }
+
+    let mut extra = inputs.clone();
+    extra.insert("cube".to_string(), Fr::one());
+    match verify_proof_labelled(&pvk, &proof, &layout, &extra) {
+        Err(SynthesisError::UnexpectedInput(label)) => assert_eq!(label, "cube"),
+        _ => panic!("expected an unexpected input"),
+    }
+}
+
+/// Proves knowledge of `a` and `b` such that `a * b = c` for public `c`.
+struct MultiplyDemo<E: Engine> {
+    a: Option<E::Fr>,
+    b: Option<E::Fr>,
+}
+
+impl<E: Engine> Circuit<E> for MultiplyDemo<E> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let a = cs.alloc(|| "a", || self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.alloc(|| "b", || self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.alloc_input(
+            || "c",
+            || {
+                let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                a.mul_assign(&b);
+                Ok(a)
+            },
+        )?;
+
+        cs.enforce(|| "a*b=c", |lc| lc + a, |lc| lc + b, |lc| lc + c);
+
+        Ok(())
+    }
+}
+
+// Runs the full generate/prove/verify pipeline over an arbitrary engine.
+fn test_pipeline<E: Engine>() {
+    use rand::rngs::StdRng;
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x42; 32]);
+
+    let params =
+        generate_random_parameters::<E, _, _>(MultiplyDemo::<E> { a: None, b: None }, rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&params.vk);
+
+    for _ in 0..10 {
+        let a = E::Fr::random(rng);
+        let b = E::Fr::random(rng);
+        let mut c = a;
+        c.mul_assign(&b);
+
+        let proof = create_random_proof(
+            MultiplyDemo::<E> {
+                a: Some(a),
+                b: Some(b),
+            },
+            &params,
+            rng,
+        )
+        .unwrap();
+
+        let mut wrong = c;
+        wrong.add_assign(&E::Fr::one());
+
+        assert!(verify_proof(&pvk, &proof, &[c]).unwrap());
+        assert!(!verify_proof(&pvk, &proof, &[wrong]).unwrap());
+        assert!(verify_proof(&pvk, &proof, &[c, c]).is_err());
+    }
+}
+
+#[test]
+fn test_pipeline_dummy_engine() {
+    test_pipeline::<DummyEngine>();
+}
+
+#[test]
+fn test_pipeline_bls12() {
+    test_pipeline::<pairing::bls12_381::Bls12>();
+}
+
+#[test]
+fn test_verification_cache() {
+    use super::VerificationCache;
+    use pairing::bls12_381::{Bls12, Fr};
+    use rand::rngs::StdRng;
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x23; 32]);
+
+    let params = generate_random_parameters::<Bls12, _, _>(
+        MultiplyDemo::<Bls12> { a: None, b: None },
+        rng,
+    )
+    .unwrap();
+
+    let a = Fr::from_str("3").unwrap();
+    let b = Fr::from_str("7").unwrap();
+    let c = Fr::from_str("21").unwrap();
+    let wrong = Fr::from_str("22").unwrap();
+
+    let proof = create_random_proof(
+        MultiplyDemo::<Bls12> {
+            a: Some(a),
+            b: Some(b),
+        },
+        &params,
+        rng,
+    )
+    .unwrap();
+
+    let cache = VerificationCache::<Bls12>::new(&params.vk, 2);
+    assert!(cache.verify_proof(&proof, &[c]).unwrap());
+    assert!(cache.verify_proof(&proof, &[c]).unwrap());
+    assert_eq!(cache.metrics().hits, 1);
+    assert_eq!(cache.metrics().misses, 1);
+
+    // Invalid results are remembered too, and errors are not.
+    assert!(!cache.verify_proof(&proof, &[wrong]).unwrap());
+    assert!(!cache.verify_proof(&proof, &[wrong]).unwrap());
+    assert!(cache.verify_proof(&proof, &[c, c]).is_err());
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.metrics().hits, 2);
+    assert_eq!(cache.metrics().hit_rate(), 0.5);
+
+    // A different proof of the same statement is a new entry, which
+    // evicts the least recently used one.
+    let other = create_random_proof(
+        MultiplyDemo::<Bls12> {
+            a: Some(a),
+            b: Some(b),
+        },
+        &params,
+        rng,
+    )
+    .unwrap();
+    assert!(cache.verify_proof(&other, &[c]).unwrap());
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.metrics().evictions, 1);
+    assert!(cache.verify_proof(&proof, &[wrong]).is_ok());
+    assert_eq!(cache.metrics().hits, 3);
+
+    // The first entry was the one evicted, and is verified again.
+    assert!(cache.verify_proof(&proof, &[c]).unwrap());
+    assert_eq!(cache.metrics().misses, 4);
+
+    cache.clear();
+    assert!(cache.is_empty());
+
+    // A cache answers only for its own key.
+    let unrelated = generate_random_parameters::<Bls12, _, _>(
+        MultiplyDemo::<Bls12> { a: None, b: None },
+        rng,
+    )
+    .unwrap();
+    let cache = VerificationCache::<Bls12>::new(&unrelated.vk, 2);
+    assert!(!cache.verify_proof(&proof, &[c]).unwrap());
+}
+
+#[test]
+fn test_prover_rng_deterministic() {
+    use rand::rngs::StdRng;
+    use rand_core::RngCore;
+
+    let mut a = ProverRng::<StdRng>::from_seed([7; 32]);
+    let mut b = ProverRng::<StdRng>::from_seed([7; 32]);
+    assert_eq!(a.next_u64(), b.next_u64());
+
+    // Reseeding draws from the operating system and diverges from the seeded stream.
+    b.reseed().unwrap();
+    assert_ne!(a.next_u64(), b.next_u64());
+}
+
+#[test]
+fn test_recorded_circuit_replay() {
+    use crate::{Index, RecordedCircuit};
+    use rand::rngs::StdRng;
+    use std::collections::BTreeMap;
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x11; 32]);
+
+    let recorded = RecordedCircuit::<DummyEngine>::record(MultiplyDemo::<DummyEngine> {
+        a: None,
+        b: None,
+    })
+    .unwrap();
+
+    let mut buf = vec![];
+    recorded.write(&mut buf).unwrap();
+    let recorded = RecordedCircuit::<DummyEngine>::read(&buf[..]).unwrap();
+
+    let params = generate_random_parameters(recorded.replay(None), rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let a = Fr::from_str("3").unwrap();
+    let b = Fr::from_str("7").unwrap();
+    let c = Fr::from_str("21").unwrap();
+
+    let mut hints = BTreeMap::new();
+    hints.insert(0, a);
+    hints.insert(1, b);
+    let witness = recorded.calculate_witness(&[c], &hints).unwrap();
+
+    let proof = create_random_proof(recorded.replay(Some(&witness)), &params, rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, &[c]).unwrap());
+
+    // Proving requires the witness, and every variable in it.
+    assert!(create_random_proof(recorded.replay(None), &params, rng).is_err());
+    let mut short = witness.clone();
+    short.aux.pop();
+    match create_random_proof(recorded.replay(Some(&short)), &params, rng) {
+        Err(e @ SynthesisError::UnassignedVariable(Index::Aux(1))) => {
+            assert_eq!(e.to_string(), "no value was assigned to aux(1)");
+        },
+        _ => panic!("expected an unassigned variable")
+    }
+}
+
+#[test]
+fn test_parameter_determinism() {
+    use super::{audit_determinism, Determinism};
+    use pairing::bls12_381::Bls12;
+    use rand_xorshift::XorShiftRng;
+
+    let seed = [
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ];
+    let circuit = || MultiplyDemo::<Bls12> { a: None, b: None };
+
+    // Golden digests of these parameters, recorded per crate version.
+    let golden = [(
+        "0.1.0",
+        hex!("432a8ef3aef752e0ba7ca6701f5ab7ad3a679b27842d0e397982f142f43d8c81"),
+    )];
+
+    assert_eq!(
+        audit_determinism::<Bls12, _, XorShiftRng, _>(circuit, seed, &golden).unwrap(),
+        Determinism::Matches
+    );
+    assert!(match audit_determinism::<Bls12, _, XorShiftRng, _>(circuit, seed, &[]).unwrap() {
+        Determinism::Unrecorded(digest) => digest == golden[0].1,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_constants() {
+    use rand::rngs::StdRng;
+
+    // Proves knowledge of x such that (x + 3) * (x - 2 + 2) = y
+    struct ConstantDemo<E: Engine> {
+        x: Option<E::Fr>,
+    }
+
+    impl<E: Engine> Circuit<E> for ConstantDemo<E> {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+            let three = cs.alloc_constant(E::Fr::from_str("3").unwrap());
+            let two = cs.alloc_constant(E::Fr::from_str("2").unwrap());
+
+            let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.alloc_input(
+                || "y",
+                || {
+                    let x = self.x.ok_or(SynthesisError::AssignmentMissing)?;
+                    let mut y = x;
+                    y.add_assign(&three.get_value());
+                    y.mul_assign(&x);
+                    Ok(y)
+                },
+            )?;
+
+            cs.enforce(
+                || "(x + 3) * x = y",
+                |lc| lc + x + three,
+                |lc| lc + x - two + two,
+                |lc| lc + y,
+            );
+
+            Ok(())
+        }
+    }
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x33; 32]);
+    let params =
+        generate_random_parameters::<DummyEngine, _, _>(ConstantDemo { x: None }, rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+
+    let proof = create_random_proof(
+        ConstantDemo::<DummyEngine> {
+            x: Some(Fr::from_str("5").unwrap()),
+        },
+        &params,
+        rng,
+    )
+    .unwrap();
+
+    assert!(verify_proof(&pvk, &proof, &[Fr::from_str("40").unwrap()]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[Fr::from_str("41").unwrap()]).unwrap());
+
+    // Requesting a constant again allocates nothing, and gives back the
+    // same term on the "one" input.
+    let mut cs = crate::gadgets::test::TestConstraintSystem::<DummyEngine>::new();
+    let first = cs.alloc_constant(Fr::from_str("3").unwrap());
+    for _ in 0..10 {
+        assert_eq!(cs.alloc_constant(Fr::from_str("3").unwrap()), first);
+    }
+    assert_ne!(cs.alloc_constant(Fr::from_str("2").unwrap()), first);
+    assert_eq!(cs.num_inputs(), 1);
+    assert_eq!(cs.num_constraints(), 0);
+}
+
+#[test]
+fn test_prover_middleware() {
+    use crate::Worker;
+    use super::{
+        create_random_proof_with_middleware, ProverConfig, ProverMiddleware, Stage, StageInfo,
+    };
+    use rand::rngs::StdRng;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<(bool, Stage, StageInfo)>,
+    }
+
+    impl ProverMiddleware for Recorder {
+        fn before(&mut self, stage: Stage, info: &StageInfo, config: &mut ProverConfig) -> Result<(), SynthesisError> {
+            config.worker = Worker::with_threads(2);
+            self.events.push((true, stage, *info));
+            Ok(())
+        }
+
+        fn after(&mut self, stage: Stage, info: &StageInfo, _: Duration) {
+            self.events.push((false, stage, *info));
+        }
+    }
+
+    struct Veto(Stage);
+
+    impl ProverMiddleware for Veto {
+        fn before(&mut self, stage: Stage, _: &StageInfo, _: &mut ProverConfig) -> Result<(), SynthesisError> {
+            if stage == self.0 {
+                Err(SynthesisError::Vetoed(format!("{:?} is disabled", stage)))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x55; 32]);
+    let params = generate_random_parameters::<DummyEngine, _, _>(
+        MultiplyDemo { a: None, b: None },
+        rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    let circuit = || MultiplyDemo::<DummyEngine> {
+        a: Some(Fr::from_str("3").unwrap()),
+        b: Some(Fr::from_str("5").unwrap()),
+    };
+
+    let mut recorder = Recorder::default();
+    let proof = create_random_proof_with_middleware(circuit(), &params, rng, &mut recorder).unwrap();
+    assert!(verify_proof(&pvk, &proof, &[Fr::from_str("15").unwrap()]).unwrap());
+
+    let synthesized = StageInfo {
+        num_inputs: 2,
+        num_aux: 2,
+        num_constraints: 1,
+    };
+    assert_eq!(
+        recorder.events,
+        vec![
+            (true, Stage::Synthesis, StageInfo::default()),
+            (false, Stage::Synthesis, StageInfo::default()),
+            (true, Stage::Fft, synthesized),
+            (false, Stage::Fft, synthesized),
+            (true, Stage::Multiexp, synthesized),
+            (false, Stage::Multiexp, synthesized),
+            (true, Stage::Finalization, synthesized),
+            (false, Stage::Finalization, synthesized),
+        ]
+    );
+
+    // The second middleware of a pair never sees a stage the first vetoed.
+    let mut composed = (Veto(Stage::Multiexp), Recorder::default());
+    match create_random_proof_with_middleware(circuit(), &params, rng, &mut composed) {
+        Err(e @ SynthesisError::Vetoed(_)) => {
+            assert_eq!(e.to_string(), "proof generation was vetoed: Multiexp is disabled")
+        }
+        _ => panic!("expected the proof to be vetoed"),
+    }
+    assert_eq!((composed.1).events.len(), 4);
+}
+
+#[test]
+fn test_prover_precomputation() {
+    use crate::DomainPrecomputation;
+    use super::create_proof_with_precomputation;
+    use rand::rngs::StdRng;
+    use std::sync::Arc;
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x56; 32]);
+    let params = generate_random_parameters::<DummyEngine, _, _>(
+        XORDemo::<DummyEngine> { a: None, b: None, _marker: PhantomData },
+        rng,
+    )
+    .unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    let circuit = || XORDemo::<DummyEngine> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    let r = Fr::from_str("27134").unwrap();
+    let s = Fr::from_str("17146").unwrap();
+    let expected = create_proof(circuit(), &params, r, s).unwrap();
+
+    // Proofs on several threads share the tables of their one domain size.
+    let precomputation = Arc::new(DomainPrecomputation::<DummyEngine>::new());
+    assert!(precomputation.is_empty());
+    let handles: Vec<_> = (0..3).map(|_| {
+        let precomputation = precomputation.clone();
+        let params = params.clone();
+        std::thread::spawn(move || {
+            create_proof_with_precomputation(circuit(), &params, r, s, Some(&precomputation), &mut ()).unwrap()
+        })
+    }).collect();
+
+    for handle in handles {
+        let proof = handle.join().unwrap();
+        assert!(proof == expected);
+        assert!(verify_proof(&pvk, &proof, &[Fr::one()]).unwrap());
+    }
+    assert_eq!(precomputation.len(), 1);
+}
+
+#[test]
+fn test_prover_in_place_fft() {
+    use super::{create_proof_with_middleware, ProverConfig, ProverMiddleware, Stage, StageInfo};
+    use rand::rngs::StdRng;
+
+    struct InPlace;
+
+    impl ProverMiddleware for InPlace {
+        fn before(&mut self, stage: Stage, _: &StageInfo, config: &mut ProverConfig) -> Result<(), SynthesisError> {
+            assert!(!config.in_place_fft);
+            config.in_place_fft = stage == Stage::Fft;
+            Ok(())
+        }
+    }
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x57; 32]);
+    let params = generate_random_parameters::<DummyEngine, _, _>(
+        XORDemo::<DummyEngine> { a: None, b: None, _marker: PhantomData },
+        rng,
+    )
+    .unwrap();
+    let circuit = || XORDemo::<DummyEngine> {
+        a: Some(true),
+        b: Some(true),
+        _marker: PhantomData,
+    };
+    let r = Fr::from_str("27134").unwrap();
+    let s = Fr::from_str("17146").unwrap();
+
+    // The in-place FFT is opt-in and yields the same proof.
+    let expected = create_proof(circuit(), &params, r, s).unwrap();
+    let proof = create_proof_with_middleware(circuit(), &params, r, s, &mut InPlace).unwrap();
+    assert!(proof == expected);
+    assert!(verify_proof(&prepare_verifying_key(&params.vk), &proof, &[Fr::zero()]).unwrap());
+}
+
+#[test]
+fn test_domain_beyond_two_adicity() {
+    use crate::{poly, Domain, DomainTooLarge, Scalar};
+
+    // The dummy field has two-adicity 10 and a cube root of unity.
+    type Dummy = Domain<DummyEngine, Scalar<DummyEngine>>;
+    assert_eq!(Dummy::max_size(), 1536);
+
+    let coeffs = |len: usize| -> Vec<Scalar<DummyEngine>> {
+        (0..len).map(|i| Scalar(Fr::from_str(&(i + 1).to_string()).unwrap())).collect()
+    };
+    for &(len, expected) in [(512, 512), (513, 768), (1000, 1536), (1536, 1536)].iter() {
+        let mut domain = Dummy::new(coeffs(len)).unwrap();
+        assert_eq!(domain.as_coeffs().len(), expected);
+
+        // The first point of the domain is one.
+        let values: Vec<Fr> = domain.as_coeffs().iter().map(|s| s.0).collect();
+        domain.fft();
+        assert_eq!(domain.as_coeffs()[0].0, poly::evaluate::<DummyEngine>(&values, &Fr::one()));
+        domain.ifft();
+        let mut padded = coeffs(len);
+        padded.resize(expected, Scalar(Fr::zero()));
+        assert!(domain.as_coeffs() == &padded[..]);
+    }
+
+    match Dummy::new(coeffs(1537)) {
+        Err(SynthesisError::DomainTooLarge(size)) => {
+            assert_eq!(size, DomainTooLarge { required: 1537, available: 1536 });
+            assert_eq!(
+                SynthesisError::DomainTooLarge(size).to_string(),
+                "evaluation domain is too large for the scalar field: 1537 points are needed but at most 1536 are available"
+            );
+        }
+        _ => panic!("expected the domain to be too large"),
+    }
+}
+
+#[test]
+fn test_mismatched_parameters() {
+    use crate::{LengthMismatch, Operand, Query};
+    use rand::rngs::StdRng;
+
+    let rng = &mut ProverRng::<StdRng>::from_seed([0x55; 32]);
+    let params = generate_random_parameters::<DummyEngine, _, _>(
+        MultiplyDemo::<DummyEngine> { a: None, b: None },
+        rng,
+    )
+    .unwrap();
+
+    // XORDemo has more constraints, so its quotient needs more H bases than
+    // MultiplyDemo's parameters hold.
+    let circuit = XORDemo::<DummyEngine> {
+        a: Some(true),
+        b: Some(false),
+        _marker: PhantomData,
+    };
+    match create_random_proof(circuit, &params, rng) {
+        Err(SynthesisError::LengthMismatch(LengthMismatch {
+            query: Some(Query::H),
+            operand: Operand::Bases,
+            expected,
+            actual,
+        })) => {
+            assert_eq!(actual, params.h.len());
+            assert!(expected > actual);
+        }
+        _ => panic!("expected a length mismatch in the h query"),
+    }
+}
@@ -1,10 +1,11 @@
 use ff::PrimeField;
+use std::collections::BTreeMap;
 use group::{CurveAffine, CurveProjective};
 use pairing::{Engine, PairingCurveAffine};
 
 use super::{PreparedVerifyingKey, Proof, VerifyingKey, Result};
 
-use crate::SynthesisError;
+use crate::{PublicInputLayout, SynthesisError};
 
 pub fn prepare_verifying_key<E>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> 
 where
@@ -54,3 +55,23 @@ where
 
     Ok(exponentiation == pvk.alpha_g1_beta_g2)
 }
+
+/// Verifies a proof against public inputs given by label rather than by
+/// position. The values are ordered by `layout`, as returned by
+/// `generate_parameters_with_layout` or `public_input_layout` for the
+/// circuit, so every public input of the circuit must be named.
+pub fn verify_proof_labelled<E>(
+    pvk: &PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    layout: &PublicInputLayout,
+    public_inputs: &BTreeMap<String, E::Fr>
+) -> Result<bool> 
+where
+    E: Engine
+{
+    let num_inputs: usize = pvk.ic.len()
+        .checked_sub(1)
+        .ok_or(SynthesisError::MalformedVerifyingKey)?;
+    let ordered: Vec<E::Fr> = layout.bind(num_inputs, public_inputs)?;
+    verify_proof(pvk, proof, &ordered)
+}
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Result, SynthesisError};
+
+/// A map from the names of public inputs allocated with
+/// `ConstraintSystem::alloc_input_named` to their position in the
+/// public input vector handed to the verifier. Names are the full
+/// namespace path of the input, such as `spend/nullifier`, and the
+/// position excludes the implicit "one" input, so the first named input
+/// is at position 0.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PublicInputLayout {
+    positions: BTreeMap<String, usize>
+}
+
+impl PublicInputLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the public input at path `name` was allocated at `position`.
+    /// Fails with `DuplicateInput` if `name` has already been recorded, as
+    /// the layout would otherwise be ambiguous.
+    pub fn insert(&mut self, name: String, position: usize) -> Result<()> {
+        if self.positions.contains_key(&name) {
+            return Err(SynthesisError::DuplicateInput(name));
+        }
+        self.positions.insert(name, position);
+        Ok(())
+    }
+
+    /// Returns the position of the public input `name`, if recorded.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.positions.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Orders labelled values into the public input vector of a circuit
+    /// with `num_inputs` public inputs, excluding the "one" input.
+    ///
+    /// Every input must be labelled for the values to be bound, otherwise
+    /// the layout does not describe the circuit and `MalformedVerifyingKey`
+    /// is returned. Labels without a value and values without a label are
+    /// rejected.
+    pub fn bind<F: Copy>(&self, num_inputs: usize, values: &BTreeMap<String, F>) -> Result<Vec<F>> {
+        if self.positions.len() != num_inputs
+            || self.positions.values().any(|position| *position >= num_inputs)
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        if let Some(label) = values.keys().find(|label| !self.positions.contains_key(*label)) {
+            return Err(SynthesisError::UnexpectedInput(label.clone()));
+        }
+
+        self.iter()
+            .map(|(name, _)| {
+                values.get(name)
+                    .copied()
+                    .ok_or_else(|| SynthesisError::MissingInput(name.to_string()))
+            })
+            .collect()
+    }
+
+    /// Iterates over the named inputs in the order they were allocated.
+    pub fn iter(&self) -> impl Iterator<Item=(&str, usize)> {
+        let mut named: Vec<(&str, usize)> = self.positions
+            .iter()
+            .map(|(name, position)| (name.as_str(), *position))
+            .collect();
+        named.sort_by_key(|(_, position)| *position);
+        named.into_iter()
+    }
+}
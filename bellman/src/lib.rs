@@ -1,12 +1,6 @@
 #![feature(try_trait)]
 #![feature(concat_idents)]
 
-#[cfg(feature = "multicore")]
-extern crate crossbeam;
-
-#[cfg(feature = "multicore")]
-extern crate num_cpus;
-
 #[cfg(test)]
 #[macro_use]
 extern crate hex_literal;
@@ -18,18 +12,37 @@ extern crate rand;
 pub mod groth16;
 
 pub mod domain;
+pub mod arith;
+pub mod poly;
 pub mod gadgets;
 pub mod error;
 pub mod namespace;
 pub mod constraint;
+pub mod stats;
+pub mod layout;
+pub mod witness;
+pub mod subcircuit;
+pub mod capabilities;
+pub mod trace;
+
+// Timing needs a clock, which wasm32 does not have.
+#[cfg(all(feature = "bench", not(target_arch = "wasm32")))]
+pub mod bench;
 
 #[macro_use]
 pub mod multicore;
 
-pub use error::{Result, SynthesisError};
+pub use error::{Result, SynthesisError, DomainTooLarge, LengthMismatch, Operand, Query};
 pub use namespace::Namespace;
 pub use constraint::ConstraintSystem;
+pub use stats::CircuitStats;
+pub use layout::PublicInputLayout;
+pub use witness::{RecordedCircuit, Replay, Witness};
+pub use subcircuit::{SubCircuit, Ports, WireMap, Standalone, embed};
+pub use capabilities::{capabilities, Capabilities};
+pub use trace::{ConstraintTrace, TraceEntry, TraceRecorder};
 pub use domain::*;
+pub use multicore::{set_default_parallelism, set_default_chunk_policy, ChunkPolicy, Worker};
 
 /// Computations are expressed in terms of arithmetic circuits, in particular
 /// rank-1 quadratic constraint systems. The `Circuit` trait represents a
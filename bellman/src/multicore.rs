@@ -1,13 +1,107 @@
 //! This is an interface for dealing with the kinds of
 //! parallel computations involved in bellman. It's
-//! currently just a thin wrapper around CpuPool and
-//! crossbeam but may be extended in the future to
-//! allow for various parallelism strategies.
+//! currently a thin wrapper around rayon, so that it
+//! composes with thread pools owned by the caller.
+//!
+//! Targets without OS threads, such as wasm32, always
+//! use the single-threaded implementation.
 
 use lazy_static::lazy_static;
+use std::cell::Cell;
+use std::sync::RwLock;
 
 lazy_static!{
     pub static ref MULTI_THREAD: implementation::Worker = implementation::Worker::new();
+    static ref DEFAULT_CHUNK_POLICY: RwLock<ChunkPolicy> = RwLock::new(ChunkPolicy::Even);
+}
+
+/// How `Worker::scope`, and so the `multi_thread!` macro, splits its
+/// elements into the chunks that are handed to threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkPolicy {
+    /// One chunk of equal size per thread, run on whichever thread is free.
+    Even,
+    /// Chunks of at most this many elements, for keeping the working set
+    /// of each chunk within a cache level. Chunks are never larger than
+    /// with `Even`, so every thread still has work.
+    Fixed(usize),
+    /// One chunk of equal size per thread, where the chunk at position `i`
+    /// always runs on thread `i` of the pool. Passes over the same data then
+    /// touch each part of it from the same thread every time, which keeps
+    /// it local to that thread's core and memory node when the threads are
+    /// pinned, as on dual-socket machines.
+    Pinned
+}
+
+impl ChunkPolicy {
+    /// The number of elements in each chunk when `elements` are split
+    /// between `threads` threads.
+    pub fn chunk_size(self, elements: usize, threads: usize) -> usize {
+        let even: usize = if elements < threads {
+            1
+        } else {
+            elements / threads
+        };
+
+        match self {
+            ChunkPolicy::Even | ChunkPolicy::Pinned => even,
+            ChunkPolicy::Fixed(size) => size.max(1).min(even)
+        }
+    }
+}
+
+/// Sets the chunk policy of every default `Worker`, including the one the
+/// library uses internally. The default is `ChunkPolicy::Even`.
+pub fn set_default_chunk_policy(policy: ChunkPolicy) {
+    *DEFAULT_CHUNK_POLICY.write().expect("default chunk policy lock was poisoned") = policy;
+}
+
+fn default_chunk_policy() -> ChunkPolicy {
+    *DEFAULT_CHUNK_POLICY.read().expect("default chunk policy lock was poisoned")
+}
+
+thread_local!{
+    // Whether the work running on this thread belongs to a deterministic
+    // worker. Workers carry it into the work they spawn, so that it reaches
+    // the library's own worker inside `install` and `enter`.
+    static DETERMINISTIC: Cell<bool> = Cell::new(false);
+}
+
+/// Runs `op` with the deterministic setting of this thread as `enabled`,
+/// restoring the previous setting afterwards.
+fn run_deterministic<F, R>(enabled: bool, op: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous: bool = self.0;
+            DETERMINISTIC.with(|deterministic| deterministic.set(previous));
+        }
+    }
+
+    let _restore = Restore(DETERMINISTIC.with(|deterministic| deterministic.replace(enabled)));
+    op()
+}
+
+fn inherited_deterministic() -> bool {
+    DETERMINISTIC.with(Cell::get)
+}
+
+#[cfg(test)]
+lazy_static!{
+    static ref DEFAULT_PARALLELISM_TESTS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
+
+/// Serialises tests which change the default parallelism with those whose
+/// expectations depend on it.
+#[cfg(test)]
+pub(crate) fn lock_default_parallelism() -> std::sync::MutexGuard<'static, ()> {
+    // A test which panicked while holding the lock has still restored the
+    // default, so the poison is ignored.
+    DEFAULT_PARALLELISM_TESTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 #[macro_export]
@@ -20,16 +114,16 @@ macro_rules! multi_thread {
         $( map_to_chunk! $chunk_block_beta:block )?
     }) => {
         crate::multicore::MULTI_THREAD.scope($elements, |scope, chunk_size| {
-            for ($first_id $(, $zipped_id)? ) in $first.chunks_mut(chunk_size)
-                $( .zip($zipped.chunks(chunk_size)) )?
+            for ($first_id $(, $zipped_id)? ) in $first.chunks_mut(chunk_size.max(1))
+                $( .zip($zipped.chunks(chunk_size.max(1))) )?
             {
-                for $value in $first_id.iter_mut()
-                    $( .zip($zipped_id) )?
-                {
-                    scope.spawn(move || {
+                scope.spawn(move || {
+                    for $value in $first_id.iter_mut()
+                        $( .zip($zipped_id) )?
+                    {
                         $code_block
-                    });
-                }
+                    }
+                });
             }
         });
     }; 
@@ -41,50 +135,184 @@ macro_rules! multi_thread {
         $( map_to_chunk! $chunk_block_beta:block )?
     }) => {
         crate::multicore::MULTI_THREAD.scope($elements, |scope, chunk_size| {
+            let chunk_size: usize = chunk_size.max(1);
             for (_i, iter) in $iter.chunks_mut(chunk_size)
                 .enumerate() 
             {
-                for (mut $idx, $value) in iter.iter_mut().enumerate() { 
-                    $idx += _i * chunk_size;
-                    scope.spawn(move || {
+                scope.spawn(move || {
+                    for (mut $idx, $value) in iter.iter_mut().enumerate() { 
+                        $idx += _i * chunk_size;
                         $code_block
-                    });
-                }
+                    }
+                });
             }
         });
     }; 
 }
 
-#[cfg(feature = "multicore")]
+#[cfg(all(feature = "multicore", not(target_arch = "wasm32")))]
 mod implementation {
-    use crossbeam::{self, Scope};
-    use futures::{Future, IntoFuture, Poll};
-    use futures_cpupool::{CpuFuture, CpuPool};
-    use num_cpus;
+    use futures::sync::oneshot;
+    use futures::{task, Async, Future, IntoFuture, Poll};
+    use lazy_static::lazy_static;
+    use rayon::{ThreadPool, ThreadPoolBuilder};
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex, RwLock};
+
+    use super::{default_chunk_policy, inherited_deterministic, run_deterministic, ChunkPolicy};
+
+    lazy_static!{
+        static ref DEFAULT_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+    }
+
+    thread_local!{
+        static ENTERED_POOL: RefCell<Option<Arc<ThreadPool>>> = RefCell::new(None);
+    }
+
+    /// Limits the parallelism of every default `Worker`, including the one
+    /// the library uses internally, to `threads` threads. Passing zero
+    /// restores the default of using the rayon pool of the calling thread.
+    ///
+    /// Panics if the thread pool cannot be created.
+    pub fn set_default_parallelism(threads: usize) {
+        let pool: Option<Arc<ThreadPool>> = if threads == 0 {
+            None
+        } else {
+            Some(Arc::new(build_pool(threads)))
+        };
+        *DEFAULT_POOL.write().expect("default thread pool lock was poisoned") = pool;
+    }
 
+    fn build_pool(threads: usize) -> ThreadPool {
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("could not start thread pool")
+    }
+
+    /// Runs work on a rayon pool. A worker made by `with_threads` always
+    /// uses its own pool. Otherwise work runs on the pool of a worker the
+    /// calling thread has entered, then on the pool of the calling thread if
+    /// it is inside `ThreadPool::install`, so that nested parallelism
+    /// composes with it, then on the pool configured with
+    /// `set_default_parallelism`, and finally on the global rayon pool.
     #[derive(Clone)]
     pub struct Worker {
-        cpus: usize,
-        pool: CpuPool,
+        pool: Option<Arc<ThreadPool>>,
+        chunk_policy: Option<ChunkPolicy>,
+        deterministic: Option<bool>,
     }
 
     impl Worker {
-        // We don't expose this outside the library so that
-        // all `Worker` instances have the same number of
-        // CPUs configured.
-        pub(crate) fn new_with_cpus(cpus: usize) -> Worker {
+        pub fn new() -> Worker {
+            Worker { pool: None, chunk_policy: None, deterministic: None }
+        }
+
+        /// A worker with a dedicated pool of `threads` threads.
+        ///
+        /// Panics if the thread pool cannot be created.
+        pub fn with_threads(threads: usize) -> Worker {
             Worker {
-                cpus: cpus,
-                pool: CpuPool::new(cpus),
+                pool: Some(Arc::new(build_pool(threads))),
+                chunk_policy: None,
+                deterministic: None,
             }
         }
 
-        pub fn new() -> Worker {
-            Self::new_with_cpus(num_cpus::get())
+        /// This worker, splitting the elements of `scope` with `policy`
+        /// rather than the default chunk policy.
+        pub fn with_chunk_policy(self, policy: ChunkPolicy) -> Worker {
+            Worker {
+                chunk_policy: Some(policy),
+                ..self
+            }
+        }
+
+        /// This worker, with parallel reductions combining their parts in
+        /// an order which does not depend on the number of threads when
+        /// `enabled`, so that intermediate results, such as the projective
+        /// coordinates of a multi-exponentiation, are the same on every
+        /// machine. Work is then no longer planned around the threads
+        /// available, which can be slower.
+        ///
+        /// Work spawned by this worker, and by default workers inside its
+        /// `install` and `enter`, inherits the setting. Off by default.
+        pub fn with_deterministic(self, enabled: bool) -> Worker {
+            Worker {
+                deterministic: Some(enabled),
+                ..self
+            }
+        }
+
+        /// Whether this worker combines parallel reductions in a fixed
+        /// order, as set by `with_deterministic` or inherited from the
+        /// worker running the calling thread.
+        pub fn is_deterministic(&self) -> bool {
+            self.deterministic.unwrap_or_else(inherited_deterministic)
+        }
+
+        fn chunk_policy(&self) -> ChunkPolicy {
+            self.chunk_policy.unwrap_or_else(default_chunk_policy)
+        }
+
+        fn pool(&self) -> Option<Arc<ThreadPool>> {
+            if self.pool.is_some() {
+                return self.pool.clone();
+            }
+            if let Some(pool) = ENTERED_POOL.with(|entered| entered.borrow().clone()) {
+                return Some(pool);
+            }
+            if rayon::current_thread_index().is_some() {
+                return None;
+            }
+            DEFAULT_POOL.read().expect("default thread pool lock was poisoned").clone()
+        }
+
+        /// Runs `op` on this worker's pool, so that library calls made
+        /// inside it, such as proving, are limited to that pool.
+        pub fn install<F, R>(&self, op: F) -> R
+        where
+            F: FnOnce() -> R + Send,
+            R: Send,
+        {
+            let deterministic: bool = self.is_deterministic();
+            match self.pool() {
+                Some(pool) => pool.install(|| run_deterministic(deterministic, op)),
+                None => run_deterministic(deterministic, op),
+            }
+        }
+
+        /// Runs `op` on the calling thread, but sends the work that default
+        /// workers spawn while it runs to this worker's pool. Unlike
+        /// `install`, `op` need not be `Send`.
+        pub fn enter<F, R>(&self, op: F) -> R
+        where
+            F: FnOnce() -> R,
+        {
+            struct Restore(Option<Arc<ThreadPool>>);
+
+            impl Drop for Restore {
+                fn drop(&mut self) {
+                    let previous = self.0.take();
+                    ENTERED_POOL.with(|entered| *entered.borrow_mut() = previous);
+                }
+            }
+
+            let deterministic: bool = self.is_deterministic();
+            let pool: Option<Arc<ThreadPool>> = match self.pool {
+                Some(ref pool) => Some(pool.clone()),
+                None => return run_deterministic(deterministic, op),
+            };
+            let _restore = Restore(ENTERED_POOL.with(|entered| entered.replace(pool)));
+            run_deterministic(deterministic, op)
         }
 
         pub fn log_num_cpus(&self) -> u32 {
-            log2_floor(self.cpus)
+            let cpus: usize = match self.pool() {
+                Some(pool) => pool.current_num_threads(),
+                None => rayon::current_num_threads(),
+            };
+            log2_floor(cpus)
         }
 
         pub fn compute<F, R>(&self, f: F) -> WorkerFuture<R::Item, R::Error>
@@ -95,27 +323,104 @@ mod implementation {
             R::Item: Send + 'static,
             R::Error: Send + 'static,
         {
-            WorkerFuture {
-                future: self.pool.spawn_fn(f),
+            let (sender, receiver) = oneshot::channel();
+            let deterministic: bool = self.is_deterministic();
+            let job = move || {
+                let _ = sender.send(run_deterministic(deterministic, || f().into_future().wait()));
+            };
+
+            match self.pool() {
+                Some(pool) => pool.spawn(job),
+                None => rayon::spawn(job),
             }
+
+            WorkerFuture { future: receiver }
+        }
+
+        /// Runs `a` and `b` on this worker's pool, in parallel if a thread
+        /// is free, and waits for both. Unlike `compute`, they may borrow
+        /// from the caller.
+        pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+        where
+            A: FnOnce() -> RA + Send,
+            B: FnOnce() -> RB + Send,
+            RA: Send,
+            RB: Send,
+        {
+            let deterministic: bool = self.is_deterministic();
+            self.install(|| rayon::join(
+                || run_deterministic(deterministic, a),
+                || run_deterministic(deterministic, b)
+            ))
         }
 
+        /// Calls `f` with a scope to spawn work into and the size of the
+        /// chunks to split `elements` into, as planned by the chunk policy,
+        /// and waits for the spawned work to finish.
         pub fn scope<'a, F, R>(&self, elements: usize, f: F) -> R
         where
-            F: FnOnce(&Scope<'a>, usize) -> R,
+            F: FnOnce(&Scope<'_, 'a>, usize) -> R + Send,
+            R: Send,
         {
-            let chunk_size = if elements < self.cpus {
-                1
-            } else {
-                elements / self.cpus
-            };
+            let policy: ChunkPolicy = self.chunk_policy();
+            let deterministic: bool = self.is_deterministic();
+            self.install(|| {
+                let cpus: usize = rayon::current_num_threads();
+                let chunk_size: usize = policy.chunk_size(elements, cpus);
+
+                rayon::scope(|scope| {
+                    if policy != ChunkPolicy::Pinned {
+                        return f(&Scope { inner: scope, pinned: None, deterministic }, chunk_size)
+                    }
+
+                    // Work is held back until every chunk has been spawned,
+                    // then each thread of the pool runs the chunks whose
+                    // position it owns.
+                    let pinned: Arc<Mutex<Vec<Option<Task<'a>>>>> = Arc::new(Mutex::new(Vec::new()));
+                    let result: R = f(&Scope { inner: scope, pinned: Some(&*pinned), deterministic }, chunk_size);
+
+                    scope.spawn_broadcast(move |_, context| {
+                        let tasks: usize = pinned.lock().expect("pinned task lock was poisoned").len();
+                        for position in (context.index()..tasks).step_by(context.num_threads()) {
+                            let task: Option<Task<'a>> = pinned.lock()
+                                .expect("pinned task lock was poisoned")[position]
+                                .take();
+                            if let Some(task) = task {
+                                task();
+                            }
+                        }
+                    });
+                    result
+                })
+            })
+        }
+    }
 
-            crossbeam::scope(|scope| f(scope, chunk_size))
+    type Task<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+    /// Adapts a rayon scope to the spawning interface the `multi_thread!`
+    /// macro was written against.
+    pub struct Scope<'s, 'a> {
+        inner: &'s rayon::Scope<'a>,
+        pinned: Option<&'s Mutex<Vec<Option<Task<'a>>>>>,
+        deterministic: bool,
+    }
+
+    impl<'s, 'a> Scope<'s, 'a> {
+        pub fn spawn<F: FnOnce() + Send + 'a>(&self, f: F) {
+            let deterministic: bool = self.deterministic;
+            let f = move || run_deterministic(deterministic, f);
+            match self.pinned {
+                Some(pinned) => pinned.lock()
+                    .expect("pinned task lock was poisoned")
+                    .push(Some(Box::new(f))),
+                None => self.inner.spawn(move |_| f()),
+            }
         }
     }
 
     pub struct WorkerFuture<T, E> {
-        future: CpuFuture<T, E>,
+        future: oneshot::Receiver<Result<T, E>>,
     }
 
     impl<T: Send + 'static, E: Send + 'static> Future for WorkerFuture<T, E> {
@@ -123,7 +428,19 @@ mod implementation {
         type Error = E;
 
         fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-            self.future.poll()
+            match self.future.poll().expect("worker thread panicked") {
+                Async::Ready(result) => result.map(Async::Ready),
+                Async::NotReady => {
+                    // A worker thread which parked waiting on its own pool
+                    // could starve it, so it runs other pending work instead
+                    // and asks to be polled again.
+                    if rayon::current_thread_index().is_some() {
+                        rayon::yield_now();
+                        task::current().notify();
+                    }
+                    Ok(Async::NotReady)
+                }
+            }
         }
     }
 
@@ -150,18 +467,214 @@ mod implementation {
         assert_eq!(log2_floor(7), 2);
         assert_eq!(log2_floor(8), 3);
     }
+
+    #[test]
+    fn test_configured_parallelism() {
+        let worker = Worker::with_threads(2);
+        assert_eq!(worker.log_num_cpus(), 1);
+        assert_eq!(worker.install(rayon::current_num_threads), 2);
+        assert_eq!(worker.compute(|| Ok::<_, ()>(rayon::current_num_threads())).wait(), Ok(2));
+        assert_eq!(worker.join(rayon::current_num_threads, || 1), (2, 1));
+
+        // Entering a worker redirects default workers without moving `op`.
+        // The process-wide default is left alone, as other tests share it.
+        let local = std::rc::Rc::new(());
+        Worker::with_threads(4).enter(|| {
+            let _ = &local;
+            assert_eq!(Worker::new().log_num_cpus(), 2);
+            assert_eq!(Worker::new().compute(|| Ok::<_, ()>(rayon::current_num_threads())).wait(), Ok(4));
+
+            // A dedicated pool and an installed pool take precedence.
+            assert_eq!(worker.log_num_cpus(), 1);
+            worker.install(|| assert_eq!(Worker::new().log_num_cpus(), 1));
+
+            // Entered workers nest, and are restored on leaving.
+            worker.enter(|| assert_eq!(Worker::new().log_num_cpus(), 1));
+            assert_eq!(Worker::new().log_num_cpus(), 2);
+        });
+    }
+
+    #[test]
+    fn test_default_parallelism() {
+        struct Restore(Option<Arc<ThreadPool>>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                *DEFAULT_POOL.write().expect("default thread pool lock was poisoned") = self.0.take();
+            }
+        }
+
+        let _lock = super::lock_default_parallelism();
+        let _restore = Restore(DEFAULT_POOL.read().unwrap().clone());
+
+        set_default_parallelism(2);
+        assert_eq!(Worker::new().log_num_cpus(), 1);
+        assert_eq!(Worker::new().compute(|| Ok::<_, ()>(rayon::current_num_threads())).wait(), Ok(2));
+        assert_eq!(Worker::new().install(rayon::current_num_threads), 2);
+
+        // A dedicated pool takes precedence over the default.
+        assert_eq!(Worker::with_threads(4).log_num_cpus(), 2);
+
+        set_default_parallelism(0);
+        assert_eq!(Worker::new().log_num_cpus(), log2_floor(rayon::current_num_threads()));
+    }
+
+    #[test]
+    fn test_deterministic_worker() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        assert!(!Worker::new().is_deterministic());
+
+        // The setting reaches default workers, such as the library's own,
+        // in every kind of work the worker runs.
+        let worker = Worker::with_threads(2).with_deterministic(true);
+        assert!(worker.install(|| Worker::new().is_deterministic()));
+        assert!(worker.enter(|| Worker::new().is_deterministic()));
+        assert_eq!(worker.compute(|| Ok::<_, ()>(Worker::new().is_deterministic())).wait(), Ok(true));
+        assert_eq!(
+            worker.join(|| Worker::new().is_deterministic(), || Worker::new().is_deterministic()),
+            (true, true)
+        );
+        let spawned = AtomicBool::new(false);
+        worker.scope(1, |scope, _| {
+            let spawned = &spawned;
+            scope.spawn(move || spawned.store(Worker::new().is_deterministic(), Ordering::SeqCst));
+        });
+        assert!(spawned.load(Ordering::SeqCst));
+
+        // Work spawned from inside it inherits it too, while a worker with
+        // its own setting keeps it. Nothing leaks to the calling thread.
+        worker.install(|| {
+            assert!(Worker::new().join(|| Worker::new().is_deterministic(), || true).0);
+            assert!(!Worker::new().with_deterministic(false).install(|| Worker::new().is_deterministic()));
+        });
+        assert!(!Worker::new().is_deterministic());
+    }
+
+    #[test]
+    fn test_chunk_policy() {
+        assert_eq!(ChunkPolicy::Even.chunk_size(100, 4), 25);
+        assert_eq!(ChunkPolicy::Even.chunk_size(3, 4), 1);
+        assert_eq!(ChunkPolicy::Fixed(10).chunk_size(100, 4), 10);
+        assert_eq!(ChunkPolicy::Fixed(50).chunk_size(100, 4), 25);
+        assert_eq!(ChunkPolicy::Fixed(0).chunk_size(100, 4), 1);
+
+        // Pinned chunks run on the thread that owns their position, so every
+        // pass over the same data sees the same threads.
+        let worker = Worker::with_threads(4).with_chunk_policy(ChunkPolicy::Pinned);
+        let pass = || {
+            let mut threads = vec![None; 100];
+            worker.scope(threads.len(), |scope, chunk_size| {
+                assert_eq!(chunk_size, 25);
+                for chunk in threads.chunks_mut(chunk_size) {
+                    scope.spawn(move || {
+                        for thread in chunk.iter_mut() {
+                            *thread = rayon::current_thread_index();
+                        }
+                    });
+                }
+            });
+            threads
+        };
+
+        let first = pass();
+        for (position, chunk) in first.chunks(25).enumerate() {
+            assert!(chunk.iter().all(|thread| *thread == Some(position)));
+        }
+        assert_eq!(pass(), first);
+    }
+
+    #[test]
+    fn test_installed_pool() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // A single thread pool must not deadlock when a task waits on work
+        // it spawned into the same pool.
+        for &threads in &[1, 4] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                let worker = Worker::new();
+                assert_eq!(worker.log_num_cpus(), log2_floor(threads));
+
+                let outer = worker.compute(move || {
+                    Worker::new().compute(|| Ok::<_, ()>(2)).map(|x| x + 1)
+                });
+                assert_eq!(outer.wait(), Ok(3));
+
+                let mut elements = vec![0usize; 100];
+                let total = AtomicUsize::new(0);
+                worker.scope(elements.len(), |scope, chunk| {
+                    for chunk in elements.chunks_mut(chunk) {
+                        let total = &total;
+                        scope.spawn(move || {
+                            for x in chunk.iter_mut() {
+                                *x = 1;
+                                total.fetch_add(1, Ordering::SeqCst);
+                            }
+                        });
+                    }
+                });
+                assert_eq!(total.load(Ordering::SeqCst), 100);
+                assert!(elements.iter().all(|x| *x == 1));
+            });
+        }
+    }
 }
 
-#[cfg(not(feature = "multicore"))]
+#[cfg(any(not(feature = "multicore"), target_arch = "wasm32"))]
 mod implementation {
     use futures::{future, Future, IntoFuture, Poll};
 
+    use super::{inherited_deterministic, run_deterministic};
+
     #[derive(Clone)]
-    pub struct Worker;
+    pub struct Worker {
+        deterministic: Option<bool>,
+    }
+
+    /// Without the `multicore` feature, or on wasm32, there is only ever one
+    /// thread.
+    pub fn set_default_parallelism(_threads: usize) {}
 
     impl Worker {
         pub fn new() -> Worker {
-            Worker
+            Worker { deterministic: None }
+        }
+
+        pub fn with_threads(_threads: usize) -> Worker {
+            Worker::new()
+        }
+
+        /// There is only one chunk on a single thread, so the policy has no
+        /// effect.
+        pub fn with_chunk_policy(self, _policy: super::ChunkPolicy) -> Worker {
+            self
+        }
+
+        /// Reductions over one thread already combine their parts in the
+        /// same order everywhere, but the setting still selects the same
+        /// split as a deterministic multi-threaded worker, so that both
+        /// give the same intermediate results.
+        pub fn with_deterministic(self, enabled: bool) -> Worker {
+            Worker { deterministic: Some(enabled) }
+        }
+
+        pub fn is_deterministic(&self) -> bool {
+            self.deterministic.unwrap_or_else(inherited_deterministic)
+        }
+
+        pub fn install<F, R>(&self, op: F) -> R
+        where
+            F: FnOnce() -> R,
+        {
+            run_deterministic(self.is_deterministic(), op)
+        }
+
+        pub fn enter<F, R>(&self, op: F) -> R
+        where
+            F: FnOnce() -> R,
+        {
+            run_deterministic(self.is_deterministic(), op)
         }
 
         pub fn log_num_cpus(&self) -> u32 {
@@ -176,14 +689,22 @@ mod implementation {
             R::Item: Send + 'static,
             R::Error: Send + 'static,
         {
-            f().into_future()
+            run_deterministic(self.is_deterministic(), f).into_future()
+        }
+
+        pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+        where
+            A: FnOnce() -> RA,
+            B: FnOnce() -> RB,
+        {
+            run_deterministic(self.is_deterministic(), || (a(), b()))
         }
 
         pub fn scope<F, R>(&self, elements: usize, f: F) -> R
         where
             F: FnOnce(&DummyScope, usize) -> R,
         {
-            f(&DummyScope, elements)
+            run_deterministic(self.is_deterministic(), || f(&DummyScope, elements))
         }
     }
 
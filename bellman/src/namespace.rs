@@ -59,6 +59,14 @@ where
         self.cs.alloc_input(annotation, f)
     }
 
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        N: Into<String>,
+    {
+        self.cs.alloc_input_named(name, f)
+    }
+
     fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
     where
         A: FnOnce() -> AR,
@@ -70,6 +78,33 @@ where
         self.cs.enforce(annotation, a, b, c)
     }
 
+    fn enforce_zero<A, AR, LA>(&mut self, annotation: A, a: LA)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.cs.enforce_zero(annotation, a)
+    }
+
+    fn enforce_equal<A, AR, LA, LB>(&mut self, annotation: A, a: LA, b: LB)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        self.cs.enforce_equal(annotation, a, b)
+    }
+
+    fn enforce_boolean<A, AR>(&mut self, annotation: A, var: Coefficient)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enforce_boolean(annotation, var)
+    }
+
     // Downstream users who use `namespace` will never interact with these
     // functions and they will never be invoked because the namespace is
     // never a root constraint system.
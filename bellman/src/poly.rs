@@ -0,0 +1,169 @@
+//! Arithmetic on polynomials over the scalar field, given by their
+//! coefficients from the constant term up. These are the operations the
+//! prover builds its quotient polynomial from, for proof systems of other
+//! shapes to reuse.
+
+use ff::{Field, ScalarEngine};
+
+use crate::domain::{Domain, Scalar};
+use crate::error::{Result, SynthesisError};
+use crate::multicore::MULTI_THREAD;
+
+use std::sync::Mutex;
+
+type Coefficients<E> = Vec<<E as ScalarEngine>::Fr>;
+
+/// The product of `a` and `b`, computed pointwise over an evaluation domain
+/// with room for every coefficient of the product.
+pub fn mul<E>(a: &[E::Fr], b: &[E::Fr]) -> Result<Coefficients<E>>
+where
+    E: ScalarEngine
+{
+    if a.is_empty() || b.is_empty() {
+        return Ok(vec![])
+    }
+
+    let len: usize = a.len() + b.len() - 1;
+    let mut a: Domain<E,Scalar<E>> = Domain::new(padded::<E>(a, len))?;
+    let mut b: Domain<E,Scalar<E>> = Domain::new(padded::<E>(b, len))?;
+
+    a.fft();
+    b.fft();
+    a *= &b;
+    drop(b);
+    a.ifft();
+
+    let mut product: Vec<E::Fr> = a.into_coeffs().into_iter().map(|s| s.0).collect();
+    product.truncate(len);
+    Ok(product)
+}
+
+/// Divides `poly` by x^n - 1, the polynomial that vanishes on an evaluation
+/// domain of `n` points, returning the quotient and the remainder. The
+/// remainder has `n` coefficients, or as many as `poly` if it has fewer.
+pub fn divide_by_vanishing<E>(poly: &[E::Fr], n: usize) -> Result<(Coefficients<E>, Coefficients<E>)>
+where
+    E: ScalarEngine
+{
+    if n == 0 {
+        return Err(SynthesisError::DivisionByZero)
+    }
+
+    // Cancelling the leading term c x^i with c x^(i - n) (x^n - 1) leaves
+    // c added to the coefficient of x^(i - n).
+    let mut remainder: Vec<E::Fr> = poly.to_vec();
+    let mut quotient: Vec<E::Fr> = vec![E::Fr::zero(); poly.len().saturating_sub(n)];
+    for i in (n..poly.len()).rev() {
+        let lead: E::Fr = remainder[i];
+        quotient[i - n] = lead;
+        remainder[i - n].add_assign(&lead);
+    }
+    remainder.truncate(n);
+
+    Ok((quotient, remainder))
+}
+
+/// Evaluates `poly` at `point` by Horner's rule, a chunk of coefficients
+/// per thread.
+pub fn evaluate<E>(poly: &[E::Fr], point: &E::Fr) -> E::Fr
+where
+    E: ScalarEngine
+{
+    let sum: Mutex<E::Fr> = Mutex::new(E::Fr::zero());
+
+    MULTI_THREAD.scope(poly.len(), |scope, chunk_size| {
+        let chunk_size: usize = chunk_size.max(1);
+        for (idx, coeffs) in poly.chunks(chunk_size).enumerate() {
+            let sum: &Mutex<E::Fr> = &sum;
+            scope.spawn(move || {
+                let mut acc: E::Fr = E::Fr::zero();
+                for coeff in coeffs.iter().rev() {
+                    acc.mul_assign(point);
+                    acc.add_assign(coeff);
+                }
+                acc.mul_assign(&point.pow(&[(idx * chunk_size) as u64]));
+                sum.lock().expect("evaluation sum lock was poisoned").add_assign(&acc);
+            });
+        }
+    });
+
+    sum.into_inner().expect("evaluation sum lock was poisoned")
+}
+
+fn padded<E: ScalarEngine>(coeffs: &[E::Fr], len: usize) -> Vec<Scalar<E>> {
+    let mut padded: Vec<Scalar<E>> = Vec::with_capacity(len);
+    padded.extend(coeffs.iter().map(|c| Scalar(*c)));
+    padded.resize(len, Scalar(E::Fr::zero()));
+    padded
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_poly_arith() {
+    use pairing::bls12_381::Fr;
+    use pairing::bls12_381::Bls12;
+
+    let rng = &mut rand::thread_rng();
+    let random = |len: usize, rng: &mut rand::rngs::ThreadRng| -> Vec<Fr> {
+        (0..len).map(|_| Fr::random(rng)).collect()
+    };
+    let naive_mul = |a: &[Fr], b: &[Fr]| -> Vec<Fr> {
+        if a.is_empty() || b.is_empty() {
+            return vec![];
+        }
+        let mut product = vec![Fr::zero(); a.len() + b.len() - 1];
+        for (i, a) in a.iter().enumerate() {
+            for (j, b) in b.iter().enumerate() {
+                let mut term = *a;
+                term.mul_assign(b);
+                product[i + j].add_assign(&term);
+            }
+        }
+        product
+    };
+
+    for &(len_a, len_b) in [(0, 3), (1, 1), (2, 3), (5, 8), (17, 40)].iter() {
+        let a = random(len_a, rng);
+        let b = random(len_b, rng);
+        assert_eq!(mul::<Bls12>(&a, &b).unwrap(), naive_mul(&a, &b));
+
+        // Evaluation agrees with a naive sum of powers, and is a homomorphism.
+        let point = Fr::random(rng);
+        let mut naive = Fr::zero();
+        for (i, coeff) in a.iter().enumerate() {
+            let mut term = point.pow(&[i as u64]);
+            term.mul_assign(coeff);
+            naive.add_assign(&term);
+        }
+        assert_eq!(evaluate::<Bls12>(&a, &point), naive);
+
+        let mut expected = naive;
+        expected.mul_assign(&evaluate::<Bls12>(&b, &point));
+        assert_eq!(evaluate::<Bls12>(&naive_mul(&a, &b), &point), expected);
+    }
+
+    // q (x^n - 1) + r divides back into q and r.
+    for &(n, len_q) in [(1, 4), (4, 0), (4, 5), (6, 13), (8, 8)].iter() {
+        let q = random(len_q, rng);
+        let r = random(n, rng);
+        let mut vanishing = vec![Fr::zero(); n + 1];
+        vanishing[0] = Fr::one();
+        vanishing[0].negate();
+        vanishing[n] = Fr::one();
+
+        let mut poly = naive_mul(&q, &vanishing);
+        poly.resize(poly.len().max(n), Fr::zero());
+        for (p, r) in poly.iter_mut().zip(r.iter()) {
+            p.add_assign(r);
+        }
+
+        let (quotient, remainder) = divide_by_vanishing::<Bls12>(&poly, n).unwrap();
+        assert_eq!(quotient, q);
+        assert_eq!(remainder, r);
+    }
+
+    let (quotient, remainder) = divide_by_vanishing::<Bls12>(&random(3, rng), 8).unwrap();
+    assert!(quotient.is_empty());
+    assert_eq!(remainder.len(), 3);
+    assert!(divide_by_vanishing::<Bls12>(&random(3, rng), 0).is_err());
+}
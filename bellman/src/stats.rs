@@ -0,0 +1,197 @@
+use ff::{Field, ScalarEngine};
+
+use crate::{Circuit, ConstraintSystem, Coefficient, Index, LinearCombination};
+use crate::error::Result;
+
+/// Statistics describing the shape of a circuit's constraint system,
+/// gathered by a dry run of synthesis which never evaluates assignments.
+/// This is useful for capacity planning before running the generator.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitStats {
+    /// Number of constraints enforced by the circuit.
+    pub constraints: usize,
+    /// Number of public inputs, including the "one" input.
+    pub inputs: usize,
+    /// Number of auxiliary (private) variables.
+    pub aux: usize,
+    /// Number of variables appearing in at least one A term.
+    pub a_density: usize,
+    /// Number of variables appearing in at least one B term.
+    pub b_density: usize,
+    /// Number of variables appearing in at least one C term.
+    pub c_density: usize,
+    /// Length of the longest linear combination passed to `enforce`.
+    pub max_lc_len: usize
+}
+
+impl CircuitStats {
+    /// Synthesize `circuit` without evaluating any assignments and
+    /// report the statistics of the resulting constraint system.
+    pub fn measure<E,C>(circuit: C) -> Result<Self>
+    where
+        E: ScalarEngine,
+        C: Circuit<E>
+    {
+        let mut cs: StatsSystem = StatsSystem::default();
+        ConstraintSystem::<E>::alloc_input(&mut cs, || "", || Ok(E::Fr::one()))?;
+        circuit.synthesize(&mut cs)?;
+
+        Ok(cs.into_stats())
+    }
+}
+
+#[derive(Default)]
+struct Density {
+    inputs: Vec<bool>,
+    aux: Vec<bool>
+}
+
+impl Density {
+    fn mark<E: ScalarEngine>(&mut self, lc: &LinearCombination<E>) {
+        for (var, _) in lc.as_ref() {
+            match var.get_unchecked() {
+                Index::Input(i) => self.inputs[i] = true,
+                Index::Aux(i) => self.aux[i] = true
+            }
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.inputs.iter()
+            .chain(self.aux.iter())
+            .filter(|used| **used)
+            .count()
+    }
+}
+
+#[derive(Default)]
+struct StatsSystem {
+    stats: CircuitStats,
+    a: Density,
+    b: Density,
+    c: Density
+}
+
+impl StatsSystem {
+    fn into_stats(self) -> CircuitStats {
+        CircuitStats {
+            a_density: self.a.total(),
+            b_density: self.b.total(),
+            c_density: self.c.total(),
+            ..self.stats
+        }
+    }
+}
+
+impl<E> ConstraintSystem<E> for StatsSystem
+where
+    E: ScalarEngine
+{
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We never invoke the assignment function, as we only
+        // care about the structure of the constraint system.
+        let index: usize = self.stats.aux;
+        self.stats.aux += 1;
+
+        self.a.aux.push(false);
+        self.b.aux.push(false);
+        self.c.aux.push(false);
+
+        Ok(Coefficient::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index: usize = self.stats.inputs;
+        self.stats.inputs += 1;
+
+        self.a.inputs.push(false);
+        self.b.inputs.push(false);
+        self.c.inputs.push(false);
+
+        Ok(Coefficient::new_unchecked(Index::Input(index)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        let a: LinearCombination<E> = a(LinearCombination::zero());
+        let b: LinearCombination<E> = b(LinearCombination::zero());
+        let c: LinearCombination<E> = c(LinearCombination::zero());
+
+        self.a.mark(&a);
+        self.b.mark(&b);
+        self.c.mark(&c);
+
+        let longest: usize = a.as_ref().len()
+            .max(b.as_ref().len())
+            .max(c.as_ref().len());
+        self.stats.max_lc_len = self.stats.max_lc_len.max(longest);
+        self.stats.constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_measure_stats() {
+    use pairing::bls12_381::Bls12;
+    use crate::SynthesisError;
+
+    struct Cube;
+
+    // Proves knowledge of x such that x^3 = y
+    impl<E: ScalarEngine> Circuit<E> for Cube {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<()> {
+            let x = cs.alloc(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+            let x_sq = cs.alloc(|| "x^2", || Err(SynthesisError::AssignmentMissing))?;
+            let y = cs.alloc_input(|| "y", || Err(SynthesisError::AssignmentMissing))?;
+
+            cs.enforce(|| "x^2", |lc| lc + x, |lc| lc + x, |lc| lc + x_sq);
+            cs.enforce(|| "x^3", |lc| lc + x_sq, |lc| lc + x, |lc| lc + y);
+            cs.enforce(|| "x + x^2", |lc| lc + x + x_sq, |lc| lc + CS::one(), |lc| lc + x + x_sq);
+
+            Ok(())
+        }
+    }
+
+    let stats = CircuitStats::measure::<Bls12, _>(Cube).unwrap();
+    assert_eq!(stats.constraints, 3);
+    assert_eq!(stats.inputs, 2);
+    assert_eq!(stats.aux, 2);
+    assert_eq!(stats.a_density, 2);
+    assert_eq!(stats.b_density, 2);
+    assert_eq!(stats.c_density, 3);
+    assert_eq!(stats.max_lc_len, 2);
+}
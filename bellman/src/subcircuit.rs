@@ -0,0 +1,184 @@
+use ff::ScalarEngine;
+
+use std::collections::BTreeMap;
+
+use crate::{Circuit, ConstraintSystem, Coefficient};
+use crate::error::{Result, SynthesisError};
+
+/// A reusable component of a larger circuit. Rather than allocating public
+/// inputs, a sub-circuit exposes the variables it wants to share with its
+/// parent as named ports, which the parent wires to its own variables.
+pub trait SubCircuit<E: ScalarEngine> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<Ports>;
+}
+
+/// The named variables a sub-circuit exposes to its parent.
+#[derive(Clone, Debug, Default)]
+pub struct Ports {
+    ports: BTreeMap<String, Coefficient>
+}
+
+impl Ports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exposes `var` as the port `name`. Fails with `DuplicatePort` if
+    /// `name` is already exposed.
+    pub fn insert<N: Into<String>>(&mut self, name: N, var: Coefficient) -> Result<()> {
+        let name: String = name.into();
+        if self.ports.contains_key(&name) {
+            return Err(SynthesisError::DuplicatePort(name));
+        }
+        self.ports.insert(name, var);
+        Ok(())
+    }
+
+    /// Returns the variable exposed as `name`, if any.
+    pub fn get(&self, name: &str) -> Option<Coefficient> {
+        self.ports.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty()
+    }
+}
+
+/// A mapping from the ports of a sub-circuit to variables of its parent.
+#[derive(Clone, Debug, Default)]
+pub struct WireMap {
+    wires: Vec<(String, Coefficient)>
+}
+
+impl WireMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wires the port `name` to the parent variable `var`.
+    pub fn wire<N: Into<String>>(mut self, name: N, var: Coefficient) -> Self {
+        self.wires.push((name.into(), var));
+        self
+    }
+}
+
+/// Synthesizes `sub` under the namespace `name` and constrains each port in
+/// `wiring` to equal the parent variable it is mapped to. The ports are
+/// returned so that unwired outputs can be used by the parent.
+///
+/// Fails with `UnknownPort` if `wiring` names a port that `sub` does not
+/// expose.
+pub fn embed<E, CS, S, N>(cs: &mut CS, name: N, sub: S, wiring: &WireMap) -> Result<Ports>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>,
+    S: SubCircuit<E>,
+    N: Into<String>
+{
+    let mut cs = cs.namespace(|| name);
+    let ports: Ports = sub.synthesize(&mut cs)?;
+
+    for (port, var) in wiring.wires.iter() {
+        let exposed: Coefficient = ports.get(port)
+            .ok_or_else(|| SynthesisError::UnknownPort(port.clone()))?;
+
+        cs.enforce_equal(
+            || format!("wire {}", port),
+            |lc| lc + exposed,
+            |lc| lc + *var
+        );
+    }
+
+    Ok(ports)
+}
+
+/// Adapts a sub-circuit into a standalone circuit whose ports are left
+/// unconnected, so that a component can be tested and measured by itself.
+pub struct Standalone<S>(pub S);
+
+impl<E, S> Circuit<E> for Standalone<S>
+where
+    E: ScalarEngine,
+    S: SubCircuit<E>
+{
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<()> {
+        self.0.synthesize(cs)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_embed_sub_circuits() {
+    use ff::{Field, PrimeField};
+    use pairing::bls12_381::{Bls12, Fr};
+    use crate::{CircuitStats, SynthesisError};
+    use crate::gadgets::test::TestConstraintSystem;
+
+    struct Square(Option<Fr>);
+
+    // Exposes x and y = x^2
+    impl SubCircuit<Bls12> for Square {
+        fn synthesize<CS: ConstraintSystem<Bls12>>(self, cs: &mut CS) -> Result<Ports> {
+            let value = self.0;
+            let x = cs.alloc(|| "x", || value.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.alloc(|| "y", || {
+                let mut tmp = value.ok_or(SynthesisError::AssignmentMissing)?;
+                tmp.square();
+                Ok(tmp)
+            })?;
+            cs.enforce(|| "x^2", |lc| lc + x, |lc| lc + x, |lc| lc + y);
+
+            let mut ports = Ports::new();
+            ports.insert("x", x)?;
+            ports.insert("y", y)?;
+            Ok(ports)
+        }
+    }
+
+    let stats = CircuitStats::measure::<Bls12, _>(Standalone(Square(None))).unwrap();
+    assert_eq!(stats.constraints, 1);
+
+    // Computes x^4 by chaining the output of one square into another.
+    let x = Fr::from_str("3").unwrap();
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let first = embed(&mut cs, "first", Square(Some(x)), &WireMap::new()).unwrap();
+
+    let mut x_sq = x;
+    x_sq.square();
+    let wiring = WireMap::new().wire("x", first.get("y").unwrap());
+    let second = embed(&mut cs, "second", Square(Some(x_sq)), &wiring).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), 3);
+    assert_eq!(cs.get("second/y"), Fr::from_str("81").unwrap());
+    assert!(second.get("y").is_some());
+
+    // Wiring to a mismatched value is caught.
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let first = embed(&mut cs, "first", Square(Some(x)), &WireMap::new()).unwrap();
+    let wiring = WireMap::new().wire("x", first.get("x").unwrap());
+    embed(&mut cs, "second", Square(Some(x_sq)), &wiring).unwrap();
+    assert!(!cs.is_satisfied());
+
+    // Wiring a port the sub-circuit does not expose is an error.
+    let mut cs = TestConstraintSystem::<Bls12>::new();
+    let wiring = WireMap::new().wire("z", first.get("x").unwrap());
+    match embed(&mut cs, "missing", Square(Some(x)), &wiring) {
+        Err(SynthesisError::UnknownPort(port)) => assert_eq!(port, "z"),
+        _ => panic!("expected an unknown port to be rejected")
+    }
+
+    // So is exposing a port twice.
+    let mut ports = Ports::new();
+    ports.insert("x", first.get("x").unwrap()).unwrap();
+    match ports.insert("x", first.get("y").unwrap()) {
+        Err(SynthesisError::DuplicatePort(port)) => assert_eq!(port, "x"),
+        _ => panic!("expected a duplicate port to be rejected")
+    }
+    assert_eq!(ports.get("x"), first.get("x"));
+}
@@ -0,0 +1,453 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use ff::ScalarEngine;
+
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Read, Write};
+
+use crate::{ConstraintSystem, Coefficient, LinearCombination};
+use crate::error::Result;
+use crate::witness::{self, Terms};
+
+/// A single `enforce` call, recorded with the namespace path it was made
+/// under and the structure of its linear combinations.
+#[derive(Clone, Debug)]
+pub struct TraceEntry<E: ScalarEngine> {
+    pub path: String,
+    pub a: Terms<E::Fr>,
+    pub b: Terms<E::Fr>,
+    pub c: Terms<E::Fr>
+}
+
+/// The sequence of constraints enforced while synthesizing a circuit.
+/// Comparing the traces of two versions of a circuit shows where a change
+/// altered the constraint structure, and so invalidated its parameters.
+#[derive(Clone, Debug)]
+pub struct ConstraintTrace<E: ScalarEngine> {
+    entries: Vec<TraceEntry<E>>
+}
+
+impl<E> ConstraintTrace<E>
+where
+    E: ScalarEngine
+{
+    pub fn new() -> Self {
+        ConstraintTrace {
+            entries: Vec::new()
+        }
+    }
+
+    pub fn entries(&self) -> &[TraceEntry<E>] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Compares this trace against `other` and describes each difference on
+    /// its own line. The constraints are first aligned by namespace path, as
+    /// a line diff aligns lines, so that inserting or removing constraints
+    /// does not report every constraint after them as changed.
+    ///
+    /// Constraints only in this trace are prefixed with `-` and numbered as
+    /// in this trace, those only in `other` with `+` and numbered as in
+    /// `other`. Aligned constraints whose linear combinations differ, and
+    /// removed constraints replaced by one of the same structure under
+    /// another path, are prefixed with `~`. The result is empty if the
+    /// traces match.
+    pub fn diff(&self, other: &Self) -> String {
+        let old: Vec<&str> = self.entries.iter().map(|entry| entry.path.as_str()).collect();
+        let new: Vec<&str> = other.entries.iter().map(|entry| entry.path.as_str()).collect();
+
+        let mut out: String = String::new();
+        let mut removed: Vec<usize> = Vec::new();
+        let mut added: Vec<usize> = Vec::new();
+        for edit in align(&old, &new).into_iter().chain(Some(Edit::End)) {
+            match edit {
+                Edit::Delete(i) => removed.push(i),
+                Edit::Insert(j) => added.push(j),
+                Edit::Keep(..) | Edit::End => {
+                    self.write_hunk(other, &removed, &added, &mut out);
+                    removed.clear();
+                    added.clear();
+
+                    if let Edit::Keep(i, j) = edit {
+                        let (old, new) = (&self.entries[i], &other.entries[j]);
+                        let pairs = [("A", &old.a, &new.a), ("B", &old.b, &new.b), ("C", &old.c, &new.c)];
+                        for (name, before, after) in pairs.iter() {
+                            if before != after {
+                                writeln!(out, "~ #{} `{}` {}: {} => {}", j, new.path, name, display::<E>(before), display::<E>(after)).unwrap();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    // Describes a run of constraints removed from this trace and added to
+    // `other` between two aligned ones. A removed and an added constraint
+    // at the same position in the run with the same structure are taken
+    // to be a rename.
+    fn write_hunk(&self, other: &Self, removed: &[usize], added: &[usize], out: &mut String) {
+        let renamed = |k: usize| -> bool {
+            match (removed.get(k), added.get(k)) {
+                (Some(&i), Some(&j)) => {
+                    let (old, new) = (&self.entries[i], &other.entries[j]);
+                    old.a == new.a && old.b == new.b && old.c == new.c
+                },
+                _ => false
+            }
+        };
+
+        for (k, &i) in removed.iter().enumerate() {
+            if renamed(k) {
+                let j: usize = added[k];
+                writeln!(out, "~ #{} renamed `{}` to `{}`", j, self.entries[i].path, other.entries[j].path).unwrap();
+            } else {
+                writeln!(out, "- #{} `{}`", i, self.entries[i].path).unwrap();
+            }
+        }
+        for (k, &j) in added.iter().enumerate() {
+            if !renamed(k) {
+                writeln!(out, "+ #{} `{}`", j, other.entries[j].path).unwrap();
+            }
+        }
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u32::<BigEndian>(self.entries.len() as u32)?;
+        for entry in self.entries.iter() {
+            writer.write_u32::<BigEndian>(entry.path.len() as u32)?;
+            writer.write_all(entry.path.as_bytes())?;
+
+            for terms in [&entry.a, &entry.b, &entry.c].iter() {
+                witness::write_terms::<E,_>(&mut writer, terms)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let len = reader.read_u32::<BigEndian>()? as usize;
+
+        // Lengths are untrusted, so the trace and each path grow as they
+        // are read rather than being allocated up front.
+        let mut entries = vec![];
+        for _ in 0..len {
+            let path_len = reader.read_u32::<BigEndian>()? as u64;
+            let mut buf = vec![];
+            reader.by_ref().take(path_len).read_to_end(&mut buf)?;
+            if (buf.len() as u64) < path_len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "path is truncated"));
+            }
+
+            let path = String::from_utf8(buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            // The trace does not record the number of variables, so indices
+            // are not range checked.
+            let a = witness::read_terms::<E,_>(&mut reader, usize::MAX, usize::MAX)?;
+            let b = witness::read_terms::<E,_>(&mut reader, usize::MAX, usize::MAX)?;
+            let c = witness::read_terms::<E,_>(&mut reader, usize::MAX, usize::MAX)?;
+
+            entries.push(TraceEntry { path, a, b, c });
+        }
+
+        Ok(ConstraintTrace { entries })
+    }
+}
+
+impl<E: ScalarEngine> PartialEq for TraceEntry<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+            && self.a == other.a
+            && self.b == other.b
+            && self.c == other.c
+    }
+}
+
+impl<E: ScalarEngine> PartialEq for ConstraintTrace<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<E> Default for ConstraintTrace<E>
+where
+    E: ScalarEngine
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Edit {
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+    End
+}
+
+/// Aligns `old` with `new` by a longest common subsequence, returned as
+/// the edits which turn one into the other. This is Myers' algorithm,
+/// which takes O((n + m) d) time and O(d^2) space for d edits, so large
+/// traces with few changes are cheap to compare.
+fn align<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let (n, m): (isize, isize) = (old.len() as isize, new.len() as isize);
+    let offset: isize = n + m + 1;
+
+    // The furthest x reached on each diagonal k = x - y, and a snapshot of
+    // the diagonals -d..=d after each number d of edits, for backtracking.
+    let mut v: Vec<isize> = vec![0; (2 * offset + 1) as usize];
+    let mut history: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=(n + m) {
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| (k + offset) as usize;
+            let mut x: isize = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y: isize = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+
+            if x >= n && y >= m {
+                history.push(v[at(-d)..=at(d)].to_vec());
+                break 'search;
+            }
+        }
+        history.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+
+    let mut edits: Vec<Edit> = Vec::new();
+    let (mut x, mut y): (isize, isize) = (n, m);
+    for d in (1..history.len() as isize).rev() {
+        let previous: &[isize] = &history[(d - 1) as usize];
+        let at = |k: isize| previous[(k + d - 1) as usize];
+
+        let k: isize = x - y;
+        let down: bool = k == -d || (k != d && at(k - 1) < at(k + 1));
+        let prev_x: isize = if down { at(k + 1) } else { at(k - 1) };
+        let prev_y: isize = prev_x - if down { k + 1 } else { k - 1 };
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        edits.push(if down {
+            Edit::Insert(prev_y as usize)
+        } else {
+            Edit::Delete(prev_x as usize)
+        });
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push(Edit::Keep(x as usize, y as usize));
+    }
+
+    edits.reverse();
+    edits
+}
+
+fn display<E: ScalarEngine>(terms: &Terms<E::Fr>) -> String {
+    if terms.is_empty() {
+        return "0".to_string()
+    }
+
+    terms.iter()
+        .map(|(index, coeff)| format!("{} * {}", coeff, index))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// A constraint system which forwards every call to `inner` while
+/// recording a `ConstraintTrace` of the constraints it enforces.
+pub struct TraceRecorder<E: ScalarEngine, CS: ConstraintSystem<E>> {
+    inner: CS,
+    trace: ConstraintTrace<E>,
+    current_namespace: Vec<String>
+}
+
+impl<E, CS> TraceRecorder<E, CS>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>
+{
+    pub fn new(inner: CS) -> Self {
+        TraceRecorder {
+            inner,
+            trace: ConstraintTrace::new(),
+            current_namespace: Vec::new()
+        }
+    }
+
+    pub fn trace(&self) -> &ConstraintTrace<E> {
+        &self.trace
+    }
+
+    pub fn into_parts(self) -> (CS, ConstraintTrace<E>) {
+        (self.inner, self.trace)
+    }
+}
+
+impl<E, CS> ConstraintSystem<E> for TraceRecorder<E, CS>
+where
+    E: ScalarEngine,
+    CS: ConstraintSystem<E>
+{
+    type Root = Self;
+
+    fn one() -> Coefficient {
+        CS::one()
+    }
+
+    fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc(annotation, f)
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inner.alloc_input(annotation, f)
+    }
+
+    fn alloc_input_named<F, N>(&mut self, name: N, f: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        N: Into<String>,
+    {
+        self.inner.alloc_input_named(name, f)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        let annotation: String = annotation().into();
+        let a: LinearCombination<E> = a(LinearCombination::zero());
+        let b: LinearCombination<E> = b(LinearCombination::zero());
+        let c: LinearCombination<E> = c(LinearCombination::zero());
+
+        let path: String = self.current_namespace
+            .iter()
+            .chain(Some(&annotation))
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.trace.entries.push(TraceEntry {
+            path,
+            a: witness::terms(a.clone()),
+            b: witness::terms(b.clone()),
+            c: witness::terms(c.clone())
+        });
+
+        self.inner.enforce(|| annotation, |lc| lc + &a, |lc| lc + &b, |lc| lc + &c);
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        self.current_namespace.push(name.clone());
+        self.inner.push_namespace(|| name);
+    }
+
+    fn pop_namespace(&mut self) {
+        self.current_namespace.pop();
+        self.inner.pop_namespace();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_trace_diff() {
+    use ff::Field;
+    use pairing::bls12_381::{Bls12, Fr};
+    use crate::gadgets::test::TestConstraintSystem;
+
+    // x * x = y, optionally followed by y * x = z, then (x + x) * 1 = y + y
+    fn synthesize<CS: ConstraintSystem<Bls12>>(cs: &mut CS, square: &str, cube: bool) -> Result<()> {
+        let x = cs.alloc(|| "x", || Ok(Fr::one()))?;
+        let y = cs.alloc(|| "y", || Ok(Fr::one()))?;
+
+        {
+            let mut cs = cs.namespace(|| square);
+            cs.enforce(|| "x^2", |lc| lc + x, |lc| lc + x, |lc| lc + y);
+            if cube {
+                let z = cs.alloc(|| "z", || Ok(Fr::one()))?;
+                cs.enforce(|| "x^3", |lc| lc + y, |lc| lc + x, |lc| lc + z);
+            }
+        }
+
+        let mut cs = cs.namespace(|| "double");
+        cs.enforce(|| "2x", |lc| lc + x + x, |lc| lc + CS::one(), |lc| lc + y + y);
+        Ok(())
+    }
+
+    let record = |square: &str, cube: bool| {
+        let mut recorder = TraceRecorder::new(TestConstraintSystem::<Bls12>::new());
+        synthesize(&mut recorder, square, cube).unwrap();
+        let (inner, trace) = recorder.into_parts();
+        assert!(inner.is_satisfied());
+        trace
+    };
+    let old: ConstraintTrace<Bls12> = record("square", false);
+    let new: ConstraintTrace<Bls12> = record("square", true);
+
+    assert_eq!(old.entries()[0].path, "square/x^2");
+    assert_eq!(old.diff(&old), "");
+
+    // The constraint after the inserted one is aligned, not reported as changed.
+    assert_eq!(old.diff(&new), "+ #1 `square/x^3`\n");
+    assert_eq!(new.diff(&old), "- #1 `square/x^3`\n");
+
+    // A constraint moved to another path but otherwise unchanged is a rename.
+    let renamed: ConstraintTrace<Bls12> = record("squared", false);
+    assert_eq!(old.diff(&renamed), "~ #0 renamed `square/x^2` to `squared/x^2`\n");
+    assert_eq!(
+        new.diff(&renamed),
+        "~ #0 renamed `square/x^2` to `squared/x^2`\n- #1 `square/x^3`\n"
+    );
+
+    let mut buf = vec![];
+    new.write(&mut buf).unwrap();
+    assert_eq!(ConstraintTrace::<Bls12>::read(&buf[..]).unwrap(), new);
+
+    // Traces and paths claiming more than follows are errors.
+    assert!(ConstraintTrace::<Bls12>::read(&[0xff, 0xff, 0xff, 0xff][..]).is_err());
+    assert!(ConstraintTrace::<Bls12>::read(&[0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff][..]).is_err());
+}
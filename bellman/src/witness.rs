@@ -0,0 +1,618 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use ff::{Field, PrimeField, PrimeFieldRepr, ScalarEngine};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use crate::{Circuit, ConstraintSystem, Coefficient, Index, LinearCombination};
+use crate::error::{Result, SynthesisError};
+
+pub(crate) type Terms<F> = Vec<(Index, F)>;
+
+/// The structure of a synthesized circuit, recorded without evaluating any
+/// assignments. A recording can be serialized and later used to compute a
+/// full witness from the public inputs alone, with hints supplying the
+/// auxiliary variables that the constraints do not determine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedCircuit<E: ScalarEngine> {
+    num_inputs: usize,
+    num_aux: usize,
+    constraints: Vec<(Terms<E::Fr>, Terms<E::Fr>, Terms<E::Fr>)>
+}
+
+/// A full assignment to a circuit. The first input is the constant one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Witness<E: ScalarEngine> {
+    pub inputs: Vec<E::Fr>,
+    pub aux: Vec<E::Fr>
+}
+
+impl<E> RecordedCircuit<E>
+where
+    E: ScalarEngine
+{
+    /// Synthesize `circuit` without evaluating any assignments and record
+    /// the resulting constraints.
+    pub fn record<C: Circuit<E>>(circuit: C) -> Result<Self> {
+        let mut cs: RecordedCircuit<E> = RecordedCircuit {
+            num_inputs: 0,
+            num_aux: 0,
+            constraints: Vec::new()
+        };
+        cs.alloc_input(|| "", || Ok(E::Fr::one()))?;
+        circuit.synthesize(&mut cs)?;
+
+        Ok(cs)
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_aux(&self) -> usize {
+        self.num_aux
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Computes a witness from the public `inputs`, excluding the constant
+    /// one, and `hints` mapping auxiliary indices to their values.
+    ///
+    /// Constraints are solved repeatedly for any auxiliary variable which is
+    /// the only unknown in the constraint and appears in just one of A, B
+    /// or C, with a nonzero coefficient. Variables which cannot be solved
+    /// this way, such as those only constrained to be boolean, must be given
+    /// as hints.
+    pub fn calculate_witness(&self, inputs: &[E::Fr], hints: &BTreeMap<usize, E::Fr>) -> Result<Witness<E>> {
+        if inputs.len() + 1 != self.num_inputs {
+            return Err(SynthesisError::MalformedWireSize)
+        }
+
+        let mut aux: Vec<Option<E::Fr>> = vec![None; self.num_aux];
+        for (index, value) in hints.iter() {
+            *aux.get_mut(*index)? = Some(*value);
+        }
+
+        let witness_inputs: Vec<E::Fr> = Some(E::Fr::one())
+            .into_iter()
+            .chain(inputs.iter().cloned())
+            .collect();
+
+        let mut solved: Vec<bool> = vec![false; self.constraints.len()];
+        let mut progress: bool = true;
+        while progress {
+            progress = false;
+            for (constraint, done) in self.constraints.iter().zip(solved.iter_mut()) {
+                if *done {
+                    continue
+                }
+                match solve::<E>(constraint, &witness_inputs, &aux) {
+                    Solution::Known => *done = true,
+                    Solution::Solved(index, value) => {
+                        aux[index] = Some(value);
+                        *done = true;
+                        progress = true;
+                    },
+                    Solution::Unknown => ()
+                }
+            }
+        }
+
+        let aux: Vec<E::Fr> = aux.into_iter()
+            .map(|value| value.ok_or(SynthesisError::AssignmentMissing))
+            .collect::<Result<_>>()?;
+
+        let witness: Witness<E> = Witness {
+            inputs: witness_inputs,
+            aux
+        };
+        if !self.is_satisfied(&witness) {
+            return Err(SynthesisError::Unsatisfiable)
+        }
+
+        Ok(witness)
+    }
+
+    /// Returns a circuit which replays the recorded constraints, so that the
+    /// generator and prover can run without re-synthesizing the original
+    /// circuit. The prover additionally requires the `witness`.
+    pub fn replay<'a>(&'a self, witness: Option<&'a Witness<E>>) -> Replay<'a, E> {
+        Replay {
+            recorded: self,
+            witness
+        }
+    }
+
+    /// Returns whether `witness` satisfies every recorded constraint.
+    pub fn is_satisfied(&self, witness: &Witness<E>) -> bool {
+        let value = |index: &Index| match index {
+            Index::Input(i) => witness.inputs.get(*i).copied(),
+            Index::Aux(i) => witness.aux.get(*i).copied()
+        };
+
+        self.constraints.iter().all(|(a, b, c)| {
+            match (eval::<E,_>(a, value), eval::<E,_>(b, value), eval::<E,_>(c, value)) {
+                (Some(mut a), Some(b), Some(c)) => {
+                    a.mul_assign(&b);
+                    a == c
+                },
+                _ => false
+            }
+        })
+    }
+
+    /// Serializes the recording. Fails with `InvalidInput` if a count or
+    /// index does not fit in 32 bits, or if a variable other than the
+    /// constant one appears in no constraint, as `read` would reject it.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if let Some(index) = self.unconstrained() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} appears in no constraint", index)
+            ))
+        }
+
+        write_u32(&mut writer, self.num_inputs)?;
+        write_u32(&mut writer, self.num_aux)?;
+        write_u32(&mut writer, self.constraints.len())?;
+
+        for (a, b, c) in self.constraints.iter() {
+            for terms in [a, b, c].iter() {
+                write_terms::<E,_>(&mut writer, terms)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let num_inputs = reader.read_u32::<BigEndian>()? as usize;
+        let num_aux = reader.read_u32::<BigEndian>()? as usize;
+        let len = reader.read_u32::<BigEndian>()? as usize;
+
+        // The length is untrusted, so the constraints grow as they are read
+        // rather than being allocated up front.
+        let mut constraints = vec![];
+        for _ in 0..len {
+            let a = read_terms::<E,_>(&mut reader, num_inputs, num_aux)?;
+            let b = read_terms::<E,_>(&mut reader, num_inputs, num_aux)?;
+            let c = read_terms::<E,_>(&mut reader, num_inputs, num_aux)?;
+            constraints.push((a, b, c));
+        }
+
+        let recorded: RecordedCircuit<E> = RecordedCircuit {
+            num_inputs,
+            num_aux,
+            constraints
+        };
+
+        // The variable counts are untrusted too, and witness calculation
+        // and replay allocate one entry per variable. Requiring each to
+        // appear in a constraint bounds them by the length of the input.
+        if let Some(index) = recorded.unconstrained() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{:?} appears in no constraint", index)
+            ))
+        }
+
+        Ok(recorded)
+    }
+
+    // Returns the first variable, other than the constant one, which appears
+    // in no constraint. Neither pass allocates more than the constraints.
+    fn unconstrained(&self) -> Option<Index> {
+        let mut inputs: BTreeSet<usize> = BTreeSet::new();
+        let mut aux: BTreeSet<usize> = BTreeSet::new();
+        for (a, b, c) in self.constraints.iter() {
+            for (index, _) in a.iter().chain(b.iter()).chain(c.iter()) {
+                match index {
+                    Index::Input(i) => inputs.insert(*i),
+                    Index::Aux(i) => aux.insert(*i)
+                };
+            }
+        }
+
+        if self.num_inputs == 0 {
+            return Some(Index::Input(0))
+        }
+        let input = (1..self.num_inputs).find(|i| !inputs.contains(i));
+        let aux = (0..self.num_aux).find(|i| !aux.contains(i));
+        input.map(Index::Input).or_else(|| aux.map(Index::Aux))
+    }
+}
+
+/// A recorded circuit replayed through `Circuit::synthesize`.
+pub struct Replay<'a, E: ScalarEngine> {
+    recorded: &'a RecordedCircuit<E>,
+    witness: Option<&'a Witness<E>>
+}
+
+impl<'a, E> Circuit<E> for Replay<'a, E>
+where
+    E: ScalarEngine
+{
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<()> {
+        let witness: Option<&Witness<E>> = self.witness;
+        // Without a witness there is nothing to assign, as when generating
+        // parameters. A witness too short for the recording is reported
+        // against the first variable it leaves unassigned.
+        let value = |index: Index| -> Result<E::Fr> {
+            let witness: &Witness<E> = witness.ok_or(SynthesisError::AssignmentMissing)?;
+            let value: Option<&E::Fr> = match index {
+                Index::Input(i) => witness.inputs.get(i),
+                Index::Aux(i) => witness.aux.get(i)
+            };
+            value.copied().ok_or(SynthesisError::UnassignedVariable(index))
+        };
+
+        // The constant one is allocated by the caller, so the recorded
+        // indices line up with those handed out by `cs`.
+        for i in 1..self.recorded.num_inputs {
+            cs.alloc_input(|| format!("input {}", i), || value(Index::Input(i)))?;
+        }
+        for i in 0..self.recorded.num_aux {
+            cs.alloc(|| format!("aux {}", i), || value(Index::Aux(i)))?;
+        }
+
+        let lc = |terms: &Terms<E::Fr>| -> LinearCombination<E> {
+            terms.iter().fold(LinearCombination::zero(), |lc, (index, coeff)| {
+                lc + (*coeff, Coefficient::new_unchecked(*index))
+            })
+        };
+
+        for (i, (a, b, c)) in self.recorded.constraints.iter().enumerate() {
+            cs.enforce(|| format!("constraint {}", i), |_| lc(a), |_| lc(b), |_| lc(c));
+        }
+
+        Ok(())
+    }
+}
+
+enum Solution<F> {
+    Known,
+    Solved(usize, F),
+    Unknown
+}
+
+fn eval<E, V>(terms: &Terms<E::Fr>, value: V) -> Option<E::Fr>
+where
+    E: ScalarEngine,
+    V: Fn(&Index) -> Option<E::Fr>
+{
+    let mut acc: E::Fr = E::Fr::zero();
+    for (index, coeff) in terms.iter() {
+        let mut tmp: E::Fr = value(index)?;
+        tmp.mul_assign(coeff);
+        acc.add_assign(&tmp);
+    }
+    Some(acc)
+}
+
+// Splits `terms` into the sum of its known terms and the coefficient of the
+// single unknown variable `index`, if that variable appears.
+fn split<E>(terms: &Terms<E::Fr>, index: usize, inputs: &[E::Fr], aux: &[Option<E::Fr>]) -> (E::Fr, Option<E::Fr>)
+where
+    E: ScalarEngine
+{
+    let mut known: E::Fr = E::Fr::zero();
+    let mut unknown: Option<E::Fr> = None;
+    for (var, coeff) in terms.iter() {
+        match var {
+            Index::Aux(i) if *i == index => {
+                let acc: &mut E::Fr = unknown.get_or_insert_with(E::Fr::zero);
+                acc.add_assign(coeff);
+            },
+            Index::Aux(i) => {
+                let mut tmp: E::Fr = aux[*i].unwrap_or_else(E::Fr::zero);
+                tmp.mul_assign(coeff);
+                known.add_assign(&tmp);
+            },
+            Index::Input(i) => {
+                let mut tmp: E::Fr = inputs[*i];
+                tmp.mul_assign(coeff);
+                known.add_assign(&tmp);
+            }
+        }
+    }
+    (known, unknown)
+}
+
+fn solve<E>(
+    (a, b, c): &(Terms<E::Fr>, Terms<E::Fr>, Terms<E::Fr>),
+    inputs: &[E::Fr],
+    aux: &[Option<E::Fr>]
+) -> Solution<E::Fr>
+where
+    E: ScalarEngine
+{
+    let mut unknowns: Vec<usize> = a.iter()
+        .chain(b.iter())
+        .chain(c.iter())
+        .filter_map(|(var, _)| match var {
+            Index::Aux(i) if aux[*i].is_none() => Some(*i),
+            _ => None
+        })
+        .collect();
+    unknowns.sort();
+    unknowns.dedup();
+
+    let index: usize = match unknowns.as_slice() {
+        [] => return Solution::Known,
+        [index] => *index,
+        _ => return Solution::Unknown
+    };
+
+    let (a, a_coeff) = split::<E>(a, index, inputs, aux);
+    let (b, b_coeff) = split::<E>(b, index, inputs, aux);
+    let (c, c_coeff) = split::<E>(c, index, inputs, aux);
+
+    // Solve `known + coeff * x = target` for x. If the terms in x cancel,
+    // this constraint does not determine x, though another may.
+    let linear = |known: E::Fr, coeff: E::Fr, mut target: E::Fr| -> Solution<E::Fr> {
+        match coeff.inverse() {
+            Some(inverse) => {
+                target.sub_assign(&known);
+                target.mul_assign(&inverse);
+                Solution::Solved(index, target)
+            },
+            None => Solution::Unknown
+        }
+    };
+
+    match (a_coeff, b_coeff, c_coeff) {
+        (None, None, Some(coeff)) => {
+            let mut target: E::Fr = a;
+            target.mul_assign(&b);
+            linear(c, coeff, target)
+        },
+        (Some(coeff), None, None) => match b.inverse() {
+            Some(inverse) => {
+                let mut target: E::Fr = c;
+                target.mul_assign(&inverse);
+                linear(a, coeff, target)
+            },
+            None => Solution::Unknown
+        },
+        (None, Some(coeff), None) => match a.inverse() {
+            Some(inverse) => {
+                let mut target: E::Fr = c;
+                target.mul_assign(&inverse);
+                linear(b, coeff, target)
+            },
+            None => Solution::Unknown
+        },
+        _ => Solution::Unknown
+    }
+}
+
+pub(crate) fn write_terms<E, W>(writer: &mut W, terms: &Terms<E::Fr>) -> io::Result<()>
+where
+    E: ScalarEngine,
+    W: Write
+{
+    write_u32(writer, terms.len())?;
+    for (index, coeff) in terms.iter() {
+        match index {
+            Index::Input(i) => {
+                writer.write_u8(0)?;
+                write_u32(writer, *i)?;
+            },
+            Index::Aux(i) => {
+                writer.write_u8(1)?;
+                write_u32(writer, *i)?;
+            }
+        }
+        coeff.into_repr().write_be(&mut *writer)?;
+    }
+    Ok(())
+}
+
+// Writes a count or index, which the format stores in 32 bits.
+fn write_u32<W: Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    let value: u32 = u32::try_from(value).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, "value does not fit in 32 bits")
+    })?;
+    writer.write_u32::<BigEndian>(value)
+}
+
+pub(crate) fn read_terms<E, R>(reader: &mut R, num_inputs: usize, num_aux: usize) -> io::Result<Terms<E::Fr>>
+where
+    E: ScalarEngine,
+    R: Read
+{
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let len = reader.read_u32::<BigEndian>()? as usize;
+    let mut terms = vec![];
+    for _ in 0..len {
+        let tag = reader.read_u8()?;
+        let i = reader.read_u32::<BigEndian>()? as usize;
+        let index = match tag {
+            0 if i < num_inputs => Index::Input(i),
+            1 if i < num_aux => Index::Aux(i),
+            _ => return Err(invalid("variable index out of range"))
+        };
+
+        let mut repr = <E::Fr as PrimeField>::Repr::default();
+        repr.read_be(&mut *reader)?;
+        let coeff = E::Fr::from_repr(repr)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        terms.push((index, coeff));
+    }
+    Ok(terms)
+}
+
+pub(crate) fn terms<E: ScalarEngine>(lc: LinearCombination<E>) -> Terms<E::Fr> {
+    lc.0.into_iter()
+        .map(|(var, coeff)| (var.get_unchecked(), coeff))
+        .collect()
+}
+
+impl<E> ConstraintSystem<E> for RecordedCircuit<E>
+where
+    E: ScalarEngine
+{
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We never invoke the assignment function, as the witness
+        // is computed from the recording later on.
+        let index: usize = self.num_aux;
+        self.num_aux += 1;
+
+        Ok(Coefficient::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Coefficient>
+    where
+        F: FnOnce() -> Result<E::Fr>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index: usize = self.num_inputs;
+        self.num_inputs += 1;
+
+        Ok(Coefficient::new_unchecked(Index::Input(index)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LB: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+        LC: FnOnce(LinearCombination<E>) -> LinearCombination<E>,
+    {
+        let a: Terms<E::Fr> = terms(a(LinearCombination::zero()));
+        let b: Terms<E::Fr> = terms(b(LinearCombination::zero()));
+        let c: Terms<E::Fr> = terms(c(LinearCombination::zero()));
+
+        self.constraints.push((a, b, c));
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+#[cfg(feature = "pairing")]
+#[test]
+fn test_calculate_witness() {
+    use pairing::bls12_381::{Bls12, Fr};
+
+    struct Cubic;
+
+    // Proves knowledge of x such that x^3 + x + 5 = y
+    impl<E: ScalarEngine> Circuit<E> for Cubic {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<()> {
+            let x = cs.alloc(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+            let x_sq = cs.alloc(|| "x^2", || Err(SynthesisError::AssignmentMissing))?;
+            let x_cu = cs.alloc(|| "x^3", || Err(SynthesisError::AssignmentMissing))?;
+            let y = cs.alloc_input(|| "y", || Err(SynthesisError::AssignmentMissing))?;
+
+            let mut five = E::Fr::one();
+            five.double();
+            five.double();
+            five.add_assign(&E::Fr::one());
+
+            cs.enforce(|| "x^2", |lc| lc + x, |lc| lc + x, |lc| lc + x_sq);
+            cs.enforce(|| "x^3", |lc| lc + x_sq, |lc| lc + x, |lc| lc + x_cu);
+            cs.enforce(|| "y", |lc| lc + x_cu + x + (five, CS::one()), |lc| lc + CS::one(), |lc| lc + y);
+
+            Ok(())
+        }
+    }
+
+    let recorded = RecordedCircuit::<Bls12>::record(Cubic).unwrap();
+    assert_eq!(recorded.num_inputs(), 2);
+    assert_eq!(recorded.num_aux(), 3);
+    assert_eq!(recorded.num_constraints(), 3);
+
+    let mut buf = vec![];
+    recorded.write(&mut buf).unwrap();
+    let recorded = RecordedCircuit::<Bls12>::read(&buf[..]).unwrap();
+
+    // Constraints and terms claiming more than follows are errors.
+    let mut truncated = buf[..8].to_vec();
+    truncated.extend_from_slice(&[0xff; 4]);
+    assert!(RecordedCircuit::<Bls12>::read(&truncated[..]).is_err());
+    let mut truncated = buf[..8].to_vec();
+    truncated.extend_from_slice(&[0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff]);
+    assert!(RecordedCircuit::<Bls12>::read(&truncated[..]).is_err());
+
+    // So are variables which no constraint refers to, which would otherwise
+    // let a short header claim billions of them.
+    let crafted: [u8; 12] = [0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0];
+    assert!(RecordedCircuit::<Bls12>::read(&crafted[..]).is_err());
+    let mut unconstrained = recorded.clone();
+    unconstrained.num_aux += 1;
+    assert!(unconstrained.write(&mut vec![]).is_err());
+    if let Some(value) = (u32::max_value() as usize).checked_add(1) {
+        assert!(write_u32(&mut vec![], value).is_err());
+    }
+
+    let y = Fr::from_str("35").unwrap();
+    let mut hints = BTreeMap::new();
+
+    // x appears in both A and B of its only constraint, so must be hinted.
+    assert!(recorded.calculate_witness(&[y], &hints).is_err());
+
+    hints.insert(0, Fr::from_str("3").unwrap());
+    let witness = recorded.calculate_witness(&[y], &hints).unwrap();
+    assert_eq!(witness.inputs, vec![Fr::one(), y]);
+    assert_eq!(witness.aux, vec![
+        Fr::from_str("3").unwrap(),
+        Fr::from_str("9").unwrap(),
+        Fr::from_str("27").unwrap()
+    ]);
+    assert!(recorded.is_satisfied(&witness));
+
+    // A hint inconsistent with the public input is caught.
+    hints.insert(0, Fr::from_str("2").unwrap());
+    match recorded.calculate_witness(&[y], &hints) {
+        Err(SynthesisError::Unsatisfiable) => (),
+        _ => panic!("expected an unsatisfiable witness")
+    }
+
+    struct Cancelling;
+
+    // The terms in x cancel in the first constraint, which must be skipped
+    // rather than divided by, leaving the second to solve for x.
+    impl<E: ScalarEngine> Circuit<E> for Cancelling {
+        fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<()> {
+            let x = cs.alloc(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+            let y = cs.alloc_input(|| "y", || Err(SynthesisError::AssignmentMissing))?;
+
+            cs.enforce(|| "cancel", |lc| lc + CS::one(), |lc| lc + CS::one(), |lc| lc + x - x + CS::one());
+            cs.enforce(|| "x = y", |lc| lc + x, |lc| lc + CS::one(), |lc| lc + y);
+
+            Ok(())
+        }
+    }
+
+    let recorded = RecordedCircuit::<Bls12>::record(Cancelling).unwrap();
+    let witness = recorded.calculate_witness(&[y], &BTreeMap::new()).unwrap();
+    assert_eq!(witness.aux, vec![y]);
+}
@@ -1,5 +1,5 @@
 // For randomness (during paramgen and proof generation)
-use rand::thread_rng;
+use rand::rngs::StdRng;
 
 // For benchmarking
 use std::time::{Duration, Instant};
@@ -17,6 +17,7 @@ use better_bellman::{Circuit, ConstraintSystem, SynthesisError};
 // We're going to use the Groth16 proving system.
 use better_bellman::groth16::{
     create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, Proof,
+    ProverRng,
 };
 
 const MIMC_ROUNDS: usize = 322;
@@ -148,9 +149,9 @@ impl<'a, E: Engine> Circuit<E> for MiMCDemo<'a, E> {
 
 #[test]
 fn test_mimc() {
-    // This may not be cryptographically safe, use
-    // `OsRng` (for example) in production software.
-    let rng = &mut thread_rng();
+    // Seeded from the operating system, and reseeded if the
+    // process forks.
+    let rng = &mut ProverRng::<StdRng>::from_entropy();
 
     // Generate the MiMC round constants
     let constants = (0..MIMC_ROUNDS)
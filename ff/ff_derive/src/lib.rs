@@ -775,6 +775,39 @@ fn prime_field_impl(
 
     let squaring_impl = sqr_impl(quote! {self}, limbs);
     let multiply_impl = mul_impl(quote! {self}, quote! {other}, limbs);
+
+    // Four-limb fields multiply four elements at a time with the vectorised
+    // backend in `ff::batch`, where the CPU has one.
+    let batch_mul_impl = if limbs == 4 {
+        quote! {
+            fn batch_mul_assign(lhs: &mut [#name], rhs: &[#name]) {
+                assert_eq!(lhs.len(), rhs.len(), "expected slices of the same length");
+
+                let mut lhs_chunks = lhs.chunks_exact_mut(4);
+                let mut rhs_chunks = rhs.chunks_exact(4);
+                for (a, b) in (&mut lhs_chunks).zip(&mut rhs_chunks) {
+                    let mut x = [(a[0].0).0, (a[1].0).0, (a[2].0).0, (a[3].0).0];
+                    let y = [(b[0].0).0, (b[1].0).0, (b[2].0).0, (b[3].0).0];
+                    if ::ff::batch::mul_assign_x4(&mut x, &y, &MODULUS.0, INV) {
+                        for (a, x) in a.iter_mut().zip(x.iter()) {
+                            (a.0).0 = *x;
+                        }
+                    } else {
+                        for (a, b) in a.iter_mut().zip(b.iter()) {
+                            a.mul_assign(b);
+                        }
+                    }
+                }
+
+                let rhs_rest = rhs_chunks.remainder();
+                for (a, b) in lhs_chunks.into_remainder().iter_mut().zip(rhs_rest.iter()) {
+                    a.mul_assign(b);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
     let montgomery_impl = mont_impl(limbs);
 
     // (self.0).0[0], (self.0).0[1], ..., 0, 0, 0, 0, ...
@@ -1020,6 +1053,8 @@ fn prime_field_impl(
                 #multiply_impl
             }
 
+            #batch_mul_impl
+
             #[inline]
             fn square(&mut self)
             {
@@ -0,0 +1,161 @@
+//! Vectorised arithmetic used by the fields generated by `ff_derive`. This
+//! is an implementation detail of the derive and is not a stable interface.
+//!
+//! Only multiplication of four-limb fields is vectorised, and only on
+//! x86_64 CPUs with AVX2. Addition and subtraction stay scalar, as a single
+//! add-with-carry chain is already cheaper than moving the limbs in and out
+//! of vectors, and there is no NEON backend for ARM.
+
+/// Multiplies each of four elements of `a` by the corresponding element of
+/// `b` in Montgomery form with R = 2^256, for a field with a four-limb
+/// `modulus` and `inv = -modulus^{-1} mod 2^64`. The elements must be
+/// reduced. Returns false, leaving `a` untouched, if this CPU has no
+/// vectorised backend.
+#[inline]
+pub fn mul_assign_x4(a: &mut [[u64; 4]; 4], b: &[[u64; 4]; 4], modulus: &[u64; 4], inv: u64) -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            unsafe { avx2::mul_assign_x4(a, b, modulus, inv) };
+            return true;
+        }
+    }
+
+    let _ = (a, b, modulus, inv);
+    false
+}
+
+/// Whether `mul_assign_x4` has a vectorised backend on this CPU.
+pub fn is_vectorised() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    // Each lane of a vector holds one of the four elements, so a field
+    // element is held as eight vectors of 32-bit limbs in 64-bit lanes. A
+    // 32-bit product plus two 32-bit carries then never overflows a lane.
+
+    /// Transposes four elements into eight vectors of 32-bit limbs, least
+    /// significant first. Only the low half of each lane is meaningful.
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(x: &[[u64; 4]; 4]) -> [__m256i; 8] {
+        let r0 = _mm256_loadu_si256(x[0].as_ptr() as *const __m256i);
+        let r1 = _mm256_loadu_si256(x[1].as_ptr() as *const __m256i);
+        let r2 = _mm256_loadu_si256(x[2].as_ptr() as *const __m256i);
+        let r3 = _mm256_loadu_si256(x[3].as_ptr() as *const __m256i);
+
+        let t0 = _mm256_unpacklo_epi64(r0, r1);
+        let t1 = _mm256_unpackhi_epi64(r0, r1);
+        let t2 = _mm256_unpacklo_epi64(r2, r3);
+        let t3 = _mm256_unpackhi_epi64(r2, r3);
+
+        let w0 = _mm256_permute2x128_si256(t0, t2, 0x20);
+        let w1 = _mm256_permute2x128_si256(t1, t3, 0x20);
+        let w2 = _mm256_permute2x128_si256(t0, t2, 0x31);
+        let w3 = _mm256_permute2x128_si256(t1, t3, 0x31);
+
+        [
+            w0,
+            _mm256_srli_epi64(w0, 32),
+            w1,
+            _mm256_srli_epi64(w1, 32),
+            w2,
+            _mm256_srli_epi64(w2, 32),
+            w3,
+            _mm256_srli_epi64(w3, 32),
+        ]
+    }
+
+    /// The inverse of `load`, for limbs which are below 2^32.
+    #[target_feature(enable = "avx2")]
+    unsafe fn store(x: &mut [[u64; 4]; 4], limbs: &[__m256i; 8]) {
+        let w0 = _mm256_or_si256(limbs[0], _mm256_slli_epi64(limbs[1], 32));
+        let w1 = _mm256_or_si256(limbs[2], _mm256_slli_epi64(limbs[3], 32));
+        let w2 = _mm256_or_si256(limbs[4], _mm256_slli_epi64(limbs[5], 32));
+        let w3 = _mm256_or_si256(limbs[6], _mm256_slli_epi64(limbs[7], 32));
+
+        let t0 = _mm256_unpacklo_epi64(w0, w1);
+        let t1 = _mm256_unpackhi_epi64(w0, w1);
+        let t2 = _mm256_unpacklo_epi64(w2, w3);
+        let t3 = _mm256_unpackhi_epi64(w2, w3);
+
+        _mm256_storeu_si256(x[0].as_mut_ptr() as *mut __m256i, _mm256_permute2x128_si256(t0, t2, 0x20));
+        _mm256_storeu_si256(x[1].as_mut_ptr() as *mut __m256i, _mm256_permute2x128_si256(t1, t3, 0x20));
+        _mm256_storeu_si256(x[2].as_mut_ptr() as *mut __m256i, _mm256_permute2x128_si256(t0, t2, 0x31));
+        _mm256_storeu_si256(x[3].as_mut_ptr() as *mut __m256i, _mm256_permute2x128_si256(t1, t3, 0x31));
+    }
+
+    /// Coarsely integrated operand scanning Montgomery multiplication over
+    /// 32-bit words, in each of the four lanes.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn mul_assign_x4(a: &mut [[u64; 4]; 4], b: &[[u64; 4]; 4], modulus: &[u64; 4], inv: u64) {
+        let mask = _mm256_set1_epi64x(0xffff_ffff);
+        let a_limbs = load(a);
+        let b_limbs = load(b);
+
+        let mut m_limbs = [_mm256_setzero_si256(); 8];
+        for (i, limb) in m_limbs.iter_mut().enumerate() {
+            *limb = _mm256_set1_epi64x((modulus[i / 2] >> ((i % 2) * 32)) as u32 as i64);
+        }
+        let inv = _mm256_set1_epi64x(inv as u32 as i64);
+
+        let mut t = [_mm256_setzero_si256(); 10];
+        for a_limb in a_limbs.iter() {
+            // t += a_i * b
+            let mut carry = _mm256_setzero_si256();
+            for j in 0..8 {
+                let sum = _mm256_add_epi64(_mm256_add_epi64(t[j], _mm256_mul_epu32(*a_limb, b_limbs[j])), carry);
+                t[j] = _mm256_and_si256(sum, mask);
+                carry = _mm256_srli_epi64(sum, 32);
+            }
+            let sum = _mm256_add_epi64(t[8], carry);
+            t[8] = _mm256_and_si256(sum, mask);
+            t[9] = _mm256_srli_epi64(sum, 32);
+
+            // t = (t + k * modulus) / 2^32, where k makes the low word zero.
+            // The multiplications only read the low half of each lane, so k
+            // needs no masking.
+            let k = _mm256_mul_epu32(t[0], inv);
+            let sum = _mm256_add_epi64(t[0], _mm256_mul_epu32(k, m_limbs[0]));
+            let mut carry = _mm256_srli_epi64(sum, 32);
+            for j in 1..8 {
+                let sum = _mm256_add_epi64(_mm256_add_epi64(t[j], _mm256_mul_epu32(k, m_limbs[j])), carry);
+                t[j - 1] = _mm256_and_si256(sum, mask);
+                carry = _mm256_srli_epi64(sum, 32);
+            }
+            let sum = _mm256_add_epi64(t[8], carry);
+            t[7] = _mm256_and_si256(sum, mask);
+            t[8] = _mm256_add_epi64(t[9], _mm256_srli_epi64(sum, 32));
+        }
+
+        // t < 2 * modulus, so subtract the modulus unless that borrows.
+        let mut reduced = [_mm256_setzero_si256(); 8];
+        let mut borrow = _mm256_setzero_si256();
+        for j in 0..8 {
+            let diff = _mm256_sub_epi64(_mm256_sub_epi64(t[j], m_limbs[j]), borrow);
+            reduced[j] = _mm256_and_si256(diff, mask);
+            borrow = _mm256_srli_epi64(diff, 63);
+        }
+        // The lanes where the subtraction borrowed past the top word, which
+        // keep t, are those where this is negative. Spread its sign to the
+        // whole lane.
+        let top = _mm256_sub_epi64(t[8], borrow);
+        let keep = _mm256_srai_epi32(_mm256_shuffle_epi32(top, 0b11_11_01_01), 31);
+
+        let mut result = [_mm256_setzero_si256(); 8];
+        for j in 0..8 {
+            result[j] = _mm256_blendv_epi8(reduced[j], t[j], keep);
+        }
+        store(a, &result);
+    }
+}
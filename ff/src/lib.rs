@@ -7,6 +7,9 @@ extern crate ff_derive;
 #[cfg(feature = "derive")]
 pub use ff_derive::*;
 
+#[doc(hidden)]
+pub mod batch;
+
 use rand_core::RngCore;
 use std::error::Error;
 use std::fmt;
@@ -46,9 +49,53 @@ pub trait Field:
     /// Multiplies another element by this element.
     fn mul_assign(&mut self, other: &Self);
 
+    /// Multiplies each element of `lhs` by the corresponding element of
+    /// `rhs`. Fields may override this with a vectorised implementation;
+    /// those derived by `ff_derive` with four limbs use AVX2 where the CPU
+    /// has it.
+    /// Panics if the slices differ in length.
+    fn batch_mul_assign(lhs: &mut [Self], rhs: &[Self]) {
+        assert_eq!(lhs.len(), rhs.len(), "expected slices of the same length");
+        for (a, b) in lhs.iter_mut().zip(rhs.iter()) {
+            a.mul_assign(b);
+        }
+    }
+
     /// Computes the multiplicative inverse of this element, if nonzero.
     fn inverse(&self) -> Option<Self>;
 
+    /// Replaces every element of `values` with its inverse, using
+    /// Montgomery's trick so that only one inversion is performed for the
+    /// whole slice. Returns false, leaving `values` untouched, if any
+    /// element is zero.
+    fn batch_invert(values: &mut [Self]) -> bool {
+        // prefixes[i] is the product of values[..i].
+        let mut prefixes: Vec<Self> = Vec::with_capacity(values.len());
+        let mut acc = Self::one();
+        for value in values.iter() {
+            if value.is_zero() {
+                return false;
+            }
+            prefixes.push(acc);
+            acc.mul_assign(value);
+        }
+
+        // The inverse of the product of all values, with one value peeled
+        // off the back at a time, leaves the inverse of each prefix.
+        let mut acc_inv = match acc.inverse() {
+            Some(inverse) => inverse,
+            None => return false,
+        };
+        for (value, prefix) in values.iter_mut().zip(prefixes).rev() {
+            let mut inverse = acc_inv;
+            inverse.mul_assign(&prefix);
+            acc_inv.mul_assign(value);
+            *value = inverse;
+        }
+
+        true
+    }
+
     /// Exponentiates this element by a power of the base prime modulus via
     /// the Frobenius automorphism.
     fn frobenius_map(&mut self, power: usize);
@@ -63,6 +63,13 @@ pub trait CurveProjective:
     /// Converts this element into its affine representation.
     fn into_affine(&self) -> Self::Affine;
 
+    /// Applies an efficiently computable endomorphism of the curve which acts
+    /// on the prime order subgroup as multiplication by a fixed scalar, if the
+    /// curve has one. See `CurveAffine::decompose_scalar`.
+    fn endomorphism(&self) -> Option<Self> {
+        None
+    }
+
     /// Recommends a wNAF window table size given a scalar. Always returns a number
     /// between 2 and 22, inclusive.
     fn recommended_wnaf_for_scalar(scalar: <Self::Scalar as PrimeField>::Repr) -> usize;
@@ -103,6 +110,25 @@ pub trait CurveAffine:
     /// Converts this element into its affine representation.
     fn into_projective(&self) -> Self::Projective;
 
+    /// Applies an efficiently computable endomorphism of the curve which acts
+    /// on the prime order subgroup as multiplication by a fixed scalar λ, if
+    /// the curve has one.
+    fn endomorphism(&self) -> Option<Self> {
+        None
+    }
+
+    /// Splits `scalar` into `(k1, k2)` with `scalar = k1 + k2 * λ`, where λ is
+    /// the scalar `endomorphism` multiplies by and both halves are about half
+    /// as wide as the scalar field, if the curve has an endomorphism.
+    fn decompose_scalar(
+        _scalar: &<Self::Scalar as PrimeField>::Repr,
+    ) -> Option<(
+        <Self::Scalar as PrimeField>::Repr,
+        <Self::Scalar as PrimeField>::Repr,
+    )> {
+        None
+    }
+
     /// Converts this element into its compressed encoding, so long as it's not
     /// the point at infinity.
     fn into_compressed(&self) -> Self::Compressed {
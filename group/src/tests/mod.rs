@@ -176,6 +176,15 @@ fn random_wnaf_tests<G: CurveProjective>() {
 
                 shared.base(g)
             };
+            let g10 = Wnaf::new().into_base(g, 1).scalar(s);
+            let g11 = {
+                let owned = Wnaf::new().into_base(g, 1);
+                let mut shared = owned.shared();
+
+                only_compiles_if_send(&shared);
+
+                shared.scalar(s)
+            };
 
             assert_eq!(g1, g2);
             assert_eq!(g1, g3);
@@ -185,6 +194,8 @@ fn random_wnaf_tests<G: CurveProjective>() {
             assert_eq!(g1, g7);
             assert_eq!(g1, g8);
             assert_eq!(g1, g9);
+            assert_eq!(g1, g10);
+            assert_eq!(g1, g11);
         }
     }
 }
@@ -1,6 +1,6 @@
 use ff::{PrimeField, PrimeFieldRepr};
 
-use super::CurveProjective;
+use super::{CurveAffine, CurveProjective};
 
 /// Replaces the contents of `table` with a w-NAF window table for the given window size.
 pub(crate) fn wnaf_table<G: CurveProjective>(table: &mut Vec<G>, mut base: G, window: usize) {
@@ -72,7 +72,87 @@ pub(crate) fn wnaf_exp<G: CurveProjective>(table: &[G], wnaf: &[i64]) -> G {
     result
 }
 
+/// Whether `Wnaf` splits scalars with the endomorphism of `G`. The window
+/// table then holds the multiples of the base followed by their images
+/// under the endomorphism, and the w-NAF form holds the forms of both
+/// halves of the scalar, so each exponentiation needs half the doublings.
+///
+/// The endomorphism only acts as multiplication by λ on the prime order
+/// subgroup, which every point is assumed to lie in. Points decoded with
+/// `into_affine_unchecked` must be checked where they are decoded.
+fn splits_scalars<G: CurveProjective>() -> bool {
+    cfg!(feature = "glv") && G::one().endomorphism().is_some()
+}
+
+/// Replaces the contents of `table` with a w-NAF window table for `base`,
+/// followed by the images of its entries under the endomorphism.
+fn wnaf_table_split<G: CurveProjective>(table: &mut Vec<G>, base: G, window: usize) {
+    wnaf_table(table, base, window);
+
+    let len = table.len();
+    for i in 0..len {
+        let image = table[i]
+            .endomorphism()
+            .expect("splitting scalars requires an endomorphism");
+        table.push(image);
+    }
+}
+
+/// Replaces the contents of `wnaf` with the length of the w-NAF form of the
+/// first half of the decomposed scalar, followed by the forms of both halves.
+fn wnaf_form_split<G: CurveProjective>(
+    wnaf: &mut Vec<i64>,
+    scalar: <G::Scalar as PrimeField>::Repr,
+    window: usize,
+) {
+    let (k1, k2) = G::Affine::decompose_scalar(&scalar)
+        .expect("splitting scalars requires a decomposition");
+
+    let mut second = vec![];
+    wnaf_form(wnaf, k1, window);
+    wnaf_form(&mut second, k2, window);
+
+    wnaf.insert(0, wnaf.len() as i64);
+    wnaf.extend_from_slice(&second);
+}
+
+/// Performs w-NAF exponentiation with a table built by `wnaf_table_split`
+/// and a form built by `wnaf_form_split` with the same window size.
+fn wnaf_exp_split<G: CurveProjective>(table: &[G], wnaf: &[i64]) -> G {
+    let (images, table) = (&table[table.len() / 2..], &table[..table.len() / 2]);
+    let first_len = wnaf[0] as usize;
+    let (first, second) = wnaf[1..].split_at(first_len);
+
+    let mut result = G::zero();
+    let mut found_one = false;
+
+    for i in (0..first.len().max(second.len())).rev() {
+        if found_one {
+            result.double();
+        }
+
+        for (form, table) in [(first, table), (second, images)].iter() {
+            let n = form.get(i).cloned().unwrap_or(0);
+            if n != 0 {
+                found_one = true;
+
+                if n > 0 {
+                    result.add_assign(&table[(n / 2) as usize]);
+                } else {
+                    result.sub_assign(&table[((-n) / 2) as usize]);
+                }
+            }
+        }
+    }
+
+    result
+}
+
 /// A "w-ary non-adjacent form" exponentiation context.
+///
+/// With the `glv` feature, scalars are split with the curve endomorphism,
+/// which gives the wrong product for a base outside the prime order
+/// subgroup.
 #[derive(Debug)]
 pub struct Wnaf<W, B, S> {
     base: B,
@@ -97,7 +177,11 @@ impl<G: CurveProjective> Wnaf<(), Vec<G>, Vec<i64>> {
         let window_size = G::recommended_wnaf_for_num_scalars(num_scalars);
 
         // Compute a wNAF table for the provided base and window size.
-        wnaf_table(&mut self.base, base, window_size);
+        if splits_scalars::<G>() {
+            wnaf_table_split(&mut self.base, base, window_size);
+        } else {
+            wnaf_table(&mut self.base, base, window_size);
+        }
 
         // Return a Wnaf object that immutably borrows the computed base storage location,
         // but mutably borrows the scalar storage location.
@@ -108,6 +192,18 @@ impl<G: CurveProjective> Wnaf<(), Vec<G>, Vec<i64>> {
         }
     }
 
+    /// Like `base`, but consumes the context so that the returned `Wnaf` object owns the window
+    /// table and can be kept for as long as the base is in use.
+    pub fn into_base(mut self, base: G, num_scalars: usize) -> Wnaf<usize, Vec<G>, Vec<i64>> {
+        let window_size = self.base(base, num_scalars).window_size;
+
+        Wnaf {
+            base: self.base,
+            scalar: self.scalar,
+            window_size,
+        }
+    }
+
     /// Given a scalar, compute its wNAF representation and return a `Wnaf` object that can perform
     /// exponentiations with `.base(..)`.
     pub fn scalar(
@@ -118,7 +214,11 @@ impl<G: CurveProjective> Wnaf<(), Vec<G>, Vec<i64>> {
         let window_size = G::recommended_wnaf_for_scalar(scalar);
 
         // Compute the wNAF form of the scalar.
-        wnaf_form(&mut self.scalar, scalar, window_size);
+        if splits_scalars::<G>() {
+            wnaf_form_split::<G>(&mut self.scalar, scalar, window_size);
+        } else {
+            wnaf_form(&mut self.scalar, scalar, window_size);
+        }
 
         // Return a Wnaf object that mutably borrows the base storage location, but
         // immutably borrows the computed wNAF form scalar location.
@@ -142,6 +242,18 @@ impl<'a, G: CurveProjective> Wnaf<usize, &'a [G], &'a mut Vec<i64>> {
     }
 }
 
+impl<G: CurveProjective> Wnaf<usize, Vec<G>, Vec<i64>> {
+    /// Constructs new space for the scalar representation while borrowing
+    /// the owned window table, for sending the window table across threads.
+    pub fn shared(&self) -> Wnaf<usize, &[G], Vec<i64>> {
+        Wnaf {
+            base: &self.base[..],
+            scalar: vec![],
+            window_size: self.window_size,
+        }
+    }
+}
+
 impl<'a, G: CurveProjective> Wnaf<usize, &'a mut Vec<G>, &'a [i64]> {
     /// Constructs new space for the window table while borrowing
     /// the computed scalar representation, for sending the scalar representation
@@ -161,8 +273,13 @@ impl<B, S: AsRef<[i64]>> Wnaf<usize, B, S> {
     where
         B: AsMut<Vec<G>>,
     {
-        wnaf_table(self.base.as_mut(), base, self.window_size);
-        wnaf_exp(self.base.as_mut(), self.scalar.as_ref())
+        if splits_scalars::<G>() {
+            wnaf_table_split(self.base.as_mut(), base, self.window_size);
+            wnaf_exp_split(self.base.as_mut(), self.scalar.as_ref())
+        } else {
+            wnaf_table(self.base.as_mut(), base, self.window_size);
+            wnaf_exp(self.base.as_mut(), self.scalar.as_ref())
+        }
     }
 }
 
@@ -175,7 +292,12 @@ impl<B, S: AsMut<Vec<i64>>> Wnaf<usize, B, S> {
     where
         B: AsRef<[G]>,
     {
-        wnaf_form(self.scalar.as_mut(), scalar, self.window_size);
-        wnaf_exp(self.base.as_ref(), self.scalar.as_mut())
+        if splits_scalars::<G>() {
+            wnaf_form_split::<G>(self.scalar.as_mut(), scalar, self.window_size);
+            wnaf_exp_split(self.base.as_ref(), self.scalar.as_mut())
+        } else {
+            wnaf_form(self.scalar.as_mut(), scalar, self.window_size);
+            wnaf_exp(self.base.as_ref(), self.scalar.as_mut())
+        }
     }
 }
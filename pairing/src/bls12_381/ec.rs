@@ -178,6 +178,19 @@ macro_rules! curve_impl {
             fn into_projective(&self) -> $projective {
                 (*self).into()
             }
+
+            fn endomorphism(&self) -> Option<Self> {
+                self.glv_endomorphism()
+            }
+
+            fn decompose_scalar(
+                scalar: &<Self::Scalar as PrimeField>::Repr,
+            ) -> Option<(
+                <Self::Scalar as PrimeField>::Repr,
+                <Self::Scalar as PrimeField>::Repr,
+            )> {
+                Self::glv_decompose(scalar)
+            }
         }
 
         impl PairingCurveAffine for $affine {
@@ -240,54 +253,25 @@ macro_rules! curve_impl {
             }
 
             fn batch_normalization(v: &mut [Self]) {
-                // Montgomery’s Trick and Fast Implementation of Masked AES
-                // Genelle, Prouff and Quisquater
-                // Section 3.2
-
-                // First pass: compute [a, ab, abc, ...]
-                let mut prod = Vec::with_capacity(v.len());
-                let mut tmp = $basefield::one();
-                for g in v
-                    .iter_mut()
-                    // Ignore normalized elements
-                    .filter(|g| !g.is_normalized())
-                {
-                    tmp.mul_assign(&g.z);
-                    prod.push(tmp);
-                }
-
-                // Invert `tmp`.
-                tmp = tmp.inverse().unwrap(); // Guaranteed to be nonzero.
-
-                // Second pass: iterate backwards to compute inverses
-                for (g, s) in v
-                    .iter_mut()
-                    // Backwards
-                    .rev()
-                    // Ignore normalized elements
+                // Montgomery's trick inverts every z coordinate at the cost
+                // of a single inversion. Normalized elements, the point at
+                // infinity among them, are skipped.
+                let mut z_inv: Vec<$basefield> = v
+                    .iter()
                     .filter(|g| !g.is_normalized())
-                    // Backwards, skip last element, fill in one for last term.
-                    .zip(
-                        prod.into_iter()
-                            .rev()
-                            .skip(1)
-                            .chain(Some($basefield::one())),
-                    )
-                {
-                    // tmp := tmp * g.z; g.z := tmp * s = 1/z
-                    let mut newtmp = tmp;
-                    newtmp.mul_assign(&g.z);
-                    g.z = tmp;
-                    g.z.mul_assign(&s);
-                    tmp = newtmp;
-                }
+                    .map(|g| g.z)
+                    .collect();
+                assert!(
+                    $basefield::batch_invert(&mut z_inv),
+                    "only the point at infinity has a zero z coordinate"
+                );
 
                 // Perform affine transformations
-                for g in v.iter_mut().filter(|g| !g.is_normalized()) {
-                    let mut z = g.z; // 1/z
+                for (g, z_inv) in v.iter_mut().filter(|g| !g.is_normalized()).zip(z_inv) {
+                    let mut z = z_inv; // 1/z
                     z.square(); // 1/z^2
                     g.x.mul_assign(&z); // x/z^2
-                    z.mul_assign(&g.z); // 1/z^3
+                    z.mul_assign(&z_inv); // 1/z^3
                     g.y.mul_assign(&z); // y/z^3
                     g.z = $basefield::one(); // z = 1
                 }
@@ -555,6 +539,10 @@ macro_rules! curve_impl {
                 (*self).into()
             }
 
+            fn endomorphism(&self) -> Option<Self> {
+                self.glv_endomorphism()
+            }
+
             fn recommended_wnaf_for_scalar(scalar: <Self::Scalar as PrimeField>::Repr) -> usize {
                 Self::empirical_recommended_wnaf_for_scalar(scalar)
             }
@@ -887,9 +875,47 @@ pub mod g1 {
         fn perform_pairing(&self, other: &G2Affine) -> Fq12 {
             super::super::Bls12::pairing(*self, *other)
         }
+
+        // (x, y) -> (BETA * x, y), which is multiplication by GLV_LAMBDA on G1.
+        fn glv_endomorphism(&self) -> Option<Self> {
+            let mut res = *self;
+            if !res.is_zero() {
+                res.x.mul_assign(&super::super::fq::BETA);
+            }
+            Some(res)
+        }
+
+        // Splits k into k % GLV_LAMBDA and k / GLV_LAMBDA by long division, so
+        // both halves of a reduced scalar are at most 128 bits.
+        fn glv_decompose(scalar: &FrRepr) -> Option<(FrRepr, FrRepr)> {
+            let mut quotient = FrRepr::default();
+            let mut remainder: u128 = 0;
+
+            for i in (0..256).rev() {
+                let bit = (scalar.0[i / 64] >> (i % 64)) & 1;
+                let carry = remainder >> 127;
+                remainder = (remainder << 1) | u128::from(bit);
+                if carry == 1 || remainder >= GLV_LAMBDA {
+                    remainder = remainder.wrapping_sub(GLV_LAMBDA);
+                    quotient.0[i / 64] |= 1 << (i % 64);
+                }
+            }
+
+            let k1 = FrRepr([remainder as u64, (remainder >> 64) as u64, 0, 0]);
+            Some((k1, quotient))
+        }
     }
 
+    // z^2 - 1, where z is the BLS parameter. This is a cube root of unity in Fr.
+    const GLV_LAMBDA: u128 = 0xac45a4010001a40200000000ffffffff;
+
     impl G1 {
+        fn glv_endomorphism(&self) -> Option<Self> {
+            let mut res = *self;
+            res.x.mul_assign(&super::super::fq::BETA);
+            Some(res)
+        }
+
         fn empirical_recommended_wnaf_for_scalar(scalar: FrRepr) -> usize {
             let num_bits = scalar.num_bits() as usize;
 
@@ -1286,6 +1312,42 @@ pub mod g1 {
         use group::tests::curve_tests;
         curve_tests::<G1>();
     }
+
+    #[test]
+    fn g1_glv_endomorphism() {
+        use rand_core::SeedableRng;
+        use rand_xorshift::XorShiftRng;
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let lambda = FrRepr([GLV_LAMBDA as u64, (GLV_LAMBDA >> 64) as u64, 0, 0]);
+        let lambda_fr = Fr::from_repr(lambda).unwrap();
+
+        // The endomorphism is multiplication by lambda on G1.
+        for _ in 0..10 {
+            let p = G1::random(&mut rng);
+            let mut expected = p;
+            expected.mul_assign(lambda);
+            assert_eq!(p.endomorphism().unwrap(), expected);
+            assert_eq!(p.into_affine().endomorphism().unwrap(), expected.into_affine());
+        }
+        assert!(G1Affine::zero().endomorphism().unwrap().is_zero());
+        assert!(G1::zero().endomorphism().unwrap().is_zero());
+
+        // k = k1 + k2 * lambda with both halves at most 128 bits.
+        for _ in 0..100 {
+            let k = Fr::random(&mut rng);
+            let (k1, k2) = G1Affine::decompose_scalar(&k.into_repr()).unwrap();
+            assert!(k1.num_bits() <= 128 && k2.num_bits() <= 128);
+
+            let mut sum = Fr::from_repr(k2).unwrap();
+            sum.mul_assign(&lambda_fr);
+            sum.add_assign(&Fr::from_repr(k1).unwrap());
+            assert_eq!(sum, k);
+        }
+    }
 }
 
 pub mod g2 {
@@ -1600,9 +1662,21 @@ pub mod g2 {
         fn perform_pairing(&self, other: &G1Affine) -> Fq12 {
             super::super::Bls12::pairing(*other, *self)
         }
+
+        fn glv_endomorphism(&self) -> Option<Self> {
+            None
+        }
+
+        fn glv_decompose(_scalar: &FrRepr) -> Option<(FrRepr, FrRepr)> {
+            None
+        }
     }
 
     impl G2 {
+        fn glv_endomorphism(&self) -> Option<Self> {
+            None
+        }
+
         fn empirical_recommended_wnaf_for_scalar(scalar: FrRepr) -> usize {
             let num_bits = scalar.num_bits() as usize;
 
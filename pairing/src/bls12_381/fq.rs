@@ -11,6 +11,18 @@ pub const B_COEFF: Fq = Fq(FqRepr([
     0x9d645513d83de7e,
 ]));
 
+// A primitive cube root of unity in Fq, such that (x, y) -> (BETA * x, y) acts on G1 as
+// multiplication by the scalar z^2 - 1, where z is the BLS parameter.
+// BETA = 4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939436
+pub const BETA: Fq = Fq(FqRepr([
+    0xcd03c9e48671f071,
+    0x5dab22461fcda5d2,
+    0x587042afd3851b95,
+    0x8eb60ebe01bacb9e,
+    0x3f97d6e83d050d2,
+    0x18f0206554638741,
+]));
+
 // The generators of G1/G2 are computed by finding the lexicographically smallest valid x coordinate,
 // and its lexicographically smallest y coordinate and multiplying it by the cofactor such that the
 // result is nonzero.
@@ -688,6 +688,37 @@ fn test_fr_mul_assign() {
     }
 }
 
+#[test]
+fn test_fr_batch_mul_assign() {
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+
+    // Agrees with multiplying one element at a time, including the largest
+    // element and lengths which are not a multiple of the batch width.
+    let mut max = Fr::zero();
+    max.sub_assign(&Fr::one());
+    for len in (0..10).chain(Some(1000)) {
+        let mut a: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        let mut b: Vec<Fr> = (0..len).map(|_| Fr::random(&mut rng)).collect();
+        if len > 4 {
+            a[1] = max;
+            b[1] = max;
+            a[2] = Fr::zero();
+            b[3] = Fr::one();
+        }
+
+        let mut expected = a.clone();
+        for (x, y) in expected.iter_mut().zip(b.iter()) {
+            x.mul_assign(y);
+        }
+
+        Fr::batch_mul_assign(&mut a, &b);
+        assert_eq!(a, expected);
+    }
+}
+
 #[test]
 fn test_fr_squaring() {
     let mut a = Fr(FrRepr([